@@ -0,0 +1,260 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A [Bevy](https://bevyengine.org) plugin for capturing webcams with `nokhwa`.
+//!
+//! Add [`NokhwaPlugin`], then spawn an entity with a [`CameraCapture`]. The plugin opens the camera on its own thread, adds a
+//! [`CameraImage`] to the entity whose [`Image`] asset is updated with every new frame, and sends [`CameraEvent`]s when the
+//! camera opens, fails, or is unplugged. Despawning the entity (or removing the [`CameraCapture`]) stops the camera.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use nokhwa::{
+    frame_to_rgba,
+    prelude::{
+        CameraFormat, CameraIndex, CaptureTrait, FrameFormat, RequestedFormat, RequestedFormatType,
+        Resolution,
+    },
+    Buffer, Camera,
+};
+use std::{
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Mutex,
+    },
+    thread,
+};
+
+/// Captures frames from every [`CameraCapture`] into [`CameraImage`]s. See the [crate docs](crate).
+pub struct NokhwaPlugin;
+
+impl Plugin for NokhwaPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CameraEvent>().add_systems(
+            PreUpdate,
+            (start_captures, stop_captures, poll_captures).chain(),
+        );
+    }
+}
+
+/// Opens a camera when added to an entity.
+#[derive(Component, Clone, Debug)]
+pub struct CameraCapture {
+    index: CameraIndex,
+    request: RequestedFormat,
+}
+
+impl CameraCapture {
+    /// Captures from the camera at `index`, at its highest resolution.
+    pub fn new(index: impl Into<CameraIndex>) -> Self {
+        Self::with_request(
+            index,
+            RequestedFormat::new(RequestedFormatType::AbsoluteHighestResolution),
+        )
+    }
+
+    /// Captures from the camera at `index`, in the format that best fits `request`.
+    pub fn with_request(index: impl Into<CameraIndex>, request: RequestedFormat) -> Self {
+        CameraCapture {
+            index: index.into(),
+            request,
+        }
+    }
+
+    /// The index of the camera.
+    #[must_use]
+    pub fn index(&self) -> &CameraIndex {
+        &self.index
+    }
+}
+
+/// Added by [`NokhwaPlugin`] next to a [`CameraCapture`]. Holds the [`Image`] that shows the latest frame.
+#[derive(Component, Clone, Debug)]
+pub struct CameraImage {
+    handle: Handle<Image>,
+}
+
+impl CameraImage {
+    /// The [`Image`] asset that is updated with every frame, e.g. to put on a sprite or material.
+    #[must_use]
+    pub fn handle(&self) -> Handle<Image> {
+        self.handle.clone()
+    }
+}
+
+/// What happened to the camera of a [`CameraCapture`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum CameraEventKind {
+    /// The camera was opened and is streaming in this format.
+    Opened(CameraFormat),
+    /// The camera was unplugged. The capture thread has stopped.
+    Disconnected,
+    /// Opening or reading from the camera failed.
+    Error(String),
+}
+
+/// Sent when something happens to the camera of the [`CameraCapture`] on `entity`.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct CameraEvent {
+    pub entity: Entity,
+    pub kind: CameraEventKind,
+}
+
+enum Message {
+    Event(CameraEventKind),
+    // Frames are sent raw and only the latest one is decoded each Bevy frame, so frames Bevy skips cost nothing.
+    Frame(Buffer, FrameFormat),
+}
+
+#[derive(Component)]
+struct CaptureTask {
+    receiver: Mutex<Receiver<Message>>,
+}
+
+fn start_captures(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    captures: Query<(Entity, &CameraCapture), Without<CaptureTask>>,
+) {
+    for (entity, capture) in &captures {
+        // Keep at most a couple of frames in flight, so a slow frame rate in Bevy never shows stale frames.
+        let (sender, receiver) = mpsc::sync_channel(2);
+        let (index, request) = (capture.index.clone(), capture.request.clone());
+        thread::spawn(move || capture_thread(index, request, sender));
+
+        let handle = images.add(rgba_image(Resolution::new(1, 1), vec![0, 0, 0, 255]));
+        commands.entity(entity).insert((
+            CameraImage { handle },
+            CaptureTask {
+                receiver: Mutex::new(receiver),
+            },
+        ));
+    }
+}
+
+fn stop_captures(mut commands: Commands, mut removed: RemovedComponents<CameraCapture>) {
+    // Dropping the receiver makes the capture thread stop.
+    for entity in removed.read() {
+        if let Some(mut entity) = commands.get_entity(entity) {
+            entity.remove::<(CaptureTask, CameraImage)>();
+        }
+    }
+}
+
+fn poll_captures(
+    tasks: Query<(Entity, &CameraImage, &CaptureTask)>,
+    mut images: ResMut<Assets<Image>>,
+    mut events: EventWriter<CameraEvent>,
+) {
+    for (entity, camera_image, task) in &tasks {
+        let Ok(receiver) = task.receiver.lock() else {
+            continue;
+        };
+        let mut latest = None;
+        for message in receiver.try_iter() {
+            match message {
+                Message::Event(kind) => events.send(CameraEvent { entity, kind }),
+                Message::Frame(frame, format) => latest = Some((frame, format)),
+            }
+        }
+
+        let Some((frame, format)) = latest else {
+            continue;
+        };
+        let rgba = match frame_to_rgba(&frame, format) {
+            Ok(rgba) => rgba,
+            Err(why) => {
+                events.send(CameraEvent {
+                    entity,
+                    kind: CameraEventKind::Error(why.to_string()),
+                });
+                continue;
+            }
+        };
+        let resolution = frame.resolution();
+        if let Some(image) = images.get_mut(&camera_image.handle) {
+            if image.width() == resolution.width()
+                && image.height() == resolution.height()
+                && image.data.len() == rgba.len()
+            {
+                image.data.copy_from_slice(&rgba);
+            } else {
+                *image = rgba_image(resolution, rgba.into_vec());
+            }
+        }
+    }
+}
+
+fn rgba_image(resolution: Resolution, rgba: Vec<u8>) -> Image {
+    Image::new(
+        Extent3d {
+            width: resolution.width(),
+            height: resolution.height(),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        rgba,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn capture_thread(index: CameraIndex, request: RequestedFormat, sender: SyncSender<Message>) {
+    let opened = Camera::with_request(index, request).and_then(|mut camera| {
+        camera.open_stream()?;
+        Ok(camera)
+    });
+    let mut camera = match opened {
+        Ok(camera) => camera,
+        Err(why) => {
+            let _ = sender.send(Message::Event(CameraEventKind::Error(why.to_string())));
+            return;
+        }
+    };
+    if let Some(format) = camera.camera_format() {
+        if sender
+            .send(Message::Event(CameraEventKind::Opened(format)))
+            .is_err()
+        {
+            return;
+        }
+    }
+
+    loop {
+        let message = match camera.frame() {
+            Ok(frame) => Message::Frame(frame, camera.frame_format()),
+            Err(why) if why.is_disconnected() => {
+                let _ = sender.send(Message::Event(CameraEventKind::Disconnected));
+                break;
+            }
+            Err(why) => Message::Event(CameraEventKind::Error(why.to_string())),
+        };
+
+        let sent = match message {
+            // Frames are dropped rather than queued when Bevy is behind.
+            Message::Frame(..) => match sender.try_send(message) {
+                Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+                Err(TrySendError::Disconnected(_)) => Err(()),
+            },
+            Message::Event(_) => sender.send(message).map_err(|_| ()),
+        };
+        if sent.is_err() {
+            break;
+        }
+    }
+    let _ = camera.stop_stream();
+}
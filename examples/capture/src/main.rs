@@ -157,12 +157,12 @@ impl FromStr for RequestedCliFormat {
 }
 
 impl RequestedCliFormat {
-    pub fn make_requested(self) -> Option<RequestedFormat<'static>> {
+    pub fn make_requested(self) -> Option<RequestedFormat> {
         match self.format_type.as_str() {
-            "AbsoluteHighestResolution" => Some(RequestedFormat::new::<RgbFormat>(
+            "AbsoluteHighestResolution" => Some(RequestedFormat::new(
                 RequestedFormatType::AbsoluteHighestResolution,
             )),
-            "AbsoluteHighestFrameRate" => Some(RequestedFormat::new::<RgbFormat>(
+            "AbsoluteHighestFrameRate" => Some(RequestedFormat::new(
                 RequestedFormatType::AbsoluteHighestFrameRate,
             )),
             "HighestResolution" => {
@@ -172,16 +172,16 @@ impl RequestedCliFormat {
                 let y = values[1].parse::<u32>().unwrap();
                 let resolution = Resolution::new(x, y);
 
-                Some(RequestedFormat::new::<RgbFormat>(
+                Some(RequestedFormat::new(
                     RequestedFormatType::HighestResolution(resolution),
                 ))
             }
             "HighestFrameRate" => {
                 let fps = self.format_option.unwrap().parse::<u32>().unwrap();
 
-                Some(RequestedFormat::new::<RgbFormat>(
-                    RequestedFormatType::HighestFrameRate(fps),
-                ))
+                Some(RequestedFormat::new(RequestedFormatType::HighestFrameRate(
+                    fps,
+                )))
             }
             "Exact" => {
                 let fmtv = self.format_option.unwrap();
@@ -193,9 +193,9 @@ impl RequestedCliFormat {
 
                 let resolution = Resolution::new(x, y);
                 let camera_format = CameraFormat::new(resolution, fourcc, fps);
-                Some(RequestedFormat::new::<RgbFormat>(
-                    RequestedFormatType::Exact(camera_format),
-                ))
+                Some(RequestedFormat::new(RequestedFormatType::Exact(
+                    camera_format,
+                )))
             }
             "Closest" => {
                 let fmtv = self.format_option.unwrap();
@@ -207,11 +207,11 @@ impl RequestedCliFormat {
 
                 let resolution = Resolution::new(x, y);
                 let camera_format = CameraFormat::new(resolution, fourcc, fps);
-                Some(RequestedFormat::new::<RgbFormat>(
-                    RequestedFormatType::Closest(camera_format),
-                ))
+                Some(RequestedFormat::new(RequestedFormatType::Closest(
+                    camera_format,
+                )))
             }
-            "None" => Some(RequestedFormat::new::<RgbFormat>(RequestedFormatType::None)),
+            "None" => Some(RequestedFormat::new(RequestedFormatType::None)),
             _ => None,
         }
     }
@@ -303,11 +303,9 @@ fn nokhwa_main() {
                 IndexKind::String(s) => CameraIndex::String(s.clone()),
                 IndexKind::Index(i) => CameraIndex::Index(*i),
             };
-            let mut camera = Camera::new(
-                index,
-                RequestedFormat::new::<RgbFormat>(RequestedFormatType::None),
-            )
-            .unwrap();
+            let mut camera =
+                Camera::with_request(index, RequestedFormat::new(RequestedFormatType::None))
+                    .unwrap();
             match kind {
                 PropertyKind::All => {
                     camera_print_controls(&camera);
@@ -350,7 +348,7 @@ fn nokhwa_main() {
 
                 camera.open_stream().unwrap();
 
-                let cb = ContextBuilder::new(&camera_info.human_name(), "Nokhwa");
+                let cb = ContextBuilder::new(camera_info.human_name(), "Nokhwa");
                 let (ctx, el) = cb.build().unwrap();
 
                 let state = CaptureState {
@@ -384,7 +382,7 @@ fn nokhwa_main() {
                 .flatten()
                 .expect("Expected AbsoluteHighestResolution, AbsoluteHighestFrameRate, HighestResolution, HighestFrameRate, Exact, Closest, or None");
 
-            let mut camera = Camera::new(index, requested).unwrap();
+            let mut camera = Camera::with_request(index, requested).unwrap();
             camera.open_stream().unwrap();
             let frame = camera.frame().unwrap();
             camera.stop_stream().unwrap();
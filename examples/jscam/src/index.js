@@ -66,7 +66,7 @@ var nokhwaCamera = undefined;
 deviceOpenButton.addEventListener("click", function(event) {
     streamPlayArea.innerHTML = "";
     let constraints = (new CameraConstraintsBuilder()).buildCameraConstraints();
-    nokhwaCamera = new NokhwaCamera(constraints).catch((err) => {console.error(err); return});
+    nokhwaCamera = NokhwaCamera.open(constraints).catch((err) => {console.error(err); return});
     nokhwaCamera = nokhwaCamera.then(
         (ok) => {
             nokhwaCamera = ok;
@@ -16,7 +16,7 @@
 
 use nokhwa::{
     nokhwa_initialize,
-    pixel_format::{RgbAFormat, RgbFormat},
+    pixel_format::RgbAFormat,
     query,
     utils::{ApiBackend, RequestedFormat, RequestedFormatType},
     CallbackCamera,
@@ -30,7 +30,7 @@ fn main() {
     let cameras = query(ApiBackend::Auto).unwrap();
     cameras.iter().for_each(|cam| println!("{:?}", cam));
 
-    let format = RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
+    let format = RequestedFormat::new(RequestedFormatType::AbsoluteHighestFrameRate);
 
     let first_camera = cameras.first().unwrap();
 
@@ -0,0 +1,199 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Exporting V4L2 capture buffers as DMABUF file descriptors, for importing into a GPU API without copying.
+
+use nokhwa_core::{frame_format::FrameFormat, types::Resolution};
+use std::{
+    io,
+    os::fd::{AsFd, BorrowedFd, OwnedFd},
+};
+
+/// The DRM format modifier of V4L2 buffers: plain, linear rows.
+pub const DRM_FORMAT_MOD_LINEAR: u64 = 0;
+
+const EGL_NONE: i32 = 0x3038;
+const EGL_WIDTH: i32 = 0x3057;
+const EGL_HEIGHT: i32 = 0x3056;
+const EGL_LINUX_DRM_FOURCC_EXT: i32 = 0x3271;
+// FD, OFFSET, PITCH for planes 0, 1, and 2.
+const EGL_DMA_BUF_PLANE_ATTRIBUTES: [[i32; 3]; 3] = [
+    [0x3272, 0x3273, 0x3274],
+    [0x3275, 0x3276, 0x3277],
+    [0x3278, 0x3279, 0x327A],
+];
+
+/// One plane of a [`DmaBufExport`], within its single DMABUF.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub struct DmaBufPlane {
+    /// Where the plane starts, in bytes.
+    pub offset: u32,
+    /// The length of a row of the plane, in bytes.
+    pub pitch: u32,
+}
+
+/// A V4L2 capture buffer exported as a DMABUF with `VIDIOC_EXPBUF`. Made by
+/// [`V4LCaptureDevice::export_dmabufs()`](crate::V4LCaptureDevice::export_dmabufs).
+///
+/// The DMABUF stays valid for as long as the file descriptor is open, even after the stream is closed, but only holds a frame
+/// while the buffer is dequeued: the frame from [`frame()`](nokhwa_core::traits::CaptureTrait::frame) is in the buffer
+/// [`last_buffer_index()`](crate::V4LCaptureDevice::last_buffer_index), until the next call to `frame()` gives it back to the
+/// driver.
+///
+/// # Importing
+/// - EGL (`EGL_EXT_image_dma_buf_import`): pass [`egl_image_attributes()`](Self::egl_image_attributes) to `eglCreateImageKHR`
+///   with the `EGL_LINUX_DMA_BUF_EXT` target and no context, then bind it with `glEGLImageTargetTexture2DOES`.
+/// - Vulkan (`VK_EXT_external_memory_dma_buf` and `VK_EXT_image_drm_format_modifier`): create the image with
+///   [`drm_fourcc()`](Self::drm_fourcc)'s format, [`DRM_FORMAT_MOD_LINEAR`], and the [`planes()`](Self::planes) as
+///   `VkSubresourceLayout`s, then import a duplicate of the file descriptor ([`try_clone_fd()`](Self::try_clone_fd)) with
+///   `VkImportMemoryFdInfoKHR`, as Vulkan takes ownership of it.
+#[derive(Debug)]
+pub struct DmaBufExport {
+    pub(crate) index: u32,
+    pub(crate) fd: OwnedFd,
+    pub(crate) length: u32,
+    pub(crate) resolution: Resolution,
+    pub(crate) format: FrameFormat,
+    pub(crate) stride: u32,
+}
+
+impl DmaBufExport {
+    /// The index of the V4L2 buffer this was exported from.
+    #[must_use]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The DMABUF file descriptor.
+    #[must_use]
+    pub fn fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+
+    /// Duplicates the file descriptor, for APIs that take ownership of it.
+    /// # Errors
+    /// If the process is out of file descriptors, this will error.
+    pub fn try_clone_fd(&self) -> io::Result<OwnedFd> {
+        self.fd.try_clone()
+    }
+
+    /// The size of the buffer, in bytes.
+    #[must_use]
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// The resolution of the frames in the buffer.
+    #[must_use]
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    /// The [`FrameFormat`] of the frames in the buffer.
+    #[must_use]
+    pub fn frame_format(&self) -> FrameFormat {
+        self.format
+    }
+
+    /// The DRM fourcc code of the frames in the buffer, or `None` if the format has none (e.g. MJPEG, which must be decoded on
+    /// the CPU).
+    #[must_use]
+    pub fn drm_fourcc(&self) -> Option<u32> {
+        let code = match self.format {
+            FrameFormat::Yuv422 => b"YUYV",
+            FrameFormat::Uyv422 => b"UYVY",
+            FrameFormat::Nv12 => b"NV12",
+            FrameFormat::Nv21 => b"NV21",
+            FrameFormat::Yv12 => b"YV12",
+            FrameFormat::Luma8 => b"R8  ",
+            // V4L2's RGB24 is R, G, B in memory, which DRM calls BGR888.
+            FrameFormat::Rgb8 => b"BG24",
+            _ => return None,
+        };
+        Some(u32::from_le_bytes(*code))
+    }
+
+    /// Where each plane of a frame is in the buffer. Packed formats have one plane, NV12/NV21 two, and YV12 three.
+    #[must_use]
+    pub fn planes(&self) -> Vec<DmaBufPlane> {
+        let luma_size = self.stride * self.resolution.height();
+        match self.format {
+            FrameFormat::Nv12 | FrameFormat::Nv21 => vec![
+                DmaBufPlane {
+                    offset: 0,
+                    pitch: self.stride,
+                },
+                DmaBufPlane {
+                    offset: luma_size,
+                    pitch: self.stride,
+                },
+            ],
+            FrameFormat::Yv12 => {
+                let chroma_pitch = self.stride / 2;
+                let chroma_size = chroma_pitch * ((self.resolution.height() + 1) / 2);
+                vec![
+                    DmaBufPlane {
+                        offset: 0,
+                        pitch: self.stride,
+                    },
+                    DmaBufPlane {
+                        offset: luma_size,
+                        pitch: chroma_pitch,
+                    },
+                    DmaBufPlane {
+                        offset: luma_size + chroma_size,
+                        pitch: chroma_pitch,
+                    },
+                ]
+            }
+            _ => vec![DmaBufPlane {
+                offset: 0,
+                pitch: self.stride,
+            }],
+        }
+    }
+
+    /// The `EGLint` attribute list for `eglCreateImageKHR(display, EGL_NO_CONTEXT, EGL_LINUX_DMA_BUF_EXT, NULL, attributes)`,
+    /// terminated by `EGL_NONE`. Returns `None` if the format has no [`drm_fourcc()`](Self::drm_fourcc).
+    ///
+    /// The file descriptor is only borrowed: EGL does not take ownership of it, so this must outlive the `EGLImage`.
+    #[must_use]
+    pub fn egl_image_attributes(&self) -> Option<Vec<i32>> {
+        use std::os::fd::AsRawFd;
+
+        let fourcc = self.drm_fourcc()?;
+        let mut attributes = vec![
+            EGL_WIDTH,
+            self.resolution.width() as i32,
+            EGL_HEIGHT,
+            self.resolution.height() as i32,
+            EGL_LINUX_DRM_FOURCC_EXT,
+            fourcc as i32,
+        ];
+        for (plane, [fd, offset, pitch]) in self.planes().iter().zip(EGL_DMA_BUF_PLANE_ATTRIBUTES) {
+            attributes.extend_from_slice(&[
+                fd,
+                self.fd.as_raw_fd(),
+                offset,
+                plane.offset as i32,
+                pitch,
+                plane.pitch as i32,
+            ]);
+        }
+        attributes.push(EGL_NONE);
+        Some(attributes)
+    }
+}
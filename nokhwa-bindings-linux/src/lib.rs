@@ -14,36 +14,70 @@
  * limitations under the License.
  */
 
+#[cfg(target_os = "linux")]
+mod dmabuf;
+#[cfg(target_os = "linux")]
+mod output;
+#[cfg(target_os = "linux")]
+mod scheduler;
+mod types;
+
 #[cfg(target_os = "linux")]
 mod internal {
+    use crate::{
+        dmabuf::DmaBufExport,
+        types::{
+            AnalogVideoStandard, DeviceEvent, DevicePriority, DvTimings, ReconnectEvent, V4L2Input,
+            V4L2InputType, V4L2Standard, WatchdogAction,
+        },
+    };
+    #[cfg(feature = "image")]
+    use image::{ImageBuffer, Rgb};
     use nokhwa_core::format_request::FormatFilter;
+    #[cfg(feature = "async")]
+    use nokhwa_core::traits::AsyncCaptureTrait;
+    #[cfg(feature = "image")]
+    use nokhwa_core::types::{buf_mjpeg_to_rgb, buf_nv12_to_rgb, buf_yuyv422_to_rgb};
     use nokhwa_core::{
         buffer::Buffer,
         error::NokhwaError,
+        frame_format::FrameFormat,
         traits::CaptureTrait,
         types::{
-            ApiBackend, CameraControl, CameraFormat, CameraIndex, CameraInfo,
-            ControlValueDescription, ControlValueSetter, FrameFormat, KnownCameraControl,
-            KnownCameraControlFlag, RequestedFormat, RequestedFormatType, Resolution,
+            ApiBackend, AudioDeviceInfo, CameraControl, CameraFormat, CameraIndex, CameraInfo,
+            CaptureParameters, ControlValueDescription, ControlValueSetter, DriverInfo, FieldOrder,
+            FrameRate, FrameTimestamp, KnownCameraControl, KnownCameraControlFlag,
+            PixelAspectRatio, Region, Resolution, Rotation,
         },
     };
+    use std::os::fd::{FromRawFd, OwnedFd};
+    use std::os::unix::io::{AsRawFd, RawFd};
     use std::{
         borrow::Cow,
-        collections::HashMap,
+        cell::OnceCell,
+        collections::{HashMap, VecDeque},
         io::{self, ErrorKind},
+        time::{Duration, Instant},
     };
+    use v4l::v4l2::{self, vidioc};
     use v4l::{
-        control::{Control, Flags, Type, Value},
+        buffer::Metadata,
+        capability::Flags as CapabilityFlags,
+        control::{Control, Description, Flags, Type, Value},
+        fraction::Fraction,
         frameinterval::FrameIntervalEnum,
         framesize::FrameSizeEnum,
-        io::traits::CaptureStream,
+        io::traits::{CaptureStream, Stream},
         prelude::MmapStream,
         video::{capture::Parameters, Capture},
-        Device, Format, FourCC,
+        Device, Format, FourCC, Timestamp,
     };
     use v4l2_sys_mit::{
-        V4L2_CID_BACKLIGHT_COMPENSATION, V4L2_CID_BRIGHTNESS, V4L2_CID_CONTRAST, V4L2_CID_EXPOSURE,
-        V4L2_CID_FOCUS_RELATIVE, V4L2_CID_GAIN, V4L2_CID_GAMMA, V4L2_CID_HUE,
+        v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE, v4l2_buffer, v4l2_cropcap, v4l2_dv_timings,
+        v4l2_edid, v4l2_event, v4l2_event_subscription, v4l2_exportbuffer, v4l2_input,
+        v4l2_jpegcompression, v4l2_memory_V4L2_MEMORY_MMAP, v4l2_rect, v4l2_selection,
+        v4l2_standard, V4L2_CID_BACKLIGHT_COMPENSATION, V4L2_CID_BRIGHTNESS, V4L2_CID_CONTRAST,
+        V4L2_CID_EXPOSURE, V4L2_CID_FOCUS_RELATIVE, V4L2_CID_GAIN, V4L2_CID_GAMMA, V4L2_CID_HUE,
         V4L2_CID_IRIS_RELATIVE, V4L2_CID_PAN_RELATIVE, V4L2_CID_SATURATION, V4L2_CID_SHARPNESS,
         V4L2_CID_TILT_RELATIVE, V4L2_CID_WHITE_BALANCE_TEMPERATURE, V4L2_CID_ZOOM_RELATIVE,
     };
@@ -70,136 +104,2154 @@ mod internal {
             KnownCameraControl::Focus => V4L2_CID_FOCUS_RELATIVE,
             KnownCameraControl::Other(id) => id as u32,
         }
-    }
+    }
+
+    /// Attempts to convert a [`u32`] V4L2 Control ID into a [`KnownCameraControl`]
+    /// If the associated control is not found, this will return `None` (`ColorEnable`, `Roll`)
+    #[allow(clippy::cast_lossless)]
+    pub fn id_to_known_camera_control(id: u32) -> KnownCameraControl {
+        match id {
+            V4L2_CID_BRIGHTNESS => KnownCameraControl::Brightness,
+            V4L2_CID_CONTRAST => KnownCameraControl::Contrast,
+            V4L2_CID_HUE => KnownCameraControl::Hue,
+            V4L2_CID_SATURATION => KnownCameraControl::Saturation,
+            V4L2_CID_SHARPNESS => KnownCameraControl::Sharpness,
+            V4L2_CID_GAMMA => KnownCameraControl::Gamma,
+            V4L2_CID_WHITE_BALANCE_TEMPERATURE => KnownCameraControl::WhiteBalance,
+            V4L2_CID_BACKLIGHT_COMPENSATION => KnownCameraControl::BacklightComp,
+            V4L2_CID_GAIN => KnownCameraControl::Gain,
+            V4L2_CID_PAN_RELATIVE => KnownCameraControl::Pan,
+            V4L2_CID_TILT_RELATIVE => KnownCameraControl::Tilt,
+            V4L2_CID_ZOOM_RELATIVE => KnownCameraControl::Zoom,
+            V4L2_CID_EXPOSURE => KnownCameraControl::Exposure,
+            V4L2_CID_IRIS_RELATIVE => KnownCameraControl::Iris,
+            V4L2_CID_FOCUS_RELATIVE => KnownCameraControl::Focus,
+            id => KnownCameraControl::Other(id as u128),
+        }
+    }
+
+    /// The number of buffers to allocate for a stream.
+    const DEFAULT_BUFFER_COUNT: u32 = 4;
+
+    /// `ENODATA`, returned by V4L2 when the current input does not support a query.
+    const ENODATA: i32 = 61;
+
+    /// `POLLIN` from `poll.h`.
+    const POLLIN: i16 = 0x001;
+
+    /// `POLLPRI` from `poll.h`, which V4L2 raises while events are pending.
+    const POLLPRI: i16 = 0x002;
+
+    /// `V4L2_EVENT_CTRL` and `V4L2_EVENT_SOURCE_CHANGE`, the event types that can be subscribed to.
+    const V4L2_EVENT_CTRL: u32 = 3;
+    const V4L2_EVENT_SOURCE_CHANGE: u32 = 5;
+
+    /// `V4L2_EVENT_SRC_CH_RESOLUTION`, and `V4L2_EVENT_CTRL_CH_RANGE`: what changed, in a source or control change event.
+    const V4L2_EVENT_SRC_CH_RESOLUTION: u32 = 0x1;
+    const V4L2_EVENT_CTRL_CH_RANGE: u32 = 0x4;
+
+    /// `V4L2_CTRL_TYPE_INTEGER64`, the only control type whose event value is 64 bits wide.
+    const V4L2_CTRL_TYPE_INTEGER64: u32 = 5;
+
+    /// `ENODEV`, returned once the device has been unplugged.
+    const ENODEV: i32 = 19;
+
+    /// `EBUSY`, returned for changes while another handle holds a higher priority (or the stream is busy).
+    const EBUSY: i32 = 16;
+
+    /// `EINVAL` and `ENOTTY`, returned for ioctls a driver does not implement.
+    const EINVAL: i32 = 22;
+    const ENOTTY: i32 = 25;
+
+    /// How often to look for an unplugged device while reconnecting.
+    const RECONNECT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+    /// Frames thrown away after switching to the photo format, while auto exposure and white balance settle.
+    const PHOTO_WARMUP_FRAMES: usize = 3;
+
+    type ReconnectCallback = Box<dyn FnMut(ReconnectEvent) + Send + 'static>;
+
+    type EventCallback = Box<dyn FnMut(DeviceEvent) + Send + 'static>;
+
+    /// Calls a raw `ioctl` on the device's file descriptor for functionality `v4l` does not wrap.
+    /// # Safety
+    /// `T` must be the argument type of `request` (e.g. `v4l2_input` for `VIDIOC_ENUMINPUT`), and any pointers inside `argp`
+    /// must be valid for what the driver reads or writes through them. Otherwise, the kernel reads or writes past `argp`.
+    pub(crate) unsafe fn v4l2_ioctl<T>(
+        device: &Device,
+        request: vidioc::_IOC_TYPE,
+        argp: &mut T,
+    ) -> io::Result<()> {
+        // SAFETY: the caller guarantees `argp` is what `request` expects, and the fd stays open as long as `device` does.
+        unsafe { v4l2::ioctl(device.handle().fd(), request, (argp as *mut T).cast()) }
+    }
+
+    /// The `open(2)` flags `VIDIOC_EXPBUF` takes for the exported file descriptor.
+    mod libc_flags {
+        pub const O_RDONLY: i32 = 0o0;
+        pub const O_CLOEXEC: i32 = 0o2_000_000;
+    }
+
+    /// The bytes per pixel of packed formats, for when the driver does not report a stride. Planar formats report the luma plane.
+    fn bytes_per_pixel(format: FrameFormat) -> u32 {
+        match format {
+            FrameFormat::Yuv422 | FrameFormat::Uyv422 => 2,
+            FrameFormat::Rgb8 => 3,
+            FrameFormat::RgbA8 => 4,
+            _ => 1,
+        }
+    }
+
+    /// `V4L2_DV_BT_656_1120`, the only type of DV timings V4L2 currently defines.
+    const V4L2_DV_BT_656_1120: u32 = 0;
+
+    /// Size in bytes of a single EDID block.
+    const EDID_BLOCK_SIZE: usize = 128;
+
+    /// Maps a `v4l2_field` of a frame `height` rows tall to a [`FieldOrder`]. `V4L2_FIELD_ANY` means the driver did not say.
+    fn field_order_from_v4l2(field: u32, height: u32) -> Option<FieldOrder> {
+        match field {
+            1 => Some(FieldOrder::Progressive),
+            2 | 3 | 7 => Some(FieldOrder::SingleField),
+            // `V4L2_FIELD_INTERLACED` leaves the order to the standard: 525 line (NTSC, 480 or 486 visible) is bottom first,
+            // 625 line (PAL, SECAM) is top first.
+            4 if matches!(height, 480 | 486) => Some(FieldOrder::BottomFirst),
+            4 | 8 => Some(FieldOrder::TopFirst),
+            9 => Some(FieldOrder::BottomFirst),
+            5 => Some(FieldOrder::SequentialTopFirst),
+            6 => Some(FieldOrder::SequentialBottomFirst),
+            _ => None,
+        }
+    }
+
+    /// `V4L2_CID_JPEG_COMPRESSION_QUALITY`, the control that replaced `VIDIOC_G/S_JPEGCOMP`.
+    const V4L2_CID_JPEG_COMPRESSION_QUALITY: u32 = 0x009d_0903;
+
+    /// `V4L2_CID_CAMERA_SENSOR_ROTATION`, not in every version of the bindings.
+    const V4L2_CID_CAMERA_SENSOR_ROTATION: u32 = 0x009a_0923;
+
+    /// Reads how the sensor is mounted from `V4L2_CID_CAMERA_SENSOR_ROTATION`, which drivers fill from the firmware
+    /// (e.g. the ACPI `_PLD` or device tree `rotation` property). Most USB webcams do not have it.
+    ///
+    /// V4L2 reports the correction counter-clockwise, [`Rotation`] is clockwise.
+    fn sensor_rotation(device: &Device) -> Option<Rotation> {
+        match device.control(V4L2_CID_CAMERA_SENSOR_ROTATION).ok()?.value {
+            Value::Integer(degrees) => Rotation::from_degrees(-i32::try_from(degrees).ok()?),
+            _ => None,
+        }
+    }
+
+    /// Reads the pixel aspect ratio of `format` from `VIDIOC_CROPCAP`, which analog and DV capture drivers fill from their
+    /// standard. V4L2 reports it as height:width of the unscaled pixels, so it is flipped, then corrected for the scaling from
+    /// the default crop rectangle to `format`.
+    fn pixel_aspect(device: &Device, format: &Format) -> Option<PixelAspectRatio> {
+        // SAFETY: `v4l2_cropcap` is a plain C struct, for which all zeroes is a valid value.
+        let mut cropcap: v4l2_cropcap = unsafe { std::mem::zeroed() };
+        cropcap.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        // SAFETY: `VIDIOC_CROPCAP` takes a `v4l2_cropcap`, which `cropcap` is.
+        unsafe { v4l2_ioctl(device, vidioc::VIDIOC_CROPCAP, &mut cropcap) }.ok()?;
+        let (aspect, crop) = (cropcap.pixelaspect, cropcap.defrect);
+        if aspect.numerator == 0
+            || crop.width == 0
+            || crop.height == 0
+            || format.width == 0
+            || format.height == 0
+        {
+            return None;
+        }
+        let numerator =
+            u64::from(aspect.denominator) * u64::from(crop.width) * u64::from(format.height);
+        let denominator =
+            u64::from(aspect.numerator) * u64::from(crop.height) * u64::from(format.width);
+        let divisor = gcd_u64(numerator, denominator);
+        Some(PixelAspectRatio::new(
+            u32::try_from(numerator / divisor).ok()?,
+            u32::try_from(denominator / divisor).ok()?,
+        ))
+    }
+
+    /// `V4L2_SEL_TGT_CROP`, `V4L2_SEL_TGT_CROP_DEFAULT`, and `V4L2_SEL_TGT_CROP_BOUNDS`: the current, default, and largest
+    /// crop rectangles of the selection API.
+    const V4L2_SEL_TGT_CROP: u32 = 0x0000;
+    const V4L2_SEL_TGT_CROP_DEFAULT: u32 = 0x0001;
+    const V4L2_SEL_TGT_CROP_BOUNDS: u32 = 0x0002;
+
+    /// Reads a capture selection rectangle with `VIDIOC_G_SELECTION`.
+    fn selection(device: &Device, target: u32) -> io::Result<v4l2_rect> {
+        // SAFETY: `v4l2_selection` is a plain C struct, for which all zeroes is a valid value.
+        let mut selection: v4l2_selection = unsafe { std::mem::zeroed() };
+        selection.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE;
+        selection.target = target;
+        // SAFETY: `VIDIOC_G_SELECTION` takes a `v4l2_selection`, which `selection` is.
+        unsafe { v4l2_ioctl(device, vidioc::VIDIOC_G_SELECTION, &mut selection) }?;
+        Ok(selection.r)
+    }
+
+    fn rect_to_region(rect: v4l2_rect) -> Option<Region> {
+        Some(Region::new(
+            u32::try_from(rect.left).ok()?,
+            u32::try_from(rect.top).ok()?,
+            rect.width,
+            rect.height,
+        ))
+    }
+
+    fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a.max(1)
+    }
+
+    /// How long to wait for the UVC metadata of a frame that has already arrived.
+    const METADATA_TIMEOUT: Duration = Duration::from_millis(100);
+
+    /// UVC payload header `bmHeaderInfo` bits for the optional presentation time stamp and source clock reference fields.
+    const UVC_HEADER_PTS: u8 = 0x04;
+    const UVC_HEADER_SCR: u8 = 0x08;
+
+    /// `MetadataId_CaptureStats` from the Microsoft UVC extensions, and its `Flags` bits for the fields read here.
+    const METADATA_ID_CAPTURE_STATS: u32 = 3;
+    const CAPTURE_STATS_FLAG_EXPOSURE_TIME: u32 = 0x1;
+    const CAPTURE_STATS_FLAG_ISO_SPEED: u32 = 0x4;
+
+    /// The UVC metadata node (`V4L2_META_FMT_UVC`) of a camera, streaming alongside its video node. `uvcvideo` fills one
+    /// metadata buffer per frame, with the same sequence number.
+    struct MetadataStream {
+        device: Device,
+        stream: MmapStream<'static>,
+        primed: bool,
+    }
+
+    impl MetadataStream {
+        /// Opens the metadata node that shares a physical device with `/dev/video{index}`, if there is one.
+        fn open(index: usize, buffer_count: u32) -> Option<Self> {
+            let group = device_group(index)?;
+            v4l::context::enum_devices()
+                .into_iter()
+                .filter(|node| {
+                    node.index() != index && device_group(node.index()).as_ref() == Some(&group)
+                })
+                .find_map(|node| {
+                    let device = Device::new(node.index()).ok()?;
+                    // Only metadata nodes accept metadata buffers.
+                    let stream = MmapStream::with_buffers(
+                        &device,
+                        v4l::buffer::Type::MetaCapture,
+                        buffer_count,
+                    )
+                    .ok()?;
+                    Some(MetadataStream {
+                        device,
+                        stream,
+                        primed: false,
+                    })
+                })
+        }
+
+        /// Dequeues metadata until the buffer for frame `sequence` comes up, and reads the capture parameters out of it.
+        fn capture_parameters(&mut self, sequence: u32) -> Option<CaptureParameters> {
+            let mut timeout = METADATA_TIMEOUT;
+            loop {
+                if self.primed {
+                    // Like the video stream, `next()` must not time out once primed, or it would queue a buffer twice.
+                    let millis = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+                    if self.device.handle().poll(POLLIN, millis).ok()? == 0 {
+                        return None;
+                    }
+                } else {
+                    self.stream.set_timeout(timeout);
+                }
+                let (data, meta) = match self.stream.next() {
+                    Ok(next) => next,
+                    Err(_) => {
+                        if !self.primed {
+                            // Every buffer is queued but none was dequeued. Turn streaming off so the next call starts over.
+                            let _ = self.stream.stop();
+                        }
+                        return None;
+                    }
+                };
+                self.primed = true;
+                let used = (meta.bytesused as usize).min(data.len());
+                // Older metadata belongs to frames that were dropped or read without it, newer to frames not read yet.
+                match i32::try_from(sequence.wrapping_sub(meta.sequence)).unwrap_or(i32::MAX) {
+                    0 => return parse_uvc_metadata(&data[..used]),
+                    behind if behind > 0 => timeout = Duration::ZERO,
+                    _ => return None,
+                }
+            }
+        }
+    }
+
+    /// Reads the capture parameters out of a `V4L2_META_FMT_UVC` buffer: a list of `uvc_meta_buf`s, each a timestamp, SOF,
+    /// and the UVC payload header of a packet. Cameras implementing the Microsoft UVC extensions put their capture stats in
+    /// the header, after the standard fields.
+    fn parse_uvc_metadata(mut data: &[u8]) -> Option<CaptureParameters> {
+        let mut parameters = CaptureParameters::new();
+        // `ns` (8 bytes), `sof` (2), then the header starting at `bHeaderLength` (1) and `bmHeaderInfo` (1).
+        while data.len() >= 12 {
+            let header_length = usize::from(data[10]);
+            let header_info = data[11];
+            if header_length < 2 {
+                break;
+            }
+            let end = (10 + header_length).min(data.len());
+            let mut skip = 0;
+            if header_info & UVC_HEADER_PTS != 0 {
+                skip += 4;
+            }
+            if header_info & UVC_HEADER_SCR != 0 {
+                skip += 6;
+            }
+            if let Some(mut items) = data[12..end].get(skip..) {
+                // Metadata items are a `KSCAMERA_METADATA_ITEMHEADER` (`MetadataId`, `Size` including the header) and data.
+                while items.len() >= 8 {
+                    let id = u32::from_le_bytes([items[0], items[1], items[2], items[3]]);
+                    let size =
+                        u32::from_le_bytes([items[4], items[5], items[6], items[7]]) as usize;
+                    if size < 8 || size > items.len() {
+                        break;
+                    }
+                    if id == METADATA_ID_CAPTURE_STATS && size >= 40 {
+                        let field = |offset: usize| {
+                            u32::from_le_bytes([
+                                items[offset],
+                                items[offset + 1],
+                                items[offset + 2],
+                                items[offset + 3],
+                            ])
+                        };
+                        let flags = field(8);
+                        if flags & CAPTURE_STATS_FLAG_EXPOSURE_TIME != 0 {
+                            // In 100ns units.
+                            let exposure = u64::from(field(16)) | (u64::from(field(20)) << 32);
+                            parameters = parameters.with_exposure_time(Duration::from_nanos(
+                                exposure.saturating_mul(100),
+                            ));
+                        }
+                        if flags & CAPTURE_STATS_FLAG_ISO_SPEED != 0 {
+                            parameters = parameters.with_iso_speed(field(36));
+                        }
+                    }
+                    items = &items[size..];
+                }
+            }
+            data = &data[end..];
+        }
+        (!parameters.is_empty()).then_some(parameters)
+    }
+
+    fn device_event_from_raw(raw: &v4l2_event) -> Option<DeviceEvent> {
+        match raw.type_ {
+            V4L2_EVENT_SOURCE_CHANGE => {
+                // SAFETY: the driver fills `u.src_change` for `V4L2_EVENT_SOURCE_CHANGE` events.
+                let changes = unsafe { raw.u.src_change.changes };
+                Some(DeviceEvent::SourceChanged {
+                    resolution_changed: changes & V4L2_EVENT_SRC_CH_RESOLUTION != 0,
+                })
+            }
+            V4L2_EVENT_CTRL => {
+                // SAFETY: the driver fills `u.ctrl` for `V4L2_EVENT_CTRL` events.
+                let ctrl = unsafe { raw.u.ctrl };
+                // SAFETY: the value is an `i64` for 64 bit controls and an `i32` otherwise. Both are plain integers, so
+                // reading either half of the union is sound.
+                let value = if ctrl.type_ == V4L2_CTRL_TYPE_INTEGER64 {
+                    unsafe { ctrl.__bindgen_anon_1.value64 }
+                } else {
+                    i64::from(unsafe { ctrl.__bindgen_anon_1.value })
+                };
+                Some(DeviceEvent::ControlChanged {
+                    control: id_to_known_camera_control(raw.id),
+                    value,
+                    range_changed: ctrl.changes & V4L2_EVENT_CTRL_CH_RANGE != 0,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn dv_timings_from_raw(raw: &v4l2_dv_timings) -> DvTimings {
+        // SAFETY: `V4L2_DV_BT_656_1120` is the only type of timings, so the union always holds `bt`, which is plain integers.
+        let bt = unsafe { raw.__bindgen_anon_1.bt };
+        DvTimings {
+            width: bt.width,
+            height: bt.height,
+            interlaced: bt.interlaced != 0,
+            polarities: bt.polarities,
+            pixel_clock: bt.pixelclock,
+            h_front_porch: bt.hfrontporch,
+            h_sync: bt.hsync,
+            h_back_porch: bt.hbackporch,
+            v_front_porch: bt.vfrontporch,
+            v_sync: bt.vsync,
+            v_back_porch: bt.vbackporch,
+            il_v_front_porch: bt.il_vfrontporch,
+            il_v_sync: bt.il_vsync,
+            il_v_back_porch: bt.il_vbackporch,
+            standards: bt.standards,
+            flags: bt.flags,
+        }
+    }
+
+    fn dv_timings_to_raw(timings: &DvTimings) -> v4l2_dv_timings {
+        // SAFETY: `v4l2_dv_timings` is a plain C struct, for which all zeroes is a valid value.
+        let mut raw: v4l2_dv_timings = unsafe { std::mem::zeroed() };
+        raw.type_ = V4L2_DV_BT_656_1120;
+        // SAFETY: the union only holds plain integers, and `type_` was just set to say it holds `bt`.
+        let bt = unsafe { &mut raw.__bindgen_anon_1.bt };
+        bt.width = timings.width;
+        bt.height = timings.height;
+        bt.interlaced = u32::from(timings.interlaced);
+        bt.polarities = timings.polarities;
+        bt.pixelclock = timings.pixel_clock;
+        bt.hfrontporch = timings.h_front_porch;
+        bt.hsync = timings.h_sync;
+        bt.hbackporch = timings.h_back_porch;
+        bt.vfrontporch = timings.v_front_porch;
+        bt.vsync = timings.v_sync;
+        bt.vbackporch = timings.v_back_porch;
+        bt.il_vfrontporch = timings.il_v_front_porch;
+        bt.il_vsync = timings.il_v_sync;
+        bt.il_vbackporch = timings.il_v_back_porch;
+        bt.standards = timings.standards;
+        bt.flags = timings.flags;
+        raw
+    }
+
+    /// A borrowed V4L2 file descriptor, so that it can be registered with an async reactor.
+    /// This does **not** close the file descriptor when dropped.
+    #[cfg(feature = "async")]
+    struct DeviceFd(RawFd);
+
+    #[cfg(feature = "async")]
+    impl AsRawFd for DeviceFd {
+        fn as_raw_fd(&self) -> RawFd {
+            self.0
+        }
+    }
+
+    /// Clears `O_NONBLOCK` on a file descriptor.
+    #[cfg(feature = "async")]
+    fn set_blocking(fd: RawFd) -> io::Result<()> {
+        // SAFETY: F_GETFL and F_SETFL only read and write the status flags of `fd`.
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags < 0 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Turns a nul-terminated fixed-size V4L2 name field into a `String`.
+    fn v4l2_str(raw: &[u8]) -> String {
+        let end = raw.iter().position(|b| *b == 0).unwrap_or(raw.len());
+        String::from_utf8_lossy(&raw[..end]).to_string()
+    }
+
+    /// Finds the physical device a `/dev/videoN` node belongs to using sysfs.
+    ///
+    /// For USB devices, the node's `device` link points at a USB interface (e.g. `.../1-2/1-2:1.0`), so the parent of that
+    /// is the USB device itself, which is shared between all the sensors (RGB, IR) of a webcam.
+    /// For other devices (e.g. platform cameras behind a media controller), the `device` link is already shared.
+    fn device_group(index: usize) -> Option<String> {
+        physical_device(&format!("/sys/class/video4linux/video{index}/device"))
+    }
+
+    /// Resolves the sysfs `device` link of a video or sound node to the physical device it belongs to. See [`device_group()`].
+    fn physical_device(device_link: &str) -> Option<String> {
+        let device_path = std::fs::canonicalize(device_link).ok()?;
+        let is_usb_interface = device_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| name.contains(':'));
+        let group_path = if is_usb_interface {
+            device_path.parent()?
+        } else {
+            device_path.as_path()
+        };
+        Some(group_path.to_string_lossy().to_string())
+    }
+
+    /// Builds the [`DriverInfo`] of `/dev/video{index}` from sysfs. `driver` and `version` come from `VIDIOC_QUERYCAP` if the
+    /// device was opened, otherwise the module name stands in for the driver name.
+    fn driver_info(
+        index: usize,
+        driver: Option<&str>,
+        version: Option<(u8, u8, u8)>,
+    ) -> Option<DriverInfo> {
+        let module = std::fs::canonicalize(format!(
+            "/sys/class/video4linux/video{index}/device/driver/module"
+        ))
+        .ok()
+        .and_then(|path| Some(path.file_name()?.to_string_lossy().to_string()));
+        let name = driver.map(ToString::to_string).or_else(|| module.clone())?;
+        // USB devices report their firmware revision as the BCD encoded `bcdDevice`, e.g. `0011` for 0.11.
+        let firmware = device_group(index)
+            .and_then(|device| std::fs::read_to_string(format!("{device}/bcdDevice")).ok())
+            .map(|bcd| {
+                let bcd = bcd.trim();
+                if bcd.len() != 4 {
+                    return bcd.to_string();
+                }
+                let (major, minor) = bcd.split_at(2);
+                let major = major.trim_start_matches('0');
+                format!("{}.{minor}", if major.is_empty() { "0" } else { major })
+            });
+        Some(
+            DriverInfo::new(&name)
+                .with_version(
+                    version.map(|(major, minor, patch)| format!("{major}.{minor}.{patch}")),
+                )
+                .with_module(module)
+                .with_firmware(firmware),
+        )
+    }
+
+    /// Finds the ALSA sound card that belongs to the same physical device as the camera at `index`, e.g. the microphone of a USB webcam.
+    ///
+    /// Like [`probe()`], this only reads sysfs and does not open the camera. The [`AudioDeviceInfo::id()`] is the ALSA device name
+    /// (`hw:CARD=<id>`), which `cpal`, `PulseAudio`, and `PipeWire` all accept or list.
+    /// # Errors
+    /// If the index is not a number, this will error. A camera without a microphone is `Ok(None)`.
+    pub fn associated_audio_device(
+        index: &CameraIndex,
+    ) -> Result<Option<AudioDeviceInfo>, NokhwaError> {
+        let index_num = index.as_index()?;
+        let Some(group) = device_group(index_num as usize) else {
+            return Ok(None);
+        };
+        let Ok(cards) = std::fs::read_dir("/sys/class/sound") else {
+            return Ok(None);
+        };
+        let card = cards.flatten().find_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let number = name.strip_prefix("card")?.parse::<u32>().ok()?;
+            let card_group = physical_device(&format!("/sys/class/sound/{name}/device"))?;
+            (card_group == group).then_some(number)
+        });
+        Ok(card.map(|number| {
+            let id = std::fs::read_to_string(format!("/sys/class/sound/card{number}/id"))
+                .map(|id| id.trim().to_string())
+                .unwrap_or_else(|_| number.to_string());
+            let name = alsa_card_name(number).unwrap_or_else(|| id.clone());
+            AudioDeviceInfo::new(&format!("hw:CARD={id}"), &name)
+        }))
+    }
+
+    /// Reads the long name of ALSA card `number` from `/proc/asound/cards`, whose lines look like
+    /// ` 1 [C920           ]: USB-Audio - HD Pro Webcam C920`.
+    fn alsa_card_name(number: u32) -> Option<String> {
+        let cards = std::fs::read_to_string("/proc/asound/cards").ok()?;
+        cards.lines().find_map(|line| {
+            let (card, rest) = line.trim_start().split_once(' ')?;
+            if card.parse::<u32>().ok()? != number {
+                return None;
+            }
+            let (_, description) = rest.split_once("]: ")?;
+            let name = description
+                .split_once(" - ")
+                .map_or(description, |(_, name)| name);
+            Some(name.trim().to_string())
+        })
+    }
+
+    /// query v4l2 cameras
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn query() -> Result<Vec<CameraInfo>, NokhwaError> {
+        Ok(v4l::context::enum_devices().iter().map(node_info).collect())
+    }
+
+    /// Gets the [`CameraInfo`] of one camera without opening it, so it is cheap and works even if another process is capturing.
+    ///
+    /// Like [`query()`], this only reads sysfs, so the description does not include the driver (use
+    /// [`V4LCaptureDevice::new()`] for that).
+    /// # Errors
+    /// If the index is not a number or there is no `/dev/videoN` node for it, this will error.
+    pub fn probe(index: &CameraIndex) -> Result<CameraInfo, NokhwaError> {
+        let index_num = index.as_index()?;
+        let node = v4l::context::Node::new(format!("/dev/video{index_num}"));
+        if !node.path().exists() {
+            return Err(NokhwaError::OpenDeviceError(
+                node.path().to_string_lossy().to_string(),
+                "Not Found".to_string(),
+            ));
+        }
+        Ok(node_info(&node))
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn node_info(node: &v4l::context::Node) -> CameraInfo {
+        let mut info = CameraInfo::new(
+            &node
+                .name()
+                .unwrap_or(format!("{}", node.path().to_string_lossy())),
+            &format!("Video4Linux Device @ {}", node.path().to_string_lossy()),
+            "",
+            &CameraIndex::Index(node.index() as u32),
+        );
+        info.set_group(device_group(node.index()).as_deref());
+        info.set_driver(driver_info(node.index(), None, None));
+        info
+    }
+
+    /// The backend struct that interfaces with V4L2.
+    /// To see what this does, please see [`CaptureTrait`].
+    /// # Quirks
+    /// - Calling [`set_resolution()`](CaptureTrait::set_resolution), [`set_frame_rate()`](CaptureTrait::set_frame_rate), or [`set_frame_format()`](CaptureTrait::set_frame_format) each internally calls [`set_camera_format()`](CaptureTrait::set_camera_format).
+    /// # Performance
+    /// Opening a device only queries its capabilities. The supported formats and controls are enumerated the first time they are
+    /// asked for and then cached, as some drivers take tens of milliseconds to list them. Switching inputs, standards, or DV timings
+    /// clears the cache. To list cameras without opening any of them, use [`query()`] or [`probe()`].
+    /// # Thread Safety
+    /// This is [`Send`]: it owns its device handle and stream, so it can be moved into a worker thread. It is not [`Sync`],
+    /// capturing needs `&mut self`. Share it between threads behind a `Mutex` if you must.
+    pub struct V4LCaptureDevice {
+        init: bool,
+        camera_format: Option<CameraFormat>,
+        camera_info: CameraInfo,
+        // The device registered with the `async-io` reactor, kept for the stream. Declared before `device`, so it is
+        // deregistered before the file descriptor is closed.
+        #[cfg(feature = "async")]
+        async_source: Option<async_io::Async<DeviceFd>>,
+        device: Device,
+        // The buffers are mmap-ed and owned by the stream itself, so it does not borrow from anything.
+        stream_handle: Option<MmapStream<'static>>,
+        last_sequence: Option<u32>,
+        dropped_frames: u64,
+        read_timeout: Option<Duration>,
+        buffer_count: u32,
+        granted_buffer_count: Option<u32>,
+        // Whether streaming is on, with every buffer but the one `next()` gives back first queued on the driver.
+        primed: bool,
+        paused: bool,
+        bus_info: String,
+        applied_controls: HashMap<KnownCameraControl, ControlValueSetter>,
+        reconnect_timeout: Option<Duration>,
+        reconnect_callback: Option<ReconnectCallback>,
+        event_callback: Option<EventCallback>,
+        disconnected: bool,
+        watchdog: Option<(Duration, WatchdogAction)>,
+        stride: Option<u32>,
+        pixel_aspect: Option<PixelAspectRatio>,
+        frame_metadata: bool,
+        metadata_stream: Option<MetadataStream>,
+        // The priority set with `set_priority()`, kept to set it again on reconnect.
+        priority: Option<DevicePriority>,
+        // The indexes of the buffers queued on the driver, in the order they were queued. Capture drivers fill buffers first
+        // in, first out, so this tells which buffer a dequeued frame is in.
+        queued_buffers: VecDeque<u32>,
+        last_buffer_index: Option<u32>,
+        // Enumerating formats and controls is slow on some drivers, so they are queried on first use and cached.
+        fourcc_cache: Option<Vec<FrameFormat>>,
+        resolution_cache: HashMap<FrameFormat, HashMap<Resolution, Vec<u32>>>,
+        control_descriptions: OnceCell<Vec<Description>>,
+        // Reused by `frame_rgb_ref()`, so decoding does not allocate once it has grown to the size of the format.
+        #[cfg(feature = "image")]
+        rgb_scratch: Vec<u8>,
+    }
+
+    const _: fn() = || {
+        fn assert_send<T: Send>() {}
+        assert_send::<V4LCaptureDevice>();
+    };
+
+    impl V4LCaptureDevice {
+        /// Creates a new capture device using the `V4L2` backend. Indexes are gives to devices by the OS, and usually numbered by order of discovery.
+        /// # Errors
+        /// This function will error if the camera is currently busy or if `V4L2` can't read device information.
+        #[allow(clippy::too_many_lines)]
+        pub fn new(index: &CameraIndex) -> Result<Self, NokhwaError> {
+            let index_num = index.as_index()?;
+            let device = Device::new(index_num as usize)
+                .map_err(|why| NokhwaError::from_io(format!("Open device {index_num}"), why))?;
+
+            let (camera_info, bus_info) = match device.query_caps() {
+                Ok(caps) => {
+                    let mut info = CameraInfo::new(
+                        &caps.card,
+                        &format!("{}: {} {}", caps.driver, caps.card, caps.bus),
+                        &format!("{} {:?}", caps.capabilities, caps.version),
+                        index,
+                    );
+                    info.set_group(device_group(index_num as usize).as_deref());
+                    info.set_rotation(sensor_rotation(&device));
+                    info.set_driver(driver_info(
+                        index_num as usize,
+                        Some(&caps.driver),
+                        Some(caps.version),
+                    ));
+                    (info, caps.bus)
+                }
+                Err(why) => {
+                    return Err(NokhwaError::GetPropertyError {
+                        property: "Capabilities".to_string(),
+                        error: why.to_string(),
+                    })
+                }
+            };
+
+            Ok(V4LCaptureDevice {
+                init: false,
+                camera_format: None,
+                camera_info,
+                #[cfg(feature = "async")]
+                async_source: None,
+                device,
+                stream_handle: None,
+                last_sequence: None,
+                dropped_frames: 0,
+                read_timeout: None,
+                buffer_count: DEFAULT_BUFFER_COUNT,
+                granted_buffer_count: None,
+                primed: false,
+                paused: false,
+                bus_info,
+                applied_controls: HashMap::new(),
+                reconnect_timeout: None,
+                reconnect_callback: None,
+                event_callback: None,
+                disconnected: false,
+                watchdog: None,
+                stride: None,
+                pixel_aspect: None,
+                frame_metadata: false,
+                metadata_stream: None,
+                priority: None,
+                queued_buffers: VecDeque::new(),
+                last_buffer_index: None,
+                fourcc_cache: None,
+                resolution_cache: HashMap::new(),
+                control_descriptions: OnceCell::new(),
+                #[cfg(feature = "image")]
+                rgb_scratch: Vec::new(),
+            })
+        }
+
+        /// Force refreshes the inner [`CameraFormat`] state.
+        /// # Errors
+        /// If the internal representation in the driver is invalid, this will error.
+        pub fn force_refresh_camera_format(&mut self) -> Result<(), NokhwaError> {
+            match self.device.format() {
+                Ok(format) => {
+                    let frame_format = fourcc_to_frameformat(format.fourcc).ok_or(
+                        NokhwaError::GetPropertyError {
+                            property: "FrameFormat".to_string(),
+                            error: "unsupported".to_string(),
+                        },
+                    )?;
+
+                    let frame_rate = match self.device.params() {
+                        Ok(params) => FrameRate::from_interval(
+                            params.interval.numerator,
+                            params.interval.denominator,
+                        ),
+                        Err(why) => {
+                            return Err(NokhwaError::GetPropertyError {
+                                property: "V4L2 FrameRate".to_string(),
+                                error: why.to_string(),
+                            })
+                        }
+                    };
+
+                    self.camera_format = Some(CameraFormat::new(
+                        Resolution::new(format.width, format.height),
+                        frame_format,
+                        frame_rate,
+                    ));
+                    Ok(())
+                }
+                Err(why) => Err(NokhwaError::GetPropertyError {
+                    property: "parameters".to_string(),
+                    error: why.to_string(),
+                }),
+            }
+        }
+
+        /// Forgets the cached formats and controls, so they are enumerated again the next time they are asked for.
+        ///
+        /// This is done automatically when switching inputs, standards, or DV timings. Call it if the device changed in some
+        /// other way, e.g. a control that changes the supported formats.
+        pub fn clear_capability_cache(&mut self) {
+            self.fourcc_cache = None;
+            self.resolution_cache.clear();
+            self.control_descriptions = OnceCell::new();
+        }
+
+        fn cached_control_descriptions(&self) -> Result<&[Description], NokhwaError> {
+            if let Some(descriptions) = self.control_descriptions.get() {
+                return Ok(descriptions);
+            }
+            let descriptions =
+                self.device
+                    .query_controls()
+                    .map_err(|why| NokhwaError::GetPropertyError {
+                        property: "V4L2 Controls".to_string(),
+                        error: why.to_string(),
+                    })?;
+            Ok(self.control_descriptions.get_or_init(|| descriptions))
+        }
+    }
+
+    impl V4LCaptureDevice {
+        /// Lists the exact frame rates the device supports for a [`FrameFormat`] and [`Resolution`], e.g. `30000/1001` for NTSC.
+        ///
+        /// Unlike [`compatible_list_by_resolution()`](CaptureTrait::compatible_list_by_resolution), these are not rounded to whole numbers.
+        /// # Errors
+        /// If the [`FrameFormat`] is not supported or the driver fails to enumerate the frame intervals, this will error.
+        pub fn compatible_frame_rates(
+            &self,
+            format: FrameFormat,
+            resolution: Resolution,
+        ) -> Result<Vec<FrameRate>, NokhwaError> {
+            let fourcc = frameformat_to_fourcc(format).ok_or(NokhwaError::GetPropertyError {
+                property: "FrameFormat".to_string(),
+                error: format!("{format} is unsupported"),
+            })?;
+            self.frame_rates(fourcc, resolution)
+        }
+
+        fn frame_rates(
+            &self,
+            fourcc: FourCC,
+            resolution: Resolution,
+        ) -> Result<Vec<FrameRate>, NokhwaError> {
+            let intervals = self
+                .device
+                .enum_frameintervals(fourcc, resolution.width(), resolution.height())
+                .map_err(|why| NokhwaError::GetPropertyError {
+                    property: "Frame rate".to_string(),
+                    error: why.to_string(),
+                })?;
+            Ok(intervals
+                .into_iter()
+                .flat_map(|interval| match interval.interval {
+                    FrameIntervalEnum::Discrete(dis) => {
+                        vec![FrameRate::from_interval(dis.numerator, dis.denominator)]
+                    }
+                    // Like stepwise frame sizes, only report the bounds.
+                    FrameIntervalEnum::Stepwise(step) => vec![
+                        FrameRate::from_interval(step.max.numerator, step.max.denominator),
+                        FrameRate::from_interval(step.min.numerator, step.min.denominator),
+                    ],
+                })
+                .collect())
+        }
+
+        /// Lists the inputs (e.g. HDMI, Composite, S-Video connectors) of this device.
+        /// # Errors
+        /// If the driver fails to enumerate the inputs, this will error.
+        pub fn inputs(&self) -> Result<Vec<V4L2Input>, NokhwaError> {
+            let mut inputs = vec![];
+            for index in 0.. {
+                // SAFETY: `v4l2_input` is a plain C struct, for which all zeroes is a valid value.
+                let mut raw_input: v4l2_input = unsafe { std::mem::zeroed() };
+                raw_input.index = index;
+                // SAFETY: `VIDIOC_ENUMINPUT` takes a `v4l2_input`, which `raw_input` is.
+                let result =
+                    unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_ENUMINPUT, &mut raw_input) };
+                match result {
+                    Ok(()) => inputs.push(V4L2Input::new(
+                        raw_input.index,
+                        &v4l2_str(&raw_input.name),
+                        V4L2InputType::from(raw_input.type_),
+                        raw_input.std,
+                        raw_input.status,
+                        raw_input.capabilities,
+                    )),
+                    // EINVAL marks the end of the list
+                    Err(why) if why.kind() == ErrorKind::InvalidInput => break,
+                    Err(why) => {
+                        return Err(NokhwaError::GetPropertyError {
+                            property: "V4L2 Inputs".to_string(),
+                            error: why.to_string(),
+                        })
+                    }
+                }
+            }
+            Ok(inputs)
+        }
+
+        /// Gets the index of the currently selected input.
+        /// # Errors
+        /// If the driver fails to report the current input, this will error.
+        pub fn input(&self) -> Result<u32, NokhwaError> {
+            let mut index: u32 = 0;
+            // SAFETY: `VIDIOC_G_INPUT` takes an `int`, and `index` is a `u32` of the same size.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_G_INPUT, &mut index) }.map_err(
+                |why| NokhwaError::GetPropertyError {
+                    property: "V4L2 Input".to_string(),
+                    error: why.to_string(),
+                },
+            )?;
+            Ok(index)
+        }
+
+        /// Selects the input (connector) to capture from. See [`inputs()`](Self::inputs) for a list of inputs.
+        ///
+        /// This will refresh the stored [`CameraFormat`], as the new input may have a different format.
+        /// # Errors
+        /// If the index is out of range, or the device is currently streaming (most drivers do not allow switching inputs while streaming), this will error.
+        pub fn set_input(&mut self, index: u32) -> Result<(), NokhwaError> {
+            let mut index_mut = index;
+            // SAFETY: `VIDIOC_S_INPUT` takes an `int`, and `index_mut` is a `u32` of the same size.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_S_INPUT, &mut index_mut) }.map_err(
+                |why| NokhwaError::SetPropertyError {
+                    property: "V4L2 Input".to_string(),
+                    value: index.to_string(),
+                    error: why.to_string(),
+                },
+            )?;
+            self.clear_capability_cache();
+            self.force_refresh_camera_format()
+        }
+
+        /// Lists the analog video standards (e.g. PAL, NTSC, SECAM) the current input supports.
+        ///
+        /// Digital devices (e.g. webcams, HDMI capture) will usually return an empty list.
+        /// # Errors
+        /// If the driver fails to enumerate the standards, this will error.
+        pub fn standards(&self) -> Result<Vec<V4L2Standard>, NokhwaError> {
+            let mut standards = vec![];
+            for index in 0.. {
+                // SAFETY: `v4l2_standard` is a plain C struct, for which all zeroes is a valid value.
+                let mut raw_std: v4l2_standard = unsafe { std::mem::zeroed() };
+                raw_std.index = index;
+                // SAFETY: `VIDIOC_ENUMSTD` takes a `v4l2_standard`, which `raw_std` is.
+                match unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_ENUMSTD, &mut raw_std) } {
+                    Ok(()) => standards.push(V4L2Standard::new(
+                        raw_std.index,
+                        raw_std.id,
+                        &v4l2_str(&raw_std.name),
+                        (
+                            raw_std.frameperiod.numerator,
+                            raw_std.frameperiod.denominator,
+                        ),
+                        raw_std.framelines,
+                    )),
+                    // EINVAL marks the end of the list, ENODATA means the input has no standards.
+                    Err(why)
+                        if why.kind() == ErrorKind::InvalidInput
+                            || why.raw_os_error() == Some(ENODATA) =>
+                    {
+                        break
+                    }
+                    Err(why) => {
+                        return Err(NokhwaError::GetPropertyError {
+                            property: "V4L2 Standards".to_string(),
+                            error: why.to_string(),
+                        })
+                    }
+                }
+            }
+            Ok(standards)
+        }
+
+        /// Gets the currently selected analog video standard as a raw `v4l2_std_id`.
+        /// # Errors
+        /// If the current input does not support video standards, this will error.
+        pub fn standard(&self) -> Result<u64, NokhwaError> {
+            let mut std_id: u64 = 0;
+            // SAFETY: `VIDIOC_G_STD` takes a `v4l2_std_id` (a `u64`), which `std_id` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_G_STD, &mut std_id) }.map_err(
+                |why| NokhwaError::GetPropertyError {
+                    property: "V4L2 Standard".to_string(),
+                    error: why.to_string(),
+                },
+            )?;
+            Ok(std_id)
+        }
+
+        /// Asks the driver to sense the analog video standard of the incoming signal. The result may contain more than one standard.
+        /// # Errors
+        /// If the driver does not support sensing, or there is no signal, this will error.
+        pub fn query_standard(&self) -> Result<u64, NokhwaError> {
+            let mut std_id: u64 = 0;
+            // SAFETY: `VIDIOC_QUERYSTD` takes a `v4l2_std_id` (a `u64`), which `std_id` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_QUERYSTD, &mut std_id) }.map_err(
+                |why| NokhwaError::GetPropertyError {
+                    property: "V4L2 Standard (Sensed)".to_string(),
+                    error: why.to_string(),
+                },
+            )?;
+            Ok(std_id)
+        }
+
+        /// Selects the analog video standard of the current input.
+        ///
+        /// This will refresh the stored [`CameraFormat`], as the standard decides the resolution and frame rate.
+        /// # Errors
+        /// If the standard is not supported by the current input, or the device is currently streaming, this will error.
+        pub fn set_standard(&mut self, standard: AnalogVideoStandard) -> Result<(), NokhwaError> {
+            let mut std_id = standard.std_id();
+            // SAFETY: `VIDIOC_S_STD` takes a `v4l2_std_id` (a `u64`), which `std_id` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_S_STD, &mut std_id) }.map_err(
+                |why| NokhwaError::SetPropertyError {
+                    property: "V4L2 Standard".to_string(),
+                    value: standard.to_string(),
+                    error: why.to_string(),
+                },
+            )?;
+            self.clear_capability_cache();
+            self.force_refresh_camera_format()
+        }
+
+        /// Gets the EDID the current input advertises to the connected source (e.g. the HDMI output of a console or PC).
+        /// # Errors
+        /// If the current input does not have an EDID (e.g. not an HDMI capture device), this will error.
+        pub fn edid(&self) -> Result<Vec<u8>, NokhwaError> {
+            let map_err = |why: io::Error| NokhwaError::GetPropertyError {
+                property: "V4L2 EDID".to_string(),
+                error: why.to_string(),
+            };
+
+            // Asking for 0 blocks makes the driver tell us how many blocks there are.
+            // SAFETY: `v4l2_edid` is a plain C struct, for which all zeroes is a valid value.
+            let mut raw_edid: v4l2_edid = unsafe { std::mem::zeroed() };
+            raw_edid.pad = self.input()?;
+            // SAFETY: `VIDIOC_G_EDID` takes a `v4l2_edid`. With `blocks` at 0, the driver only reports the block count
+            // and does not write to `edid`.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_G_EDID, &mut raw_edid) }
+                .map_err(map_err)?;
+
+            let mut edid = vec![0_u8; raw_edid.blocks as usize * EDID_BLOCK_SIZE];
+            if edid.is_empty() {
+                return Ok(edid);
+            }
+            raw_edid.start_block = 0;
+            raw_edid.edid = edid.as_mut_ptr();
+            // SAFETY: `VIDIOC_G_EDID` takes a `v4l2_edid`, and writes at most `blocks` blocks of 128 bytes to `edid`,
+            // which points into the live `Vec` of exactly that size.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_G_EDID, &mut raw_edid) }
+                .map_err(map_err)?;
+            Ok(edid)
+        }
+
+        /// Sets the EDID the current input advertises to the connected source. This lets you force the source into e.g. 1080p60 or 4K output modes.
+        ///
+        /// The `edid` must be a whole number of 128 byte blocks. An empty `edid` disables the EDID, which usually makes the source believe nothing is connected.
+        /// # Errors
+        /// If the `edid` is not a multiple of 128 bytes, the current input does not support EDIDs, or the driver rejects the EDID, this will error.
+        #[allow(clippy::cast_possible_truncation)]
+        pub fn set_edid(&mut self, edid: &[u8]) -> Result<(), NokhwaError> {
+            if edid.len() % EDID_BLOCK_SIZE != 0 {
+                return Err(NokhwaError::SetPropertyError {
+                    property: "V4L2 EDID".to_string(),
+                    value: format!("{} bytes", edid.len()),
+                    error: "EDID must be a multiple of 128 bytes".to_string(),
+                });
+            }
+
+            let mut edid_mut = edid.to_vec();
+            // SAFETY: `v4l2_edid` is a plain C struct, for which all zeroes is a valid value.
+            let mut raw_edid: v4l2_edid = unsafe { std::mem::zeroed() };
+            raw_edid.pad = self.input()?;
+            raw_edid.blocks = (edid.len() / EDID_BLOCK_SIZE) as u32;
+            raw_edid.edid = edid_mut.as_mut_ptr();
+            // SAFETY: `VIDIOC_S_EDID` takes a `v4l2_edid`, and reads `blocks` blocks of 128 bytes from `edid`, which
+            // points into `edid_mut`, of exactly that size.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_S_EDID, &mut raw_edid) }.map_err(
+                |why| NokhwaError::SetPropertyError {
+                    property: "V4L2 EDID".to_string(),
+                    value: format!("{} blocks", raw_edid.blocks),
+                    error: why.to_string(),
+                },
+            )
+        }
+
+        /// Gets the [`DvTimings`] the device is currently configured to receive.
+        /// # Errors
+        /// If the current input is not a digital video input, this will error.
+        pub fn dv_timings(&self) -> Result<DvTimings, NokhwaError> {
+            // SAFETY: `v4l2_dv_timings` is a plain C struct, for which all zeroes is a valid value.
+            let mut raw_timings: v4l2_dv_timings = unsafe { std::mem::zeroed() };
+            // SAFETY: `VIDIOC_G_DV_TIMINGS` takes a `v4l2_dv_timings`, which `raw_timings` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_G_DV_TIMINGS, &mut raw_timings) }
+                .map_err(|why| NokhwaError::GetPropertyError {
+                    property: "V4L2 DV Timings".to_string(),
+                    error: why.to_string(),
+                })?;
+            Ok(dv_timings_from_raw(&raw_timings))
+        }
+
+        /// Asks the device to detect the [`DvTimings`] of the incoming signal.
+        /// # Errors
+        /// If there is no signal, the signal is unstable, the timings are out of range, or the current input is not a digital video input, this will error.
+        pub fn query_dv_timings(&self) -> Result<DvTimings, NokhwaError> {
+            // SAFETY: `v4l2_dv_timings` is a plain C struct, for which all zeroes is a valid value.
+            let mut raw_timings: v4l2_dv_timings = unsafe { std::mem::zeroed() };
+            // SAFETY: `VIDIOC_QUERY_DV_TIMINGS` takes a `v4l2_dv_timings`, which `raw_timings` is.
+            unsafe {
+                v4l2_ioctl(
+                    &self.device,
+                    vidioc::VIDIOC_QUERY_DV_TIMINGS,
+                    &mut raw_timings,
+                )
+            }
+            .map_err(|why| NokhwaError::GetPropertyError {
+                property: "V4L2 DV Timings (Detected)".to_string(),
+                error: why.to_string(),
+            })?;
+            Ok(dv_timings_from_raw(&raw_timings))
+        }
+
+        /// Sets the [`DvTimings`] the device should receive. Usually, you want to pass the result of [`query_dv_timings()`](Self::query_dv_timings) here after the source changed.
+        ///
+        /// This will refresh the stored [`CameraFormat`], as the timings decide the resolution.
+        /// # Errors
+        /// If the timings are not supported, or the device is currently streaming, this will error.
+        pub fn set_dv_timings(&mut self, timings: DvTimings) -> Result<(), NokhwaError> {
+            let mut raw_timings = dv_timings_to_raw(&timings);
+            // SAFETY: `VIDIOC_S_DV_TIMINGS` takes a `v4l2_dv_timings`, which `raw_timings` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_S_DV_TIMINGS, &mut raw_timings) }
+                .map_err(|why| NokhwaError::SetPropertyError {
+                    property: "V4L2 DV Timings".to_string(),
+                    value: timings.to_string(),
+                    error: why.to_string(),
+                })?;
+            self.clear_capability_cache();
+            self.force_refresh_camera_format()
+        }
+
+        /// Waits, without blocking the thread, until the device has a frame ready to be dequeued.
+        /// The device is registered with the `async-io` reactor once per stream, so this works with any executor.
+        #[cfg(feature = "async")]
+        async fn frame_ready(&mut self) -> Result<(), NokhwaError> {
+            if self.async_source.is_none() {
+                let fd = self.as_raw_fd();
+                let source = async_io::Async::new(DeviceFd(fd))
+                    .map_err(|why| NokhwaError::from_io("Register device with reactor", why))?;
+                // `Async::new()` makes the file descriptor non-blocking, which the blocking reads of `frame()` do not
+                // expect. Only readiness is waited for here, so switch it back.
+                set_blocking(fd).map_err(|why| NokhwaError::from_io("Set device blocking", why))?;
+                self.async_source = Some(source);
+            }
+            if let Some(source) = &self.async_source {
+                source
+                    .readable()
+                    .await
+                    .map_err(|why| NokhwaError::from_io("Wait for frame", why))?;
+            }
+            Ok(())
+        }
+
+        /// Opens the stream if needed and waits until a frame is ready. A paused stream returns right away, so reading the
+        /// frame errors.
+        #[cfg(feature = "async")]
+        async fn stream_ready(&mut self) -> Result<(), NokhwaError> {
+            if !self.is_stream_open() {
+                self.open_stream()?;
+            }
+            if self.paused {
+                return Ok(());
+            }
+            // A single buffer stream is only started by a blocking read, see `start_streaming()`.
+            if !self.primed {
+                return Err(NokhwaError::ReadFrameError(
+                    "Streaming is off, a stream needs at least 2 buffers to be awaited".to_string(),
+                ));
+            }
+            self.frame_ready().await
+        }
+
+        /// Dequeues the next frame into a [`Buffer`], waiting as long as it takes.
+        fn dequeue_frame(&mut self) -> Result<Buffer, NokhwaError> {
+            let cam_fmt = self.current_format()?;
+            let stride = self.stride;
+            let pixel_aspect = self.pixel_aspect;
+            let (raw_frame, meta) = self.next_buffer()?;
+            let capture = timestamp_to_duration(meta.timestamp);
+            let mut timestamp = FrameTimestamp::now(capture);
+            // Drivers stamp buffers with CLOCK_MONOTONIC (uvcvideo always does), so the frame's age is the clock's distance from it.
+            if let Some(latency) = capture.and_then(|capture| monotonic_now()?.checked_sub(capture))
+            {
+                timestamp = timestamp.with_driver_latency(latency);
+            }
+            let mut buffer = Buffer::new(cam_fmt.resolution(), raw_frame, cam_fmt.format())
+                .with_timestamp(timestamp)
+                .with_sequence(meta.sequence);
+            if let Some(field_order) = field_order_from_v4l2(meta.field, cam_fmt.height()) {
+                buffer = buffer.with_field_order(field_order);
+            }
+            if let Some(pixel_aspect) = pixel_aspect {
+                buffer = buffer.with_pixel_aspect(pixel_aspect);
+            }
+            if self.event_callback.is_some() {
+                self.dispatch_events();
+            }
+            if let Some(parameters) = self
+                .metadata_stream
+                .as_mut()
+                .and_then(|metadata| metadata.capture_parameters(meta.sequence))
+            {
+                buffer = buffer.with_capture_parameters(parameters);
+            }
+            Ok(match stride {
+                Some(stride) => buffer.with_stride(stride),
+                None => buffer,
+            })
+        }
+
+        fn watched_frame(
+            &mut self,
+            interval: Duration,
+            action: WatchdogAction,
+        ) -> Result<Buffer, NokhwaError> {
+            match self.frame_timeout(interval) {
+                Err(NokhwaError::TimeoutError(_)) => {}
+                other => return other,
+            }
+            if action == WatchdogAction::Restart {
+                self.stream_off()?;
+                self.start_streaming()?;
+                match self.frame_timeout(interval) {
+                    Err(NokhwaError::TimeoutError(_)) => {}
+                    other => return other,
+                }
+            }
+            Err(NokhwaError::StreamStalledError(interval))
+        }
+
+        /// Turns streaming off, keeping the buffers. [`start_streaming()`](Self::start_streaming) turns it back on.
+        fn stream_off(&mut self) -> Result<(), NokhwaError> {
+            if let Some(stream) = &mut self.stream_handle {
+                if self.primed {
+                    stream
+                        .stop()
+                        .map_err(|why| NokhwaError::StreamShutdownError(why.to_string()))?;
+                }
+            }
+            self.primed = false;
+            self.queued_buffers.clear();
+            Ok(())
+        }
+
+        /// Queues the buffers and turns streaming on (STREAMON), so the device becomes readable as soon as the driver filled
+        /// one, before the first frame is asked for.
+        ///
+        /// `next()` gives the buffer it returned last (the first one, on a new stream) back to the driver before it dequeues,
+        /// so that one is left out and queued by the first dequeue. A stream with a single buffer has nothing else to queue,
+        /// and is started by its first dequeue instead.
+        fn start_streaming(&mut self) -> Result<(), NokhwaError> {
+            let count = self.granted_buffer_count.unwrap_or_default();
+            let held = self.last_buffer_index.unwrap_or(0);
+            if let Some(stream) = &mut self.stream_handle {
+                if self.primed || count < 2 {
+                    return Ok(());
+                }
+                self.queued_buffers.clear();
+                for index in (0..count).filter(|index| *index != held) {
+                    stream
+                        .queue(index as usize)
+                        .map_err(|why| NokhwaError::from_io("Queue buffer", why))?;
+                    self.queued_buffers.push_back(index);
+                }
+                stream
+                    .start()
+                    .map_err(|why| NokhwaError::from_io("Start streaming", why))?;
+                self.primed = true;
+            }
+            Ok(())
+        }
+
+        /// Dequeues the next filled buffer, trimmed to the bytes the driver actually used, along with its metadata.
+        fn next_buffer(&mut self) -> Result<(&[u8], Metadata), NokhwaError> {
+            if self.paused {
+                return Err(NokhwaError::ReadFrameError("Stream Paused".to_string()));
+            }
+            if self.disconnected && self.reconnect_timeout.is_some() {
+                self.reconnect()?;
+            }
+            if self.stream_handle.is_some() {
+                if self.primed {
+                    self.queued_buffers
+                        .push_back(self.last_buffer_index.unwrap_or(0));
+                } else {
+                    let count = self.granted_buffer_count.unwrap_or_default();
+                    self.queued_buffers = (0..count).collect();
+                }
+            }
+            match &mut self.stream_handle {
+                // If streaming is off, `next()` queues every buffer and turns it on.
+                Some(sh) => match sh.next() {
+                    // Compressed formats (MJPEG) only fill part of the buffer.
+                    Ok((data, meta)) => {
+                        // The driver increments the sequence for every frame it captured, even the ones it had to drop
+                        // because all buffers were full.
+                        if let Some(last) = self.last_sequence {
+                            let missed = meta.sequence.wrapping_sub(last).saturating_sub(1);
+                            self.dropped_frames += u64::from(missed);
+                            #[cfg(feature = "tracing")]
+                            if missed > 0 {
+                                tracing::debug!(
+                                    missed,
+                                    total = self.dropped_frames,
+                                    sequence = meta.sequence,
+                                    "V4L2 driver dropped frames"
+                                );
+                            }
+                        }
+                        self.last_sequence = Some(meta.sequence);
+                        self.primed = true;
+                        self.last_buffer_index = self.queued_buffers.pop_front();
+                        let used = (meta.bytesused as usize).min(data.len());
+                        Ok((&data[..used], *meta))
+                    }
+                    Err(why) if why.kind() == ErrorKind::TimedOut => Err(
+                        NokhwaError::TimeoutError(self.read_timeout.unwrap_or_default()),
+                    ),
+                    Err(why) if why.raw_os_error() == Some(ENODEV) => {
+                        self.disconnected = true;
+                        self.emit_reconnect_event(ReconnectEvent::Disconnected);
+                        Err(NokhwaError::from_io("Dequeue frame", why))
+                    }
+                    Err(why) => Err(NokhwaError::from_io("Dequeue frame", why)),
+                },
+                None => Err(NokhwaError::ReadFrameError(
+                    "Stream Not Started".to_string(),
+                )),
+            }
+        }
+
+        /// Will get a frame from the camera, waiting at most `timeout` for one to arrive.
+        ///
+        /// Use this instead of [`frame()`](CaptureTrait::frame) if the device may stall (e.g. a flaky USB hub), which would otherwise block forever.
+        /// # Errors
+        /// If no frame arrived within `timeout`, this returns [`TimeoutError`](NokhwaError::TimeoutError). The stream is left open,
+        /// so you may retry or restart it with [`stop_stream()`](CaptureTrait::stop_stream) and [`open_stream()`](CaptureTrait::open_stream).
+        /// Otherwise, this errors the same way as [`frame()`](CaptureTrait::frame).
+        pub fn frame_timeout(&mut self, timeout: Duration) -> Result<Buffer, NokhwaError> {
+            if !self.is_stream_open() {
+                self.open_stream()?;
+            }
+            if self.primed {
+                // `v4l` re-queues the previous buffer before waiting, so a timeout inside `next()` would queue it twice
+                // on the following call. Wait for readiness ourselves instead.
+                let millis = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+                let ready = self
+                    .device
+                    .handle()
+                    .poll(POLLIN, millis)
+                    .map_err(|why| NokhwaError::from_io("Poll device", why))?;
+                if ready == 0 {
+                    return Err(NokhwaError::TimeoutError(timeout));
+                }
+                return self.dequeue_frame();
+            }
+
+            // Streaming is not on yet, so `next()` will start it and wait on the first frame.
+            if let Some(stream) = &mut self.stream_handle {
+                stream.set_timeout(timeout);
+            }
+            self.read_timeout = Some(timeout);
+            let frame = self.dequeue_frame();
+            self.read_timeout = None;
+            if let Some(stream) = &mut self.stream_handle {
+                stream.clear_timeout();
+                // Only a single buffer stream gets here, see `start_streaming()`. Its buffer is queued but was not dequeued,
+                // so turn streaming off for the next call to start over cleanly.
+                if matches!(frame, Err(NokhwaError::TimeoutError(_))) {
+                    stream
+                        .stop()
+                        .map_err(|why| NokhwaError::from_io("Stop stream", why))?;
+                    self.queued_buffers.clear();
+                }
+            }
+            frame
+        }
+
+        /// Will get a frame from the camera if one is ready, without waiting. Returns `Ok(None)` if no new frame has arrived yet.
+        ///
+        /// This lets a single-threaded event loop interleave capture with other work.
+        /// # Errors
+        /// This errors the same way as [`frame()`](CaptureTrait::frame).
+        pub fn try_frame(&mut self) -> Result<Option<Buffer>, NokhwaError> {
+            match self.frame_timeout(Duration::ZERO) {
+                Ok(frame) => Ok(Some(frame)),
+                Err(NokhwaError::TimeoutError(_)) => Ok(None),
+                Err(why) => Err(why),
+            }
+        }
+
+        /// Dequeues a frame after the device's file descriptor (see [`AsRawFd`]) was reported readable by your own reactor
+        /// (`epoll`, `mio`, `tokio`'s `AsyncFd`, ...). [`open_stream()`](CaptureTrait::open_stream) turns streaming on, so
+        /// open the stream, wait until the file descriptor is readable, and call this.
+        ///
+        /// This never blocks. Returns `Ok(None)` on a spurious wakeup.
+        /// # Errors
+        /// If the stream is not open or paused, or the frame could not be dequeued, this will error. A stream with a single
+        /// buffer (see [`set_buffer_count()`](Self::set_buffer_count)) only starts on its first blocking read, so it can not
+        /// be polled and errors as well.
+        pub fn read_ready_frame(&mut self) -> Result<Option<Buffer>, NokhwaError> {
+            if !self.is_stream_open() {
+                return Err(NokhwaError::ReadFrameError(
+                    "Stream Not Started".to_string(),
+                ));
+            }
+            if self.paused {
+                return Err(NokhwaError::ReadFrameError("Stream Paused".to_string()));
+            }
+            if !self.primed {
+                return Err(NokhwaError::ReadFrameError(
+                    "Streaming is off, a stream needs at least 2 buffers to be polled".to_string(),
+                ));
+            }
+            self.try_frame()
+        }
+
+        /// Takes a still photo at the highest resolution the camera supports for the current [`FrameFormat`].
+        ///
+        /// `uvcvideo` does not expose the UVC still image pin, so this temporarily switches the camera to its highest resolution,
+        /// captures a frame, then restores the previous [`CameraFormat`] (and stream state). Expect the video stream to be interrupted for a moment.
+        /// # Errors
+        /// If the formats cannot be listed or set, or the frame cannot be captured, this will error. The previous format is restored in any case.
+        pub fn take_photo(&mut self) -> Result<Buffer, NokhwaError> {
+            let previous = self.current_format()?;
+            let was_open = self.is_stream_open();
+
+            let photo_format = self
+                .compatible_camera_formats()?
+                .into_iter()
+                .filter(|format| format.format() == previous.format())
+                .max_by_key(|format| (format.resolution().pixel_count(), format.frame_rate()));
+
+            let photo_format = match photo_format {
+                Some(format) if format.resolution() != previous.resolution() => format,
+                // Already at the highest resolution, just grab a frame.
+                _ => return self.frame(),
+            };
+
+            self.set_camera_format(photo_format)?;
+            let photo = self.photo_frame();
+
+            let restored = self.set_camera_format(previous).and_then(|()| {
+                if was_open || !self.is_stream_open() {
+                    Ok(())
+                } else {
+                    self.stop_stream()
+                }
+            });
+            let photo = photo?;
+            restored?;
+            Ok(photo)
+        }
+
+        fn photo_frame(&mut self) -> Result<Buffer, NokhwaError> {
+            if !self.is_stream_open() {
+                self.open_stream()?;
+            }
+            for _ in 0..PHOTO_WARMUP_FRAMES {
+                self.frame()?;
+            }
+            self.frame()
+        }
+
+        /// Sets the stream watchdog. If no frame arrives within `interval` during [`frame()`](CaptureTrait::frame), `action` is taken.
+        /// Some webcams silently stop delivering frames after running for a long time, and only recover after streaming is restarted.
+        ///
+        /// Pass `None` to disable it (the default). The watchdog does not apply to [`frame_raw()`](CaptureTrait::frame_raw).
+        pub fn set_watchdog(&mut self, watchdog: Option<(Duration, WatchdogAction)>) {
+            self.watchdog = watchdog;
+        }
+
+        /// Gets the stream watchdog interval and action, if set.
+        #[must_use]
+        pub fn watchdog(&self) -> Option<(Duration, WatchdogAction)> {
+            self.watchdog
+        }
+
+        /// Enables automatic reconnection. If the device is unplugged, the frame call that notices it errors as usual, and the next one waits up to
+        /// `timeout` for a device with the same USB port (bus info) and name to reappear. It then re-applies the [`CameraFormat`] and any controls
+        /// set with [`set_camera_control()`](CaptureTrait::set_camera_control), and reopens the stream if it was open.
+        ///
+        /// Pass `None` to disable it (the default).
+        pub fn set_auto_reconnect(&mut self, timeout: Option<Duration>) {
+            self.reconnect_timeout = timeout;
+        }
+
+        /// Sets a callback that is called with a [`ReconnectEvent`] when the device is disconnected and when reconnection succeeds or fails.
+        pub fn set_reconnect_callback(
+            &mut self,
+            callback: impl FnMut(ReconnectEvent) + Send + 'static,
+        ) {
+            self.reconnect_callback = Some(Box::new(callback));
+        }
+
+        /// Subscribes to source change events (`V4L2_EVENT_SOURCE_CHANGE`) of the current input, so a capture card reports
+        /// [`DeviceEvent::SourceChanged`] when the signal plugged into it changes resolution, instead of streaming garbage.
+        ///
+        /// Subscriptions belong to the open device, so they must be made again after a reconnect.
+        /// # Errors
+        /// If the driver does not support source change events (most webcams), this will error.
+        pub fn subscribe_source_change(&mut self) -> Result<(), NokhwaError> {
+            let input = self.input().unwrap_or(0);
+            self.subscribe_event(V4L2_EVENT_SOURCE_CHANGE, input)
+                .map_err(|why| NokhwaError::from_io("Subscribe to source change events", why))
+        }
+
+        /// Subscribes to change events (`V4L2_EVENT_CTRL`) of `controls`, reported as [`DeviceEvent::ControlChanged`].
+        /// # Errors
+        /// If the driver does not support control events, or one of the controls does not exist, this will error. The
+        /// controls before it stay subscribed.
+        pub fn subscribe_control_changes(
+            &mut self,
+            controls: &[KnownCameraControl],
+        ) -> Result<(), NokhwaError> {
+            for control in controls {
+                self.subscribe_event(V4L2_EVENT_CTRL, known_camera_control_to_id(*control))
+                    .map_err(|why| {
+                        NokhwaError::from_io(format!("Subscribe to {control} change events"), why)
+                    })?;
+            }
+            Ok(())
+        }
+
+        /// Unsubscribes from all events (`V4L2_EVENT_ALL`).
+        /// # Errors
+        /// If the driver rejects it, this will error.
+        pub fn unsubscribe_events(&mut self) -> Result<(), NokhwaError> {
+            // SAFETY: `v4l2_event_subscription` is a plain C struct, for which all zeroes is a valid value.
+            let mut subscription: v4l2_event_subscription = unsafe { std::mem::zeroed() };
+            // SAFETY: `VIDIOC_UNSUBSCRIBE_EVENT` takes a `v4l2_event_subscription`, which `subscription` is.
+            unsafe {
+                v4l2_ioctl(
+                    &self.device,
+                    vidioc::VIDIOC_UNSUBSCRIBE_EVENT,
+                    &mut subscription,
+                )
+            }
+            .map_err(|why| NokhwaError::from_io("Unsubscribe from events", why))
+        }
+
+        /// Sets a callback that is called with every [`DeviceEvent`] that arrived, each time a frame is captured. Without a
+        /// callback, collect them with [`pending_events()`](Self::pending_events) instead, which also works while frames
+        /// have stopped arriving.
+        pub fn set_event_callback(&mut self, callback: impl FnMut(DeviceEvent) + Send + 'static) {
+            self.event_callback = Some(Box::new(callback));
+        }
+
+        /// Dequeues the events that have arrived since the last call, without waiting.
+        /// # Errors
+        /// If dequeuing an event fails, this will error.
+        pub fn pending_events(&mut self) -> Result<Vec<DeviceEvent>, NokhwaError> {
+            let mut events = Vec::new();
+            while self.event_pending() {
+                // SAFETY: `v4l2_event` is a plain C struct, for which all zeroes is a valid value.
+                let mut raw_event: v4l2_event = unsafe { std::mem::zeroed() };
+                // SAFETY: `VIDIOC_DQEVENT` takes a `v4l2_event`, which `raw_event` is.
+                unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_DQEVENT, &mut raw_event) }
+                    .map_err(|why| NokhwaError::from_io("Dequeue event", why))?;
+                if let Some(event) = device_event_from_raw(&raw_event) {
+                    events.push(event);
+                }
+            }
+            Ok(events)
+        }
+
+        fn subscribe_event(&self, event_type: u32, id: u32) -> io::Result<()> {
+            // SAFETY: `v4l2_event_subscription` is a plain C struct, for which all zeroes is a valid value.
+            let mut subscription: v4l2_event_subscription = unsafe { std::mem::zeroed() };
+            subscription.type_ = event_type;
+            subscription.id = id;
+            // SAFETY: `VIDIOC_SUBSCRIBE_EVENT` takes a `v4l2_event_subscription`, which `subscription` is.
+            unsafe {
+                v4l2_ioctl(
+                    &self.device,
+                    vidioc::VIDIOC_SUBSCRIBE_EVENT,
+                    &mut subscription,
+                )
+            }
+        }
+
+        /// Checks for `POLLPRI` without waiting. `VIDIOC_DQEVENT` would block on an empty queue.
+        fn event_pending(&self) -> bool {
+            let mut pollfd = libc::pollfd {
+                fd: self.device.handle().fd(),
+                events: POLLPRI,
+                revents: 0,
+            };
+            // SAFETY: `pollfd` is a single `pollfd`, matching the count of 1, and outlives the call, which does not wait.
+            let ready = unsafe { libc::poll(&mut pollfd, 1, 0) };
+            ready > 0 && pollfd.revents & POLLPRI != 0
+        }
+
+        fn dispatch_events(&mut self) {
+            let events = match self.pending_events() {
+                Ok(events) => events,
+                Err(_why) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(error = %_why, "Failed to dequeue V4L2 events");
+                    return;
+                }
+            };
+            if let Some(callback) = &mut self.event_callback {
+                for event in events {
+                    callback(event);
+                }
+            }
+        }
+
+        /// Checks if the device was found to be unplugged and has not been reconnected yet.
+        #[must_use]
+        pub fn is_disconnected(&self) -> bool {
+            self.disconnected
+        }
+
+        fn emit_reconnect_event(&mut self, event: ReconnectEvent) {
+            #[cfg(feature = "tracing")]
+            tracing::info!(?event, bus = %self.bus_info, "V4L2 reconnect event");
+            if let Some(callback) = &mut self.reconnect_callback {
+                callback(event);
+            }
+        }
+
+        /// Looks for the capture node with our bus info and card name.
+        fn find_replugged_device(&self) -> Option<(usize, Device)> {
+            let card = self.camera_info.human_name();
+            v4l::context::enum_devices().into_iter().find_map(|node| {
+                let device = Device::new(node.index()).ok()?;
+                let caps = device.query_caps().ok()?;
+                (caps.bus == self.bus_info
+                    && caps.card == card
+                    && caps.capabilities.contains(CapabilityFlags::VIDEO_CAPTURE))
+                .then_some((node.index(), device))
+            })
+        }
+
+        fn reconnect(&mut self) -> Result<(), NokhwaError> {
+            let timeout = self.reconnect_timeout.unwrap_or_default();
+            let was_streaming = self.stream_handle.is_some();
+            // The buffers belong to the old file descriptor, which is dead.
+            self.stream_handle = None;
+            self.metadata_stream = None;
+            #[cfg(feature = "async")]
+            {
+                self.async_source = None;
+            }
+            self.primed = false;
+
+            let deadline = Instant::now() + timeout;
+            let (index, device) = loop {
+                if let Some(found) = self.find_replugged_device() {
+                    break found;
+                }
+                if Instant::now() >= deadline {
+                    let error = format!("device did not reappear within {timeout:?}");
+                    self.emit_reconnect_event(ReconnectEvent::ReconnectFailed(error.clone()));
+                    return Err(NokhwaError::OpenDeviceError(self.bus_info.clone(), error));
+                }
+                std::thread::sleep(RECONNECT_POLL_INTERVAL);
+            };
+
+            self.device = device;
+            self.disconnected = false;
+            #[allow(clippy::cast_possible_truncation)]
+            let index = index as u32;
+            self.camera_info.set_index(CameraIndex::Index(index));
+
+            let result = self.reapply_configuration(was_streaming);
+            match &result {
+                Ok(()) => self.emit_reconnect_event(ReconnectEvent::Reconnected { index }),
+                Err(why) => {
+                    self.emit_reconnect_event(ReconnectEvent::ReconnectFailed(why.to_string()));
+                }
+            }
+            result
+        }
+
+        fn reapply_configuration(&mut self, open_stream: bool) -> Result<(), NokhwaError> {
+            if let Some(priority) = self.priority {
+                self.set_priority(priority)?;
+            }
+            if let Some(format) = self.camera_format {
+                self.set_camera_format(format)?;
+            }
+            for (id, value) in self.applied_controls.clone() {
+                self.set_camera_control(id, value)?;
+            }
+            if open_stream {
+                self.open_stream()?;
+            }
+            Ok(())
+        }
+
+        /// Changes the format of an open stream with as little teardown as possible: streaming is turned off and the buffers
+        /// released (drivers refuse to change the format while they are allocated), the new format and frame rate are set,
+        /// controls set with [`set_camera_control()`](CaptureTrait::set_camera_control) are applied again (some drivers reset
+        /// them on a format change), and the stream is reopened. Frames still queued in the old format are dropped.
+        ///
+        /// Drivers adjust a format they can not do to the closest one they can. Unlike
+        /// [`set_camera_format()`](CaptureTrait::set_camera_format), which restores the previous format and errors then, this
+        /// keeps the adjusted format and returns it; compare it to `format` if only an exact match will do. If the stream is
+        /// not open, this only sets the format.
+        /// # Errors
+        /// If the driver rejects the format, or the stream can not be reopened, this will error. In that case the previous
+        /// format is restored (and its stream reopened) if possible.
+        pub fn reconfigure(&mut self, format: CameraFormat) -> Result<CameraFormat, NokhwaError> {
+            let was_streaming = self.stream_handle.is_some();
+            let was_paused = self.paused;
+            let prev_format = self.camera_format;
+
+            let result = self.stop_stream().and_then(|()| self.apply_format(format));
+            let result = match result {
+                Ok(()) if was_streaming => self.reopen_stream(was_paused),
+                result => result,
+            };
+            if let Err(why) = result {
+                if let Some(prev_format) = prev_format {
+                    if self.apply_format(prev_format).is_ok() && was_streaming {
+                        let _ = self.reopen_stream(was_paused);
+                    }
+                }
+                return Err(why);
+            }
+
+            for (id, value) in self.applied_controls.clone() {
+                if let Err(_why) = self.set_camera_control(id, value) {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(control = %id, error = %_why, "Failed to restore control after reconfiguring");
+                }
+            }
+            self.current_format()
+        }
+
+        // Opens the stream again after a reconfigure. Opening turns streaming on, so a stream that was paused is paused again
+        // (turned off in the driver) rather than only flagged as paused.
+        fn reopen_stream(&mut self, paused: bool) -> Result<(), NokhwaError> {
+            self.open_stream()?;
+            if paused {
+                self.pause_stream()?;
+            }
+            Ok(())
+        }
+
+        // Sets the format and frame rate on the device, and reads back what the driver made of them.
+        fn apply_format(&mut self, format: CameraFormat) -> Result<(), NokhwaError> {
+            let fourcc =
+                frameformat_to_fourcc(format.format()).ok_or(NokhwaError::SetPropertyError {
+                    property: "FrameFormat".to_string(),
+                    value: format.format().to_string(),
+                    error: "unsupported".to_string(),
+                })?;
+            let v4l_format = Format::new(format.width(), format.height(), fourcc);
+            let (numerator, denominator) = format.frame_rate().as_interval();
+            let frame_rate = Parameters::new(Fraction::new(numerator, denominator));
+            if let Err(why) = Capture::set_format(&self.device, &v4l_format) {
+                return Err(self.set_error("Resolution, FrameFormat", v4l_format.to_string(), why));
+            }
+            if let Err(why) = Capture::set_params(&self.device, &frame_rate) {
+                return Err(self.set_error("Frame rate", frame_rate.to_string(), why));
+            }
+            self.force_refresh_camera_format()
+        }
+
+        /// Pauses the stream: streaming is turned off (STREAMOFF), but the buffers and the negotiated format are kept,
+        /// so [`resume_stream()`](Self::resume_stream) is much cheaper than closing and reopening the stream.
+        ///
+        /// While paused, the stream is still considered open, but getting a frame will error.
+        /// # Errors
+        /// If the stream is not open, or the driver fails to turn off streaming, this will error.
+        pub fn pause_stream(&mut self) -> Result<(), NokhwaError> {
+            if self.stream_handle.is_none() {
+                return Err(NokhwaError::StreamShutdownError(
+                    "Stream Not Started".to_string(),
+                ));
+            }
+            if self.paused {
+                return Ok(());
+            }
+            self.stream_off()?;
+            self.paused = true;
+            Ok(())
+        }
+
+        /// Resumes a stream paused with [`pause_stream()`](Self::pause_stream), turning streaming back on.
+        /// # Errors
+        /// If the stream is not open, or the driver fails to turn on streaming, this will error.
+        pub fn resume_stream(&mut self) -> Result<(), NokhwaError> {
+            if self.stream_handle.is_none() {
+                return Err(NokhwaError::OpenStreamError(
+                    "Stream Not Started".to_string(),
+                ));
+            }
+            self.start_streaming()?;
+            self.paused = false;
+            self.last_sequence = None;
+            Ok(())
+        }
+
+        /// Checks if the stream is paused.
+        #[must_use]
+        pub fn is_stream_paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Whether streaming is on, so the device's file descriptor becomes readable when a frame arrives.
+        pub(crate) fn is_streaming(&self) -> bool {
+            self.primed
+        }
+
+        /// The number of buffers that will be requested from the driver when the stream is opened. Defaults to 4.
+        #[must_use]
+        pub fn buffer_count(&self) -> u32 {
+            self.buffer_count
+        }
+
+        /// Sets the number of buffers to request from the driver. This takes effect the next time [`open_stream()`](CaptureTrait::open_stream) is called.
+        ///
+        /// More buffers tolerate a slower consumer without dropping frames, at the cost of memory and latency (a full queue is older frames).
+        /// # Errors
+        /// If `count` is 0, this will error.
+        pub fn set_buffer_count(&mut self, count: u32) -> Result<(), NokhwaError> {
+            if count == 0 {
+                return Err(NokhwaError::SetPropertyError {
+                    property: "Buffer Count".to_string(),
+                    value: count.to_string(),
+                    error: "Must be at least 1".to_string(),
+                });
+            }
+            self.buffer_count = count;
+            Ok(())
+        }
+
+        /// The number of buffers the driver actually granted for the open stream, or `None` if the stream is not open.
+        #[must_use]
+        pub fn granted_buffer_count(&self) -> Option<u32> {
+            self.granted_buffer_count
+        }
+
+        /// The JPEG compression quality (0 to 100, higher is better) of an MJPEG stream, from the
+        /// `V4L2_CID_JPEG_COMPRESSION_QUALITY` control or, on older drivers, `VIDIOC_G_JPEGCOMP`.
+        /// # Errors
+        /// If the driver exposes neither, this will error. `uvcvideo` only does for cameras that map the UVC compression
+        /// quality to a control.
+        pub fn jpeg_quality(&self) -> Result<u32, NokhwaError> {
+            if let Ok(Control {
+                value: Value::Integer(quality),
+                ..
+            }) = self.device.control(V4L2_CID_JPEG_COMPRESSION_QUALITY)
+            {
+                return Ok(u32::try_from(quality.clamp(0, 100)).unwrap_or_default());
+            }
+            // SAFETY: `v4l2_jpegcompression` is a plain C struct, for which all zeroes is a valid value.
+            let mut jpegcomp: v4l2_jpegcompression = unsafe { std::mem::zeroed() };
+            // SAFETY: `VIDIOC_G_JPEGCOMP` takes a `v4l2_jpegcompression`, which `jpegcomp` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_G_JPEGCOMP, &mut jpegcomp) }.map_err(
+                |why| NokhwaError::GetPropertyError {
+                    property: "JPEG Quality".to_string(),
+                    error: why.to_string(),
+                },
+            )?;
+            Ok(u32::try_from(jpegcomp.quality.clamp(0, 100)).unwrap_or_default())
+        }
+
+        /// Sets the JPEG compression quality (0 to 100, clamped) of an MJPEG stream. Lowering it shrinks frames, which helps
+        /// on a crowded USB hub where the camera would otherwise drop frames. Drivers round to the qualities they support,
+        /// so the quality actually set is returned.
+        /// # Errors
+        /// If the driver supports neither the control nor `VIDIOC_S_JPEGCOMP`, or rejects the value, this will error.
+        pub fn set_jpeg_quality(&mut self, quality: u32) -> Result<u32, NokhwaError> {
+            let quality = quality.min(100);
+            let set_control = self.device.set_control(Control {
+                id: V4L2_CID_JPEG_COMPRESSION_QUALITY,
+                value: Value::Integer(i64::from(quality)),
+            });
+            if set_control.is_err() {
+                // SAFETY: `v4l2_jpegcompression` is a plain C struct, for which all zeroes is a valid value.
+                let mut jpegcomp: v4l2_jpegcompression = unsafe { std::mem::zeroed() };
+                // Keep the markers and APP/COM data the driver has.
+                // SAFETY: `VIDIOC_G_JPEGCOMP` takes a `v4l2_jpegcompression`, which `jpegcomp` is.
+                let _ =
+                    unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_G_JPEGCOMP, &mut jpegcomp) };
+                jpegcomp.quality = i32::try_from(quality).unwrap_or(100);
+                // SAFETY: `VIDIOC_S_JPEGCOMP` takes a `v4l2_jpegcompression`, which `jpegcomp` is.
+                unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_S_JPEGCOMP, &mut jpegcomp) }
+                    .map_err(|why| NokhwaError::SetPropertyError {
+                        property: "JPEG Quality".to_string(),
+                        value: quality.to_string(),
+                        error: why.to_string(),
+                    })?;
+            }
+            self.jpeg_quality()
+        }
+
+        /// The highest access priority any handle holds on the device (`VIDIOC_G_PRIORITY`), which is not necessarily the
+        /// one this device set.
+        /// # Errors
+        /// If the driver does not support priorities, this will error.
+        pub fn priority(&self) -> Result<DevicePriority, NokhwaError> {
+            let mut priority: u32 = 0;
+            // SAFETY: `VIDIOC_G_PRIORITY` takes an `enum v4l2_priority` (a `u32`), which `priority` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_G_PRIORITY, &mut priority) }
+                .map_err(|why| NokhwaError::from_io("Get priority", why))?;
+            Ok(DevicePriority::from(priority))
+        }
+
+        /// Sets the access priority of this device (`VIDIOC_S_PRIORITY`). A recording application should take
+        /// [`DevicePriority::Record`], so background processes can not change the format or controls mid-recording; they
+        /// get a [`PriorityError`](NokhwaError::PriorityError) instead. The priority is set again after a reconnect.
+        /// # Errors
+        /// If another handle already holds [`DevicePriority::Record`], this will error with a
+        /// [`PriorityError`](NokhwaError::PriorityError).
+        pub fn set_priority(&mut self, priority: DevicePriority) -> Result<(), NokhwaError> {
+            let mut raw = u32::from(priority);
+            // SAFETY: `VIDIOC_S_PRIORITY` takes an `enum v4l2_priority` (a `u32`), which `raw` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_S_PRIORITY, &mut raw) }
+                .map_err(|why| self.set_error("Priority", priority.to_string(), why))?;
+            self.priority = Some(priority);
+            Ok(())
+        }
+
+        // Turns an error from changing the device into a `PriorityError` if another handle holds a higher priority.
+        fn set_error(&self, property: &str, value: String, why: io::Error) -> NokhwaError {
+            if why.raw_os_error() == Some(EBUSY) {
+                let ours = self.priority.unwrap_or(DevicePriority::Interactive);
+                if let Ok(holder) = self.priority() {
+                    if holder > ours {
+                        return NokhwaError::PriorityError {
+                            property: property.to_string(),
+                            holder: holder.to_string(),
+                        };
+                    }
+                }
+            }
+            NokhwaError::from_io(format!("Set {property} to {value}"), why)
+        }
+
+        /// The largest region of the sensor [`set_crop()`](CaptureTrait::set_crop) can crop to, from `VIDIOC_G_SELECTION`.
+        /// # Errors
+        /// If the driver does not support the selection API, this will error.
+        pub fn crop_bounds(&self) -> Result<Region, NokhwaError> {
+            selection(&self.device, V4L2_SEL_TGT_CROP_BOUNDS)
+                .ok()
+                .and_then(rect_to_region)
+                .ok_or(NokhwaError::UnsupportedOperationError(
+                    ApiBackend::Video4Linux,
+                ))
+        }
+
+        /// Turns attaching the exposure time and gain each frame was captured with (see [`Buffer::capture_parameters()`]) on or
+        /// off. This streams the camera's UVC metadata node alongside it, so it only works with `uvcvideo` cameras that report
+        /// capture stats in their payload headers (the Microsoft UVC extensions), and takes effect the next time
+        /// [`open_stream()`](CaptureTrait::open_stream) is called. Off by default.
+        pub fn set_frame_metadata(&mut self, enabled: bool) {
+            self.frame_metadata = enabled;
+        }
+
+        /// Whether the UVC metadata node is read for per frame capture parameters.
+        #[must_use]
+        pub fn frame_metadata(&self) -> bool {
+            self.frame_metadata
+        }
+
+        /// Returns true if the open stream found a UVC metadata node to read capture parameters from.
+        #[must_use]
+        pub fn has_metadata_stream(&self) -> bool {
+            self.metadata_stream.is_some()
+        }
 
-    /// Attempts to convert a [`u32`] V4L2 Control ID into a [`KnownCameraControl`]
-    /// If the associated control is not found, this will return `None` (`ColorEnable`, `Roll`)
-    #[allow(clippy::cast_lossless)]
-    pub fn id_to_known_camera_control(id: u32) -> KnownCameraControl {
-        match id {
-            V4L2_CID_BRIGHTNESS => KnownCameraControl::Brightness,
-            V4L2_CID_CONTRAST => KnownCameraControl::Contrast,
-            V4L2_CID_HUE => KnownCameraControl::Hue,
-            V4L2_CID_SATURATION => KnownCameraControl::Saturation,
-            V4L2_CID_SHARPNESS => KnownCameraControl::Sharpness,
-            V4L2_CID_GAMMA => KnownCameraControl::Gamma,
-            V4L2_CID_WHITE_BALANCE_TEMPERATURE => KnownCameraControl::WhiteBalance,
-            V4L2_CID_BACKLIGHT_COMPENSATION => KnownCameraControl::BacklightComp,
-            V4L2_CID_GAIN => KnownCameraControl::Gain,
-            V4L2_CID_PAN_RELATIVE => KnownCameraControl::Pan,
-            V4L2_CID_TILT_RELATIVE => KnownCameraControl::Tilt,
-            V4L2_CID_ZOOM_RELATIVE => KnownCameraControl::Zoom,
-            V4L2_CID_EXPOSURE => KnownCameraControl::Exposure,
-            V4L2_CID_IRIS_RELATIVE => KnownCameraControl::Iris,
-            V4L2_CID_FOCUS_RELATIVE => KnownCameraControl::Focus,
-            id => KnownCameraControl::Other(id as u128),
+        /// The shape of the pixels in the current format, from `VIDIOC_CROPCAP`. Analog and DV capture cards report
+        /// non-square pixels for their standard; most webcams report [`PixelAspectRatio::SQUARE`] or nothing at all.
+        #[must_use]
+        pub fn pixel_aspect_ratio(&self) -> Option<PixelAspectRatio> {
+            let format = Capture::format(&self.device).ok()?;
+            pixel_aspect(&self.device, &format)
         }
-    }
 
-    /// query v4l2 cameras
-    #[allow(clippy::unnecessary_wraps)]
-    #[allow(clippy::cast_possible_truncation)]
-    pub fn query() -> Result<Vec<CameraInfo>, NokhwaError> {
-        Ok({
-            let camera_info: Vec<CameraInfo> = v4l::context::enum_devices()
-                .iter()
-                .map(|node| {
-                    CameraInfo::new(
-                        &node
-                            .name()
-                            .unwrap_or(format!("{}", node.path().to_string_lossy())),
-                        &format!("Video4Linux Device @ {}", node.path().to_string_lossy()),
-                        "",
-                        CameraIndex::Index(node.index() as u32),
-                    )
+        /// Exports every buffer of the open stream as a DMABUF (`VIDIOC_EXPBUF`), so frames can be imported into EGL or Vulkan
+        /// without copying them. See [`DmaBufExport`] for how.
+        ///
+        /// The frame returned by [`frame()`](CaptureTrait::frame) (or [`frame_raw()`](CaptureTrait::frame_raw)) is in the
+        /// buffer at [`last_buffer_index()`](Self::last_buffer_index), and stays there until the next frame is requested.
+        /// # Errors
+        /// If the stream is not open, or the driver does not support exporting buffers (e.g. `uvcvideo` on older kernels), this will error.
+        pub fn export_dmabufs(&self) -> Result<Vec<DmaBufExport>, NokhwaError> {
+            let count = self
+                .granted_buffer_count
+                .ok_or_else(|| NokhwaError::GetPropertyError {
+                    property: "DMABUF".to_string(),
+                    error: "The stream is not open".to_string(),
+                })?;
+            let camera_format = self.current_format()?;
+            let resolution = camera_format.resolution();
+            let stride = self
+                .stride
+                .unwrap_or_else(|| resolution.width() * bytes_per_pixel(camera_format.format()));
+
+            (0..count)
+                .map(|index| {
+                    // SAFETY: `v4l2_buffer` is a plain C struct, for which all zeroes is a valid value.
+                    let mut raw_buffer: v4l2_buffer = unsafe { std::mem::zeroed() };
+                    raw_buffer.index = index;
+                    raw_buffer.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                    raw_buffer.memory = v4l2_memory_V4L2_MEMORY_MMAP;
+                    // SAFETY: `VIDIOC_QUERYBUF` takes a `v4l2_buffer`, which `raw_buffer` is. Single planar buffers do
+                    // not use `m.planes`, so nothing is written through a pointer.
+                    unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_QUERYBUF, &mut raw_buffer) }
+                        .map_err(|why| NokhwaError::from_io("Query buffer", why))?;
+
+                    // SAFETY: `v4l2_exportbuffer` is a plain C struct, for which all zeroes is a valid value.
+                    let mut export: v4l2_exportbuffer = unsafe { std::mem::zeroed() };
+                    export.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                    export.index = index;
+                    export.flags = (libc_flags::O_CLOEXEC | libc_flags::O_RDONLY) as u32;
+                    // SAFETY: `VIDIOC_EXPBUF` takes a `v4l2_exportbuffer`, which `export` is.
+                    unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_EXPBUF, &mut export) }
+                        .map_err(|why| NokhwaError::from_io("Export buffer as DMABUF", why))?;
+
+                    Ok(DmaBufExport {
+                        index,
+                        // SAFETY: VIDIOC_EXPBUF returned a new file descriptor that nothing else owns.
+                        fd: unsafe { OwnedFd::from_raw_fd(export.fd) },
+                        length: raw_buffer.length,
+                        resolution,
+                        format: camera_format.format(),
+                        stride,
+                    })
                 })
-                .collect();
-            camera_info
-        })
-    }
+                .collect()
+        }
 
-    /// The backend struct that interfaces with V4L2.
-    /// To see what this does, please see [`CaptureTrait`].
-    /// # Quirks
-    /// - Calling [`set_resolution()`](CaptureTrait::set_resolution), [`set_frame_rate()`](CaptureTrait::set_frame_rate), or [`set_frame_format()`](CaptureTrait::set_frame_format) each internally calls [`set_camera_format()`](CaptureTrait::set_camera_format).
-    pub struct V4LCaptureDevice<'a> {
-        init: bool,
-        camera_format: Option<CameraFormat>,
-        camera_info: CameraInfo,
-        device: Device,
-        stream_handle: Option<MmapStream<'a>>,
-    }
+        /// The index of the buffer the last frame was dequeued from, to find it among the
+        /// [`export_dmabufs()`](Self::export_dmabufs). `None` if no frame was dequeued since the stream was opened.
+        #[must_use]
+        pub fn last_buffer_index(&self) -> Option<u32> {
+            self.last_buffer_index
+        }
 
-    impl<'a> V4LCaptureDevice<'a> {
-        /// Creates a new capture device using the `V4L2` backend. Indexes are gives to devices by the OS, and usually numbered by order of discovery.
-        /// # Errors
-        /// This function will error if the camera is currently busy or if `V4L2` can't read device information.
-        #[allow(clippy::too_many_lines)]
-        pub fn new(index: &CameraIndex) -> Result<Self, NokhwaError> {}
+        /// Counts the mapped capture buffers by querying indices until the driver rejects one.
+        fn count_mapped_buffers(&self) -> u32 {
+            let mut count = 0;
+            loop {
+                // SAFETY: `v4l2_buffer` is a plain C struct, for which all zeroes is a valid value.
+                let mut raw_buffer: v4l2_buffer = unsafe { std::mem::zeroed() };
+                raw_buffer.index = count;
+                raw_buffer.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE;
+                raw_buffer.memory = v4l2_memory_V4L2_MEMORY_MMAP;
+                // SAFETY: `VIDIOC_QUERYBUF` takes a `v4l2_buffer`, which `raw_buffer` is. Single planar buffers do not
+                // use `m.planes`, so nothing is written through a pointer.
+                if unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_QUERYBUF, &mut raw_buffer) }
+                    .is_err()
+                {
+                    return count;
+                }
+                count += 1;
+            }
+        }
 
-        /// Force refreshes the inner [`CameraFormat`] state.
-        /// # Errors
-        /// If the internal representation in the driver is invalid, this will error.
-        pub fn force_refresh_camera_format(&mut self) -> Result<(), NokhwaError> {
-            match self.device.format() {
-                Ok(format) => {
-                    let frame_format = fourcc_to_frameformat(format.fourcc).ok_or(
-                        NokhwaError::GetPropertyError {
-                            property: "FrameFormat".to_string(),
-                            error: "unsupported".to_string(),
-                        },
-                    )?;
+        /// The number of frames the driver captured but that were never dequeued, since the stream was last opened.
+        ///
+        /// This grows when the consumer can't keep up (all buffers were full) or the device skipped frames, e.g. due to USB bandwidth.
+        #[must_use]
+        pub fn dropped_frames(&self) -> u64 {
+            self.dropped_frames
+        }
 
-                    let fps = match self.device.params() {
-                        Ok(params) => {
-                            if params.interval.numerator != 1
-                                || params.interval.denominator % params.interval.numerator != 0
-                            {
-                                return Err(NokhwaError::GetPropertyError {
-                                    property: "V4L2 FrameRate".to_string(),
-                                    error: format!(
-                                        "Framerate not whole number: {} / {}",
-                                        params.interval.denominator, params.interval.numerator
-                                    ),
-                                });
-                            }
+        /// The sequence number of the last frame dequeued, if any.
+        #[must_use]
+        pub fn last_sequence(&self) -> Option<u32> {
+            self.last_sequence
+        }
 
-                            if params.interval.numerator == 1 {
-                                params.interval.denominator
-                            } else {
-                                params.interval.denominator / params.interval.numerator
-                            }
-                        }
-                        Err(why) => {
-                            return Err(NokhwaError::GetPropertyError {
-                                property: "V4L2 FrameRate".to_string(),
-                                error: why.to_string(),
-                            })
-                        }
-                    };
+        /// Gets the current [`CameraFormat`], erroring if the camera has not been initialized yet.
+        fn current_format(&self) -> Result<CameraFormat, NokhwaError> {
+            self.camera_format.ok_or(NokhwaError::UnitializedError)
+        }
 
-                    self.camera_format = CameraFormat::new(
-                        Resolution::new(format.width, format.height),
-                        frame_format: ,
-                        fps,
-                    );
-                    Ok(())
+        /// Will get a frame from the camera and decode it into an RGB888 [`ImageBuffer`].
+        ///
+        /// `MJPEG`, `YUYV`, `NV12`, `GRAY` and `RGB3` frames are supported. This allocates a new buffer for every frame;
+        /// see [`frame_rgb_ref()`](Self::frame_rgb_ref) to avoid that.
+        /// # Errors
+        /// If the frame could not be captured (see [`frame_raw()`](CaptureTrait::frame_raw)), the frame format can not be decoded, or the decoding fails, this will error.
+        #[cfg(feature = "image")]
+        pub fn frame_rgb(&mut self) -> Result<ImageBuffer<Rgb<u8>, Vec<u8>>, NokhwaError> {
+            let camera_format = self.current_format()?;
+            let resolution = camera_format.resolution();
+            let mut rgb = Vec::new();
+            decode_rgb_into(camera_format, &self.frame_raw()?, &mut rgb)?;
+            ImageBuffer::from_raw(resolution.width(), resolution.height(), rgb)
+                .ok_or_else(|| wrong_size(camera_format.format()))
+        }
+
+        /// Like [`frame_rgb()`](Self::frame_rgb), but decodes into a buffer kept inside the device and returns an [`ImageBuffer`]
+        /// borrowing it. The buffer is sized for the current format and reused for every frame, so this does not allocate.
+        /// # Errors
+        /// If the frame could not be captured (see [`frame_raw()`](CaptureTrait::frame_raw)), the frame format can not be decoded, or the decoding fails, this will error.
+        #[cfg(feature = "image")]
+        pub fn frame_rgb_ref(&mut self) -> Result<ImageBuffer<Rgb<u8>, &[u8]>, NokhwaError> {
+            let camera_format = self.current_format()?;
+            let resolution = camera_format.resolution();
+            // `frame_raw()` borrows all of `self`, so the scratch buffer is moved out while decoding.
+            let mut scratch = std::mem::take(&mut self.rgb_scratch);
+            let decoded = self
+                .frame_raw()
+                .and_then(|raw_frame| decode_rgb_into(camera_format, &raw_frame, &mut scratch));
+            self.rgb_scratch = scratch;
+            decoded?;
+            ImageBuffer::from_raw(
+                resolution.width(),
+                resolution.height(),
+                self.rgb_scratch.as_slice(),
+            )
+            .ok_or_else(|| wrong_size(camera_format.format()))
+        }
+    }
+
+    /// Decodes `raw_frame` into `out`, resizing it to fit (which only allocates if it grows).
+    #[cfg(feature = "image")]
+    fn decode_rgb_into(
+        camera_format: CameraFormat,
+        raw_frame: &[u8],
+        out: &mut Vec<u8>,
+    ) -> Result<(), NokhwaError> {
+        let resolution = camera_format.resolution();
+        let pixels = resolution.width() as usize * resolution.height() as usize;
+        out.resize(pixels * 3, 0);
+        match camera_format.format() {
+            FrameFormat::MJpeg => buf_mjpeg_to_rgb(raw_frame, out, false),
+            FrameFormat::Yuv422 => buf_yuyv422_to_rgb(raw_frame, out, false),
+            FrameFormat::Nv12 => buf_nv12_to_rgb(resolution, raw_frame, out, false),
+            FrameFormat::Luma8 if raw_frame.len() >= pixels => {
+                for (rgb, luma) in out.chunks_exact_mut(3).zip(raw_frame) {
+                    rgb.fill(*luma);
                 }
-                Err(why) => Err(NokhwaError::GetPropertyError {
-                    property: "parameters".to_string(),
-                    error: why.to_string(),
-                }),
+                Ok(())
             }
+            FrameFormat::Rgb8 if raw_frame.len() >= out.len() => {
+                let len = out.len();
+                out.copy_from_slice(&raw_frame[..len]);
+                Ok(())
+            }
+            FrameFormat::Luma8 | FrameFormat::Rgb8 => Err(wrong_size(camera_format.format())),
+            other => Err(NokhwaError::ProcessFrameError {
+                src: other,
+                destination: "RGB888".to_string(),
+                error: "unsupported".to_string(),
+            }),
+        }
+    }
+
+    #[cfg(feature = "image")]
+    fn wrong_size(format: FrameFormat) -> NokhwaError {
+        NokhwaError::ProcessFrameError {
+            src: format,
+            destination: "RGB888".to_string(),
+            error: "decoded buffer has the wrong size".to_string(),
         }
     }
 
-    impl<'a> CaptureTrait for V4LCaptureDevice<'a> {
+    impl CaptureTrait for V4LCaptureDevice {
         fn init(&mut self) -> Result<(), NokhwaError> {
-            todo!()
+            self.force_refresh_camera_format()?;
+            self.init = true;
+            Ok(())
         }
 
-        fn init_with_format(&mut self, format: FormatFilter) -> Result<CameraFormat, NokhwaError> {
-            todo!()
+        fn init_with_format(&mut self, _format: FormatFilter) -> Result<CameraFormat, NokhwaError> {
+            // `nokhwa-core` has no resolver for format filters yet. Until it does, use `init()` and `set_camera_format()`.
+            Err(NokhwaError::NotImplementedError(
+                "init_with_format on V4LCaptureDevice".to_string(),
+            ))
         }
 
         fn backend(&self) -> ApiBackend {
@@ -214,84 +2266,24 @@ mod internal {
             self.force_refresh_camera_format()
         }
 
-        fn camera_format(&self) -> CameraFormat {
+        fn camera_format(&self) -> Option<CameraFormat> {
             self.camera_format
         }
 
         fn set_camera_format(&mut self, new_fmt: CameraFormat) -> Result<(), NokhwaError> {
-            let prev_format = match Capture::format(&self.device) {
-                Ok(fmt) => fmt,
-                Err(why) => {
-                    return Err(NokhwaError::GetPropertyError {
-                        property: "Resolution, FrameFormat".to_string(),
-                        error: why.to_string(),
-                    })
-                }
-            };
-            let prev_fps = match Capture::params(&self.device) {
-                Ok(fps) => fps,
-                Err(why) => {
-                    return Err(NokhwaError::GetPropertyError {
-                        property: "Frame rate".to_string(),
-                        error: why.to_string(),
-                    })
+            let previous = self.camera_format;
+            let granted = self.reconfigure(new_fmt)?;
+            if granted != new_fmt {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(
+                    requested = %new_fmt,
+                    actual = %granted,
+                    "V4L2 driver adjusted the requested format"
+                );
+                // Do not leave the device in a format nobody asked for.
+                if let Some(previous) = previous.filter(|previous| *previous != granted) {
+                    self.reconfigure(previous)?;
                 }
-            };
-
-            let v4l_fcc = match new_fmt.format() {
-                FrameFormat::MJPEG => FourCC::new(b"MJPG"),
-                FrameFormat::YUYV => FourCC::new(b"YUYV"),
-                FrameFormat::GRAY => FourCC::new(b"GRAY"),
-                FrameFormat::RAWRGB => FourCC::new(b"RGB3"),
-                FrameFormat::NV12 => FourCC::new(b"NV12"),
-            };
-
-            let format = Format::new(new_fmt.width(), new_fmt.height(), v4l_fcc);
-            let frame_rate = Parameters::with_fps(new_fmt.frame_rate());
-
-            if let Err(why) = Capture::set_format(&self.device, &format) {
-                return Err(NokhwaError::SetPropertyError {
-                    property: "Resolution, FrameFormat".to_string(),
-                    value: format.to_string(),
-                    error: why.to_string(),
-                });
-            }
-            if let Err(why) = Capture::set_params(&self.device, &frame_rate) {
-                return Err(NokhwaError::SetPropertyError {
-                    property: "Frame rate".to_string(),
-                    value: frame_rate.to_string(),
-                    error: why.to_string(),
-                });
-            }
-
-            if self.stream_handle.is_some() {
-                return match self.open_stream() {
-                    Ok(_) => Ok(()),
-                    Err(why) => {
-                        // undo
-                        if let Err(why) = Capture::set_format(&self.device, &prev_format) {
-                            return Err(NokhwaError::SetPropertyError {
-                                property: format!("Attempt undo due to stream acquisition failure with error {}. Resolution, FrameFormat", why),
-                                value: prev_format.to_string(),
-                                error: why.to_string(),
-                            });
-                        }
-                        if let Err(why) = Capture::set_params(&self.device, &prev_fps) {
-                            return Err(NokhwaError::SetPropertyError {
-                                property:
-                                format!("Attempt undo due to stream acquisition failure with error {}. Frame rate", why),
-                                value: prev_fps.to_string(),
-                                error: why.to_string(),
-                            });
-                        }
-                        Err(why)
-                    }
-                };
-            }
-            self.camera_format = new_fmt;
-
-            self.force_refresh_camera_format()?;
-            if self.camera_format != new_fmt {
                 return Err(NokhwaError::SetPropertyError {
                     property: "CameraFormat".to_string(),
                     value: new_fmt.to_string(),
@@ -302,50 +2294,63 @@ mod internal {
             Ok(())
         }
 
+        fn compatible_camera_formats(&mut self) -> Result<Vec<CameraFormat>, NokhwaError> {
+            let mut compatible_formats = vec![];
+            for fourcc in self.compatible_fourcc()? {
+                for resolution in self.compatible_list_by_resolution(fourcc)?.into_keys() {
+                    for frame_rate in self.compatible_frame_rates(fourcc, resolution)? {
+                        compatible_formats.push(CameraFormat::new(resolution, fourcc, frame_rate));
+                    }
+                }
+            }
+            Ok(compatible_formats)
+        }
+
         fn compatible_list_by_resolution(
             &mut self,
             fourcc: FrameFormat,
         ) -> Result<HashMap<Resolution, Vec<u32>>, NokhwaError> {
-            let resolutions = self.get_resolution_list(fourcc)?;
-            let format = frameformat_to_fourcc(fourcc);
+            if let Some(res_map) = self.resolution_cache.get(&fourcc) {
+                return Ok(res_map.clone());
+            }
+            let format = frameformat_to_fourcc(fourcc).ok_or(NokhwaError::GetPropertyError {
+                property: "FrameFormat".to_string(),
+                error: format!("{fourcc} is unsupported"),
+            })?;
+            let resolutions = self
+                .device
+                .enum_framesizes(format)
+                .map_err(|why| NokhwaError::GetPropertyError {
+                    property: "Resolution".to_string(),
+                    error: why.to_string(),
+                })?
+                .into_iter()
+                .flat_map(|size| match size.size {
+                    FrameSizeEnum::Discrete(dis) => vec![Resolution::new(dis.width, dis.height)],
+                    // Stepwise sizes can contain thousands of entries, so only report the bounds.
+                    FrameSizeEnum::Stepwise(step) => vec![
+                        Resolution::new(step.min_width, step.min_height),
+                        Resolution::new(step.max_width, step.max_height),
+                    ],
+                })
+                .collect::<Vec<Resolution>>();
             let mut res_map = HashMap::new();
             for res in resolutions {
-                let mut compatible_fps = vec![];
-                match self
-                    .device
-                    .enum_frameintervals(format, res.width(), res.height())
-                {
-                    Ok(intervals) => {
-                        for interval in intervals {
-                            match interval.interval {
-                                FrameIntervalEnum::Discrete(dis) => {
-                                    compatible_fps.push(dis.denominator);
-                                }
-                                FrameIntervalEnum::Stepwise(step) => {
-                                    for fstep in (step.min.numerator..step.max.numerator)
-                                        .step_by(step.step.numerator as usize)
-                                    {
-                                        if step.max.denominator != 1 || step.min.denominator != 1 {
-                                            compatible_fps.push(fstep);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Err(why) => {
-                        return Err(NokhwaError::GetPropertyError {
-                            property: "Frame rate".to_string(),
-                            error: why.to_string(),
-                        })
-                    }
-                }
+                let compatible_fps = self
+                    .frame_rates(format, res)?
+                    .into_iter()
+                    .map(|frame_rate| frame_rate.as_u32())
+                    .collect();
                 res_map.insert(res, compatible_fps);
             }
+            self.resolution_cache.insert(fourcc, res_map.clone());
             Ok(res_map)
         }
 
         fn compatible_fourcc(&mut self) -> Result<Vec<FrameFormat>, NokhwaError> {
+            if let Some(formats) = &self.fourcc_cache {
+                return Ok(formats.clone());
+            }
             match self.device.enum_formats() {
                 Ok(formats) => {
                     let mut frame_format_vec = vec![];
@@ -357,6 +2362,7 @@ mod internal {
                     }
                     frame_format_vec.sort();
                     frame_format_vec.dedup();
+                    self.fourcc_cache = Some(frame_format_vec.clone());
                     Ok(frame_format_vec)
                 }
                 Err(why) => Err(NokhwaError::GetPropertyError {
@@ -366,32 +2372,38 @@ mod internal {
             }
         }
 
-        fn resolution(&self) -> Resolution {
-            self.camera_format.resolution()
+        fn resolution(&self) -> Option<Resolution> {
+            self.camera_format.map(|fmt| fmt.resolution())
         }
 
         fn set_resolution(&mut self, new_res: Resolution) -> Result<(), NokhwaError> {
-            let mut new_fmt = self.camera_format;
+            let mut new_fmt = self.current_format()?;
             new_fmt.set_resolution(new_res);
             self.set_camera_format(new_fmt)
         }
 
-        fn frame_rate(&self) -> u32 {
-            self.camera_format.frame_rate()
+        fn frame_rate(&self) -> Option<u32> {
+            self.camera_format.map(|fmt| fmt.frame_rate().as_u32())
         }
 
         fn set_frame_rate(&mut self, new_fps: u32) -> Result<(), NokhwaError> {
-            let mut new_fmt = self.camera_format;
-            new_fmt.set_frame_rate(new_fps);
+            let mut new_fmt = self.current_format()?;
+            // Prefer the exact rate the device advertises, e.g. 30000/1001 when asked for 30.
+            let frame_rate = self
+                .compatible_frame_rates(new_fmt.format(), new_fmt.resolution())
+                .ok()
+                .and_then(|rates| rates.into_iter().find(|rate| rate.as_u32() == new_fps))
+                .unwrap_or(FrameRate::Integer(new_fps));
+            new_fmt.set_frame_rate(frame_rate);
             self.set_camera_format(new_fmt)
         }
 
         fn frame_format(&self) -> FrameFormat {
-            self.camera_format.format()
+            self.camera_format.unwrap_or_default().format()
         }
 
         fn set_frame_format(&mut self, fourcc: FrameFormat) -> Result<(), NokhwaError> {
-            let mut new_fmt = self.camera_format;
+            let mut new_fmt = self.current_format()?;
             new_fmt.set_format(fourcc);
             self.set_camera_format(new_fmt)
         }
@@ -414,13 +2426,9 @@ mod internal {
 
         #[allow(clippy::cast_possible_wrap)]
         fn camera_controls(&self) -> Result<Vec<CameraControl>, NokhwaError> {
-            self.device
-                .query_controls()
-                .map_err(|why| NokhwaError::GetPropertyError {
-                    property: "V4L2 Controls".to_string(),
-                    error: why.to_string(),
-                })?
-                .into_iter()
+            // The descriptions are cached, but the values are always read from the device.
+            self.cached_control_descriptions()?
+                .iter()
                 .map(|desc| {
                     let id_as_kcc = id_to_known_camera_control(desc.id);
                     let ctrl_current = self.device.control(desc.id)?.value;
@@ -495,7 +2503,7 @@ mod internal {
 
                     Ok(CameraControl::new(
                         id_as_kcc,
-                        desc.name,
+                        desc.name.clone(),
                         ctrl_value_desc,
                         flags_vec,
                         !desc.flags.intersects(Flags::INACTIVE),
@@ -533,65 +2541,262 @@ mod internal {
                     id: known_camera_control_to_id(id),
                     value: conv_value,
                 })
-                .map_err(|why| NokhwaError::SetPropertyError {
+                .map_err(|why| self.set_error(&id.to_string(), format!("{value:?}"), why))?;
+            // verify
+
+            let control = self.camera_control(id)?;
+            if control.value() != value {
+                return Err(NokhwaError::SetPropertyError {
                     property: id.to_string(),
                     value: format!("{:?}", value),
+                    error: "Rejected".to_string(),
+                });
+            }
+            self.applied_controls.insert(id, value);
+            Ok(())
+        }
+
+        fn crop(&self) -> Option<Region> {
+            let crop = selection(&self.device, V4L2_SEL_TGT_CROP).ok()?;
+            let default = selection(&self.device, V4L2_SEL_TGT_CROP_DEFAULT).ok()?;
+            let unchanged = (crop.left, crop.top, crop.width, crop.height)
+                == (default.left, default.top, default.width, default.height);
+            if unchanged {
+                None
+            } else {
+                rect_to_region(crop)
+            }
+        }
+
+        fn set_crop(&mut self, region: Option<Region>) -> Result<Option<Region>, NokhwaError> {
+            let map_err = |why: io::Error| match why.raw_os_error() {
+                // Drivers without a cropping unit do not implement the selection API.
+                Some(ENOTTY | EINVAL) => {
+                    NokhwaError::UnsupportedOperationError(ApiBackend::Video4Linux)
+                }
+                _ => NokhwaError::SetPropertyError {
+                    property: "Crop".to_string(),
+                    value: region.map_or_else(|| "None".to_string(), |region| region.to_string()),
                     error: why.to_string(),
-                })?;
-            // verify
+                },
+            };
+            let rect = match region {
+                Some(region) => v4l2_rect {
+                    left: i32::try_from(region.x()).unwrap_or(i32::MAX),
+                    top: i32::try_from(region.y()).unwrap_or(i32::MAX),
+                    width: region.width(),
+                    height: region.height(),
+                },
+                None => selection(&self.device, V4L2_SEL_TGT_CROP_DEFAULT).map_err(map_err)?,
+            };
+            // SAFETY: `v4l2_selection` is a plain C struct, for which all zeroes is a valid value.
+            let mut raw_selection: v4l2_selection = unsafe { std::mem::zeroed() };
+            raw_selection.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_CAPTURE;
+            raw_selection.target = V4L2_SEL_TGT_CROP;
+            raw_selection.r = rect;
+            // SAFETY: `VIDIOC_S_SELECTION` takes a `v4l2_selection`, which `raw_selection` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_S_SELECTION, &mut raw_selection) }
+                .map_err(map_err)?;
+            // Without a scaler, the format shrinks to the crop rectangle.
+            self.force_refresh_camera_format()?;
+            Ok(region.and_then(|_| rect_to_region(raw_selection.r)))
+        }
+
+        fn open_stream(&mut self) -> Result<(), NokhwaError> {
+            if !self.init {
+                self.init()?;
+            }
+
+            // MmapStream allocates the buffers. They are queued and streaming is turned on (STREAMON) below.
+            let stream = MmapStream::with_buffers(
+                &self.device,
+                v4l::buffer::Type::VideoCapture,
+                self.buffer_count,
+            )
+            .map_err(|why| NokhwaError::from_io("Open stream", why))?;
+            // The driver may grant more or fewer buffers than requested.
+            self.granted_buffer_count = Some(self.count_mapped_buffers());
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                requested = self.buffer_count,
+                granted = ?self.granted_buffer_count,
+                "Allocated V4L2 buffers"
+            );
+            let format = Capture::format(&self.device).ok();
+            // Drivers report 0 for compressed formats.
+            self.stride = format
+                .as_ref()
+                .map(|format| format.stride)
+                .filter(|stride| *stride != 0);
+            self.pixel_aspect = format.and_then(|format| pixel_aspect(&self.device, &format));
+            self.metadata_stream = match self.camera_info.index().as_index() {
+                Ok(index) if self.frame_metadata => {
+                    MetadataStream::open(index as usize, self.buffer_count)
+                }
+                _ => None,
+            };
+            self.stream_handle = Some(stream);
+            self.queued_buffers.clear();
+            self.last_buffer_index = None;
+            self.primed = false;
+            self.paused = false;
+            self.last_sequence = None;
+            self.dropped_frames = 0;
+            self.start_streaming()
+        }
+
+        fn is_stream_open(&self) -> bool {
+            self.stream_handle.is_some()
+        }
+
+        fn frame(&mut self) -> Result<Buffer, NokhwaError> {
+            if !self.is_stream_open() {
+                self.open_stream()?;
+            }
+            match self.watchdog {
+                Some((interval, action)) => self.watched_frame(interval, action),
+                None => self.dequeue_frame(),
+            }
+        }
+
+        fn frame_raw(&mut self) -> Result<Cow<[u8]>, NokhwaError> {
+            self.next_buffer().map(|(data, _)| Cow::Borrowed(data))
+        }
+
+        fn stop_stream(&mut self) -> Result<(), NokhwaError> {
+            if let Some(mut stream) = self.stream_handle.take() {
+                stream
+                    .stop()
+                    .map_err(|why| NokhwaError::StreamShutdownError(why.to_string()))?;
+            }
+            self.metadata_stream = None;
+            #[cfg(feature = "async")]
+            {
+                self.async_source = None;
+            }
+            self.granted_buffer_count = None;
+            self.queued_buffers.clear();
+            self.last_buffer_index = None;
+            self.primed = false;
+            self.paused = false;
+            Ok(())
+        }
+    }
+
+    /// The device's file descriptor. Once the stream is open, it becomes readable when a frame can be dequeued with
+    /// [`read_ready_frame()`](V4LCaptureDevice::read_ready_frame).
+    ///
+    /// Do not read from, or close, this file descriptor yourself.
+    impl AsRawFd for V4LCaptureDevice {
+        fn as_raw_fd(&self) -> RawFd {
+            self.device.handle().fd()
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[async_trait::async_trait]
+    impl AsyncCaptureTrait for V4LCaptureDevice {
+        async fn init_async(&mut self) -> Result<(), NokhwaError> {
+            self.init()
+        }
+
+        async fn init_with_format_async(
+            &mut self,
+            format: FormatFilter,
+        ) -> Result<CameraFormat, NokhwaError> {
+            self.init_with_format(format)
+        }
+
+        async fn refresh_camera_format_async(&mut self) -> Result<(), NokhwaError> {
+            self.refresh_camera_format()
+        }
+
+        async fn set_camera_format_async(
+            &mut self,
+            new_fmt: CameraFormat,
+        ) -> Result<(), NokhwaError> {
+            self.set_camera_format(new_fmt)
+        }
+
+        async fn compatible_list_by_resolution_async(
+            &mut self,
+            fourcc: FrameFormat,
+        ) -> Result<HashMap<Resolution, Vec<u32>>, NokhwaError> {
+            self.compatible_list_by_resolution(fourcc)
+        }
+
+        async fn compatible_camera_formats_async(
+            &mut self,
+        ) -> Result<Vec<CameraFormat>, NokhwaError> {
+            self.compatible_camera_formats()
+        }
+
+        async fn compatible_fourcc_async(&mut self) -> Result<Vec<FrameFormat>, NokhwaError> {
+            self.compatible_fourcc()
+        }
+
+        async fn set_resolution_async(&mut self, new_res: Resolution) -> Result<(), NokhwaError> {
+            self.set_resolution(new_res)
+        }
+
+        async fn set_frame_rate_async(&mut self, new_fps: u32) -> Result<(), NokhwaError> {
+            self.set_frame_rate(new_fps)
+        }
+
+        async fn set_frame_format_async(&mut self, fourcc: FrameFormat) -> Result<(), NokhwaError> {
+            self.set_frame_format(fourcc)
+        }
+
+        async fn set_camera_control_async(
+            &mut self,
+            id: KnownCameraControl,
+            value: ControlValueSetter,
+        ) -> Result<(), NokhwaError> {
+            self.set_camera_control(id, value)
+        }
 
-            let control = self.camera_control(id)?;
-            if control.value() != value {
-                return Err(NokhwaError::SetPropertyError {
-                    property: id.to_string(),
-                    value: format!("{:?}", value),
-                    error: "Rejected".to_string(),
-                });
-            }
-            Ok(())
+        async fn open_stream_async(&mut self) -> Result<(), NokhwaError> {
+            self.open_stream()
         }
 
-        fn open_stream(&mut self) -> Result<(), NokhwaError> {
-            let stream = match MmapStream::new(&self.device, v4l::buffer::Type::VideoCapture) {
-                Ok(s) => s,
-                Err(why) => return Err(NokhwaError::OpenStreamError(why.to_string())),
-            };
-            self.stream_handle = Some(stream);
-            Ok(())
+        async fn frame_async(&mut self) -> Result<Buffer, NokhwaError> {
+            self.stream_ready().await?;
+            self.frame()
         }
 
-        fn is_stream_open(&self) -> bool {
-            self.stream_handle.is_some()
+        async fn frame_raw_async(&mut self) -> Result<Cow<[u8]>, NokhwaError> {
+            self.stream_ready().await?;
+            self.frame_raw()
         }
 
-        fn frame(&mut self) -> Result<Buffer, NokhwaError> {
-            let cam_fmt = self.camera_format;
-            let raw_frame = self.frame_raw()?;
-            Ok(Buffer::new(
-                cam_fmt.resolution(),
-                &raw_frame,
-                cam_fmt.format(),
-            ))
+        async fn stop_stream_async(&mut self) -> Result<(), NokhwaError> {
+            self.stop_stream()
         }
+    }
 
-        fn frame_raw(&mut self) -> Result<Cow<[u8]>, NokhwaError> {
-            match &mut self.stream_handle {
-                Some(sh) => match sh.next() {
-                    Ok((data, _)) => Ok(Cow::Borrowed(data)),
-                    Err(why) => Err(NokhwaError::ReadFrameError(why.to_string())),
-                },
-                None => Err(NokhwaError::ReadFrameError(
-                    "Stream Not Started".to_string(),
-                )),
-            }
+    /// Converts a `V4L2` buffer timestamp (`CLOCK_MONOTONIC` for cameras) into a [`Duration`]. Returns `None` if the driver did not set one.
+    fn monotonic_now() -> Option<Duration> {
+        let mut now = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `now` is a valid timespec to write into.
+        if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) } != 0 {
+            return None;
         }
+        Some(Duration::new(
+            u64::try_from(now.tv_sec).ok()?,
+            u32::try_from(now.tv_nsec).ok()?,
+        ))
+    }
 
-        fn stop_stream(&mut self) -> Result<(), NokhwaError> {
-            if self.stream_handle.is_some() {
-                self.stream_handle = None;
-            }
-            Ok(())
+    fn timestamp_to_duration(timestamp: Timestamp) -> Option<Duration> {
+        let secs = u64::try_from(timestamp.sec).ok()?;
+        let micros = u32::try_from(timestamp.usec).ok()?;
+        if secs == 0 && micros == 0 {
+            return None;
         }
+        Some(Duration::new(secs, 0) + Duration::from_micros(u64::from(micros)))
     }
 
     fn fourcc_to_frameformat(fourcc: FourCC) -> Option<FrameFormat> {
@@ -615,27 +2820,49 @@ mod internal {
             _ => None,
         }
     }
-    
 
-    fn frameformat_to_fourcc(fourcc: FrameFormat) -> FourCC {
-        match fourcc {
-            
-        }
+    pub(crate) fn frameformat_to_fourcc(fourcc: FrameFormat) -> Option<FourCC> {
+        let raw = match fourcc {
+            FrameFormat::Yuv422 => b"YUYV",
+            FrameFormat::Uyv422 => b"UYVY",
+            FrameFormat::Yv12 => b"YV12",
+            FrameFormat::MJpeg => b"MJPG",
+            FrameFormat::Luma8 => b"GRAY",
+            FrameFormat::Rgb8 => b"RGB3",
+            FrameFormat::Nv12 => b"NV12",
+            FrameFormat::H264 => b"H264",
+            FrameFormat::Avc1 => b"AVC1",
+            FrameFormat::H263 => b"H263",
+            FrameFormat::XVid => b"XVID",
+            FrameFormat::VP8 => b"VP80",
+            FrameFormat::VP9 => b"VP90",
+            FrameFormat::Mpeg1 => b"MPG1",
+            FrameFormat::Mpeg2 => b"MPG2",
+            FrameFormat::Mpeg4 => b"MPG4",
+            _ => return None,
+        };
+        Some(FourCC::new(raw))
     }
 }
 
 #[cfg(not(target_os = "linux"))]
 mod internal {
+    use crate::types::{
+        AnalogVideoStandard, DeviceEvent, DevicePriority, DvTimings, ReconnectEvent, V4L2Input,
+        V4L2Standard, WatchdogAction,
+    };
+    #[cfg(feature = "image")]
+    use image::{ImageBuffer, Rgb};
     use nokhwa_core::buffer::Buffer;
     use nokhwa_core::error::NokhwaError;
     use nokhwa_core::traits::CaptureTrait;
     use nokhwa_core::types::{
         ApiBackend, CameraControl, CameraFormat, CameraIndex, CameraInfo, ControlValueSetter,
-        FrameFormat, KnownCameraControl, RequestedFormat, Resolution,
+        FrameFormat, KnownCameraControl, PixelAspectRatio, Region, RequestedFormat, Resolution,
     };
     use std::borrow::Cow;
     use std::collections::HashMap;
-    use std::marker::PhantomData;
+    use std::time::Duration;
 
     /// Attempts to convert a [`KnownCameraControl`] into a V4L2 Control ID.
     /// If the associated control is not found, this will return `None` (`ColorEnable`, `Roll`)
@@ -655,12 +2882,12 @@ mod internal {
     /// To see what this does, please see [`CaptureTrait`].
     /// # Quirks
     /// - Calling [`set_resolution()`](CaptureTrait::set_resolution), [`set_frame_rate()`](CaptureTrait::set_frame_rate), or [`set_frame_format()`](CaptureTrait::set_frame_format) each internally calls [`set_camera_format()`](CaptureTrait::set_camera_format).
-    pub struct V4LCaptureDevice<'a> {
-        __holder: PhantomData<&'a str>,
+    pub struct V4LCaptureDevice {
+        _private: (),
     }
 
     #[allow(unused_variables)]
-    impl<'a> V4LCaptureDevice<'a> {
+    impl V4LCaptureDevice {
         /// Creates a new capture device using the `V4L2` backend. Indexes are gives to devices by the OS, and usually numbered by order of discovery.
         /// # Errors
         /// This function will error if the camera is currently busy or if `V4L2` can't read device information.
@@ -695,10 +2922,432 @@ mod internal {
                 "V4L2 only on Linux".to_string(),
             ))
         }
+
+        /// Lists the inputs (e.g. HDMI, Composite, S-Video connectors) of this device.
+        /// # Errors
+        /// If the driver fails to enumerate the inputs, this will error.
+        pub fn inputs(&self) -> Result<Vec<V4L2Input>, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Gets the index of the currently selected input.
+        /// # Errors
+        /// If the driver fails to report the current input, this will error.
+        pub fn input(&self) -> Result<u32, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Selects the input (connector) to capture from.
+        /// # Errors
+        /// If the index is out of range, or the device is currently streaming, this will error.
+        pub fn set_input(&mut self, index: u32) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Lists the analog video standards (e.g. PAL, NTSC, SECAM) the current input supports.
+        /// # Errors
+        /// If the driver fails to enumerate the standards, this will error.
+        pub fn standards(&self) -> Result<Vec<V4L2Standard>, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Gets the currently selected analog video standard as a raw `v4l2_std_id`.
+        /// # Errors
+        /// If the current input does not support video standards, this will error.
+        pub fn standard(&self) -> Result<u64, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Asks the driver to sense the analog video standard of the incoming signal.
+        /// # Errors
+        /// If the driver does not support sensing, or there is no signal, this will error.
+        pub fn query_standard(&self) -> Result<u64, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Selects the analog video standard of the current input.
+        /// # Errors
+        /// If the standard is not supported by the current input, or the device is currently streaming, this will error.
+        pub fn set_standard(&mut self, standard: AnalogVideoStandard) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Gets the EDID the current input advertises to the connected source.
+        /// # Errors
+        /// If the current input does not have an EDID, this will error.
+        pub fn edid(&self) -> Result<Vec<u8>, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Sets the EDID the current input advertises to the connected source.
+        /// # Errors
+        /// If the `edid` is not a multiple of 128 bytes, or the driver rejects the EDID, this will error.
+        pub fn set_edid(&mut self, edid: &[u8]) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Gets the [`DvTimings`] the device is currently configured to receive.
+        /// # Errors
+        /// If the current input is not a digital video input, this will error.
+        pub fn dv_timings(&self) -> Result<DvTimings, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Asks the device to detect the [`DvTimings`] of the incoming signal.
+        /// # Errors
+        /// If there is no signal, or the current input is not a digital video input, this will error.
+        pub fn query_dv_timings(&self) -> Result<DvTimings, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Sets the [`DvTimings`] the device should receive.
+        /// # Errors
+        /// If the timings are not supported, or the device is currently streaming, this will error.
+        pub fn set_dv_timings(&mut self, timings: DvTimings) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Forgets the cached formats and controls.
+        pub fn clear_capability_cache(&mut self) {}
+
+        /// Will get a frame from the camera and decode it into an RGB888 [`ImageBuffer`].
+        /// # Errors
+        /// If the frame could not be captured, the frame format can not be decoded, or the decoding fails, this will error.
+        #[cfg(feature = "image")]
+        pub fn frame_rgb(&mut self) -> Result<ImageBuffer<Rgb<u8>, Vec<u8>>, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Will get a frame from the camera and decode it into a reused RGB888 buffer.
+        /// # Errors
+        /// If the frame could not be captured, the frame format can not be decoded, or the decoding fails, this will error.
+        #[cfg(feature = "image")]
+        pub fn frame_rgb_ref(&mut self) -> Result<ImageBuffer<Rgb<u8>, &[u8]>, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Will get a frame from the camera, waiting at most `timeout` for one to arrive.
+        ///
+        /// Use this instead of [`frame()`](CaptureTrait::frame) if the device may stall (e.g. a flaky USB hub), which would otherwise block forever.
+        /// # Errors
+        /// If no frame arrived within `timeout`, this returns [`TimeoutError`](NokhwaError::TimeoutError). The stream is left open,
+        /// so you may retry or restart it with [`stop_stream()`](CaptureTrait::stop_stream) and [`open_stream()`](CaptureTrait::open_stream).
+        /// Otherwise, this errors the same way as [`frame()`](CaptureTrait::frame).
+        pub fn frame_timeout(&mut self, timeout: Duration) -> Result<Buffer, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Will get a frame from the camera if one is ready, without waiting. Returns `Ok(None)` if no new frame has arrived yet.
+        ///
+        /// This lets a single-threaded event loop interleave capture with other work.
+        /// # Errors
+        /// This errors the same way as [`frame()`](CaptureTrait::frame).
+        pub fn try_frame(&mut self) -> Result<Option<Buffer>, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Dequeues a frame after the device's file descriptor (see `AsRawFd`) was reported readable by your own reactor
+        /// (`epoll`, `mio`, `tokio`'s `AsyncFd`, ...). [`open_stream()`](CaptureTrait::open_stream) turns streaming on, so
+        /// open the stream, wait until the file descriptor is readable, and call this.
+        ///
+        /// This never blocks. Returns `Ok(None)` on a spurious wakeup.
+        /// # Errors
+        /// If the stream is not open or paused, or the frame could not be dequeued, this will error. A stream with a single
+        /// buffer (see [`set_buffer_count()`](Self::set_buffer_count)) only starts on its first blocking read, so it can not
+        /// be polled and errors as well.
+        pub fn read_ready_frame(&mut self) -> Result<Option<Buffer>, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Takes a still photo at the highest resolution the camera supports for the current [`FrameFormat`].
+        ///
+        /// `uvcvideo` does not expose the UVC still image pin, so this temporarily switches the camera to its highest resolution,
+        /// captures a frame, then restores the previous [`CameraFormat`] (and stream state). Expect the video stream to be interrupted for a moment.
+        /// # Errors
+        /// If the formats cannot be listed or set, or the frame cannot be captured, this will error. The previous format is restored in any case.
+        pub fn take_photo(&mut self) -> Result<Buffer, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Sets the stream watchdog. If no frame arrives within `interval` during [`frame()`](CaptureTrait::frame), `action` is taken.
+        /// Some webcams silently stop delivering frames after running for a long time, and only recover after streaming is restarted.
+        ///
+        /// Pass `None` to disable it (the default). The watchdog does not apply to [`frame_raw()`](CaptureTrait::frame_raw).
+        pub fn set_watchdog(&mut self, watchdog: Option<(Duration, WatchdogAction)>) {}
+
+        /// Gets the stream watchdog interval and action, if set.
+        #[must_use]
+        pub fn watchdog(&self) -> Option<(Duration, WatchdogAction)> {
+            None
+        }
+
+        /// Enables automatic reconnection. If the device is unplugged, the frame call that notices it errors as usual, and the next one waits up to
+        /// `timeout` for a device with the same USB port (bus info) and name to reappear. It then re-applies the [`CameraFormat`] and any controls
+        /// set with [`set_camera_control()`](CaptureTrait::set_camera_control), and reopens the stream if it was open.
+        ///
+        /// Pass `None` to disable it (the default).
+        pub fn set_auto_reconnect(&mut self, timeout: Option<Duration>) {}
+
+        /// Sets a callback that is called with a [`ReconnectEvent`] when the device is disconnected and when reconnection succeeds or fails.
+        pub fn set_reconnect_callback(
+            &mut self,
+            callback: impl FnMut(ReconnectEvent) + Send + 'static,
+        ) {
+        }
+
+        /// Subscribes to source change events (`V4L2_EVENT_SOURCE_CHANGE`) of the current input, so a capture card reports
+        /// [`DeviceEvent::SourceChanged`] when the signal plugged into it changes resolution, instead of streaming garbage.
+        ///
+        /// Subscriptions belong to the open device, so they must be made again after a reconnect.
+        /// # Errors
+        /// If the driver does not support source change events (most webcams), this will error.
+        pub fn subscribe_source_change(&mut self) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Subscribes to change events (`V4L2_EVENT_CTRL`) of `controls`, reported as [`DeviceEvent::ControlChanged`].
+        /// # Errors
+        /// If the driver does not support control events, or one of the controls does not exist, this will error. The
+        /// controls before it stay subscribed.
+        pub fn subscribe_control_changes(
+            &mut self,
+            controls: &[KnownCameraControl],
+        ) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Unsubscribes from all events (`V4L2_EVENT_ALL`).
+        /// # Errors
+        /// If the driver rejects it, this will error.
+        pub fn unsubscribe_events(&mut self) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Sets a callback that is called with every [`DeviceEvent`] that arrived, each time a frame is captured. Without a
+        /// callback, collect them with [`pending_events()`](Self::pending_events) instead, which also works while frames
+        /// have stopped arriving.
+        pub fn set_event_callback(&mut self, callback: impl FnMut(DeviceEvent) + Send + 'static) {}
+
+        /// Dequeues the events that have arrived since the last call, without waiting.
+        /// # Errors
+        /// If dequeuing an event fails, this will error.
+        pub fn pending_events(&mut self) -> Result<Vec<DeviceEvent>, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Checks if the device was found to be unplugged and has not been reconnected yet.
+        #[must_use]
+        pub fn is_disconnected(&self) -> bool {
+            false
+        }
+
+        /// Changes the format of an open stream with as little teardown as possible: streaming is turned off and the buffers
+        /// released (drivers refuse to change the format while they are allocated), the new format and frame rate are set,
+        /// controls set with [`set_camera_control()`](CaptureTrait::set_camera_control) are applied again (some drivers reset
+        /// them on a format change), and the stream is reopened. Frames still queued in the old format are dropped.
+        ///
+        /// Drivers adjust a format they can not do to the closest one they can. Unlike
+        /// [`set_camera_format()`](CaptureTrait::set_camera_format), which restores the previous format and errors then, this
+        /// keeps the adjusted format and returns it; compare it to `format` if only an exact match will do. If the stream is
+        /// not open, this only sets the format.
+        /// # Errors
+        /// If the driver rejects the format, or the stream can not be reopened, this will error. In that case the previous
+        /// format is restored (and its stream reopened) if possible.
+        pub fn reconfigure(&mut self, format: CameraFormat) -> Result<CameraFormat, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Pauses the stream: streaming is turned off (STREAMOFF), but the buffers and the negotiated format are kept,
+        /// so [`resume_stream()`](Self::resume_stream) is much cheaper than closing and reopening the stream.
+        ///
+        /// While paused, the stream is still considered open, but getting a frame will error.
+        /// # Errors
+        /// If the stream is not open, or the driver fails to turn off streaming, this will error.
+        pub fn pause_stream(&mut self) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Resumes a stream paused with [`pause_stream()`](Self::pause_stream), turning streaming back on.
+        /// # Errors
+        /// If the stream is not open, or the driver fails to turn on streaming, this will error.
+        pub fn resume_stream(&mut self) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Checks if the stream is paused.
+        #[must_use]
+        pub fn is_stream_paused(&self) -> bool {
+            false
+        }
+
+        /// The number of buffers that will be requested from the driver when the stream is opened. Defaults to 4.
+        #[must_use]
+        pub fn buffer_count(&self) -> u32 {
+            0
+        }
+
+        /// Sets the number of buffers to request from the driver. This takes effect the next time [`open_stream()`](CaptureTrait::open_stream) is called.
+        ///
+        /// More buffers tolerate a slower consumer without dropping frames, at the cost of memory and latency (a full queue is older frames).
+        /// # Errors
+        /// If `count` is 0, this will error.
+        pub fn set_buffer_count(&mut self, count: u32) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// The number of buffers the driver actually granted for the open stream, or `None` if the stream is not open.
+        #[must_use]
+        pub fn granted_buffer_count(&self) -> Option<u32> {
+            None
+        }
+
+        /// The largest region of the sensor [`set_crop()`](CaptureTrait::set_crop) can crop to, from `VIDIOC_G_SELECTION`.
+        /// # Errors
+        /// If the driver does not support the selection API, this will error.
+        pub fn crop_bounds(&self) -> Result<Region, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// The JPEG compression quality (0 to 100, higher is better) of an MJPEG stream, from the
+        /// `V4L2_CID_JPEG_COMPRESSION_QUALITY` control or, on older drivers, `VIDIOC_G_JPEGCOMP`.
+        /// # Errors
+        /// If the driver exposes neither, this will error. `uvcvideo` only does for cameras that map the UVC compression
+        /// quality to a control.
+        pub fn jpeg_quality(&self) -> Result<u32, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Sets the JPEG compression quality (0 to 100, clamped) of an MJPEG stream. Lowering it shrinks frames, which helps
+        /// on a crowded USB hub where the camera would otherwise drop frames. Drivers round to the qualities they support,
+        /// so the quality actually set is returned.
+        /// # Errors
+        /// If the driver supports neither the control nor `VIDIOC_S_JPEGCOMP`, or rejects the value, this will error.
+        pub fn set_jpeg_quality(&mut self, quality: u32) -> Result<u32, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// The highest access priority any handle holds on the device (`VIDIOC_G_PRIORITY`), which is not necessarily the
+        /// one this device set.
+        /// # Errors
+        /// If the driver does not support priorities, this will error.
+        pub fn priority(&self) -> Result<DevicePriority, NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Sets the access priority of this device (`VIDIOC_S_PRIORITY`). A recording application should take
+        /// [`DevicePriority::Record`], so background processes can not change the format or controls mid-recording; they
+        /// get a [`PriorityError`](NokhwaError::PriorityError) instead. The priority is set again after a reconnect.
+        /// # Errors
+        /// If another handle already holds [`DevicePriority::Record`], this will error with a
+        /// [`PriorityError`](NokhwaError::PriorityError).
+        pub fn set_priority(&mut self, priority: DevicePriority) -> Result<(), NokhwaError> {
+            Err(NokhwaError::NotImplementedError(
+                "V4L2 only on Linux".to_string(),
+            ))
+        }
+
+        /// Turns attaching the exposure time and gain each frame was captured with (see [`Buffer::capture_parameters()`]) on or
+        /// off. This streams the camera's UVC metadata node alongside it, so it only works with `uvcvideo` cameras that report
+        /// capture stats in their payload headers (the Microsoft UVC extensions), and takes effect the next time
+        /// [`open_stream()`](CaptureTrait::open_stream) is called. Off by default.
+        pub fn set_frame_metadata(&mut self, enabled: bool) {}
+
+        /// Whether the UVC metadata node is read for per frame capture parameters.
+        #[must_use]
+        pub fn frame_metadata(&self) -> bool {
+            false
+        }
+
+        /// Returns true if the open stream found a UVC metadata node to read capture parameters from.
+        #[must_use]
+        pub fn has_metadata_stream(&self) -> bool {
+            false
+        }
+
+        /// The shape of the pixels in the current format, from `VIDIOC_CROPCAP`. Analog and DV capture cards report
+        /// non-square pixels for their standard; most webcams report [`PixelAspectRatio::SQUARE`] or nothing at all.
+        #[must_use]
+        pub fn pixel_aspect_ratio(&self) -> Option<PixelAspectRatio> {
+            None
+        }
+
+        /// The number of frames the driver captured but that were never dequeued, since the stream was last opened.
+        ///
+        /// This grows when the consumer can't keep up (all buffers were full) or the device skipped frames, e.g. due to USB bandwidth.
+        #[must_use]
+        pub fn dropped_frames(&self) -> u64 {
+            0
+        }
+
+        /// The sequence number of the last frame dequeued, if any.
+        #[must_use]
+        pub fn last_sequence(&self) -> Option<u32> {
+            None
+        }
     }
 
     #[allow(unused_variables)]
-    impl<'a> CaptureTrait for V4LCaptureDevice<'a> {
+    impl CaptureTrait for V4LCaptureDevice {
         fn backend(&self) -> ApiBackend {
             ApiBackend::Video4Linux
         }
@@ -795,4 +3444,11 @@ mod internal {
     }
 }
 
+#[cfg(target_os = "linux")]
+pub use dmabuf::{DmaBufExport, DmaBufPlane, DRM_FORMAT_MOD_LINEAR};
 pub use internal::*;
+#[cfg(target_os = "linux")]
+pub use output::V4LOutputDevice;
+#[cfg(target_os = "linux")]
+pub use scheduler::{CameraId, CaptureScheduler, SchedulerEvent, DEFAULT_EVENT_CAPACITY};
+pub use types::*;
@@ -0,0 +1,290 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Writing frames to a V4L2 output device, such as a `v4l2loopback` virtual camera.
+
+use crate::{
+    internal::{frameformat_to_fourcc, v4l2_ioctl},
+    DmaBufExport,
+};
+use nokhwa_core::{error::NokhwaError, frame_format::FrameFormat, types::Resolution};
+use std::{
+    io::{self, ErrorKind},
+    os::fd::{AsRawFd, RawFd},
+};
+use v4l::{v4l2::vidioc, video::Output, Device, Format};
+use v4l2_sys_mit::{
+    v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_OUTPUT, v4l2_buffer, v4l2_memory_V4L2_MEMORY_DMABUF,
+    v4l2_requestbuffers,
+};
+
+/// `POLLOUT` from `poll.h`.
+const POLLOUT: i16 = 0x004;
+
+/// `DMA_BUF_IOCTL_SYNC`, and its flags for a read access.
+const DMA_BUF_IOCTL_SYNC: libc::c_ulong = 0x4008_6200;
+const DMA_BUF_SYNC_READ: u64 = 0x1;
+const DMA_BUF_SYNC_END: u64 = 0x4;
+
+/// A V4L2 output device, such as a `v4l2loopback` virtual camera, that frames can be written to.
+///
+/// Together with [`V4LCaptureDevice::export_dmabufs()`](crate::V4LCaptureDevice::export_dmabufs), this passes captured frames
+/// to the output without copying them through user space: [`write_dmabuf()`](Self::write_dmabuf) queues the capture buffer
+/// on the output if the driver imports DMABUFs (`V4L2_MEMORY_DMABUF`). `v4l2loopback` does not, so it falls back to
+/// mapping the DMABUF and writing it, which is still a single copy.
+/// ```ignore
+/// let mut camera = V4LCaptureDevice::new(&CameraIndex::Index(0))?;
+/// camera.open_stream()?;
+/// let frame = camera.frame()?;
+/// let buffers = camera.export_dmabufs()?;
+/// let format = camera.camera_format().unwrap();
+/// let mut output = V4LOutputDevice::new(10, format.resolution(), format.format())?;
+/// let index = camera.last_buffer_index().unwrap() as usize;
+/// output.write_dmabuf(&buffers[index], frame.buffer().len())?;
+/// ```
+pub struct V4LOutputDevice {
+    device: Device,
+    resolution: Resolution,
+    format: FrameFormat,
+    // Whether the driver imports DMABUFs, found out on the first `write_dmabuf()`.
+    dmabuf_import: Option<bool>,
+    streaming: bool,
+}
+
+impl V4LOutputDevice {
+    /// Opens the output device `/dev/video{index}` and sets its format, e.g. of a `v4l2loopback` device created with
+    /// `modprobe v4l2loopback video_nr=10`.
+    /// # Errors
+    /// If the device can not be opened, or does not accept the format, this will error.
+    pub fn new(
+        index: usize,
+        resolution: Resolution,
+        format: FrameFormat,
+    ) -> Result<Self, NokhwaError> {
+        let device = Device::new(index)
+            .map_err(|why| NokhwaError::OpenDeviceError(index.to_string(), why.to_string()))?;
+        let fourcc = frameformat_to_fourcc(format).ok_or(NokhwaError::SetPropertyError {
+            property: "FrameFormat".to_string(),
+            value: format.to_string(),
+            error: "unsupported".to_string(),
+        })?;
+        let requested = Format::new(resolution.width(), resolution.height(), fourcc);
+        let granted = Output::set_format(&device, &requested).map_err(|why| {
+            NokhwaError::from_io(format!("Set output format to {requested}"), why)
+        })?;
+        if granted.width != requested.width
+            || granted.height != requested.height
+            || granted.fourcc != requested.fourcc
+        {
+            return Err(NokhwaError::SetPropertyError {
+                property: "Output Format".to_string(),
+                value: requested.to_string(),
+                error: format!("the device chose {granted} instead"),
+            });
+        }
+        Ok(V4LOutputDevice {
+            device,
+            resolution,
+            format,
+            dmabuf_import: None,
+            streaming: false,
+        })
+    }
+
+    /// The resolution of the frames this device takes.
+    #[must_use]
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    /// The [`FrameFormat`] of the frames this device takes.
+    #[must_use]
+    pub fn frame_format(&self) -> FrameFormat {
+        self.format
+    }
+
+    /// Whether the driver imports DMABUFs, or `None` if [`write_dmabuf()`](Self::write_dmabuf) has not been called yet.
+    #[must_use]
+    pub fn supports_dmabuf_import(&self) -> Option<bool> {
+        self.dmabuf_import
+    }
+
+    /// Writes the first `bytes_used` bytes of an exported capture buffer, e.g. the length of the frame dequeued into it, to
+    /// the output. This returns once the output is done with the buffer, so it can go back to the capture device.
+    /// # Errors
+    /// If the buffer can not be queued or mapped, or writing it fails, this will error.
+    pub fn write_dmabuf(
+        &mut self,
+        buffer: &DmaBufExport,
+        bytes_used: usize,
+    ) -> Result<(), NokhwaError> {
+        let bytes_used = bytes_used.min(buffer.length() as usize);
+        let dmabuf_import = match self.dmabuf_import {
+            Some(dmabuf_import) => dmabuf_import,
+            None => {
+                // One buffer is enough, as each frame is handed back before the next is queued.
+                // SAFETY: `v4l2_requestbuffers` is a plain C struct, for which all zeroes is a valid (empty) value.
+                let mut request: v4l2_requestbuffers = unsafe { std::mem::zeroed() };
+                request.count = 1;
+                request.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_OUTPUT;
+                request.memory = v4l2_memory_V4L2_MEMORY_DMABUF;
+                // SAFETY: `VIDIOC_REQBUFS` takes a `v4l2_requestbuffers`, which `request` is.
+                let supported =
+                    unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_REQBUFS, &mut request) }
+                        .is_ok()
+                        && request.count > 0;
+                *self.dmabuf_import.insert(supported)
+            }
+        };
+        if dmabuf_import {
+            self.queue_dmabuf(buffer, bytes_used)
+                .map_err(|why| NokhwaError::from_io("Queue DMABUF on output", why))
+        } else {
+            let fd = buffer.fd().as_raw_fd();
+            let mapped = MappedDmaBuf::new(fd, buffer.length() as usize)
+                .map_err(|why| NokhwaError::from_io("Map DMABUF", why))?;
+            self.write_frame(&mapped.data()[..bytes_used])
+        }
+    }
+
+    /// Writes a frame to the output with `write()`, copying it. `data` must be a whole frame in the output's format.
+    /// # Errors
+    /// If the device rejects the write (e.g. because DMABUFs were queued on it before), this will error.
+    pub fn write_frame(&mut self, data: &[u8]) -> Result<(), NokhwaError> {
+        let fd = self.device.handle().fd();
+        let mut written = 0;
+        while written < data.len() {
+            let rest = &data[written..];
+            // SAFETY: `rest` is a live slice, so its pointer is valid for reads of `rest.len()` bytes, and `fd` stays open
+            // as long as `self.device` does.
+            let result = unsafe { libc::write(fd, rest.as_ptr().cast(), rest.len()) };
+            if result < 0 {
+                let why = io::Error::last_os_error();
+                if why.kind() == ErrorKind::WouldBlock {
+                    self.wait_writable()
+                        .map_err(|why| NokhwaError::from_io("Wait for output", why))?;
+                    continue;
+                }
+                return Err(NokhwaError::from_io("Write frame to output", why));
+            }
+            written += result as usize;
+        }
+        Ok(())
+    }
+
+    fn queue_dmabuf(&mut self, buffer: &DmaBufExport, bytes_used: usize) -> io::Result<()> {
+        // SAFETY: `v4l2_buffer` is a plain C struct (and union), for which all zeroes is a valid value.
+        let mut raw_buffer: v4l2_buffer = unsafe { std::mem::zeroed() };
+        raw_buffer.index = 0;
+        raw_buffer.type_ = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_OUTPUT;
+        raw_buffer.memory = v4l2_memory_V4L2_MEMORY_DMABUF;
+        raw_buffer.m.fd = buffer.fd().as_raw_fd();
+        raw_buffer.length = buffer.length();
+        raw_buffer.bytesused = u32::try_from(bytes_used).unwrap_or(u32::MAX);
+        // SAFETY: `VIDIOC_QBUF` takes a `v4l2_buffer`, which `raw_buffer` is. `m.fd` is a DMABUF that `buffer` keeps
+        // open until it is dequeued below.
+        unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_QBUF, &mut raw_buffer) }?;
+        if !self.streaming {
+            let mut buffer_type = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_OUTPUT;
+            // SAFETY: `VIDIOC_STREAMON` takes the `v4l2_buf_type` (a `u32`) to start, which `buffer_type` is.
+            unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_STREAMON, &mut buffer_type) }?;
+            self.streaming = true;
+        }
+        // Wait for the driver to finish reading the buffer.
+        loop {
+            // SAFETY: `VIDIOC_DQBUF` takes a `v4l2_buffer`, which `raw_buffer` is.
+            match unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_DQBUF, &mut raw_buffer) } {
+                Err(why) if why.kind() == ErrorKind::WouldBlock => self.wait_writable()?,
+                result => return result,
+            }
+        }
+    }
+
+    fn wait_writable(&self) -> io::Result<()> {
+        self.device.handle().poll(POLLOUT, -1).map(|_| ())
+    }
+}
+
+impl Drop for V4LOutputDevice {
+    fn drop(&mut self) {
+        if self.streaming {
+            let mut buffer_type = v4l2_buf_type_V4L2_BUF_TYPE_VIDEO_OUTPUT;
+            // SAFETY: `VIDIOC_STREAMOFF` takes the `v4l2_buf_type` (a `u32`) to stop, which `buffer_type` is.
+            let _ = unsafe { v4l2_ioctl(&self.device, vidioc::VIDIOC_STREAMOFF, &mut buffer_type) };
+        }
+    }
+}
+
+/// A DMABUF mapped for reading, between `DMA_BUF_IOCTL_SYNC` start and end so the CPU sees what the device wrote.
+struct MappedDmaBuf {
+    fd: RawFd,
+    pointer: *mut libc::c_void,
+    length: usize,
+}
+
+impl MappedDmaBuf {
+    fn new(fd: RawFd, length: usize) -> io::Result<Self> {
+        // SAFETY: a fresh, read only, shared mapping at an address of the kernel's choosing can not alias any Rust memory.
+        // A bad `fd` or `length` makes the call fail with `MAP_FAILED`, which is checked below.
+        let pointer = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                length,
+                libc::PROT_READ,
+                libc::MAP_SHARED,
+                fd,
+                0,
+            )
+        };
+        if pointer == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        let mapped = MappedDmaBuf {
+            fd,
+            pointer,
+            length,
+        };
+        mapped.sync(DMA_BUF_SYNC_READ)?;
+        Ok(mapped)
+    }
+
+    fn data(&self) -> &[u8] {
+        // SAFETY: `pointer` is a successful, readable mapping of `length` bytes (`mmap` never returns null for one), which is
+        // only unmapped when `self` is dropped, so it outlives the returned slice. Nothing in this process writes to it.
+        unsafe { std::slice::from_raw_parts(self.pointer.cast(), self.length) }
+    }
+
+    fn sync(&self, flags: u64) -> io::Result<()> {
+        let sync = flags;
+        // SAFETY: `DMA_BUF_IOCTL_SYNC` reads a single `u64` of flags, and `sync` is one that lives across the call. A bad `fd`
+        // makes the call fail instead.
+        if unsafe { libc::ioctl(self.fd, DMA_BUF_IOCTL_SYNC, &sync) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MappedDmaBuf {
+    fn drop(&mut self) {
+        let _ = self.sync(DMA_BUF_SYNC_READ | DMA_BUF_SYNC_END);
+        // SAFETY: `pointer` and `length` are exactly what `mmap` returned and was given, the mapping is unmapped only here,
+        // and no slice from `data()` can outlive `self`.
+        unsafe {
+            libc::munmap(self.pointer, self.length);
+        }
+    }
+}
@@ -0,0 +1,411 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Capturing from many cameras on a few threads, by waiting for their file descriptors with `epoll`.
+
+use crate::V4LCaptureDevice;
+use nokhwa_core::{buffer::Buffer, error::NokhwaError, traits::CaptureTrait};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// How many events can be waiting in [`CaptureScheduler::events()`] before frames are dropped.
+pub const DEFAULT_EVENT_CAPACITY: usize = 32;
+
+// The epoll token of the eventfd that wakes a worker up to stop. Camera tokens are their `CameraId`.
+const WAKE_TOKEN: u64 = u64::MAX;
+const MAX_EVENTS: usize = 16;
+
+/// Identifies a camera added to a [`CaptureScheduler`]. Every [`SchedulerEvent`] is tagged with one.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub struct CameraId(u64);
+
+impl CameraId {
+    /// The raw value of the ID. IDs are given out in order, starting at 0, and never reused by the same scheduler.
+    #[must_use]
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for CameraId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Camera {}", self.0)
+    }
+}
+
+/// Something that happened to one of the cameras of a [`CaptureScheduler`].
+#[derive(Debug)]
+pub enum SchedulerEvent {
+    /// A new frame.
+    Frame {
+        /// The camera the frame is from.
+        camera: CameraId,
+        /// The frame.
+        frame: Buffer,
+    },
+    /// The camera failed to capture. If the device was unplugged or its stream failed, it is removed from the scheduler after this.
+    Error {
+        /// The camera that failed.
+        camera: CameraId,
+        /// What went wrong.
+        error: NokhwaError,
+    },
+}
+
+impl SchedulerEvent {
+    /// The camera this event is about.
+    #[must_use]
+    pub fn camera(&self) -> CameraId {
+        match self {
+            SchedulerEvent::Frame { camera, .. } | SchedulerEvent::Error { camera, .. } => *camera,
+        }
+    }
+}
+
+type Devices = Arc<Mutex<HashMap<u64, V4LCaptureDevice>>>;
+
+struct Worker {
+    epoll: Arc<OwnedFd>,
+    wake: OwnedFd,
+    devices: Devices,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// Captures from many [`V4LCaptureDevice`]s on a small pool of worker threads, instead of one blocking thread per camera.
+///
+/// Each worker waits on the file descriptors of its cameras with `epoll`, and only touches a camera when it has a frame ready,
+/// so one slow camera does not hold up the others. Frames from all cameras arrive through one channel, see
+/// [`events()`](Self::events), tagged with the [`CameraId`] returned by [`add()`](Self::add).
+///
+/// If the events are not read fast enough, new frames are dropped rather than blocking the workers. See
+/// [`dropped_frames()`](Self::dropped_frames).
+/// # Example
+/// ```no_run
+/// # use nokhwa_bindings_linux::{CaptureScheduler, SchedulerEvent, V4LCaptureDevice};
+/// # use nokhwa_core::{traits::CaptureTrait, types::CameraIndex};
+/// let mut scheduler = CaptureScheduler::new(2)?;
+/// for index in 0..4 {
+///     let mut camera = V4LCaptureDevice::new(&CameraIndex::Index(index))?;
+///     camera.init()?;
+///     scheduler.add(camera)?;
+/// }
+/// for event in scheduler.events() {
+///     if let SchedulerEvent::Frame { camera, frame } = event {
+///         println!("{camera}: {}", frame.resolution());
+///     }
+/// }
+/// # Ok::<(), nokhwa_core::error::NokhwaError>(())
+/// ```
+pub struct CaptureScheduler {
+    workers: Vec<Worker>,
+    events: Receiver<SchedulerEvent>,
+    sender: SyncSender<SchedulerEvent>,
+    dropped_frames: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    next_id: u64,
+}
+
+impl CaptureScheduler {
+    /// Starts a scheduler with `workers` threads (at least 1) and room for [`DEFAULT_EVENT_CAPACITY`] events.
+    /// # Errors
+    /// If `epoll` or the threads could not be created, this will error.
+    pub fn new(workers: usize) -> Result<Self, NokhwaError> {
+        Self::with_capacity(workers, DEFAULT_EVENT_CAPACITY)
+    }
+
+    /// Starts a scheduler with `workers` threads (at least 1) and room for `capacity` events (at least 1).
+    /// # Errors
+    /// If `epoll` or the threads could not be created, this will error.
+    pub fn with_capacity(workers: usize, capacity: usize) -> Result<Self, NokhwaError> {
+        let (sender, events) = mpsc::sync_channel(capacity.max(1));
+        let mut scheduler = CaptureScheduler {
+            workers: Vec::with_capacity(workers.max(1)),
+            events,
+            sender,
+            dropped_frames: Arc::new(AtomicU64::new(0)),
+            stop: Arc::new(AtomicBool::new(false)),
+            next_id: 0,
+        };
+        for number in 0..workers.max(1) {
+            let worker = scheduler.spawn_worker(number)?;
+            scheduler.workers.push(worker);
+        }
+        Ok(scheduler)
+    }
+
+    fn spawn_worker(&self, number: usize) -> Result<Worker, NokhwaError> {
+        // SAFETY: epoll_create1 and eventfd return new file descriptors that nothing else owns, or -1.
+        let epoll = cvt(unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) })
+            .map(|fd| Arc::new(unsafe { OwnedFd::from_raw_fd(fd) }))
+            .map_err(|why| NokhwaError::from_io("Create epoll", why))?;
+        let wake = cvt(unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) })
+            .map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+            .map_err(|why| NokhwaError::from_io("Create eventfd", why))?;
+        epoll_ctl(&epoll, libc::EPOLL_CTL_ADD, wake.as_raw_fd(), WAKE_TOKEN)
+            .map_err(|why| NokhwaError::from_io("Register eventfd", why))?;
+
+        let devices = Devices::default();
+        let context = WorkerContext {
+            epoll: epoll.clone(),
+            devices: devices.clone(),
+            sender: self.sender.clone(),
+            dropped_frames: self.dropped_frames.clone(),
+            stop: self.stop.clone(),
+        };
+        let handle = thread::Builder::new()
+            .name(format!("nokhwa-scheduler-{number}"))
+            .spawn(move || context.run())
+            .map_err(|why| NokhwaError::from_io("Spawn scheduler worker", why))?;
+
+        Ok(Worker {
+            epoll,
+            wake,
+            devices,
+            handle: Some(handle),
+        })
+    }
+
+    /// Adds a camera, opening (or resuming) its stream if it is not streaming yet. It is given to the worker with the fewest cameras.
+    /// # Errors
+    /// If the stream could not be opened, has a single buffer (which can not be polled, see
+    /// [`read_ready_frame()`](V4LCaptureDevice::read_ready_frame)), or the camera could not be registered with `epoll`, this
+    /// will error. The camera is dropped in that case.
+    pub fn add(&mut self, mut device: V4LCaptureDevice) -> Result<CameraId, NokhwaError> {
+        if !device.is_stream_open() {
+            device.open_stream()?;
+        }
+        if device.is_stream_paused() {
+            device.resume_stream()?;
+        }
+        // epoll reports an error right away for a device that is not streaming.
+        if !device.is_streaming() {
+            return Err(NokhwaError::OpenStreamError(
+                "Streaming is off, a stream needs at least 2 buffers to be polled".to_string(),
+            ));
+        }
+        let id = self.next_id;
+        let worker = self
+            .workers
+            .iter()
+            .min_by_key(|worker| lock(&worker.devices).len())
+            .ok_or_else(|| NokhwaError::GeneralError("The scheduler has no workers".to_string()))?;
+
+        // Insert first, so the worker finds the camera as soon as epoll reports it.
+        let fd = device.as_raw_fd();
+        lock(&worker.devices).insert(id, device);
+        if let Err(why) = epoll_ctl(&worker.epoll, libc::EPOLL_CTL_ADD, fd, id) {
+            lock(&worker.devices).remove(&id);
+            return Err(NokhwaError::from_io("Register camera with epoll", why));
+        }
+        self.next_id += 1;
+        Ok(CameraId(id))
+    }
+
+    /// Removes a camera and gives it back, with its stream still open. Returns `None` if there is no such camera, e.g. because it
+    /// was unplugged.
+    pub fn remove(&mut self, camera: CameraId) -> Option<V4LCaptureDevice> {
+        self.workers.iter().find_map(|worker| {
+            let mut devices = lock(&worker.devices);
+            let device = devices.remove(&camera.0)?;
+            // Deregistering can only fail if the fd was never registered, which it always is here.
+            let _ = epoll_ctl(
+                &worker.epoll,
+                libc::EPOLL_CTL_DEL,
+                device.as_raw_fd(),
+                camera.0,
+            );
+            Some(device)
+        })
+    }
+
+    /// Runs `f` on a camera, e.g. to change a control. The camera's worker waits for it, so keep it short.
+    /// Returns `None` if there is no such camera.
+    pub fn with_camera<R>(
+        &self,
+        camera: CameraId,
+        f: impl FnOnce(&mut V4LCaptureDevice) -> R,
+    ) -> Option<R> {
+        self.workers
+            .iter()
+            .find_map(|worker| lock(&worker.devices).get_mut(&camera.0).map(f))
+    }
+
+    /// The cameras currently in the scheduler.
+    #[must_use]
+    pub fn cameras(&self) -> Vec<CameraId> {
+        let mut cameras = self
+            .workers
+            .iter()
+            .flat_map(|worker| lock(&worker.devices).keys().copied().collect::<Vec<_>>())
+            .map(CameraId)
+            .collect::<Vec<_>>();
+        cameras.sort();
+        cameras
+    }
+
+    /// The events of all cameras, in the order they happened. Iterating over this never ends, use
+    /// [`try_iter()`](Receiver::try_iter) or [`next_event()`](Self::next_event) to poll instead.
+    #[must_use]
+    pub fn events(&self) -> &Receiver<SchedulerEvent> {
+        &self.events
+    }
+
+    /// Waits up to `timeout` for the next event.
+    #[must_use]
+    pub fn next_event(&self, timeout: Duration) -> Option<SchedulerEvent> {
+        match self.events.recv_timeout(timeout) {
+            Ok(event) => Some(event),
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// How many frames were dropped because [`events()`](Self::events) was full.
+    #[must_use]
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for CaptureScheduler {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        for worker in &mut self.workers {
+            let one = 1_u64;
+            // SAFETY: eventfd reads and writes are exactly 8 bytes.
+            unsafe {
+                libc::write(
+                    worker.wake.as_raw_fd(),
+                    std::ptr::addr_of!(one).cast(),
+                    std::mem::size_of::<u64>(),
+                );
+            }
+            if let Some(handle) = worker.handle.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+struct WorkerContext {
+    epoll: Arc<OwnedFd>,
+    devices: Devices,
+    sender: SyncSender<SchedulerEvent>,
+    dropped_frames: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+}
+
+impl WorkerContext {
+    fn run(self) {
+        let mut ready = [libc::epoll_event { events: 0, u64: 0 }; MAX_EVENTS];
+        while !self.stop.load(Ordering::SeqCst) {
+            // SAFETY: `ready` is valid for MAX_EVENTS events.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            let count = unsafe {
+                libc::epoll_wait(
+                    self.epoll.as_raw_fd(),
+                    ready.as_mut_ptr(),
+                    MAX_EVENTS as i32,
+                    -1,
+                )
+            };
+            let count = match cvt(count) {
+                Ok(count) => usize::try_from(count).unwrap_or_default(),
+                Err(why) if why.kind() == io::ErrorKind::Interrupted => continue,
+                // The epoll fd is owned by us, so this cannot happen.
+                Err(_) => return,
+            };
+
+            for event in &ready[..count] {
+                let token = event.u64;
+                if token != WAKE_TOKEN {
+                    self.capture(token, event.events);
+                }
+            }
+        }
+    }
+
+    fn capture(&self, token: u64, readiness: u32) {
+        let camera = CameraId(token);
+        let mut devices = lock(&self.devices);
+        // The camera may have been removed between epoll_wait and here.
+        let Some(device) = devices.get_mut(&token) else {
+            return;
+        };
+
+        // After an unplug, or once streaming stopped, the fd reports an error forever. Retrying would spin, so stop watching it.
+        if readiness & (libc::EPOLLERR | libc::EPOLLHUP) as u32 != 0 {
+            let _ = epoll_ctl(&self.epoll, libc::EPOLL_CTL_DEL, device.as_raw_fd(), token);
+            devices.remove(&token);
+            drop(devices);
+            self.send(SchedulerEvent::Error {
+                camera,
+                error: NokhwaError::ReadFrameError(
+                    "The device was unplugged or stopped streaming".to_string(),
+                ),
+            });
+            return;
+        }
+
+        let event = match device.read_ready_frame() {
+            Ok(Some(frame)) => SchedulerEvent::Frame { camera, frame },
+            Ok(None) => return,
+            Err(error) => SchedulerEvent::Error { camera, error },
+        };
+        drop(devices);
+        self.send(event);
+    }
+
+    fn send(&self, event: SchedulerEvent) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(event) {
+            self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+fn epoll_ctl(epoll: &OwnedFd, operation: i32, fd: RawFd, token: u64) -> io::Result<()> {
+    let mut event = libc::epoll_event {
+        events: libc::EPOLLIN as u32,
+        u64: token,
+    };
+    // SAFETY: `event` outlives the call, and the kernel does not keep the pointer.
+    cvt(unsafe { libc::epoll_ctl(epoll.as_raw_fd(), operation, fd, &mut event) }).map(|_| ())
+}
+
+fn cvt(result: i32) -> io::Result<i32> {
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
+
+// A panic in `with_camera()` poisons the lock, but leaves the devices usable.
+fn lock(devices: &Devices) -> std::sync::MutexGuard<'_, HashMap<u64, V4LCaptureDevice>> {
+    devices
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
@@ -0,0 +1,438 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! V4L2 specific types that do not have an equivalent in `nokhwa-core`.
+
+use nokhwa_core::types::KnownCameraControl;
+use std::fmt::{Display, Formatter};
+
+/// The kind of connector a [`V4L2Input`] is.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum V4L2InputType {
+    /// A TV tuner.
+    Tuner,
+    /// Any non-tuner video input (e.g. HDMI, Composite, S-Video, the camera sensor itself).
+    Camera,
+    /// A touch device.
+    Touch,
+    /// Unknown input type. Listed is the raw V4L2 value.
+    Other(u32),
+}
+
+impl From<u32> for V4L2InputType {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => V4L2InputType::Tuner,
+            2 => V4L2InputType::Camera,
+            3 => V4L2InputType::Touch,
+            other => V4L2InputType::Other(other),
+        }
+    }
+}
+
+impl Display for V4L2InputType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// A single input (connector) of a V4L2 device, as reported by `VIDIOC_ENUMINPUT`.
+///
+/// Capture cards usually expose multiple inputs (HDMI, Composite, S-Video) on a single device node.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct V4L2Input {
+    index: u32,
+    name: String,
+    input_type: V4L2InputType,
+    standards: u64,
+    status: u32,
+    capabilities: u32,
+}
+
+impl V4L2Input {
+    /// Creates a new [`V4L2Input`].
+    #[must_use]
+    pub fn new(
+        index: u32,
+        name: &str,
+        input_type: V4L2InputType,
+        standards: u64,
+        status: u32,
+        capabilities: u32,
+    ) -> Self {
+        V4L2Input {
+            index,
+            name: name.to_string(),
+            input_type,
+            standards,
+            status,
+            capabilities,
+        }
+    }
+
+    /// The index of this input. Pass this to `set_input()`.
+    #[must_use]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The driver-given name of this input (e.g. "HDMI 1").
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The [`V4L2InputType`] of this input.
+    #[must_use]
+    pub fn input_type(&self) -> V4L2InputType {
+        self.input_type
+    }
+
+    /// The raw `v4l2_std_id` bitmask of analog video standards this input supports.
+    #[must_use]
+    pub fn standards(&self) -> u64 {
+        self.standards
+    }
+
+    /// The raw `V4L2_IN_ST_*` status flags of this input (e.g. no power, no signal).
+    #[must_use]
+    pub fn status(&self) -> u32 {
+        self.status
+    }
+
+    /// Returns true if the driver reports a signal on this input.
+    #[must_use]
+    pub fn has_signal(&self) -> bool {
+        // V4L2_IN_ST_NO_POWER | V4L2_IN_ST_NO_SIGNAL
+        self.status & 0x0000_0003 == 0
+    }
+
+    /// The raw `V4L2_IN_CAP_*` capability flags of this input.
+    #[must_use]
+    pub fn capabilities(&self) -> u32 {
+        self.capabilities
+    }
+}
+
+impl Display for V4L2Input {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Index: {}, Name: {}, Type: {}, Signal: {}",
+            self.index,
+            self.name,
+            self.input_type,
+            self.has_signal()
+        )
+    }
+}
+
+/// An analog video standard family. Analog capture devices need this set correctly or they will deliver
+/// corrupted frames, or frames at the wrong rate.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum AnalogVideoStandard {
+    /// All PAL variants (B, B1, G, H, I, D, D1, K, M, N, Nc, 60).
+    Pal,
+    /// All NTSC variants (M, M-JP, 443, M-KR).
+    Ntsc,
+    /// All SECAM variants (B, D, G, H, K, K1, L, LC).
+    Secam,
+    /// A raw `v4l2_std_id` bitmask.
+    Other(u64),
+}
+
+impl AnalogVideoStandard {
+    /// Gets the `v4l2_std_id` bitmask of this standard.
+    #[must_use]
+    pub fn std_id(self) -> u64 {
+        match self {
+            AnalogVideoStandard::Pal => 0x0000_0fff,
+            AnalogVideoStandard::Ntsc => 0x0000_f000,
+            AnalogVideoStandard::Secam => 0x00ff_0000,
+            AnalogVideoStandard::Other(id) => id,
+        }
+    }
+
+    /// Gets the [`AnalogVideoStandard`] family a `v4l2_std_id` belongs to. If the bitmask spans more than one family, this returns [`AnalogVideoStandard::Other`].
+    #[must_use]
+    pub fn from_std_id(id: u64) -> Self {
+        [
+            AnalogVideoStandard::Pal,
+            AnalogVideoStandard::Ntsc,
+            AnalogVideoStandard::Secam,
+        ]
+        .into_iter()
+        .find(|standard| id != 0 && id & !standard.std_id() == 0)
+        .unwrap_or(AnalogVideoStandard::Other(id))
+    }
+}
+
+impl Display for AnalogVideoStandard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalogVideoStandard::Other(id) => write!(f, "Other({id:#x})"),
+            standard => write!(f, "{standard:?}"),
+        }
+    }
+}
+
+/// A video standard supported by a V4L2 device, as reported by `VIDIOC_ENUMSTD`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct V4L2Standard {
+    index: u32,
+    id: u64,
+    name: String,
+    frame_period: (u32, u32),
+    frame_lines: u32,
+}
+
+impl V4L2Standard {
+    /// Creates a new [`V4L2Standard`].
+    #[must_use]
+    pub fn new(
+        index: u32,
+        id: u64,
+        name: &str,
+        frame_period: (u32, u32),
+        frame_lines: u32,
+    ) -> Self {
+        V4L2Standard {
+            index,
+            id,
+            name: name.to_string(),
+            frame_period,
+            frame_lines,
+        }
+    }
+
+    /// The enumeration index of this standard.
+    #[must_use]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The raw `v4l2_std_id` bitmask of this standard. Pass this to `set_standard()`.
+    #[must_use]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The [`AnalogVideoStandard`] family of this standard.
+    #[must_use]
+    pub fn family(&self) -> AnalogVideoStandard {
+        AnalogVideoStandard::from_std_id(self.id)
+    }
+
+    /// The driver-given name of this standard (e.g. "PAL-B/G").
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The frame period (**not** frame rate) as a `(numerator, denominator)` fraction of seconds.
+    #[must_use]
+    pub fn frame_period(&self) -> (u32, u32) {
+        self.frame_period
+    }
+
+    /// Total lines per frame, including blanking.
+    #[must_use]
+    pub fn frame_lines(&self) -> u32 {
+        self.frame_lines
+    }
+}
+
+impl Display for V4L2Standard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Name: {}, ID: {:#x}, Period: {}/{}, Lines: {}",
+            self.name, self.id, self.frame_period.0, self.frame_period.1, self.frame_lines
+        )
+    }
+}
+
+/// Digital Video (e.g. HDMI, DVI, `DisplayPort`) BT.656/BT.1120 timings, as used by `VIDIOC_*_DV_TIMINGS`.
+///
+/// All horizontal values are in pixels, all vertical values are in lines. The `il_` prefixed fields
+/// are only used for the second field of interlaced formats.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DvTimings {
+    pub width: u32,
+    pub height: u32,
+    pub interlaced: bool,
+    pub polarities: u32,
+    pub pixel_clock: u64,
+    pub h_front_porch: u32,
+    pub h_sync: u32,
+    pub h_back_porch: u32,
+    pub v_front_porch: u32,
+    pub v_sync: u32,
+    pub v_back_porch: u32,
+    pub il_v_front_porch: u32,
+    pub il_v_sync: u32,
+    pub il_v_back_porch: u32,
+    pub standards: u32,
+    pub flags: u32,
+}
+
+impl DvTimings {
+    /// Total width of a line, including blanking.
+    #[must_use]
+    pub fn total_width(&self) -> u32 {
+        self.width
+            .saturating_add(self.h_front_porch)
+            .saturating_add(self.h_sync)
+            .saturating_add(self.h_back_porch)
+    }
+
+    /// Total height of a frame, including blanking (and the second field, if interlaced).
+    #[must_use]
+    pub fn total_height(&self) -> u32 {
+        let progressive = self
+            .height
+            .saturating_add(self.v_front_porch)
+            .saturating_add(self.v_sync)
+            .saturating_add(self.v_back_porch);
+        if self.interlaced {
+            progressive
+                .saturating_add(self.il_v_front_porch)
+                .saturating_add(self.il_v_sync)
+                .saturating_add(self.il_v_back_porch)
+        } else {
+            progressive
+        }
+    }
+
+    /// The frame rate these timings result in. Returns `0.0` if the total frame size is 0.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn frame_rate(&self) -> f64 {
+        let total = u64::from(self.total_width()) * u64::from(self.total_height());
+        if total == 0 {
+            return 0.0;
+        }
+        self.pixel_clock as f64 / total as f64
+    }
+}
+
+impl Display for DvTimings {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{}{}@{:.2}, Pixel Clock: {}Hz",
+            self.width,
+            self.height,
+            if self.interlaced { "i" } else { "p" },
+            self.frame_rate(),
+            self.pixel_clock
+        )
+    }
+}
+
+/// Reported by a [`V4LCaptureDevice`](crate::V4LCaptureDevice) with auto-reconnect enabled, as the device goes away and comes back.
+#[derive(Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum ReconnectEvent {
+    /// The device was unplugged (`ENODEV`).
+    Disconnected,
+    /// The device reappeared at `index`, and its format and controls were re-applied.
+    Reconnected { index: u32 },
+    /// The device did not reappear in time, or could not be reconfigured.
+    ReconnectFailed(String),
+}
+
+impl Display for ReconnectEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// A V4L2 event a [`V4LCaptureDevice`](crate::V4LCaptureDevice) subscribed to, see
+/// [`subscribe_source_change()`](crate::V4LCaptureDevice::subscribe_source_change) and
+/// [`subscribe_control_changes()`](crate::V4LCaptureDevice::subscribe_control_changes).
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeviceEvent {
+    /// The input signal changed, e.g. the source plugged into an HDMI capture card switched resolution. If
+    /// `resolution_changed`, frames no longer match the format: stop the stream, query the new timings (e.g.
+    /// [`query_dv_timings()`](crate::V4LCaptureDevice::query_dv_timings)), set a matching format, and reopen it.
+    SourceChanged { resolution_changed: bool },
+    /// A control changed, e.g. because another application or an automatic mode (auto exposure) changed it. If
+    /// `range_changed`, its minimum, maximum, or step changed as well.
+    ControlChanged {
+        control: KnownCameraControl,
+        value: i64,
+        range_changed: bool,
+    },
+}
+
+impl Display for DeviceEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// What the stream watchdog of a [`V4LCaptureDevice`](crate::V4LCaptureDevice) does when no frame arrives in time.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum WatchdogAction {
+    /// Return a [`StreamStalledError`](nokhwa_core::error::NokhwaError::StreamStalledError).
+    Error,
+    /// Turn streaming off and on again (STREAMOFF/STREAMON) and wait once more, only erroring if that did not help either.
+    Restart,
+}
+
+impl Display for WatchdogAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// The access priority of a V4L2 file handle (`VIDIOC_S_PRIORITY`). While a handle holds a priority, handles with a lower
+/// one can not change the format, controls, or input of the device.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum DevicePriority {
+    /// For background processes, e.g. monitoring. Every other handle can override them.
+    Background,
+    /// The default priority of a newly opened device.
+    Interactive,
+    /// For recording: only one handle can hold it, and no other handle can change the device while it does.
+    Record,
+}
+
+impl From<u32> for DevicePriority {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => DevicePriority::Background,
+            3 => DevicePriority::Record,
+            _ => DevicePriority::Interactive,
+        }
+    }
+}
+
+impl From<DevicePriority> for u32 {
+    fn from(value: DevicePriority) -> Self {
+        match value {
+            DevicePriority::Background => 1,
+            DevicePriority::Interactive => 2,
+            DevicePriority::Record => 3,
+        }
+    }
+}
+
+impl Display for DevicePriority {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
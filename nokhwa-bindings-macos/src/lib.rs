@@ -519,7 +519,12 @@ mod internal {
         .devices())
     }
 
+    #[deprecated(since = "0.11.0", note = "please use `raw_device_info` instead.")]
     pub fn get_raw_device_info(index: CameraIndex, device: *mut Object) -> CameraInfo {
+        raw_device_info(index, device)
+    }
+
+    pub fn raw_device_info(index: CameraIndex, device: *mut Object) -> CameraInfo {
         let name = nsstr_to_str(unsafe { msg_send![device, localizedName] });
         let manufacturer = nsstr_to_str(unsafe { msg_send![device, manufacturer] });
         let position: AVCaptureDevicePosition = unsafe { msg_send![device, position] };
@@ -823,10 +828,7 @@ mod internal {
             let mut devices = Vec::with_capacity(objects_len as usize);
             for index in 0..objects_len {
                 let device = unsafe { device_ns_array.objectAtIndex(index) };
-                devices.push(get_raw_device_info(
-                    CameraIndex::Index(index as u32),
-                    device,
-                ));
+                devices.push(raw_device_info(CameraIndex::Index(index as u32), device));
             }
 
             devices
@@ -853,7 +855,7 @@ mod internal {
 
                     match devices.get(*idx as usize) {
                         Some(device) => Ok(AVCaptureDevice::from_id(
-                            &device.misc(),
+                            device.misc(),
                             Some(index.clone()),
                         )?),
                         None => Err(NokhwaError::OpenDeviceError(
@@ -877,7 +879,7 @@ mod internal {
                     "Device is null".to_string(),
                 ));
             }
-            let camera_info = get_raw_device_info(
+            let camera_info = raw_device_info(
                 index_hint.unwrap_or_else(|| CameraIndex::String(id.to_string())),
                 capture,
             );
@@ -1035,7 +1037,12 @@ mod internal {
         // 4 => Exposure Target Bias
         // 5 => Exposure ISO
         // 6 => Exposure Duration
+        #[deprecated(since = "0.11.0", note = "please use `controls` instead.")]
         pub fn get_controls(&self) -> Result<Vec<CameraControl>, NokhwaError> {
+            self.controls()
+        }
+
+        pub fn controls(&self) -> Result<Vec<CameraControl>, NokhwaError> {
             let active_format: *mut Object = unsafe { msg_send![self.inner, activeFormat] };
 
             let mut controls = vec![];
@@ -1493,7 +1500,7 @@ mod internal {
             id: KnownCameraControl,
             value: ControlValueSetter,
         ) -> Result<(), NokhwaError> {
-            let rc = self.get_controls()?;
+            let rc = self.controls()?;
             let controls = rc
                 .iter()
                 .map(|cc| (cc.control(), cc))
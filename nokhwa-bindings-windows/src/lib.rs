@@ -553,11 +553,11 @@ pub mod wmf {
             self.device_specifier.index()
         }
 
-        pub fn name(&self) -> String {
+        pub fn name(&self) -> &str {
             self.device_specifier.human_name()
         }
 
-        pub fn symlink(&self) -> String {
+        pub fn symlink(&self) -> &str {
             self.device_specifier.misc()
         }
 
@@ -642,7 +642,7 @@ pub mod wmf {
                     None => {
                         index += 1;
                         continue;
-                    },
+                    }
                 };
 
                 for frame_rate in framerate_list {
@@ -1258,12 +1258,12 @@ pub mod wmf {
             &self.camera
         }
 
-        pub fn name(&self) -> String {
-            String::new()
+        pub fn name(&self) -> &str {
+            ""
         }
 
-        pub fn symlink(&self) -> String {
-            String::new()
+        pub fn symlink(&self) -> &str {
+            ""
         }
 
         pub fn compatible_format_list(&mut self) -> Result<Vec<CameraFormat>, NokhwaError> {
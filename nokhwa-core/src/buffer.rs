@@ -14,13 +14,25 @@
  * limitations under the License.
  */
 
-use crate::{frame_format::SourceFrameFormat, types::Resolution};
+use crate::error::NokhwaError;
+use crate::{
+    frame_format::SourceFrameFormat,
+    pool::{BufferPool, FrameBuffer},
+    types::{
+        buf_mjpeg_to_rgb, CaptureParameters, FieldOrder, FrameStats, FrameTimestamp,
+        PixelAspectRatio, Resolution,
+    },
+};
 use bytes::Bytes;
+#[cfg(feature = "image-types")]
 use image::ImageBuffer;
-use crate::error::NokhwaError;
+use std::borrow::Cow;
 
 /// A buffer returned by a camera to accommodate custom decoding.
-/// Contains information of Resolution, the buffer's [`FrameFormat`], and the buffer.
+/// Contains information of Resolution, the buffer's [`FrameFormat`], the buffer, and (if the backend provides it) when the frame was captured.
+///
+/// Cloning a [`Buffer`] is cheap: the data is reference counted, so every clone (e.g. one for a preview, one for a recorder, one
+/// for analysis) shares the same allocation.
 ///
 /// Note that decoding on the main thread **will** decrease your performance and lead to dropped frames.
 #[derive(Clone, Debug, Hash, PartialOrd, PartialEq, Eq)]
@@ -28,20 +40,120 @@ pub struct Buffer {
     resolution: Resolution,
     buffer: Bytes,
     source_frame_format: SourceFrameFormat,
+    timestamp: Option<FrameTimestamp>,
+    sequence: Option<u32>,
+    stride: Option<u32>,
+    field_order: Option<FieldOrder>,
+    pixel_aspect: Option<PixelAspectRatio>,
+    capture_parameters: Option<CaptureParameters>,
 }
 
+/// A captured frame: the pixel data along with its [`Resolution`], source format, stride, timestamp, and sequence number.
+pub type Frame = Buffer;
+
 impl Buffer {
-    /// Creates a new buffer with a [`&[u8]`].
+    /// Creates a new buffer with a [`&[u8]`]. The data is copied into a buffer from [`BufferPool::global()`], which is reused
+    /// once this [`Buffer`] (and all of its clones) are dropped.
     #[must_use]
     #[inline]
     pub fn new(res: Resolution, buf: &[u8], source_frame_format: SourceFrameFormat) -> Self {
+        Self::from_pooled(
+            res,
+            BufferPool::global().copy_from_slice(buf),
+            source_frame_format,
+        )
+    }
+
+    /// Creates a new buffer that takes ownership of a [`FrameBuffer`], without copying. The [`FrameBuffer`] goes back to its pool
+    /// once this [`Buffer`] (and all of its clones) are dropped.
+    #[must_use]
+    #[inline]
+    pub fn from_pooled(
+        res: Resolution,
+        buf: FrameBuffer,
+        source_frame_format: SourceFrameFormat,
+    ) -> Self {
+        Self::from_bytes(res, Bytes::from_owner(buf), source_frame_format)
+    }
+
+    /// Creates a new buffer that takes ownership of `buf` (e.g. a `Vec<u8>` or [`Bytes`]) without copying it.
+    #[must_use]
+    #[inline]
+    pub fn from_bytes(
+        res: Resolution,
+        buf: impl Into<Bytes>,
+        source_frame_format: SourceFrameFormat,
+    ) -> Self {
         Self {
             resolution: res,
-            buffer: Bytes::copy_from_slice(buf),
+            buffer: buf.into(),
             source_frame_format,
+            timestamp: None,
+            sequence: None,
+            stride: None,
+            field_order: None,
+            pixel_aspect: None,
+            capture_parameters: None,
+        }
+    }
+
+    /// Creates a new buffer from the result of [`frame_raw()`](crate::traits::CaptureTrait::frame_raw). Owned data is taken
+    /// without copying; borrowed data is copied like [`new()`](Self::new).
+    #[must_use]
+    #[inline]
+    pub fn from_cow(
+        res: Resolution,
+        buf: Cow<'_, [u8]>,
+        source_frame_format: SourceFrameFormat,
+    ) -> Self {
+        match buf {
+            Cow::Owned(owned) => Self::from_bytes(res, owned, source_frame_format),
+            Cow::Borrowed(borrowed) => Self::new(res, borrowed, source_frame_format),
         }
     }
 
+    /// Attaches a [`FrameTimestamp`] to this buffer.
+    #[must_use]
+    pub fn with_timestamp(mut self, timestamp: FrameTimestamp) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Attaches the length of a row in bytes, including any padding, to this buffer.
+    #[must_use]
+    pub fn with_stride(mut self, stride: u32) -> Self {
+        self.stride = Some(stride);
+        self
+    }
+
+    /// Attaches the driver's sequence number to this buffer.
+    #[must_use]
+    pub fn with_sequence(mut self, sequence: u32) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+
+    /// Attaches how the rows of this buffer are split into interlaced fields.
+    #[must_use]
+    pub fn with_field_order(mut self, field_order: FieldOrder) -> Self {
+        self.field_order = Some(field_order);
+        self
+    }
+
+    /// Attaches the shape of this buffer's pixels.
+    #[must_use]
+    pub fn with_pixel_aspect(mut self, pixel_aspect: PixelAspectRatio) -> Self {
+        self.pixel_aspect = Some(pixel_aspect);
+        self
+    }
+
+    /// Attaches the exposure the camera reported using for this buffer.
+    #[must_use]
+    pub fn with_capture_parameters(mut self, capture_parameters: CaptureParameters) -> Self {
+        self.capture_parameters = Some(capture_parameters);
+        self
+    }
+
     /// Get the [`Resolution`] of this buffer.
     #[must_use]
     pub fn resolution(&self) -> Resolution {
@@ -54,7 +166,7 @@ impl Buffer {
         &self.buffer
     }
 
-    /// Get a owned version of this buffer.
+    /// Get the data of this buffer as [`Bytes`]. This shares the allocation instead of copying it.
     #[must_use]
     pub fn buffer_bytes(&self) -> Bytes {
         self.buffer.clone()
@@ -65,6 +177,110 @@ impl Buffer {
     pub fn source_frame_format(&self) -> SourceFrameFormat {
         self.source_frame_format
     }
+
+    /// Get the [`FrameTimestamp`] of this buffer, if the backend provided one.
+    #[must_use]
+    pub fn timestamp(&self) -> Option<FrameTimestamp> {
+        self.timestamp
+    }
+
+    /// Get the length of a row in bytes, including any padding, if known. This is `None` for compressed formats such as MJPEG.
+    #[must_use]
+    pub fn stride(&self) -> Option<u32> {
+        self.stride
+    }
+
+    /// Get how the rows of this buffer are split into interlaced fields, if the backend reported it. Frames from analog capture
+    /// cards are usually interlaced, see [`FieldOrder`].
+    #[must_use]
+    pub fn field_order(&self) -> Option<FieldOrder> {
+        self.field_order
+    }
+
+    /// Get the shape of this buffer's pixels, if the backend reported it. Anything other than
+    /// [`PixelAspectRatio::SQUARE`] (typical of DV and analog capture) looks squashed unless resampled.
+    #[must_use]
+    pub fn pixel_aspect(&self) -> Option<PixelAspectRatio> {
+        self.pixel_aspect
+    }
+
+    /// Get the exposure time and gain the camera actually captured this buffer with, if the backend reported them. Unlike
+    /// the requested control values, these are exact, which photogrammetry and HDR merging need.
+    #[must_use]
+    pub fn capture_parameters(&self) -> Option<CaptureParameters> {
+        self.capture_parameters
+    }
+
+    /// Get the driver's sequence number of this buffer, if the backend provided one.
+    /// This increases by one for every frame the device captured, so a gap means frames were dropped.
+    #[must_use]
+    pub fn sequence(&self) -> Option<u32> {
+        self.sequence
+    }
+
+    /// Computes the brightness statistics (luma histogram, mean brightness, clipping) of this frame, going by its
+    /// [`source_frame_format()`](Self::source_frame_format).
+    ///
+    /// For YUV and grayscale formats this only reads the luma plane, with no conversion, so it is cheap enough to run on every frame.
+    /// RGB frames have their luma approximated per pixel. MJPEG frames have to be decoded first (with the `mjpeg` feature), which costs
+    /// as much as decoding them for display.
+    /// # Errors
+    /// If the format is not supported, the buffer is smaller than its resolution says, or decoding fails, this will error.
+    pub fn stats(&self) -> Result<FrameStats, NokhwaError> {
+        let format = self.source_frame_format;
+        let width = self.resolution.width() as usize;
+        let height = self.resolution.height() as usize;
+        let (bytes_per_pixel, luma_offset) = match format {
+            FrameFormat::Yuv422 => (2, 0),
+            FrameFormat::Uyv422 => (2, 1),
+            FrameFormat::Nv12 | FrameFormat::Nv21 | FrameFormat::Yv12 | FrameFormat::Luma8 => {
+                (1, 0)
+            }
+            FrameFormat::Rgb8 => (3, 0),
+            FrameFormat::RgbA8 => (4, 0),
+            FrameFormat::MJpeg => {
+                let mut rgb = BufferPool::global().get(width * height * 3);
+                buf_mjpeg_to_rgb(&self.buffer, &mut rgb, false)?;
+                return Ok(FrameStats::from_rgb(&rgb, 3));
+            }
+            other => {
+                return Err(NokhwaError::ProcessFrameError {
+                    src: other,
+                    destination: "Statistics".to_string(),
+                    error: "unsupported".to_string(),
+                })
+            }
+        };
+        let row_size = width * bytes_per_pixel;
+        let stride = self
+            .stride
+            .map_or(row_size, |stride| (stride as usize).max(row_size));
+        if height > 0 && self.buffer.len() < stride * (height - 1) + row_size {
+            return Err(NokhwaError::ProcessFrameError {
+                src: format,
+                destination: "Statistics".to_string(),
+                error: format!(
+                    "{} bytes is too small for {}",
+                    self.buffer.len(),
+                    self.resolution
+                ),
+            });
+        }
+
+        let rows = self.buffer.chunks(stride.max(1)).take(height);
+        Ok(match bytes_per_pixel {
+            3 | 4 => FrameStats::from_luma(
+                rows.flat_map(|row| FrameStats::rgb_luma_iter(&row[..row_size], bytes_per_pixel)),
+            ),
+            _ => FrameStats::from_luma(rows.flat_map(|row| {
+                row[..row_size]
+                    .iter()
+                    .skip(luma_offset)
+                    .step_by(bytes_per_pixel)
+                    .copied()
+            })),
+        })
+    }
 }
 
 #[cfg(feature = "opencv-mat")]
@@ -86,7 +302,7 @@ impl Buffer {
                 })?;
         Ok(image)
     }
-    
+
     /// Decodes a image with allocation using the provided [`FormatDecoder`] into a `buffer`.
     /// # Errors
     /// Will error when the decoding fails, or the provided buffer is too small.
@@ -119,7 +335,7 @@ impl Buffer {
     ) -> Result<opencv::core::Mat, NokhwaError> {
         use image::Pixel;
         use opencv::core::{Mat, Mat_AUTO_STEP, CV_8UC1, CV_8UC2, CV_8UC3, CV_8UC4};
-    
+
         let array_type = match F::Output::CHANNEL_COUNT {
             1 => CV_8UC1,
             2 => CV_8UC2,
@@ -133,7 +349,7 @@ impl Buffer {
                 })
             }
         };
-    
+
         unsafe {
             // TODO: Look into removing this unnecessary copy.
             let mat1 = Mat::new_rows_cols_with_data(
@@ -148,15 +364,18 @@ impl Buffer {
                 destination: "OpenCV Mat".to_string(),
                 error: why.to_string(),
             })?;
-    
+
             Ok(mat1)
         }
     }
 }
 
-#[cfg(feature = "wgpu-types")]
-use wgpu::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, ImageCopyTexture, TextureAspect, ImageDataLayout};
 use crate::frame_format::FrameFormat;
+#[cfg(feature = "wgpu-types")]
+use wgpu::{
+    Extent3d, ImageCopyTexture, ImageDataLayout, TextureAspect, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages,
+};
 
 #[cfg(feature = "wgpu-types")]
 impl Buffer {
@@ -171,13 +390,13 @@ impl Buffer {
         label: Option<&'a str>,
     ) -> Result<wgpu::Texture, NokhwaError> {
         let frame = self.frame()?.decode_image::<RgbAFormat>()?;
-    
+
         let texture_size = Extent3d {
             width: frame.width(),
             height: frame.height(),
             depth_or_array_layers: 1,
         };
-    
+
         let texture = device.create_texture(&TextureDescriptor {
             label,
             size: texture_size,
@@ -188,10 +407,10 @@ impl Buffer {
             usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
             view_formats: &[TextureFormat::Rgba8UnormSrgb],
         });
-    
+
         let width_nonzero = 4 * frame.width();
         let height_nonzero = frame.height();
-    
+
         queue.write_texture(
             ImageCopyTexture {
                 texture: &texture,
@@ -207,7 +426,7 @@ impl Buffer {
             },
             texture_size,
         );
-    
+
         Ok(texture)
     }
 }
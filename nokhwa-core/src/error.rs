@@ -15,13 +15,22 @@
  */
 
 use crate::{frame_format::FrameFormat, types::ApiBackend};
+use std::{error::Error as StdError, io, sync::Arc, time::Duration};
 use thiserror::Error;
 
+// `errno` values shared by Linux and macOS, used to classify errors. Windows and macOS frameworks do not report these,
+// so there the predicates only look at the variant.
+const ENXIO: i64 = 6;
+const EBUSY: i64 = 16;
+const ENODEV: i64 = 19;
+const EINVAL: i64 = 22;
+const ENOTTY: i64 = 25;
+
 /// All errors in `nokhwa`.
 #[allow(clippy::module_name_repetitions)]
 #[derive(Error, Debug, Clone)]
 pub enum NokhwaError {
-    #[error("Unitialized Camera. Call `init()` first!")]
+    #[error("Uninitialized Camera. Call `init()` first!")]
     UnitializedError,
     #[error("Could not initialize {backend}: {error}")]
     InitializeError { backend: ApiBackend, error: String },
@@ -41,10 +50,17 @@ pub enum NokhwaError {
         value: String,
         error: String,
     },
+    /// Another handle holds a higher access priority on the device (e.g. a recording application), so it can not be changed.
+    #[error("Could not set device property {property}: another application holds {holder} priority on the device")]
+    PriorityError { property: String, holder: String },
     #[error("Could not open device stream: {0}")]
     OpenStreamError(String),
     #[error("Could not capture frame: {0}")]
     ReadFrameError(String),
+    #[error("Timed out after {0:?} waiting for a frame")]
+    TimeoutError(Duration),
+    #[error("Stream stalled: no frame arrived for {0:?}")]
+    StreamStalledError(Duration),
     #[error("Could not process frame {src} to {destination}: {error}")]
     ProcessFrameError {
         src: FrameFormat,
@@ -57,4 +73,65 @@ pub enum NokhwaError {
     UnsupportedOperationError(ApiBackend),
     #[error("This operation is not implemented yet: {0}")]
     NotImplementedError(String),
+    /// A call into the OS or driver failed. The original error is kept as the [`source()`](std::error::Error::source),
+    /// and `code` is the raw OS error code if there is one (`errno` on Linux, `HRESULT` on Windows, `OSStatus` on macOS).
+    #[error("{operation} failed: {source}")]
+    OsError {
+        operation: String,
+        code: Option<i64>,
+        source: Arc<dyn StdError + Send + Sync>,
+    },
+}
+
+impl NokhwaError {
+    /// Wraps an [`io::Error`] returned by the OS while doing `operation` (e.g. "Set V4L2 format"), keeping its error code.
+    #[must_use]
+    pub fn from_io(operation: impl Into<String>, error: io::Error) -> Self {
+        NokhwaError::OsError {
+            operation: operation.into(),
+            code: error.raw_os_error().map(i64::from),
+            source: Arc::new(error),
+        }
+    }
+
+    /// Returns `true` if the camera went away, e.g. it was unplugged.
+    #[must_use]
+    pub fn is_disconnected(&self) -> bool {
+        matches!(self.os_error_code(), Some(ENODEV | ENXIO))
+    }
+
+    /// Returns `true` if the camera is held by another application (or another [`Camera`](crate::traits::CaptureTrait) in this one).
+    #[must_use]
+    pub fn is_busy(&self) -> bool {
+        self.os_error_code() == Some(EBUSY)
+    }
+
+    /// Returns `true` if the request was not supported by the backend or the device, e.g. an invalid format or a missing control.
+    /// Retrying the same request will not help.
+    #[must_use]
+    pub fn is_unsupported(&self) -> bool {
+        matches!(
+            self,
+            NokhwaError::UnsupportedOperationError(_) | NokhwaError::NotImplementedError(_)
+        ) || matches!(self.os_error_code(), Some(EINVAL | ENOTTY))
+    }
+
+    /// Returns `true` if no frame arrived in time. See [`TimeoutError`](NokhwaError::TimeoutError) and
+    /// [`StreamStalledError`](NokhwaError::StreamStalledError).
+    #[must_use]
+    pub fn is_timeout(&self) -> bool {
+        matches!(
+            self,
+            NokhwaError::TimeoutError(_) | NokhwaError::StreamStalledError(_)
+        )
+    }
+
+    /// Gets the raw OS error code (`errno`, `HRESULT`, or `OSStatus`) behind this error, if there is one.
+    #[must_use]
+    pub fn os_error_code(&self) -> Option<i64> {
+        match self {
+            NokhwaError::OsError { code, .. } => *code,
+            _ => None,
+        }
+    }
 }
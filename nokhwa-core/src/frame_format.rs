@@ -14,12 +14,10 @@
  * limitations under the License.
  */
 
-use crate::{buffer::Buffer, types::ApiBackend};
-use image::{ImageBuffer, Pixel};
+use crate::{error::NokhwaError, types::ApiBackend};
 use std::{
-    error::Error,
     fmt::{Display, Formatter},
-    ops::Deref,
+    str::FromStr,
 };
 
 /// Describes a frame format (i.e. how the bytes themselves are encoded). Often called `FourCC`.
@@ -110,16 +108,76 @@ impl FrameFormat {
     pub const LUMA: &'static [FrameFormat] = &[FrameFormat::Luma8];
 
     pub const RGB: &'static [FrameFormat] = &[FrameFormat::Rgb8, FrameFormat::RgbA8];
+
+    /// Gets the commonly used `FourCC` code of this format (e.g. `MJPG` for [`FrameFormat::MJpeg`]).
+    /// Returns `None` for [`FrameFormat::Custom`] and [`FrameFormat::PlatformSpecificCustomFormat`].
+    #[must_use]
+    pub fn fourcc(&self) -> Option<&'static str> {
+        FOURCC_NAMES
+            .iter()
+            .find(|(format, _)| format == self)
+            .map(|(_, fourcc)| *fourcc)
+    }
 }
 
+const FOURCC_NAMES: &[(FrameFormat, &str)] = &[
+    (FrameFormat::H265, "HEVC"),
+    (FrameFormat::H264, "H264"),
+    (FrameFormat::H263, "H263"),
+    (FrameFormat::Avc1, "AVC1"),
+    (FrameFormat::Mpeg1, "MPG1"),
+    (FrameFormat::Mpeg2, "MPG2"),
+    (FrameFormat::Mpeg4, "MPG4"),
+    (FrameFormat::MJpeg, "MJPG"),
+    (FrameFormat::XVid, "XVID"),
+    (FrameFormat::VP8, "VP80"),
+    (FrameFormat::VP9, "VP90"),
+    (FrameFormat::Yuv422, "YUYV"),
+    (FrameFormat::Uyv422, "UYVY"),
+    (FrameFormat::Nv12, "NV12"),
+    (FrameFormat::Nv21, "NV21"),
+    (FrameFormat::Yv12, "YV12"),
+    (FrameFormat::Luma8, "GREY"),
+    (FrameFormat::Rgb8, "RGB3"),
+    (FrameFormat::RgbA8, "AB24"),
+];
+
 impl Display for FrameFormat {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{self:?}")
     }
 }
 
+/// Parses either a `FourCC` code (e.g. `MJPG`, `YUYV`) or the name of the variant (e.g. `MJpeg`). Both are case-insensitive.
+/// [`FrameFormat::Custom`] is parsed from the form it is printed in, e.g. `Custom(1234)`.
+/// [`FrameFormat::PlatformSpecificCustomFormat`] can not be parsed.
+impl FromStr for FrameFormat {
+    type Err = NokhwaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let custom = s
+            .get(.."Custom(".len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case("Custom("))
+            .and_then(|_| s["Custom(".len()..].strip_suffix(')'))
+            .and_then(|code| code.trim().parse().ok())
+            .map(FrameFormat::Custom);
+        FOURCC_NAMES
+            .iter()
+            .find(|(format, fourcc)| {
+                fourcc.eq_ignore_ascii_case(s) || format!("{format:?}").eq_ignore_ascii_case(s)
+            })
+            .map(|(format, _)| *format)
+            .or(custom)
+            .ok_or_else(|| NokhwaError::StructureError {
+                structure: "FrameFormat".to_string(),
+                error: format!("Unknown frame format {s}"),
+            })
+    }
+}
+
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlatformFrameFormat {
     backend: ApiBackend,
     format: u128,
@@ -20,10 +20,13 @@
 
 //! Core type definitions for `nokhwa`
 pub mod buffer;
+#[cfg(feature = "image-types")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "image-types")))]
+pub mod decoder;
 pub mod error;
 pub mod format_request;
 pub mod frame_format;
+pub mod pool;
 pub mod traits;
 pub mod types;
-pub mod decoder;
 pub mod utils;
@@ -0,0 +1,270 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Reusable frame buffers, so capturing and decoding does not allocate a new multi-megabyte buffer for every frame.
+
+use std::{
+    fmt::{Debug, Formatter},
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, OnceLock, Weak},
+};
+
+/// The number of buffers [`BufferPool::global()`] keeps around.
+pub const GLOBAL_POOL_SIZE: usize = 8;
+
+/// A pool of byte buffers. [`get()`](Self::get) hands out a [`FrameBuffer`], which goes back into the pool when it is dropped,
+/// so the next frame can reuse its allocation.
+///
+/// The pool keeps at most `max_buffers` buffers; extra ones are freed. Cloning a [`BufferPool`] gives another handle to the same pool.
+#[derive(Clone)]
+pub struct BufferPool {
+    inner: Arc<PoolInner>,
+}
+
+struct PoolInner {
+    buffers: Mutex<Vec<Vec<u8>>>,
+    max_buffers: usize,
+}
+
+impl BufferPool {
+    /// Creates an empty pool that keeps up to `max_buffers` buffers.
+    #[must_use]
+    pub fn new(max_buffers: usize) -> Self {
+        BufferPool {
+            inner: Arc::new(PoolInner {
+                buffers: Mutex::new(Vec::with_capacity(max_buffers)),
+                max_buffers,
+            }),
+        }
+    }
+
+    /// The pool used by [`Buffer::new()`](crate::buffer::Buffer::new) and the frame decoders. It keeps [`GLOBAL_POOL_SIZE`] buffers.
+    #[must_use]
+    pub fn global() -> &'static BufferPool {
+        static GLOBAL: OnceLock<BufferPool> = OnceLock::new();
+        GLOBAL.get_or_init(|| BufferPool::new(GLOBAL_POOL_SIZE))
+    }
+
+    /// Gets a buffer of exactly `len` bytes, reusing a pooled allocation if one is large enough. The contents are zeroed.
+    #[must_use]
+    pub fn get(&self, len: usize) -> FrameBuffer {
+        let mut data = self.take(len);
+        data.clear();
+        data.resize(len, 0);
+        FrameBuffer {
+            data,
+            pool: Arc::downgrade(&self.inner),
+        }
+    }
+
+    /// Gets a buffer holding a copy of `data`. See [`get()`](Self::get).
+    #[must_use]
+    pub fn copy_from_slice(&self, data: &[u8]) -> FrameBuffer {
+        let mut buffer = self.take(data.len());
+        buffer.clear();
+        buffer.extend_from_slice(data);
+        FrameBuffer {
+            data: buffer,
+            pool: Arc::downgrade(&self.inner),
+        }
+    }
+
+    /// The number of buffers currently waiting in the pool.
+    #[must_use]
+    pub fn available(&self) -> usize {
+        self.inner.buffers.lock().map_or(0, |buffers| buffers.len())
+    }
+
+    // Takes the smallest pooled buffer that fits `len`, or the largest one (to be grown) if none do.
+    fn take(&self, len: usize) -> Vec<u8> {
+        let Ok(mut buffers) = self.inner.buffers.lock() else {
+            return Vec::with_capacity(len);
+        };
+        let fitting = buffers
+            .iter()
+            .enumerate()
+            .filter(|(_, buffer)| buffer.capacity() >= len)
+            .min_by_key(|(_, buffer)| buffer.capacity())
+            .or_else(|| {
+                buffers
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, buffer)| buffer.capacity())
+            })
+            .map(|(index, _)| index);
+        match fitting {
+            Some(index) => buffers.swap_remove(index),
+            None => Vec::with_capacity(len),
+        }
+    }
+}
+
+impl Debug for BufferPool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("available", &self.available())
+            .field("max_buffers", &self.inner.max_buffers)
+            .finish()
+    }
+}
+
+impl PoolInner {
+    fn give_back(&self, data: Vec<u8>) {
+        if let Ok(mut buffers) = self.buffers.lock() {
+            if buffers.len() < self.max_buffers {
+                buffers.push(data);
+            }
+        }
+    }
+}
+
+/// A byte buffer from a [`BufferPool`]. It dereferences to `[u8]`, and goes back into its pool when dropped.
+///
+/// Use [`into_vec()`](Self::into_vec) to keep the data as a `Vec<u8>` instead; the allocation then leaves the pool.
+pub struct FrameBuffer {
+    data: Vec<u8>,
+    pool: Weak<PoolInner>,
+}
+
+impl FrameBuffer {
+    /// Takes the data out of the pool as a `Vec<u8>`.
+    #[must_use]
+    pub fn into_vec(mut self) -> Vec<u8> {
+        self.pool = Weak::new();
+        std::mem::take(&mut self.data)
+    }
+}
+
+impl From<Vec<u8>> for FrameBuffer {
+    /// Wraps `data` without a pool. It is freed normally when dropped.
+    fn from(data: Vec<u8>) -> Self {
+        FrameBuffer {
+            data,
+            pool: Weak::new(),
+        }
+    }
+}
+
+impl Deref for FrameBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl DerefMut for FrameBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.data
+    }
+}
+
+impl AsRef<[u8]> for FrameBuffer {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl AsMut<[u8]> for FrameBuffer {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+}
+
+impl Debug for FrameBuffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrameBuffer")
+            .field("len", &self.data.len())
+            .field("pooled", &(self.pool.strong_count() > 0))
+            .finish()
+    }
+}
+
+impl Drop for FrameBuffer {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.upgrade() {
+            pool.give_back(std::mem::take(&mut self.data));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BufferPool, FrameBuffer};
+
+    #[test]
+    fn dropped_buffers_are_reused() {
+        let pool = BufferPool::new(2);
+        let buffer = pool.get(64);
+        let address = buffer.as_ptr();
+        drop(buffer);
+        assert_eq!(pool.available(), 1);
+
+        let buffer = pool.get(32);
+        assert_eq!(buffer.as_ptr(), address);
+        assert_eq!(pool.available(), 0);
+    }
+
+    #[test]
+    fn reused_buffers_are_zeroed() {
+        let pool = BufferPool::new(1);
+        let mut buffer = pool.get(16);
+        buffer.fill(0xff);
+        drop(buffer);
+
+        let buffer = pool.get(8);
+        assert_eq!(&*buffer, [0; 8]);
+    }
+
+    #[test]
+    fn take_prefers_the_smallest_fitting_buffer() {
+        let pool = BufferPool::new(3);
+        let (small, medium, large) = (pool.get(16), pool.get(64), pool.get(256));
+        let (small_address, medium_address) = (small.as_ptr(), medium.as_ptr());
+        drop((small, medium, large));
+
+        assert_eq!(pool.get(32).as_ptr(), medium_address);
+        assert_eq!(pool.available(), 3);
+        // Nothing fits, so the largest buffer is taken and grown, leaving the smaller ones.
+        let grown = pool.get(1024);
+        assert_eq!(grown.len(), 1024);
+        assert_eq!(pool.get(64).as_ptr(), medium_address);
+        assert_eq!(pool.get(1).as_ptr(), small_address);
+    }
+
+    #[test]
+    fn pool_keeps_at_most_max_buffers() {
+        let pool = BufferPool::new(1);
+        drop((pool.get(8), pool.get(8)));
+        assert_eq!(pool.available(), 1);
+    }
+
+    #[test]
+    fn copy_from_slice_copies() {
+        let pool = BufferPool::new(1);
+        assert_eq!(&*pool.copy_from_slice(&[1, 2, 3]), [1, 2, 3]);
+        assert_eq!(pool.available(), 1);
+    }
+
+    #[test]
+    fn detached_buffers_do_not_return() {
+        let pool = BufferPool::new(1);
+        let data = pool.get(8).into_vec();
+        assert_eq!(data.len(), 8);
+        drop(FrameBuffer::from(data));
+        assert_eq!(pool.available(), 0);
+    }
+}
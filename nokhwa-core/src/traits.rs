@@ -15,17 +15,20 @@
  */
 
 use crate::{
-    buffer::Buffer,
+    buffer::{Buffer, Frame},
     error::NokhwaError,
     format_request::FormatFilter,
     frame_format::SourceFrameFormat,
     types::{
-        ApiBackend, CameraControl, CameraFormat, CameraInfo, ControlValueSetter,
-        KnownCameraControl, Resolution,
+        ApiBackend, CameraControl, CameraFormat, CameraInfo, ControlValueSetter, FrameRate,
+        KnownCameraControl, Region, RequestedFormat, RequestedFormatType, Resolution,
     },
 };
 use std::{borrow::Cow, collections::HashMap};
 
+/// The [`ApiBackend`] a backend struct implements, known at compile time.
+///
+/// This has an associated constant, so it can not be used as a trait object. Use [`CaptureTrait::backend()`] on a `dyn CaptureTrait` instead.
 pub trait Backend {
     const BACKEND: ApiBackend;
 }
@@ -37,6 +40,9 @@ pub trait Backend {
 /// - Backends, if not provided with a camera format, will be spawned with 640x480@15 FPS, MJPEG [`CameraFormat`].
 /// - Behaviour can differ from backend to backend. While the Camera struct abstracts most of this away, if you plan to use the raw backend structs please read the `Quirks` section of each backend.
 /// - If you call [`stop_stream()`](CaptureTrait::stop_stream()), you will usually need to call [`open_stream()`](CaptureTrait::open_stream()) to get more frames from the camera.
+///
+/// This trait is object safe, so a backend chosen at runtime can be stored as a `Box<dyn CaptureTrait + Send>`. Generic
+/// methods must be bound by `where Self: Sized` to keep it that way.
 pub trait CaptureTrait {
     /// Initialize the camera, preparing it for use, with a random format (usually the first one).
     fn init(&mut self) -> Result<(), NokhwaError>;
@@ -66,6 +72,62 @@ pub trait CaptureTrait {
     /// If you started the stream and the camera rejects the new camera format, this will return an error.
     fn set_camera_format(&mut self, new_fmt: CameraFormat) -> Result<(), NokhwaError>;
 
+    /// Resolves the [`RequestedFormat`] against the formats the camera supports (see [`compatible_camera_formats()`](CaptureTrait::compatible_camera_formats))
+    /// and sets the result. This will reset the current stream if used while stream is opened.
+    ///
+    /// This will return the new [`CameraFormat`].
+    /// # Errors
+    /// If nothing the camera supports fits the request, or the camera rejects the new camera format, this will return an error.
+    fn set_camera_request(
+        &mut self,
+        request: RequestedFormat,
+    ) -> Result<CameraFormat, NokhwaError> {
+        let formats = self.compatible_camera_formats()?;
+        let new_fmt = request
+            .fulfill(&formats)
+            .ok_or_else(|| NokhwaError::SetPropertyError {
+                property: "CameraFormat".to_string(),
+                value: request.to_string(),
+                error: "No supported format fits the request".to_string(),
+            })?;
+        self.set_camera_format(new_fmt)?;
+        Ok(new_fmt)
+    }
+
+    /// Switches to the highest [`Resolution`] the camera supports in the current [`FrameFormat`](crate::frame_format::FrameFormat),
+    /// at the highest frame rate available for it. This will reset the current stream if used while stream is opened.
+    ///
+    /// This will return the new [`CameraFormat`].
+    /// # Errors
+    /// If the formats could not be listed or the camera rejects the new camera format, this will return an error.
+    fn set_highest_resolution(&mut self) -> Result<CameraFormat, NokhwaError> {
+        let request = match self.camera_format() {
+            Some(current) => RequestedFormat::with_formats(
+                RequestedFormatType::AbsoluteHighestResolution,
+                &[current.format()],
+            ),
+            None => RequestedFormat::new(RequestedFormatType::AbsoluteHighestResolution),
+        };
+        self.set_camera_request(request)
+    }
+
+    /// Switches to the highest frame rate the camera supports in the current [`FrameFormat`](crate::frame_format::FrameFormat),
+    /// at the highest [`Resolution`] available for it. This will reset the current stream if used while stream is opened.
+    ///
+    /// This will return the new [`CameraFormat`].
+    /// # Errors
+    /// If the formats could not be listed or the camera rejects the new camera format, this will return an error.
+    fn set_highest_framerate(&mut self) -> Result<CameraFormat, NokhwaError> {
+        let request = match self.camera_format() {
+            Some(current) => RequestedFormat::with_formats(
+                RequestedFormatType::AbsoluteHighestFrameRate,
+                &[current.format()],
+            ),
+            None => RequestedFormat::new(RequestedFormatType::AbsoluteHighestFrameRate),
+        };
+        self.set_camera_request(request)
+    }
+
     /// A hashmap of [`Resolution`]s mapped to framerates. Not sorted!
     /// # Errors
     /// This will error if the camera is not queryable or a query operation has failed. Some backends will error this out as a Unsupported Operation ([`UnsupportedOperationError`](NokhwaError::UnsupportedOperationError)).
@@ -82,7 +144,11 @@ pub trait CaptureTrait {
         for fourcc in self.compatible_fourcc()? {
             for (resolution, fps_list) in self.compatible_list_by_resolution(fourcc)? {
                 for fps in fps_list {
-                    compatible_formats.push(CameraFormat::new(resolution, fourcc, fps));
+                    compatible_formats.push(CameraFormat::new(
+                        resolution,
+                        fourcc,
+                        FrameRate::from(fps),
+                    ));
                 }
             }
         }
@@ -126,8 +192,7 @@ pub trait CaptureTrait {
     /// This will also update the cache.
     /// # Errors
     /// If you started the stream and the camera rejects the new frame format, this will return an error.
-    fn set_frame_format(&mut self, fourcc: SourceFrameFormat)
-        -> Result<(), NokhwaError>;
+    fn set_frame_format(&mut self, fourcc: SourceFrameFormat) -> Result<(), NokhwaError>;
 
     /// Gets the value of [`KnownCameraControl`].
     /// # Errors
@@ -172,6 +237,39 @@ pub trait CaptureTrait {
     /// If the backend fails to get the frame (e.g. already taken, busy, doesn't exist anymore), or [`open_stream()`](CaptureTrait::open_stream()) has not been called yet, this will error.
     fn frame_raw(&mut self) -> Result<Cow<[u8]>, NokhwaError>;
 
+    /// Will get `count` consecutive frames from the camera, as fast as the camera delivers them. Each [`Buffer`] keeps its timestamp (if any),
+    /// so the frames can be lined up afterwards, e.g. for HDR or focus stacking.
+    ///
+    /// This opens the stream if it is not open yet.
+    /// # Errors
+    /// If any frame fails to be captured, this will error and the frames captured so far are discarded.
+    fn frame_burst(&mut self, count: usize) -> Result<Vec<Buffer>, NokhwaError> {
+        if !self.is_stream_open() {
+            self.open_stream()?;
+        }
+        let mut frames = Vec::with_capacity(count);
+        for _ in 0..count {
+            frames.push(self.frame()?);
+        }
+        Ok(frames)
+    }
+
+    /// The region of the sensor the device crops frames to, or `None` if it does not crop (or can not tell).
+    fn crop(&self) -> Option<Region> {
+        None
+    }
+
+    /// Asks the device to crop frames to `region` at the source, which saves bandwidth and CPU compared to cropping decoded
+    /// frames. Pass `None` to undo cropping. Drivers round the region to what the hardware supports, so the region actually
+    /// granted is returned, and the [`camera_format()`](CaptureTrait::camera_format) may change to match it.
+    /// # Errors
+    /// If the backend or device does not support cropping, this will error. The default implementation always errors with
+    /// [`UnsupportedOperationError`](NokhwaError::UnsupportedOperationError).
+    fn set_crop(&mut self, region: Option<Region>) -> Result<Option<Region>, NokhwaError> {
+        let _ = region;
+        Err(NokhwaError::UnsupportedOperationError(self.backend()))
+    }
+
     // #[cfg(feature = "wgpu-types")]
     // #[cfg_attr(feature = "docs-features", doc(cfg(feature = "wgpu-types")))]
     // /// Directly copies a frame to a Wgpu texture. This will automatically convert the frame into a RGBA frame.
@@ -232,12 +330,31 @@ pub trait CaptureTrait {
     //     Ok(texture)
     // }
 
-    /// Will drop the stream.
+    /// Gets an iterator over frames from the camera. See [`FrameIter`].
+    /// ```ignore
+    /// for frame in camera.frames_iter().take(100) {
+    ///     let frame = frame?;
+    /// }
+    /// ```
+    fn frames_iter(&mut self) -> FrameIter<'_, Self>
+    where
+        Self: Sized,
+    {
+        FrameIter::new(self)
+    }
+
+    /// Will stop the stream, releasing its buffers and turning off streaming.
+    ///
+    /// The device stays open and keeps its [`CameraFormat`] and controls, so the stream may be opened again with [`open_stream()`](CaptureTrait::open_stream()).
+    /// Calling this when the stream is not open does nothing.
     /// # Errors
     /// Please check the `Quirks` section of each backend.
     fn stop_stream(&mut self) -> Result<(), NokhwaError>;
 }
 
+// Fails to compile if `CaptureTrait` stops being object safe.
+const _: fn(&dyn CaptureTrait) = |_| {};
+
 impl<T> From<T> for Box<dyn CaptureTrait>
 where
     T: CaptureTrait + 'static,
@@ -247,6 +364,43 @@ where
     }
 }
 
+/// An endless iterator over frames from a camera, opening the stream on the first call if needed.
+///
+/// Once an error is returned, the iterator ends. Use [`FrameIter::new`] to iterate over a `dyn` [`CaptureTrait`].
+pub struct FrameIter<'a, C: CaptureTrait + ?Sized> {
+    camera: &'a mut C,
+    errored: bool,
+}
+
+impl<'a, C: CaptureTrait + ?Sized> FrameIter<'a, C> {
+    /// Creates a new [`FrameIter`] over the frames of `camera`.
+    pub fn new(camera: &'a mut C) -> Self {
+        FrameIter {
+            camera,
+            errored: false,
+        }
+    }
+}
+
+impl<'a, C: CaptureTrait + ?Sized> Iterator for FrameIter<'a, C> {
+    type Item = Result<Frame, NokhwaError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.errored {
+            return None;
+        }
+        let frame = if self.camera.is_stream_open() {
+            self.camera.frame()
+        } else {
+            self.camera.open_stream().and_then(|()| self.camera.frame())
+        };
+        self.errored = frame.is_err();
+        Some(frame)
+    }
+}
+
+impl<'a, C: CaptureTrait + ?Sized> std::iter::FusedIterator for FrameIter<'a, C> {}
+
 #[cfg(feature = "async")]
 #[cfg_attr(feature = "async", async_trait::async_trait)]
 pub trait AsyncCaptureTrait: CaptureTrait {
@@ -254,8 +408,10 @@ pub trait AsyncCaptureTrait: CaptureTrait {
     async fn init_async(&mut self) -> Result<(), NokhwaError>;
 
     /// Initialize the camera, preparing it for use, with a format that fits the supplied [`FormatFilter`].
-    async fn init_with_format_async(&mut self, format: FormatFilter)
-        -> Result<CameraFormat, NokhwaError>;
+    async fn init_with_format_async(
+        &mut self,
+        format: FormatFilter,
+    ) -> Result<CameraFormat, NokhwaError>;
 
     /// Forcefully refreshes the stored camera format, bringing it into sync with "reality" (current camera state)
     /// # Errors
@@ -344,7 +500,21 @@ pub trait AsyncCaptureTrait: CaptureTrait {
     /// If the backend fails to get the frame (e.g. already taken, busy, doesn't exist anymore), or [`open_stream()`](CaptureTrait::open_stream()) has not been called yet, this will error.
     async fn frame_raw_async(&mut self) -> Result<Cow<[u8]>, NokhwaError>;
 
-    /// Will drop the stream.
+    /// Will get `count` consecutive frames from the camera. See [`frame_burst()`](CaptureTrait::frame_burst()).
+    /// # Errors
+    /// If any frame fails to be captured, this will error and the frames captured so far are discarded.
+    async fn frame_burst_async(&mut self, count: usize) -> Result<Vec<Buffer>, NokhwaError> {
+        if !self.is_stream_open() {
+            self.open_stream_async().await?;
+        }
+        let mut frames = Vec::with_capacity(count);
+        for _ in 0..count {
+            frames.push(self.frame_async().await?);
+        }
+        Ok(frames)
+    }
+
+    /// Will stop the stream, releasing its buffers and turning off streaming. See [`stop_stream()`](CaptureTrait::stop_stream()).
     /// # Errors
     /// Please check the `Quirks` section of each backend.
     async fn stop_stream_async(&mut self) -> Result<(), NokhwaError>;
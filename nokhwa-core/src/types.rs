@@ -1,7 +1,4 @@
-use crate::{
-    error::NokhwaError,
-    frame_format::{FrameFormat},
-};
+use crate::{error::NokhwaError, frame_format::FrameFormat};
 #[cfg(feature = "serialize")]
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
@@ -9,9 +6,16 @@ use std::{
     borrow::Borrow,
     cmp::Ordering,
     fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
+    str::FromStr,
+    sync::atomic::{self, AtomicU8},
+    time::{Duration, SystemTime},
 };
+#[cfg(feature = "output-wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
 
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct Range<T>
 where
     T: Copy + Clone + Debug + PartialOrd + PartialEq,
@@ -93,7 +97,6 @@ where
         true
     }
 
-
     pub fn set_minimum(&mut self, minimum: Option<T>) {
         self.minimum = minimum;
     }
@@ -143,8 +146,10 @@ where
 }
 
 /// Describes the index of the camera.
-/// - Index: A numbered index
-/// - String: A string, used for `IPCameras`.
+/// - Index: A numbered index, e.g. `0` for `/dev/video0` on `V4L2`.
+/// - String: A string identifier, for backends that identify devices by a path, GUID, or `deviceId` (browser).
+///
+/// Anything that takes a [`CameraIndex`] by value also accepts a `u32`, `String`, or `&str` through [`Into`].
 #[derive(Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum CameraIndex {
@@ -202,6 +207,24 @@ impl Default for CameraIndex {
     }
 }
 
+impl From<u32> for CameraIndex {
+    fn from(index: u32) -> Self {
+        CameraIndex::Index(index)
+    }
+}
+
+impl From<String> for CameraIndex {
+    fn from(identifier: String) -> Self {
+        CameraIndex::String(identifier)
+    }
+}
+
+impl From<&str> for CameraIndex {
+    fn from(identifier: &str) -> Self {
+        CameraIndex::String(identifier.to_string())
+    }
+}
+
 impl TryFrom<CameraIndex> for u32 {
     type Error = NokhwaError;
 
@@ -225,26 +248,84 @@ impl TryFrom<CameraIndex> for usize {
 /// This is exported as `JSResolution`
 #[cfg_attr(feature = "output-wasm", wasm_bindgen)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, Default, Hash, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub struct Resolution {
     pub width_x: u32,
     pub height_y: u32,
 }
 
-#[cfg_attr(feature = "output-wasm", wasm_bindgen)]
 impl Resolution {
+    /// 320x240 (4:3)
+    pub const QVGA: Resolution = Resolution::new(320, 240);
+    /// 640x480 (4:3)
+    pub const VGA: Resolution = Resolution::new(640, 480);
+    /// 800x600 (4:3)
+    pub const SVGA: Resolution = Resolution::new(800, 600);
+    /// 1280x720 (16:9)
+    pub const HD_720P: Resolution = Resolution::new(1280, 720);
+    /// 1920x1080 (16:9)
+    pub const FULL_HD: Resolution = Resolution::new(1920, 1080);
+    /// 2560x1440 (16:9)
+    pub const QHD: Resolution = Resolution::new(2560, 1440);
+    /// 3840x2160 (16:9)
+    pub const UHD_4K: Resolution = Resolution::new(3840, 2160);
+
     /// Create a new resolution from 2 image size coordinates.
-    /// # JS-WASM
-    /// This is exported as a constructor for [`Resolution`].
     #[must_use]
-    #[cfg_attr(feature = "output-wasm", wasm_bindgen(constructor))]
-    pub fn new(x: u32, y: u32) -> Self {
+    pub const fn new(x: u32, y: u32) -> Self {
         Resolution {
             width_x: x,
             height_y: y,
         }
     }
 
+    /// Gets the number of pixels in a frame of this resolution.
+    #[must_use]
+    pub const fn pixel_count(&self) -> u64 {
+        self.width_x as u64 * self.height_y as u64
+    }
+
+    /// Gets the aspect ratio in its lowest terms, e.g. `(16, 9)` for 1920x1080. A zero sized resolution returns itself.
+    #[must_use]
+    pub const fn aspect_ratio(&self) -> (u32, u32) {
+        let divisor = gcd(self.width_x, self.height_y);
+        if divisor == 0 {
+            return (self.width_x, self.height_y);
+        }
+        (self.width_x / divisor, self.height_y / divisor)
+    }
+
+    /// Gets the aspect ratio as width divided by height, e.g. `1.777...` for 1920x1080.
+    #[must_use]
+    pub fn aspect_ratio_float(&self) -> f32 {
+        self.width_x as f32 / self.height_y as f32
+    }
+
+    /// Returns `true` if this resolution has the aspect ratio `width:height`, e.g. `is_aspect_ratio(16, 9)`.
+    #[must_use]
+    pub const fn is_aspect_ratio(&self, width: u32, height: u32) -> bool {
+        self.width_x as u64 * height as u64 == self.height_y as u64 * width as u64
+    }
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Resolution::VGA
+    }
+}
+
+#[cfg_attr(feature = "output-wasm", wasm_bindgen)]
+impl Resolution {
+    /// Create a new resolution from 2 image size coordinates.
+    /// # JS-WASM
+    /// This is exported as a constructor for [`Resolution`].
+    #[cfg(feature = "output-wasm")]
+    #[must_use]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(constructor))]
+    pub fn js_new(x: u32, y: u32) -> Self {
+        Resolution::new(x, y)
+    }
+
     /// Get the width of Resolution
     /// # JS-WASM
     /// This is exported as `get_Width`.
@@ -288,6 +369,22 @@ impl Display for Resolution {
     }
 }
 
+/// Parses a [`Resolution`] in the form `1920x1080`.
+impl FromStr for Resolution {
+    type Err = NokhwaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || NokhwaError::StructureError {
+            structure: "Resolution".to_string(),
+            error: format!("Expected WIDTHxHEIGHT, got {s}"),
+        };
+        let (width, height) = s.trim().split_once(['x', 'X']).ok_or_else(error)?;
+        let width = width.trim().parse().map_err(|_| error())?;
+        let height = height.trim().parse().map_err(|_| error())?;
+        Ok(Resolution::new(width, height))
+    }
+}
+
 impl PartialOrd for Resolution {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -304,73 +401,156 @@ impl Ord for Resolution {
     }
 }
 
-#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
-#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 /// The frame rate of a camera.
+///
+/// Drivers usually report frame rates as a rational number, e.g. NTSC's 29.97 FPS is exactly `30000/1001`. Use
+/// [`FrameRate::Fraction`] to keep those exact; [`as_u32()`](Self::as_u32) rounds to the nearest whole frame rate for convenience.
+/// Two frame rates are equal if they are the same rational number, so `Integer(30)` equals `Fraction { numerator: 30, denominator: 1 }`.
+/// A fraction with a denominator of 0 (e.g. from a driver that reports a zero frame interval) is treated as 0 FPS.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub enum FrameRate {
     /// The driver reports the frame rate as a clean integer (e.g. 30 FPS).
     Integer(u32),
     /// The driver reports the frame rate as a floating point number (e.g. 29.97 FPS)
     Float(f32),
-    /// The driver reports the frame rate as a fraction (e.g. 2997/1000 FPS)
-    Fraction {
-        numerator: u16,
-        denominator: u16,
-    }
+    /// The driver reports the frame rate as a fraction (e.g. 30000/1001 FPS)
+    Fraction { numerator: u32, denominator: u32 },
 }
 
 impl FrameRate {
-    pub fn new_integer(fps: u32) -> Self {
+    #[must_use]
+    pub const fn new_integer(fps: u32) -> Self {
         FrameRate::Integer(fps)
     }
 
-    pub fn new_float(fps: f32) -> Self {
+    #[must_use]
+    pub const fn new_float(fps: f32) -> Self {
         FrameRate::Float(fps)
     }
 
-    pub fn new_fraction(numerator: u16, denominator: u16) -> Self {
+    #[must_use]
+    pub const fn new_fraction(numerator: u32, denominator: u32) -> Self {
         FrameRate::Fraction {
             numerator,
             denominator,
         }
     }
 
+    /// Creates a [`FrameRate`] from a frame interval (the time per frame, in seconds, as `numerator/denominator`),
+    /// which is how `V4L2`, Media Foundation, and `AVFoundation` describe frame rates.
+    #[must_use]
+    pub fn from_interval(numerator: u32, denominator: u32) -> Self {
+        FrameRate::Fraction {
+            numerator: denominator,
+            denominator: numerator,
+        }
+        .reduced()
+    }
+
+    /// Gets the frame interval (the time per frame, in seconds) as `(numerator, denominator)`. See [`from_interval()`](Self::from_interval).
+    #[must_use]
+    pub fn as_interval(&self) -> (u32, u32) {
+        let (numerator, denominator) = self.as_fraction();
+        (denominator, numerator)
+    }
+
+    /// Gets the frame rate as a `(numerator, denominator)` fraction in its lowest terms.
+    /// [`FrameRate::Float`] is approximated to a denominator of 1000, and a zero denominator becomes `(0, 1)`.
+    #[must_use]
+    pub fn as_fraction(&self) -> (u32, u32) {
+        let (numerator, denominator) = match *self {
+            FrameRate::Integer(fps) => (fps, 1),
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            FrameRate::Float(fps) => ((fps * 1000.0).round() as u32, 1000),
+            FrameRate::Fraction {
+                numerator,
+                denominator,
+            } => (numerator, denominator),
+        };
+        // Without this, `x/0` would compare equal to every frame rate, which breaks `Ord`.
+        if denominator == 0 {
+            return (0, 1);
+        }
+        let divisor = gcd(numerator, denominator);
+        (numerator / divisor, denominator / divisor)
+    }
+
+    /// Simplifies this frame rate, e.g. `Fraction { numerator: 60, denominator: 2 }` becomes `Integer(30)`.
+    #[must_use]
+    pub fn reduced(&self) -> Self {
+        match self.as_fraction() {
+            (fps, 1) => FrameRate::Integer(fps),
+            (numerator, denominator) => FrameRate::Fraction {
+                numerator,
+                denominator,
+            },
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
     pub fn as_float(&self) -> f32 {
-        match self {
+        match *self {
             FrameRate::Integer(fps) => fps as f32,
             FrameRate::Float(fps) => fps,
-            FrameRate::Fraction { numerator, denominator } => (numerator as f32) / (denominator as f32)
+            FrameRate::Fraction { denominator: 0, .. } => 0.0,
+            FrameRate::Fraction {
+                numerator,
+                denominator,
+            } => (numerator as f32) / (denominator as f32),
         }
     }
 
+    /// Gets the frame rate rounded to the nearest whole number, e.g. 30 for 29.97 FPS.
+    #[must_use]
     pub fn as_u32(&self) -> u32 {
-        match self {
-            FrameRate::Integer(fps) => *fps,
-            FrameRate::Float(fps) => fps as u32,
-            FrameRate::Fraction { numerator, denominator } => numerator / denominator,
-        }
+        let (numerator, denominator) = self.as_fraction();
+        let rounded = (u64::from(numerator) + u64::from(denominator) / 2) / u64::from(denominator);
+        u32::try_from(rounded).unwrap_or(u32::MAX)
     }
 }
 
+const fn gcd(mut a: u32, mut b: u32) -> u32 {
+    while b != 0 {
+        let rem = a % b;
+        a = b;
+        b = rem;
+    }
+    a
+}
+
 impl Default for FrameRate {
     fn default() -> Self {
         FrameRate::Integer(30)
     }
 }
 
+impl PartialEq for FrameRate {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_fraction() == other.as_fraction()
+    }
+}
+
+impl Eq for FrameRate {}
+
+impl Hash for FrameRate {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_fraction().hash(state);
+    }
+}
+
 impl PartialOrd for FrameRate {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let this_float = self.as_float();
-        let other = other.as_float();
-        this_float.partial_cmp(&other)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for FrameRate {
     fn cmp(&self, other: &Self) -> Ordering {
-        let this_float = self.as_float();
-        let other = other.as_float();
-        this_float.total_cmp(&other)
+        let (a_num, a_den) = self.as_fraction();
+        let (b_num, b_den) = other.as_fraction();
+        (u64::from(a_num) * u64::from(b_den)).cmp(&(u64::from(b_num) * u64::from(a_den)))
     }
 }
 
@@ -399,8 +579,8 @@ impl From<f32> for FrameRate {
     }
 }
 
-impl From<(u16, u16)> for FrameRate {
-    fn from(value: (u16, u16)) -> Self {
+impl From<(u32, u32)> for FrameRate {
+    fn from(value: (u32, u32)) -> Self {
         FrameRate::Fraction {
             numerator: value.0,
             denominator: value.1,
@@ -410,7 +590,10 @@ impl From<(u16, u16)> for FrameRate {
 
 /// This is a convenience struct that holds all information about the format of a webcam stream.
 /// It consists of a [`Resolution`], [`FrameFormat`], and a frame rate(u8).
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+///
+/// [`CameraFormat`]s are ordered by the pixel count of their resolution, then by frame rate, so the "best" format is the greatest one.
+/// See [`best_of()`](CameraFormat::best_of).
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct CameraFormat {
     resolution: Resolution,
@@ -421,7 +604,7 @@ pub struct CameraFormat {
 impl CameraFormat {
     /// Construct a new [`CameraFormat`]
     #[must_use]
-    pub fn new(resolution: Resolution, format: FrameFormat, frame_rate: FrameRate) -> Self {
+    pub const fn new(resolution: Resolution, format: FrameFormat, frame_rate: FrameRate) -> Self {
         CameraFormat {
             resolution,
             format,
@@ -431,7 +614,7 @@ impl CameraFormat {
 
     /// [`CameraFormat::new()`], but raw.
     #[must_use]
-    pub fn new_from(res_x: u32, res_y: u32, format: FrameFormat, fps: FrameRate) -> Self {
+    pub const fn new_from(res_x: u32, res_y: u32, format: FrameFormat, fps: FrameRate) -> Self {
         CameraFormat {
             resolution: Resolution {
                 width_x: res_x,
@@ -486,31 +669,233 @@ impl CameraFormat {
     pub fn set_format(&mut self, format: FrameFormat) {
         self.format = format;
     }
+
+    /// Picks the best [`CameraFormat`] (highest resolution, then highest frame rate) out of `formats`, e.g. from
+    /// [`compatible_camera_formats()`](crate::traits::CaptureTrait::compatible_camera_formats). Returns `None` if `formats` is empty.
+    pub fn best_of(formats: impl IntoIterator<Item = CameraFormat>) -> Option<CameraFormat> {
+        formats.into_iter().max()
+    }
+
+    /// Like [`best_of()`](CameraFormat::best_of), but only considers formats with the given [`FrameFormat`].
+    pub fn best_of_format(
+        formats: impl IntoIterator<Item = CameraFormat>,
+        format: FrameFormat,
+    ) -> Option<CameraFormat> {
+        formats.into_iter().filter(|fmt| fmt.format == format).max()
+    }
+
+    /// Sorts `formats` from best to worst.
+    pub fn sort_best_first(formats: &mut [CameraFormat]) {
+        formats.sort_unstable_by(|a, b| b.cmp(a));
+    }
+}
+
+impl PartialOrd for CameraFormat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CameraFormat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.resolution
+            .pixel_count()
+            .cmp(&other.resolution.pixel_count())
+            .then(self.frame_rate.cmp(&other.frame_rate))
+            .then(self.resolution.cmp(&other.resolution))
+            .then(self.format.cmp(&other.format))
+    }
 }
 
 impl Default for CameraFormat {
     fn default() -> Self {
         CameraFormat {
-            resolution: Resolution::new(640, 480),
+            resolution: Resolution::VGA,
             format: FrameFormat::MJpeg,
             frame_rate: FrameRate::Integer(30),
         }
     }
 }
 
+/// Formats as `1920x1080@30 MJPG`. Fractional frame rates are written as a fraction (`1920x1080@30000/1001 MJPG`),
+/// and formats without a `FourCC` code use their variant name (`1920x1080@30 Custom(1234)`). This can be parsed back with
+/// [`FromStr`], except for [`FrameFormat::PlatformSpecificCustomFormat`], which has no parseable form.
 impl Display for CameraFormat {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}@{}FPS, {} Format",
-            self.resolution, self.frame_rate, self.format
-        )
+        write!(f, "{}@", self.resolution)?;
+        match self.frame_rate {
+            FrameRate::Integer(fps) => write!(f, "{fps}")?,
+            FrameRate::Float(fps) => write!(f, "{fps}")?,
+            FrameRate::Fraction {
+                numerator,
+                denominator,
+            } => write!(f, "{numerator}/{denominator}")?,
+        }
+        match self.format.fourcc() {
+            Some(fourcc) => write!(f, " {fourcc}"),
+            None => write!(f, " {:?}", self.format),
+        }
+    }
+}
+
+/// Parses a [`CameraFormat`] in the form written by its [`Display`] implementation, e.g. `1920x1080@30 MJPG`.
+impl FromStr for CameraFormat {
+    type Err = NokhwaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || NokhwaError::StructureError {
+            structure: "CameraFormat".to_string(),
+            error: format!("Expected WIDTHxHEIGHT@FPS FORMAT, got {s}"),
+        };
+        let (resolution, rest) = s.trim().split_once('@').ok_or_else(error)?;
+        let (frame_rate, format) = rest
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(error)?;
+        let frame_rate = match frame_rate.split_once('/') {
+            Some((numerator, denominator)) => FrameRate::Fraction {
+                numerator: numerator.parse().map_err(|_| error())?,
+                denominator: denominator.parse().map_err(|_| error())?,
+            },
+            None => match frame_rate.parse::<u32>() {
+                Ok(fps) => FrameRate::Integer(fps),
+                Err(_) => FrameRate::Float(frame_rate.parse().map_err(|_| error())?),
+            },
+        };
+        Ok(CameraFormat::new(
+            resolution.parse()?,
+            format.parse()?,
+            frame_rate,
+        ))
+    }
+}
+
+/// Describes which of the camera's supported [`CameraFormat`]s should be picked. Used with [`RequestedFormat`].
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum RequestedFormatType {
+    /// Pick the highest [`Resolution`], then the highest frame rate at that resolution.
+    AbsoluteHighestResolution,
+    /// Pick the highest frame rate, then the highest [`Resolution`] at that frame rate.
+    AbsoluteHighestFrameRate,
+    /// Pick the highest frame rate at the given [`Resolution`].
+    HighestResolution(Resolution),
+    /// Pick the highest [`Resolution`] at the given frame rate.
+    HighestFrameRate(u32),
+    /// Pick exactly this [`CameraFormat`]. If the camera does not support it, nothing is picked.
+    Exact(CameraFormat),
+    /// Pick the supported [`CameraFormat`] that is closest to this one.
+    ///
+    /// A matching [`FrameFormat`] is preferred first, then the closest [`Resolution`], then the closest frame rate.
+    Closest(CameraFormat),
+    /// Pick whatever the camera lists first.
+    None,
+}
+
+impl Display for RequestedFormatType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// A request for a [`CameraFormat`], expressed as an intent (e.g. "the highest resolution") instead of a fixed format.
+///
+/// Backends resolve it against the formats the device actually supports using [`fulfill()`](Self::fulfill), so asking
+/// for something the camera can not do never silently gets you a different format from the driver.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct RequestedFormat {
+    requested_format: RequestedFormatType,
+    allowed_formats: Vec<FrameFormat>,
+}
+
+impl RequestedFormat {
+    /// Creates a new [`RequestedFormat`] that accepts any [`FrameFormat`].
+    #[must_use]
+    pub fn new(requested: RequestedFormatType) -> Self {
+        RequestedFormat {
+            requested_format: requested,
+            allowed_formats: vec![],
+        }
+    }
+
+    /// Creates a new [`RequestedFormat`] that only accepts the given [`FrameFormat`]s.
+    #[must_use]
+    pub fn with_formats(requested: RequestedFormatType, allowed_formats: &[FrameFormat]) -> Self {
+        RequestedFormat {
+            requested_format: requested,
+            allowed_formats: allowed_formats.to_vec(),
+        }
+    }
+
+    /// Gets the [`RequestedFormatType`].
+    #[must_use]
+    pub fn requested_format_type(&self) -> RequestedFormatType {
+        self.requested_format
+    }
+
+    /// Gets the accepted [`FrameFormat`]s. If this is empty, any [`FrameFormat`] is accepted.
+    #[must_use]
+    pub fn allowed_formats(&self) -> &[FrameFormat] {
+        &self.allowed_formats
+    }
+
+    /// Picks the [`CameraFormat`] out of `all_formats` that best fits this request. Returns `None` if nothing fits.
+    #[must_use]
+    pub fn fulfill(&self, all_formats: &[CameraFormat]) -> Option<CameraFormat> {
+        let mut candidates = all_formats.iter().copied().filter(|format| {
+            self.allowed_formats.is_empty() || self.allowed_formats.contains(&format.format())
+        });
+
+        match self.requested_format {
+            RequestedFormatType::AbsoluteHighestResolution => {
+                candidates.max_by_key(|format| (format.resolution(), format.frame_rate()))
+            }
+            RequestedFormatType::AbsoluteHighestFrameRate => {
+                candidates.max_by_key(|format| (format.frame_rate(), format.resolution()))
+            }
+            RequestedFormatType::HighestResolution(resolution) => candidates
+                .filter(|format| format.resolution() == resolution)
+                .max_by_key(CameraFormat::frame_rate),
+            RequestedFormatType::HighestFrameRate(fps) => candidates
+                .filter(|format| format.frame_rate().as_u32() == fps)
+                .max_by_key(CameraFormat::resolution),
+            RequestedFormatType::Exact(exact) => candidates.find(|format| *format == exact),
+            RequestedFormatType::Closest(closest) => candidates.min_by(|a, b| {
+                let distance = |format: &CameraFormat| {
+                    let width = f64::from(format.width()) - f64::from(closest.width());
+                    let height = f64::from(format.height()) - f64::from(closest.height());
+                    let fps = format.frame_rate().as_float() - closest.frame_rate().as_float();
+                    (
+                        format.format() != closest.format(),
+                        width.hypot(height),
+                        fps.abs(),
+                    )
+                };
+                let (a_format, a_res, a_fps) = distance(a);
+                let (b_format, b_res, b_fps) = distance(b);
+                a_format
+                    .cmp(&b_format)
+                    .then(a_res.total_cmp(&b_res))
+                    .then(a_fps.total_cmp(&b_fps))
+            }),
+            RequestedFormatType::None => candidates.next(),
+        }
+    }
+}
+
+impl Display for RequestedFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
     }
 }
 
 /// Information about a Camera e.g. its name.
 /// `description` amd `misc` may contain information that may differ from backend to backend. Refer to each backend for details.
 /// `index` is a camera's index given to it by (usually) the OS usually in the order it is known to the system.
+/// `group` identifies the physical device this camera belongs to, if the backend can tell (e.g. the RGB and IR camera of a Windows Hello webcam share a group).
+/// `rotation` is how the sensor is mounted, if the backend can tell. See [`CameraInfo::rotation()`].
+/// `driver` describes the driver and firmware behind the camera, if the backend can tell. Include it in bug reports.
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd)]
 #[cfg_attr(feature = "output-wasm", wasm_bindgen)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
@@ -519,6 +904,11 @@ pub struct CameraInfo {
     description: String,
     misc: String,
     index: CameraIndex,
+    group: Option<String>,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    rotation: Option<Rotation>,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    driver: Option<DriverInfo>,
 }
 
 #[cfg_attr(feature = "output-wasm", wasm_bindgen(js_class = CameraInfo))]
@@ -537,20 +927,26 @@ impl CameraInfo {
             description: description.to_string(),
             misc: misc.to_string(),
             index,
+            group: None,
+            rotation: None,
+            driver: None,
         }
     }
 
     /// Get a reference to the device info's human readable name.
+    #[must_use]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(skip))]
+    pub fn human_name(&self) -> &str {
+        &self.human_name
+    }
+
+    /// Get the device info's human readable name.
     /// # JS-WASM
-    /// This is exported as a `get_HumanReadableName`.
+    /// This is exported as a `get_HumanReadableName`. `wasm-bindgen` can not return references, so this clones.
+    #[cfg(feature = "output-wasm")]
     #[must_use]
-    #[cfg_attr(
-    feature = "output-wasm",
-    wasm_bindgen(getter = HumanReadableName)
-    )]
-    // yes, i know, unnecessary alloc this, unnecessary alloc that
-    // but wasm bindgen
-    pub fn human_name(&self) -> String {
+    #[wasm_bindgen(getter = HumanReadableName)]
+    pub fn js_human_name(&self) -> String {
         self.human_name.clone()
     }
 
@@ -583,11 +979,19 @@ impl CameraInfo {
     }
 
     /// Get a reference to the device info's misc.
+    #[must_use]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(skip))]
+    pub fn misc(&self) -> &str {
+        &self.misc
+    }
+
+    /// Get the device info's misc.
     /// # JS-WASM
-    /// This is exported as a `get_MiscString`.
+    /// This is exported as a `get_MiscString`. `wasm-bindgen` can not return references, so this clones.
+    #[cfg(feature = "output-wasm")]
     #[must_use]
-    #[cfg_attr(feature = "output-wasm", wasm_bindgen(getter = MiscString))]
-    pub fn misc(&self) -> String {
+    #[wasm_bindgen(getter = MiscString)]
+    pub fn js_misc(&self) -> String {
         self.misc.clone()
     }
 
@@ -616,6 +1020,63 @@ impl CameraInfo {
         self.index = index;
     }
 
+    /// Get the identifier of the physical device this camera belongs to, if known.
+    ///
+    /// Cameras that share a group are different sensors (e.g. RGB, IR, depth) of the same device.
+    /// The identifier itself is backend specific and should only be compared to other identifiers from the same backend.
+    #[must_use]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(skip))]
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    /// Set the identifier of the physical device this camera belongs to.
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(skip))]
+    pub fn set_group(&mut self, group: Option<&str>) {
+        self.group = group.map(ToString::to_string);
+    }
+
+    /// Get how far frames from this camera must be rotated clockwise to appear upright, if known.
+    ///
+    /// This is reported by the platform for sensors that are mounted sideways or upside down, such as the front camera
+    /// of many tablets and convertibles. [`None`] means the backend could not tell, which usually means upright.
+    #[must_use]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(skip))]
+    pub fn rotation(&self) -> Option<Rotation> {
+        self.rotation
+    }
+
+    /// Set how far frames from this camera must be rotated clockwise to appear upright.
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(skip))]
+    pub fn set_rotation(&mut self, rotation: Option<Rotation>) {
+        self.rotation = rotation;
+    }
+
+    /// Get the driver and firmware behind this camera, if known.
+    #[must_use]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(skip))]
+    pub fn driver(&self) -> Option<&DriverInfo> {
+        self.driver.as_ref()
+    }
+
+    /// Set the driver and firmware behind this camera.
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(skip))]
+    pub fn set_driver(&mut self, driver: Option<DriverInfo>) {
+        self.driver = driver;
+    }
+
+    /// Returns true if `other` is a different sensor of the same physical device as this camera.
+    #[must_use]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(skip))]
+    pub fn is_sibling_of(&self, other: &CameraInfo) -> bool {
+        match (&self.group, &other.group) {
+            (Some(this_group), Some(other_group)) => {
+                this_group == other_group && self.index != other.index
+            }
+            _ => false,
+        }
+    }
+
     // /// Gets the device info's index as an `u32`.
     // /// # Errors
     // /// If the index is not parsable as a `u32`, this will error.
@@ -646,59 +1107,183 @@ impl Display for CameraInfo {
     }
 }
 
-/// The list of known camera controls to the library. <br>
-/// These can control the picture brightness, etc. <br>
-/// Note that not all backends/devices support all these. Run [`supported_camera_controls()`](crate::traits::CaptureTrait::camera_controls) to see which ones can be set.
-#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+/// The driver (and firmware) behind a camera, for triaging camera specific bugs. See [`CameraInfo::driver()`].
+/// - `name` is the driver's name, e.g. `uvcvideo`.
+/// - `version` is the driver's version, if reported. On `V4L2` this is the kernel version the driver was built for.
+/// - `module` is the kernel module or system component the driver lives in, if known.
+/// - `firmware` is the device's firmware revision, if known (e.g. the `bcdDevice` of a USB webcam).
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-pub enum KnownCameraControl {
-    Brightness,
-    Contrast,
-    Hue,
-    Saturation,
-    Sharpness,
-    Gamma,
-    WhiteBalance,
-    BacklightComp,
-    Gain,
-    Pan,
-    Tilt,
-    Zoom,
-    Exposure,
-    Iris,
-    Focus,
-    /// Other camera control. Listed is the ID.
-    /// Wasteful, however is needed for a unified API across Windows, Linux, and MacOSX due to Microsoft's usage of GUIDs.
-    ///
-    /// THIS SHOULD ONLY BE USED WHEN YOU KNOW THE PLATFORM THAT YOU ARE RUNNING ON.
-    Other(u128),
-}
-
-/// All camera controls in an array.
-#[must_use]
-pub const fn all_known_camera_controls() -> [KnownCameraControl; 15] {
-    [
-        KnownCameraControl::Brightness,
-        KnownCameraControl::Contrast,
-        KnownCameraControl::Hue,
-        KnownCameraControl::Saturation,
-        KnownCameraControl::Sharpness,
-        KnownCameraControl::Gamma,
-        KnownCameraControl::WhiteBalance,
-        KnownCameraControl::BacklightComp,
-        KnownCameraControl::Gain,
-        KnownCameraControl::Pan,
-        KnownCameraControl::Tilt,
-        KnownCameraControl::Zoom,
-        KnownCameraControl::Exposure,
-        KnownCameraControl::Iris,
-        KnownCameraControl::Focus,
-    ]
+pub struct DriverInfo {
+    name: String,
+    version: Option<String>,
+    module: Option<String>,
+    firmware: Option<String>,
 }
 
-impl Display for KnownCameraControl {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", &self)
+impl DriverInfo {
+    /// Create a new [`DriverInfo`] for the driver called `name`.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        DriverInfo {
+            name: name.to_string(),
+            ..DriverInfo::default()
+        }
+    }
+
+    /// Sets the driver version.
+    #[must_use]
+    pub fn with_version(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Sets the module the driver lives in.
+    #[must_use]
+    pub fn with_module(mut self, module: Option<String>) -> Self {
+        self.module = module;
+        self
+    }
+
+    /// Sets the firmware revision of the device.
+    #[must_use]
+    pub fn with_firmware(mut self, firmware: Option<String>) -> Self {
+        self.firmware = firmware;
+        self
+    }
+
+    /// The driver's name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The driver's version, if reported.
+    #[must_use]
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// The kernel module or system component the driver lives in, if known.
+    #[must_use]
+    pub fn module(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+
+    /// The device's firmware revision, if known.
+    #[must_use]
+    pub fn firmware(&self) -> Option<&str> {
+        self.firmware.as_deref()
+    }
+}
+
+impl Display for DriverInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(version) = &self.version {
+            write!(f, " {version}")?;
+        }
+        if let Some(module) = &self.module {
+            write!(f, " (module {module})")?;
+        }
+        if let Some(firmware) = &self.firmware {
+            write!(f, ", firmware {firmware}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A microphone (or other audio capture device), e.g. the one built into a webcam.
+/// `id` is how the platform's audio APIs name the device (e.g. `hw:CARD=C920` for ALSA), `name` is for showing to the user.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct AudioDeviceInfo {
+    id: String,
+    name: String,
+}
+
+impl AudioDeviceInfo {
+    /// Create a new [`AudioDeviceInfo`].
+    #[must_use]
+    pub fn new(id: &str, name: &str) -> Self {
+        AudioDeviceInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    /// The identifier to open the device with using the platform's audio APIs.
+    #[must_use]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The human readable name of the device.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for AudioDeviceInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.id)
+    }
+}
+
+/// The list of known camera controls to the library. <br>
+/// These can control the picture brightness, etc. <br>
+/// Note that not all backends/devices support all these. Run [`supported_camera_controls()`](crate::traits::CaptureTrait::camera_controls) to see which ones can be set.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum KnownCameraControl {
+    Brightness,
+    Contrast,
+    Hue,
+    Saturation,
+    Sharpness,
+    Gamma,
+    WhiteBalance,
+    BacklightComp,
+    Gain,
+    Pan,
+    Tilt,
+    Zoom,
+    Exposure,
+    Iris,
+    Focus,
+    /// Other camera control. Listed is the ID.
+    /// Wasteful, however is needed for a unified API across Windows, Linux, and MacOSX due to Microsoft's usage of GUIDs.
+    ///
+    /// THIS SHOULD ONLY BE USED WHEN YOU KNOW THE PLATFORM THAT YOU ARE RUNNING ON.
+    Other(u128),
+}
+
+/// All camera controls in an array.
+#[must_use]
+pub const fn all_known_camera_controls() -> [KnownCameraControl; 15] {
+    [
+        KnownCameraControl::Brightness,
+        KnownCameraControl::Contrast,
+        KnownCameraControl::Hue,
+        KnownCameraControl::Saturation,
+        KnownCameraControl::Sharpness,
+        KnownCameraControl::Gamma,
+        KnownCameraControl::WhiteBalance,
+        KnownCameraControl::BacklightComp,
+        KnownCameraControl::Gain,
+        KnownCameraControl::Pan,
+        KnownCameraControl::Tilt,
+        KnownCameraControl::Zoom,
+        KnownCameraControl::Exposure,
+        KnownCameraControl::Iris,
+        KnownCameraControl::Focus,
+    ]
+}
+
+impl Display for KnownCameraControl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", &self)
     }
 }
 
@@ -1372,6 +1957,515 @@ impl Display for ApiBackend {
     }
 }
 
+/// How the rows of a frame are split into the two fields of an interlaced picture. Analog sources (capture cards, DV) capture
+/// the even and odd rows at different times, which looks combed when shown as one frame. See [`Deinterlace`].
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum FieldOrder {
+    /// The frame is not interlaced.
+    Progressive,
+    /// The fields are interleaved, and the top field (the even rows) was captured first.
+    TopFirst,
+    /// The fields are interleaved, and the bottom field (the odd rows) was captured first.
+    BottomFirst,
+    /// The top field is stored in the upper half of the buffer and the bottom field in the lower half, top field first.
+    SequentialTopFirst,
+    /// The bottom field is stored in the upper half of the buffer and the top field in the lower half, bottom field first.
+    SequentialBottomFirst,
+    /// Each buffer holds a single field, at half the vertical resolution. There is nothing to deinterlace.
+    SingleField,
+}
+
+impl FieldOrder {
+    /// Returns true if the frame holds two fields that need deinterlacing.
+    #[must_use]
+    pub fn is_interlaced(self) -> bool {
+        !matches!(self, FieldOrder::Progressive | FieldOrder::SingleField)
+    }
+}
+
+impl Display for FieldOrder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// How to turn an interlaced frame into a progressive one.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Deinterlace {
+    /// Keep the field that was captured first and interpolate the rows of the other one. Sharp motion, half the vertical detail.
+    #[default]
+    Bob,
+    /// Blend every row with its neighbours (a 1-2-1 vertical filter). Keeps more detail on still scenes, but moving edges ghost.
+    LinearBlend,
+}
+
+impl Display for Deinterlace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// The shape of a pixel, as the ratio of its width to its height. Webcams have square (1:1) pixels, but DV and analog standards
+/// sample wider or narrower than that (e.g. 10:11 for 525 line/NTSC at 720x480), which looks squashed unless resampled with
+/// [`square_resolution()`](Self::square_resolution).
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct PixelAspectRatio {
+    numerator: u32,
+    denominator: u32,
+}
+
+impl PixelAspectRatio {
+    /// Square pixels, 1:1.
+    pub const SQUARE: PixelAspectRatio = PixelAspectRatio {
+        numerator: 1,
+        denominator: 1,
+    };
+
+    /// Creates a pixel aspect ratio of `numerator`:`denominator` (width:height), reduced to lowest terms. A zero in either
+    /// term is treated as square.
+    #[must_use]
+    pub const fn new(numerator: u32, denominator: u32) -> Self {
+        if numerator == 0 || denominator == 0 {
+            return PixelAspectRatio::SQUARE;
+        }
+        let divisor = gcd(numerator, denominator);
+        PixelAspectRatio {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    /// The width term of the ratio.
+    #[must_use]
+    pub fn numerator(self) -> u32 {
+        self.numerator
+    }
+
+    /// The height term of the ratio.
+    #[must_use]
+    pub fn denominator(self) -> u32 {
+        self.denominator
+    }
+
+    /// Returns true if pixels are as wide as they are tall.
+    #[must_use]
+    pub fn is_square(self) -> bool {
+        self.numerator == self.denominator
+    }
+
+    /// The ratio as a number, e.g. `0.909` for 10:11.
+    #[must_use]
+    pub fn as_f64(self) -> f64 {
+        f64::from(self.numerator) / f64::from(self.denominator)
+    }
+
+    /// The [`Resolution`] a `resolution` sized frame of these pixels has once resampled to square pixels. Only the width
+    /// changes, rounded to the nearest pixel, so the rows (and any interlaced fields) stay intact.
+    #[must_use]
+    pub fn square_resolution(self, resolution: Resolution) -> Resolution {
+        let width = (u64::from(resolution.width()) * u64::from(self.numerator)
+            + u64::from(self.denominator) / 2)
+            / u64::from(self.denominator);
+        Resolution::new(
+            u32::try_from(width).unwrap_or(u32::MAX).max(1),
+            resolution.height(),
+        )
+    }
+}
+
+impl Default for PixelAspectRatio {
+    fn default() -> Self {
+        PixelAspectRatio::SQUARE
+    }
+}
+
+impl Display for PixelAspectRatio {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.numerator, self.denominator)
+    }
+}
+
+/// A rectangle of a frame, in pixels from its top left corner, e.g. a region of interest to crop to.
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct Region {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Region {
+    /// Creates a `width` by `height` region whose top left corner is at (`x`, `y`).
+    #[must_use]
+    pub const fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Region {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The horizontal position of the left edge.
+    #[must_use]
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    /// The vertical position of the top edge.
+    #[must_use]
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    /// The width.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The height.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The size of the region.
+    #[must_use]
+    pub fn resolution(&self) -> Resolution {
+        Resolution::new(self.width, self.height)
+    }
+
+    /// Shrinks the region to the part that lies within a `resolution` sized frame. Returns `None` if nothing does.
+    #[must_use]
+    pub fn clamp_to(&self, resolution: Resolution) -> Option<Region> {
+        let right = self.x.saturating_add(self.width).min(resolution.width());
+        let bottom = self.y.saturating_add(self.height).min(resolution.height());
+        (right > self.x && bottom > self.y)
+            .then(|| Region::new(self.x, self.y, right - self.x, bottom - self.y))
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}+{}+{}", self.width, self.height, self.x, self.y)
+    }
+}
+
+/// The exposure the camera actually used for a frame, as reported by the device along with it (e.g. through the UVC
+/// metadata node on `V4L2`). Unlike reading the controls, this is exact per frame even while auto exposure is adjusting.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct CaptureParameters {
+    exposure_time: Option<Duration>,
+    iso_speed: Option<u32>,
+}
+
+impl CaptureParameters {
+    /// Creates empty capture parameters.
+    #[must_use]
+    pub fn new() -> Self {
+        CaptureParameters::default()
+    }
+
+    /// Sets the exposure time.
+    #[must_use]
+    pub fn with_exposure_time(mut self, exposure_time: Duration) -> Self {
+        self.exposure_time = Some(exposure_time);
+        self
+    }
+
+    /// Sets the sensor gain, as an ISO speed.
+    #[must_use]
+    pub fn with_iso_speed(mut self, iso_speed: u32) -> Self {
+        self.iso_speed = Some(iso_speed);
+        self
+    }
+
+    /// How long the frame was exposed for, if the device reported it.
+    #[must_use]
+    pub fn exposure_time(&self) -> Option<Duration> {
+        self.exposure_time
+    }
+
+    /// The sensor gain the frame was captured with, as an ISO speed (e.g. 100 for unity gain on most sensors), if the device
+    /// reported it.
+    #[must_use]
+    pub fn iso_speed(&self) -> Option<u32> {
+        self.iso_speed
+    }
+
+    /// Returns true if the device reported nothing.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.exposure_time.is_none() && self.iso_speed.is_none()
+    }
+}
+
+/// One exposure of an exposure bracket. See `Camera::capture_bracketed()`.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum ExposureValue {
+    /// Set the exposure control to exactly this value, in the backend's units (e.g. 100µs steps on `V4L2`).
+    Absolute(i64),
+    /// Expose this many stops brighter (positive) or darker (negative) than the current exposure, e.g. `-2.0` for a quarter
+    /// of the exposure time. This assumes the exposure control is linear in time, which it is on `V4L2` and most UVC cameras.
+    Stops(f32),
+}
+
+impl ExposureValue {
+    /// The exposure control value this resolves to, given the `current` value and the control's range, snapped to `step`.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn resolve(self, current: i64, min: i64, max: i64, step: i64) -> i64 {
+        let value = match self {
+            ExposureValue::Absolute(value) => value,
+            ExposureValue::Stops(stops) => {
+                (current as f64 * 2_f64.powf(f64::from(stops))).round() as i64
+            }
+        };
+        let step = step.max(1);
+        let snapped = min + (value.saturating_sub(min) + step / 2) / step * step;
+        snapped.clamp(min, max.max(min))
+    }
+}
+
+impl Display for ExposureValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExposureValue::Absolute(value) => write!(f, "{value}"),
+            ExposureValue::Stops(stops) => write!(f, "{stops:+} EV"),
+        }
+    }
+}
+
+/// Brightness statistics of a frame: a luma histogram, the mean brightness, and how much of the frame is clipped.
+/// For auto-exposure and quality checks. See `Buffer::stats()`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct FrameStats {
+    #[cfg_attr(feature = "serialize", serde(with = "serde_histogram"))]
+    histogram: [u64; 256],
+    pixels: u64,
+    sum: u64,
+}
+
+impl FrameStats {
+    /// Computes the statistics of luma (brightness) values.
+    #[must_use]
+    pub fn from_luma(luma: impl IntoIterator<Item = u8>) -> Self {
+        let mut stats = FrameStats {
+            histogram: [0; 256],
+            pixels: 0,
+            sum: 0,
+        };
+        for value in luma {
+            stats.histogram[usize::from(value)] += 1;
+        }
+        stats.pixels = stats.histogram.iter().sum();
+        stats.sum = stats
+            .histogram
+            .iter()
+            .enumerate()
+            .map(|(value, count)| value as u64 * count)
+            .sum();
+        stats
+    }
+
+    /// Computes the statistics of packed RGB data with `pixel_size` bytes per pixel (3 for RGB888, 4 for RGBA8888).
+    /// Luma is approximated as `(R + 2G + B) / 4`.
+    #[must_use]
+    pub fn from_rgb(data: &[u8], pixel_size: usize) -> Self {
+        Self::from_luma(Self::rgb_luma_iter(data, pixel_size))
+    }
+
+    /// The approximate luma of each pixel of packed RGB data.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn rgb_luma_iter(data: &[u8], pixel_size: usize) -> impl Iterator<Item = u8> + '_ {
+        data.chunks_exact(pixel_size.max(3)).map(|pixel| {
+            ((u16::from(pixel[0]) + 2 * u16::from(pixel[1]) + u16::from(pixel[2])) / 4) as u8
+        })
+    }
+
+    /// How many pixels have each luma value, from 0 (black) to 255 (white).
+    #[must_use]
+    pub fn histogram(&self) -> &[u64; 256] {
+        &self.histogram
+    }
+
+    /// How many pixels were counted.
+    #[must_use]
+    pub fn pixels(&self) -> u64 {
+        self.pixels
+    }
+
+    /// The average luma, from 0 to 255.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn mean_brightness(&self) -> f32 {
+        if self.pixels == 0 {
+            return 0.0;
+        }
+        (self.sum as f64 / self.pixels as f64) as f32
+    }
+
+    /// The luma value that `percent` percent of the pixels are at or below, e.g. `50.0` for the median.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn percentile(&self, percent: f32) -> u8 {
+        let target =
+            (f64::from(percent.clamp(0.0, 100.0)) / 100.0 * self.pixels as f64).ceil() as u64;
+        let mut seen = 0;
+        for (value, count) in self.histogram.iter().enumerate() {
+            seen += count;
+            if seen >= target.max(1) {
+                return value as u8;
+            }
+        }
+        u8::MAX
+    }
+
+    /// The percentage (0 to 100) of pixels with a luma at or below `level`.
+    #[must_use]
+    pub fn percent_at_or_below(&self, level: u8) -> f32 {
+        self.percent_of(self.histogram[..=usize::from(level)].iter().sum())
+    }
+
+    /// The percentage (0 to 100) of pixels with a luma at or above `level`.
+    #[must_use]
+    pub fn percent_at_or_above(&self, level: u8) -> f32 {
+        self.percent_of(self.histogram[usize::from(level)..].iter().sum())
+    }
+
+    /// The percentage (0 to 100) of pixels that are fully black, i.e. underexposed beyond recovery.
+    #[must_use]
+    pub fn shadows_clipped(&self) -> f32 {
+        self.percent_at_or_below(0)
+    }
+
+    /// The percentage (0 to 100) of pixels that are fully white, i.e. overexposed beyond recovery.
+    #[must_use]
+    pub fn highlights_clipped(&self) -> f32 {
+        self.percent_at_or_above(u8::MAX)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn percent_of(&self, count: u64) -> f32 {
+        if self.pixels == 0 {
+            return 0.0;
+        }
+        (count as f64 * 100.0 / self.pixels as f64) as f32
+    }
+}
+
+/// `serde` only implements arrays of up to 32 elements, so the histogram is (de)serialized as a sequence.
+#[cfg(feature = "serialize")]
+mod serde_histogram {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        histogram: &[u64; 256],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        histogram.as_slice().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u64; 256], D::Error> {
+        let histogram = Vec::<u64>::deserialize(deserializer)?;
+        histogram
+            .try_into()
+            .map_err(|histogram: Vec<u64>| D::Error::invalid_length(histogram.len(), &"256 bins"))
+    }
+}
+
+/// When a frame was captured.
+/// - `capture` is the timestamp given by the driver, if the backend provides one. Its epoch is backend specific (e.g. `CLOCK_MONOTONIC` on `V4L2`),
+/// so it should only be compared against other frames from the same camera. This is what you want for A/V sync and frame pacing.
+/// - `system` is the wall-clock time at which nokhwa received the frame from the backend.
+/// - `driver_latency` is how old the frame already was when nokhwa received it, if the backend can tell (`V4L2` can).
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub struct FrameTimestamp {
+    capture: Option<Duration>,
+    system: SystemTime,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    driver_latency: Option<Duration>,
+}
+
+impl FrameTimestamp {
+    /// Creates a new [`FrameTimestamp`].
+    #[must_use]
+    pub fn new(capture: Option<Duration>, system: SystemTime) -> Self {
+        FrameTimestamp {
+            capture,
+            system,
+            driver_latency: None,
+        }
+    }
+
+    /// Creates a [`FrameTimestamp`] with the driver's capture time, received now.
+    #[must_use]
+    pub fn now(capture: Option<Duration>) -> Self {
+        FrameTimestamp::new(capture, SystemTime::now())
+    }
+
+    /// Sets how long the frame sat in the driver between being captured and being received.
+    #[must_use]
+    pub fn with_driver_latency(mut self, latency: Duration) -> Self {
+        self.driver_latency = Some(latency);
+        self
+    }
+
+    /// How long the frame sat in the driver between being captured and being received, if the backend knows.
+    #[must_use]
+    pub fn driver_latency(&self) -> Option<Duration> {
+        self.driver_latency
+    }
+
+    /// How old the frame is at `now`: the [`driver_latency()`](Self::driver_latency) (if known) plus the time since it was received.
+    #[must_use]
+    pub fn age_at(&self, now: SystemTime) -> Duration {
+        self.driver_latency.unwrap_or_default()
+            + now.duration_since(self.system).unwrap_or_default()
+    }
+
+    /// The driver provided capture timestamp.
+    #[must_use]
+    pub fn capture(&self) -> Option<Duration> {
+        self.capture
+    }
+
+    /// The wall-clock time this frame was received.
+    #[must_use]
+    pub fn system(&self) -> SystemTime {
+        self.system
+    }
+
+    /// The time between `earlier` and this frame, using the driver timestamps if both frames have one, falling back to wall-clock time.
+    /// Returns `None` if `earlier` is not actually earlier.
+    #[must_use]
+    pub fn since(&self, earlier: &FrameTimestamp) -> Option<Duration> {
+        match (self.capture, earlier.capture) {
+            (Some(now), Some(then)) => now.checked_sub(then),
+            _ => self.system.duration_since(earlier.system).ok(),
+        }
+    }
+}
+
+impl Display for FrameTimestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
 // /// A webcam index that supports both strings and integers. Most backends take an int, but `IPCamera`s take a URL (string).
 // #[derive(Clone, Debug, Hash, PartialEq, PartialOrd)]
 // pub enum CameraIndex {
@@ -1379,63 +2473,188 @@ impl Display for ApiBackend {
 //     String(String),
 // }
 
-// impl CameraIndex {
-//     /// Gets the device info's index as an `u32`.
-//     /// # Errors
-//     /// If the index is not parsable as a `u32`, this will error.
-//     pub fn as_index(&self) -> Result<u32, NokhwaError> {
-//         match self {
-//             CameraIndex::Index(i) => Ok(*i),
-//             CameraIndex::String(s) => match s.parse::<u32>() {
-//                 Ok(p) => Ok(p),
-//                 Err(why) => Err(NokhwaError::GetPropertyError {
-//                     property: "index-int".to_string(),
-//                     error: why.to_string(),
-//                 }),
-//             },
-//         }
-//     }
-// }
+/// How much to shrink a MJPEG frame while decoding it.
+///
+/// JPEG decoders can skip most of the IDCT work when scaling down by a power of two, so decoding a 3840x2160 frame at
+/// [`DecodeScale::Quarter`] for a 960x540 preview is far cheaper than decoding it in full and scaling it down afterwards.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum DecodeScale {
+    /// Decode at full size.
+    #[default]
+    Full,
+    /// Decode at 1/2 the width and height.
+    Half,
+    /// Decode at 1/4 the width and height.
+    Quarter,
+    /// Decode at 1/8 the width and height.
+    Eighth,
+}
 
-// impl Display for CameraIndex {
-//     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-//         match self {
-//             CameraIndex::Index(idx) => {
-//                 write!(f, "{}", idx)
-//             }
-//             CameraIndex::String(ip) => {
-//                 write!(f, "{}", ip)
-//             }
-//         }
-//     }
-// }
+impl DecodeScale {
+    /// The scale as a numerator over 8, the way `libjpeg` takes it.
+    #[must_use]
+    pub fn numerator(self) -> u8 {
+        match self {
+            DecodeScale::Full => 8,
+            DecodeScale::Half => 4,
+            DecodeScale::Quarter => 2,
+            DecodeScale::Eighth => 1,
+        }
+    }
 
-// impl From<u32> for CameraIndex {
-//     fn from(v: u32) -> Self {
-//         CameraIndex::Index(v)
-//     }
-// }
+    /// The scale that shrinks a `source` sized frame the most while keeping it at least as large as `target`, e.g.
+    /// [`DecodeScale::Quarter`] for a 3840x2160 frame shown in a 960x540 preview.
+    #[must_use]
+    pub fn fitting(source: Resolution, target: Resolution) -> Self {
+        [DecodeScale::Eighth, DecodeScale::Quarter, DecodeScale::Half]
+            .into_iter()
+            .find(|scale| {
+                let scaled = scale.apply(source);
+                scaled.width() >= target.width() && scaled.height() >= target.height()
+            })
+            .unwrap_or(DecodeScale::Full)
+    }
 
-// /// Trait for strings that can be converted to [`CameraIndex`]es.
-// pub trait ValidString: AsRef<str> {}
-//
-// impl ValidString for String {}
-// impl<'a> ValidString for &'a String {}
-// impl<'a> ValidString for &'a mut String {}
-// impl<'a> ValidString for Cow<'a, str> {}
-// impl<'a> ValidString for &'a Cow<'a, str> {}
-// impl<'a> ValidString for &'a mut Cow<'a, str> {}
-// impl<'a> ValidString for &'a str {}
-// impl<'a> ValidString for &'a mut str {}
-
-// impl<T> From<T> for CameraIndex
-// where
-//     T: ValidString,
-// {
-//     fn from(v: T) -> Self {
-//         CameraIndex::String(v.as_ref().to_string())
-//     }
-// }
+    /// The [`Resolution`] a `resolution` sized frame decodes to. Like `libjpeg`, odd sizes are rounded up.
+    #[must_use]
+    pub fn apply(self, resolution: Resolution) -> Resolution {
+        let numerator = u32::from(self.numerator());
+        Resolution::new(
+            (resolution.width() * numerator + 7) / 8,
+            (resolution.height() * numerator + 7) / 8,
+        )
+    }
+}
+
+impl Display for DecodeScale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/8", self.numerator())
+    }
+}
+
+/// A clockwise rotation by a multiple of 90 degrees.
+///
+/// On [`CameraInfo`] this is how far frames must be rotated to appear upright, e.g. [`Rotation::Clockwise90`] for a
+/// portrait-mounted laptop or tablet sensor that delivers frames lying on their side.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum Rotation {
+    /// No rotation.
+    #[default]
+    None,
+    /// 90 degrees clockwise.
+    Clockwise90,
+    /// 180 degrees.
+    Rotate180,
+    /// 270 degrees clockwise, or 90 degrees counter-clockwise.
+    Clockwise270,
+}
+
+impl Rotation {
+    /// The rotation for `degrees` clockwise. Returns [`None`] if `degrees` is not a multiple of 90.
+    #[must_use]
+    pub fn from_degrees(degrees: i32) -> Option<Self> {
+        match degrees.rem_euclid(360) {
+            0 => Some(Rotation::None),
+            90 => Some(Rotation::Clockwise90),
+            180 => Some(Rotation::Rotate180),
+            270 => Some(Rotation::Clockwise270),
+            _ => None,
+        }
+    }
+
+    /// The rotation in degrees clockwise.
+    #[must_use]
+    pub fn degrees(self) -> u32 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 90,
+            Rotation::Rotate180 => 180,
+            Rotation::Clockwise270 => 270,
+        }
+    }
+
+    /// The rotation that undoes this one.
+    #[must_use]
+    pub fn inverse(self) -> Self {
+        match self {
+            Rotation::Clockwise90 => Rotation::Clockwise270,
+            Rotation::Clockwise270 => Rotation::Clockwise90,
+            other => other,
+        }
+    }
+
+    /// Returns true if this swaps the width and height of a frame.
+    #[must_use]
+    pub fn is_transposing(self) -> bool {
+        matches!(self, Rotation::Clockwise90 | Rotation::Clockwise270)
+    }
+
+    /// The [`Resolution`] a `resolution` sized frame has after being rotated.
+    #[must_use]
+    pub fn apply(self, resolution: Resolution) -> Resolution {
+        if self.is_transposing() {
+            Resolution::new(resolution.height(), resolution.width())
+        } else {
+            resolution
+        }
+    }
+}
+
+impl Display for Rotation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} degrees", self.degrees())
+    }
+}
+
+/// What a stream of a multi-sensor device (e.g. a Windows Hello or depth camera) captures.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum StreamKind {
+    /// A regular color image.
+    Color,
+    /// An infrared image, usually grayscale.
+    Infrared,
+    /// A depth map.
+    Depth,
+    /// Something else, e.g. a metadata stream.
+    Other,
+}
+
+impl StreamKind {
+    /// Guesses the kind of a stream from the name of its camera and the format it delivers.
+    ///
+    /// No backend reports this directly, so this looks for "depth", "infrared", or "IR" in the name, and treats streams that
+    /// only deliver grayscale as infrared. Everything else is assumed to be color.
+    #[must_use]
+    pub fn guess(info: &CameraInfo, format: FrameFormat) -> Self {
+        let name = info.human_name().to_lowercase();
+        let has_word = |word: &str| {
+            name.split(|c: char| !c.is_alphanumeric())
+                .any(|part| part == word)
+        };
+        if has_word("depth") {
+            StreamKind::Depth
+        } else if has_word("infrared") || has_word("ir") || format == FrameFormat::Luma8 {
+            StreamKind::Infrared
+        } else {
+            StreamKind::Color
+        }
+    }
+}
+
+impl Display for StreamKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            StreamKind::Color => "Color",
+            StreamKind::Infrared => "Infrared",
+            StreamKind::Depth => "Depth",
+            StreamKind::Other => "Other",
+        };
+        write!(f, "{kind}")
+    }
+}
 
 #[cfg(all(feature = "mjpeg", not(target_arch = "wasm")))]
 #[cfg_attr(feature = "docs-features", doc(cfg(feature = "mjpeg")))]
@@ -1443,11 +2662,13 @@ impl Display for ApiBackend {
 fn decompress<'a>(
     data: &'a [u8],
     rgba: bool,
+    scale: DecodeScale,
 ) -> Result<mozjpeg::decompress::DecompressStarted<'a>, NokhwaError> {
     use mozjpeg::Decompress;
 
     match Decompress::new_mem(data) {
-        Ok(decompress) => {
+        Ok(mut decompress) => {
+            decompress.scale(scale.numerator());
             let decompressor_res = if rgba {
                 decompress.rgba()
             } else {
@@ -1484,7 +2705,7 @@ fn decompress<'a>(
 #[cfg_attr(feature = "docs-features", doc(cfg(feature = "mjpeg")))]
 #[inline]
 pub fn mjpeg_to_rgb(data: &[u8], rgba: bool) -> Result<Vec<u8>, NokhwaError> {
-    let mut jpeg_decompress = decompress(data, rgba)?;
+    let mut jpeg_decompress = decompress(data, rgba, DecodeScale::Full)?;
 
     let scanlines_res: Option<Vec<u8>> = jpeg_decompress.read_scanlines_flat();
     // assert!(jpeg_decompress.finish_decompress());
@@ -1520,7 +2741,30 @@ pub fn mjpeg_to_rgb(_data: &[u8], _rgba: bool) -> Result<Vec<u8>, NokhwaError> {
 #[cfg_attr(feature = "docs-features", doc(cfg(feature = "mjpeg")))]
 #[inline]
 pub fn buf_mjpeg_to_rgb(data: &[u8], dest: &mut [u8], rgba: bool) -> Result<(), NokhwaError> {
-    let mut jpeg_decompress = decompress(data, rgba)?;
+    buf_mjpeg_to_rgb_scaled(data, dest, rgba, DecodeScale::Full)
+}
+
+#[cfg(not(all(feature = "mjpeg", not(target_arch = "wasm"))))]
+pub fn buf_mjpeg_to_rgb(_data: &[u8], _dest: &mut [u8], _rgba: bool) -> Result<(), NokhwaError> {
+    Err(NokhwaError::NotImplementedError(
+        "Not available on WASM".to_string(),
+    ))
+}
+
+/// Equivalent to [`buf_mjpeg_to_rgb`] except the frame is shrunk by `scale` while decoding. `dest` must fit the
+/// [`DecodeScale::apply()`]-ed resolution of the frame.
+/// # Errors
+/// If the decoding fails (e.g. invalid MJpeg stream), the buffer is not the right size, or you are doing this on `WebAssembly`, this will error.
+#[cfg(all(feature = "mjpeg", not(target_arch = "wasm")))]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "mjpeg")))]
+#[inline]
+pub fn buf_mjpeg_to_rgb_scaled(
+    data: &[u8],
+    dest: &mut [u8],
+    rgba: bool,
+    scale: DecodeScale,
+) -> Result<(), NokhwaError> {
+    let mut jpeg_decompress = decompress(data, rgba, scale)?;
 
     // assert_eq!(dest.len(), jpeg_decompress.min_flat_buffer_size());
     if dest.len() != jpeg_decompress.min_flat_buffer_size() {
@@ -1544,12 +2788,108 @@ pub fn buf_mjpeg_to_rgb(data: &[u8], dest: &mut [u8], rgba: bool) -> Result<(),
 }
 
 #[cfg(not(all(feature = "mjpeg", not(target_arch = "wasm"))))]
-pub fn buf_mjpeg_to_rgb(_data: &[u8], _dest: &mut [u8], _rgba: bool) -> Result<(), NokhwaError> {
+pub fn buf_mjpeg_to_rgb_scaled(
+    _data: &[u8],
+    _dest: &mut [u8],
+    _rgba: bool,
+    _scale: DecodeScale,
+) -> Result<(), NokhwaError> {
     Err(NokhwaError::NotImplementedError(
         "Not available on WASM".to_string(),
     ))
 }
 
+/// Which implementation converts YUV frames to RGB. See [`set_color_conversion_engine()`].
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+pub enum ColorConversionEngine {
+    /// nokhwa's own portable converters. This is the default without the `color-dcv` feature.
+    BuiltIn,
+    /// [`dcv-color-primitives`](https://github.com/aws/dcv-color-primitives), which has SSE, AVX2, and NEON paths and is much
+    /// faster on ARM. Needs the `color-dcv` feature, and is the default with it. Conversions it does not support (e.g. YUYV)
+    /// and any conversion it fails at fall back to [`BuiltIn`](Self::BuiltIn).
+    DcvColorPrimitives,
+}
+
+impl Display for ColorConversionEngine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+static COLOR_CONVERSION_ENGINE: AtomicU8 =
+    AtomicU8::new(if cfg!(feature = "color-dcv") { 1 } else { 0 });
+
+/// Selects the [`ColorConversionEngine`] used by the YUV to RGB conversions (e.g. [`buf_nv12_to_rgb()`]) for the whole process.
+/// # Errors
+/// If the engine was not compiled in (e.g. [`DcvColorPrimitives`](ColorConversionEngine::DcvColorPrimitives) without the
+/// `color-dcv` feature), this will error and leave the engine as it was.
+pub fn set_color_conversion_engine(engine: ColorConversionEngine) -> Result<(), NokhwaError> {
+    let value = match engine {
+        ColorConversionEngine::BuiltIn => 0,
+        ColorConversionEngine::DcvColorPrimitives if cfg!(feature = "color-dcv") => 1,
+        ColorConversionEngine::DcvColorPrimitives => {
+            return Err(NokhwaError::GeneralError(
+                "The dcv-color-primitives engine needs the `color-dcv` feature".to_string(),
+            ))
+        }
+    };
+    COLOR_CONVERSION_ENGINE.store(value, atomic::Ordering::Relaxed);
+    Ok(())
+}
+
+/// The [`ColorConversionEngine`] in use. See [`set_color_conversion_engine()`].
+#[must_use]
+pub fn color_conversion_engine() -> ColorConversionEngine {
+    match COLOR_CONVERSION_ENGINE.load(atomic::Ordering::Relaxed) {
+        1 => ColorConversionEngine::DcvColorPrimitives,
+        _ => ColorConversionEngine::BuiltIn,
+    }
+}
+
+/// Converts NV12 (BT.601, limited range, like the built in converter) with `dcv-color-primitives`. It has no NV12 to RGBA
+/// conversion, so RGBA goes through BGRA.
+#[cfg(feature = "color-dcv")]
+fn dcv_nv12_to_rgb(
+    resolution: Resolution,
+    data: &[u8],
+    out: &mut [u8],
+    rgba: bool,
+) -> Result<(), dcv_color_primitives::ErrorKind> {
+    use dcv_color_primitives::{convert_image, ColorSpace, ImageFormat, PixelFormat};
+
+    let src_format = ImageFormat {
+        pixel_format: PixelFormat::Nv12,
+        color_space: ColorSpace::Bt601,
+        num_planes: 1,
+    };
+    let dst_format = ImageFormat {
+        pixel_format: if rgba {
+            PixelFormat::Bgra
+        } else {
+            PixelFormat::Rgb
+        },
+        color_space: ColorSpace::Rgb,
+        num_planes: 1,
+    };
+    convert_image(
+        resolution.width(),
+        resolution.height(),
+        &src_format,
+        None,
+        &[data],
+        &dst_format,
+        None,
+        &mut [&mut *out],
+    )?;
+    if rgba {
+        for pixel in out.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    Ok(())
+}
+
 /// Returns the predicted size of the destination Yuv422422 buffer.
 #[inline]
 pub fn yuyv422_predicted_size(size: usize, rgba: bool) -> usize {
@@ -1571,7 +2911,6 @@ pub fn yuyv422_to_rgb(data: &[u8], rgba: bool) -> Result<Vec<u8>, NokhwaError> {
 /// If the stream is invalid Yuv422, or the destination buffer is not large enough, this will error.
 #[inline]
 pub fn buf_yuyv422_to_rgb(data: &[u8], dest: &mut [u8], rgba: bool) -> Result<(), NokhwaError> {
-    let mut buf: Vec<u8> = Vec::new();
     if data.len() % 4 != 0 {
         return Err(NokhwaError::ProcessFrameError {
             src: FrameFormat::Yuv422.into(),
@@ -1580,7 +2919,20 @@ pub fn buf_yuyv422_to_rgb(data: &[u8], dest: &mut [u8], rgba: bool) -> Result<()
                 .to_string(),
         });
     }
-    for chunk in data.chunks_exact(4) {
+    if dest.len() != yuyv422_predicted_size(data.len(), rgba) {
+        return Err(NokhwaError::ProcessFrameError {
+            src: FrameFormat::Yuv422.into(),
+            destination: "RGB888".to_string(),
+            error: "Bad decoded buffer size".to_string(),
+        });
+    }
+
+    // Write straight into `dest`, so decoding into a reused buffer does not allocate.
+    let pixel_size = if rgba { 4 } else { 3 };
+    for (chunk, out) in data
+        .chunks_exact(4)
+        .zip(dest.chunks_exact_mut(2 * pixel_size))
+    {
         let y0 = chunk[0] as f32;
         let u = chunk[1] as f32;
         let y1 = chunk[2] as f32;
@@ -1595,14 +2947,13 @@ pub fn buf_yuyv422_to_rgb(data: &[u8], dest: &mut [u8], rgba: bool) -> Result<()
         let b1 = y1 + 1.732446 * (u - 128.);
 
         if rgba {
-            buf.extend_from_slice(&[
+            out.copy_from_slice(&[
                 r0 as u8, g0 as u8, b0 as u8, 255, r1 as u8, g1 as u8, b1 as u8, 255,
             ]);
         } else {
-            buf.extend_from_slice(&[r0 as u8, g0 as u8, b0 as u8, r1 as u8, g1 as u8, b1 as u8]);
+            out.copy_from_slice(&[r0 as u8, g0 as u8, b0 as u8, r1 as u8, g1 as u8, b1 as u8]);
         }
     }
-    dest.copy_from_slice(&buf);
     Ok(())
 }
 
@@ -1690,6 +3041,13 @@ pub fn buf_nv12_to_rgb(
         });
     }
 
+    #[cfg(feature = "color-dcv")]
+    if color_conversion_engine() == ColorConversionEngine::DcvColorPrimitives
+        && dcv_nv12_to_rgb(resolution, data, out, rgba).is_ok()
+    {
+        return Ok(());
+    }
+
     let rgba_size = if rgba { 4 } else { 3 };
 
     let y_section = (resolution.width() * resolution.height()) as usize;
@@ -1734,3 +3092,133 @@ pub fn buf_nv12_to_rgb(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CameraFormat, FrameRate, Resolution};
+    use crate::frame_format::FrameFormat;
+
+    #[test]
+    fn camera_format_display_parses_back() {
+        let formats = [
+            CameraFormat::new(
+                Resolution::new(1920, 1080),
+                FrameFormat::MJpeg,
+                FrameRate::Integer(30),
+            ),
+            CameraFormat::new(
+                Resolution::new(1280, 720),
+                FrameFormat::Yuv422,
+                FrameRate::new_fraction(30000, 1001),
+            ),
+            CameraFormat::new(
+                Resolution::new(640, 480),
+                FrameFormat::Nv12,
+                FrameRate::Float(7.5),
+            ),
+            CameraFormat::new(
+                Resolution::new(320, 240),
+                FrameFormat::Custom(0x3231_564E),
+                FrameRate::Integer(15),
+            ),
+        ];
+        for format in formats {
+            let written = format.to_string();
+            let parsed = written.parse::<CameraFormat>().unwrap();
+            assert_eq!(parsed, format, "{written}");
+            assert_eq!(parsed.format(), format.format(), "{written}");
+        }
+        assert_eq!(formats[0].to_string(), "1920x1080@30 MJPG");
+        assert_eq!(formats[3].to_string(), "320x240@15 Custom(842094158)");
+    }
+
+    #[test]
+    fn frame_rate_fraction_is_in_lowest_terms() {
+        assert_eq!(FrameRate::Integer(30).as_fraction(), (30, 1));
+        assert_eq!(FrameRate::new_fraction(60, 2).as_fraction(), (30, 1));
+        assert_eq!(
+            FrameRate::new_fraction(60000, 2002).as_fraction(),
+            (30000, 1001)
+        );
+        assert_eq!(FrameRate::Float(29.97).as_fraction(), (2997, 100));
+        assert_eq!(FrameRate::new_fraction(0, 5).as_fraction(), (0, 1));
+    }
+
+    #[test]
+    fn frame_rate_zero_denominator_is_zero_fps() {
+        assert_eq!(FrameRate::new_fraction(0, 0).as_fraction(), (0, 1));
+        assert_eq!(FrameRate::new_fraction(30, 0).as_fraction(), (0, 1));
+        assert_eq!(FrameRate::from_interval(0, 30).as_fraction(), (0, 1));
+        assert_eq!(FrameRate::new_fraction(30, 0).as_u32(), 0);
+        assert!(FrameRate::new_fraction(30, 0).as_float().abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn frame_rate_reduced() {
+        assert!(matches!(
+            FrameRate::new_fraction(60, 2).reduced(),
+            FrameRate::Integer(30)
+        ));
+        assert!(matches!(
+            FrameRate::new_fraction(60000, 2002).reduced(),
+            FrameRate::Fraction {
+                numerator: 30000,
+                denominator: 1001
+            }
+        ));
+        assert!(matches!(
+            FrameRate::from_interval(1001, 30000),
+            FrameRate::Fraction {
+                numerator: 30000,
+                denominator: 1001
+            }
+        ));
+        assert_eq!(FrameRate::from_interval(1, 25).as_interval(), (1, 25));
+    }
+
+    #[test]
+    fn frame_rate_as_u32_rounds() {
+        assert_eq!(FrameRate::Integer(30).as_u32(), 30);
+        assert_eq!(FrameRate::new_fraction(30000, 1001).as_u32(), 30);
+        assert_eq!(FrameRate::new_fraction(15, 2).as_u32(), 8);
+        assert_eq!(FrameRate::new_fraction(29, 2).as_u32(), 15);
+        assert_eq!(FrameRate::Float(59.94).as_u32(), 60);
+        assert_eq!(FrameRate::new_fraction(u32::MAX, 1).as_u32(), u32::MAX);
+    }
+
+    #[test]
+    fn frame_rate_ordering_agrees_with_equality() {
+        let ntsc = FrameRate::new_fraction(30000, 1001);
+        assert!(ntsc < FrameRate::Integer(30));
+        assert!(ntsc > FrameRate::Integer(29));
+        assert_eq!(FrameRate::Integer(30), FrameRate::new_fraction(30, 1));
+        assert_eq!(
+            FrameRate::Integer(30).cmp(&FrameRate::new_fraction(60, 2)),
+            std::cmp::Ordering::Equal
+        );
+
+        let zero = FrameRate::new_fraction(0, 0);
+        assert_eq!(zero, FrameRate::Integer(0));
+        assert!(zero < FrameRate::Integer(1));
+        assert_ne!(zero, FrameRate::Integer(30));
+
+        let mut rates = vec![
+            FrameRate::Integer(60),
+            zero,
+            ntsc,
+            FrameRate::Float(15.0),
+            FrameRate::Integer(30),
+        ];
+        rates.sort();
+        assert_eq!(
+            rates,
+            [
+                zero,
+                FrameRate::Integer(15),
+                ntsc,
+                FrameRate::Integer(30),
+                FrameRate::Integer(60)
+            ]
+        );
+    }
+}
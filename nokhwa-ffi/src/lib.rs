@@ -0,0 +1,431 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A stable C ABI for `nokhwa`, so C, C++, C# (and anything else that can call into a C library) can use it as a capture layer.
+//!
+//! The header is generated by `cbindgen` into `include/nokhwa.h` at build time.
+//!
+//! Every function returns a [`NokhwaStatus`]. If it is not [`NokhwaStatus::Ok`], [`nokhwa_last_error()`] describes what went wrong.
+//! Panics never unwind into the caller: they are caught and reported as [`NokhwaStatus::Panic`].
+//! Handles ([`NokhwaCameraList`], [`NokhwaCamera`]) are owned by the caller and must be freed with their `_free`/`_close` function.
+
+use nokhwa::prelude::{
+    ApiBackend, Buffer, Camera, CameraFormat, CameraIndex, CameraInfo, CaptureTrait, FrameFormat,
+    FrameRate, NokhwaError, Resolution,
+};
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{c_char, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    str::FromStr,
+};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+/// The result of a `nokhwa_*` call.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum NokhwaStatus {
+    Ok = 0,
+    /// A pointer was null, or an index was out of range.
+    InvalidArgument = 1,
+    /// The camera was unplugged.
+    Disconnected = 2,
+    /// The camera is in use by another process.
+    Busy = 3,
+    /// The camera or backend does not support the operation or format.
+    Unsupported = 4,
+    Timeout = 5,
+    /// Any other error.
+    Error = 6,
+    /// `nokhwa` panicked. The camera should be closed, as it may be in an inconsistent state.
+    Panic = 7,
+}
+
+impl From<&NokhwaError> for NokhwaStatus {
+    fn from(error: &NokhwaError) -> Self {
+        if error.is_disconnected() {
+            NokhwaStatus::Disconnected
+        } else if error.is_busy() {
+            NokhwaStatus::Busy
+        } else if error.is_unsupported() {
+            NokhwaStatus::Unsupported
+        } else if error.is_timeout() {
+            NokhwaStatus::Timeout
+        } else {
+            NokhwaStatus::Error
+        }
+    }
+}
+
+fn set_last_error(message: impl Into<Vec<u8>>) {
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn status<T>(result: Result<T, NokhwaError>, on_ok: impl FnOnce(T)) -> NokhwaStatus {
+    match result {
+        Ok(value) => {
+            on_ok(value);
+            NokhwaStatus::Ok
+        }
+        Err(why) => {
+            set_last_error(why.to_string());
+            NokhwaStatus::from(&why)
+        }
+    }
+}
+
+/// Runs the body of an `extern "C"` function, returning `fallback` if it panics. Unwinding into C is undefined behavior.
+fn guard<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|payload| {
+        set_last_error(format!("Panicked: {}", panic_message(&*payload)));
+        fallback
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+fn invalid_argument(what: &str) -> NokhwaStatus {
+    set_last_error(format!("Invalid argument: {what}"));
+    NokhwaStatus::InvalidArgument
+}
+
+/// Packs a `FourCC` string (e.g. `MJPG`) into a little endian `u32`, the same way `V4L2` and `Media Foundation` do.
+fn pack_fourcc(format: FrameFormat) -> u32 {
+    format.fourcc().map_or(0, |fourcc| {
+        let mut bytes = [0_u8; 4];
+        for (byte, char) in bytes.iter_mut().zip(fourcc.bytes()) {
+            *byte = char;
+        }
+        u32::from_le_bytes(bytes)
+    })
+}
+
+fn unpack_fourcc(fourcc: u32) -> Result<FrameFormat, NokhwaError> {
+    let bytes = fourcc.to_le_bytes();
+    let fourcc = String::from_utf8_lossy(&bytes);
+    FrameFormat::from_str(&fourcc)
+}
+
+/// Gets a description of the last error that happened on this thread, or null if there was none.
+///
+/// The string is owned by `nokhwa` and is valid until the next failing `nokhwa_*` call on this thread.
+#[no_mangle]
+pub extern "C" fn nokhwa_last_error() -> *const c_char {
+    guard(ptr::null(), || {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(ptr::null(), |message| message.as_ptr())
+        })
+    })
+}
+
+/// A list of cameras, returned by [`nokhwa_query()`]. Free it with [`nokhwa_camera_list_free()`].
+pub struct NokhwaCameraList {
+    cameras: Vec<(CameraInfo, CString)>,
+}
+
+/// Queries the cameras available with the native backend of the platform.
+/// # Safety
+/// `out_list` must be a valid pointer to write a [`NokhwaCameraList`] pointer to.
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_query(out_list: *mut *mut NokhwaCameraList) -> NokhwaStatus {
+    guard(NokhwaStatus::Panic, || {
+        if out_list.is_null() {
+            return invalid_argument("out_list is null");
+        }
+        status(nokhwa::query(ApiBackend::Auto), |cameras| {
+            let cameras = cameras
+                .into_iter()
+                .map(|info| {
+                    let name = CString::new(info.human_name()).unwrap_or_default();
+                    (info, name)
+                })
+                .collect();
+            *out_list = Box::into_raw(Box::new(NokhwaCameraList { cameras }));
+        })
+    })
+}
+
+/// Gets the number of cameras in the list.
+/// # Safety
+/// `list` must be null or a list returned by [`nokhwa_query()`].
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_camera_list_len(list: *const NokhwaCameraList) -> usize {
+    guard(0, || list.as_ref().map_or(0, |list| list.cameras.len()))
+}
+
+/// Gets the human-readable name of the camera at `position`, or null if `position` is out of range.
+///
+/// The string is owned by the list and is valid until the list is freed.
+/// # Safety
+/// `list` must be null or a list returned by [`nokhwa_query()`].
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_camera_list_name(
+    list: *const NokhwaCameraList,
+    position: usize,
+) -> *const c_char {
+    guard(ptr::null(), || {
+        list.as_ref()
+            .and_then(|list| list.cameras.get(position))
+            .map_or(ptr::null(), |(_, name)| name.as_ptr())
+    })
+}
+
+/// Gets the index of the camera at `position`, to pass to [`nokhwa_open()`].
+/// # Safety
+/// `list` must be null or a list returned by [`nokhwa_query()`]. `out_index` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_camera_list_index(
+    list: *const NokhwaCameraList,
+    position: usize,
+    out_index: *mut u32,
+) -> NokhwaStatus {
+    guard(NokhwaStatus::Panic, || {
+        let Some((info, _)) = list.as_ref().and_then(|list| list.cameras.get(position)) else {
+            return invalid_argument("list is null or position is out of range");
+        };
+        if out_index.is_null() {
+            return invalid_argument("out_index is null");
+        }
+        status(info.index().as_index(), |index| *out_index = index)
+    })
+}
+
+/// Frees a list returned by [`nokhwa_query()`]. Passing null does nothing.
+/// # Safety
+/// `list` must be null or a list returned by [`nokhwa_query()`] that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_camera_list_free(list: *mut NokhwaCameraList) {
+    guard((), || {
+        if !list.is_null() {
+            drop(Box::from_raw(list));
+        }
+    })
+}
+
+/// A camera format. `fourcc` is a little endian packed `FourCC` code (e.g. `'M' | 'J' << 8 | 'P' << 16 | 'G' << 24` for `MJPG`).
+///
+/// The frame rate is `frame_rate_numerator / frame_rate_denominator` frames per second, so fractional rates such as NTSC's
+/// `30000 / 1001` survive a [`nokhwa_get_format()`] and [`nokhwa_set_format()`] round trip.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
+pub struct NokhwaFormat {
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate_numerator: u32,
+    pub frame_rate_denominator: u32,
+    pub fourcc: u32,
+}
+
+impl From<CameraFormat> for NokhwaFormat {
+    fn from(format: CameraFormat) -> Self {
+        let (frame_rate_numerator, frame_rate_denominator) = format.frame_rate().as_fraction();
+        NokhwaFormat {
+            width: format.width(),
+            height: format.height(),
+            frame_rate_numerator,
+            frame_rate_denominator,
+            fourcc: pack_fourcc(format.format()),
+        }
+    }
+}
+
+impl TryFrom<NokhwaFormat> for CameraFormat {
+    type Error = NokhwaError;
+
+    fn try_from(format: NokhwaFormat) -> Result<Self, Self::Error> {
+        if format.frame_rate_denominator == 0 {
+            return Err(NokhwaError::SetPropertyError {
+                property: "FrameRate".to_string(),
+                value: format!("{}/0", format.frame_rate_numerator),
+                error: "The denominator is 0".to_string(),
+            });
+        }
+        Ok(CameraFormat::new(
+            Resolution::new(format.width, format.height),
+            unpack_fourcc(format.fourcc)?,
+            FrameRate::new_fraction(format.frame_rate_numerator, format.frame_rate_denominator)
+                .reduced(),
+        ))
+    }
+}
+
+/// A frame captured by [`nokhwa_get_frame()`].
+///
+/// `data` is owned by the camera and is valid until the next [`nokhwa_get_frame()`] or [`nokhwa_close()`] on that camera.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct NokhwaFrame {
+    pub data: *const u8,
+    pub len: usize,
+    pub width: u32,
+    pub height: u32,
+    /// The length of a row in bytes, or 0 if the backend does not report it.
+    pub stride: u32,
+    pub fourcc: u32,
+    /// The sequence number of the frame, or 0 if the backend does not report it.
+    pub sequence: u32,
+}
+
+/// An open camera, returned by [`nokhwa_open()`]. Close it with [`nokhwa_close()`].
+pub struct NokhwaCamera {
+    camera: Camera,
+    last_frame: Option<Buffer>,
+}
+
+/// Opens the camera at `index` with the native backend of the platform.
+/// # Safety
+/// `out_camera` must be a valid pointer to write a [`NokhwaCamera`] pointer to.
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_open(
+    index: u32,
+    out_camera: *mut *mut NokhwaCamera,
+) -> NokhwaStatus {
+    guard(NokhwaStatus::Panic, || {
+        if out_camera.is_null() {
+            return invalid_argument("out_camera is null");
+        }
+        let camera = Camera::new(CameraIndex::Index(index)).and_then(|mut camera| {
+            camera.init()?;
+            Ok(camera)
+        });
+        status(camera, |camera| {
+            *out_camera = Box::into_raw(Box::new(NokhwaCamera {
+                camera,
+                last_frame: None,
+            }));
+        })
+    })
+}
+
+/// Gets the current format of the camera.
+/// # Safety
+/// `camera` must be a camera returned by [`nokhwa_open()`]. `out_format` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_get_format(
+    camera: *const NokhwaCamera,
+    out_format: *mut NokhwaFormat,
+) -> NokhwaStatus {
+    guard(NokhwaStatus::Panic, || {
+        let (Some(camera), false) = (camera.as_ref(), out_format.is_null()) else {
+            return invalid_argument("camera or out_format is null");
+        };
+        status(
+            camera
+                .camera
+                .camera_format()
+                .ok_or(NokhwaError::UnitializedError),
+            |format| *out_format = NokhwaFormat::from(format),
+        )
+    })
+}
+
+/// Sets the format of the camera. If the stream is open, it is restarted.
+/// # Safety
+/// `camera` must be a camera returned by [`nokhwa_open()`].
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_set_format(
+    camera: *mut NokhwaCamera,
+    format: NokhwaFormat,
+) -> NokhwaStatus {
+    guard(NokhwaStatus::Panic, || {
+        let Some(camera) = camera.as_mut() else {
+            return invalid_argument("camera is null");
+        };
+        let result = CameraFormat::try_from(format)
+            .and_then(|format| camera.camera.set_camera_format(format));
+        status(result, |()| {})
+    })
+}
+
+/// Opens the camera's stream. This must be called before [`nokhwa_get_frame()`].
+/// # Safety
+/// `camera` must be a camera returned by [`nokhwa_open()`].
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_open_stream(camera: *mut NokhwaCamera) -> NokhwaStatus {
+    guard(NokhwaStatus::Panic, || {
+        let Some(camera) = camera.as_mut() else {
+            return invalid_argument("camera is null");
+        };
+        status(camera.camera.open_stream(), |()| {})
+    })
+}
+
+/// Captures a frame, blocking until one is available.
+/// # Safety
+/// `camera` must be a camera returned by [`nokhwa_open()`]. `out_frame` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_get_frame(
+    camera: *mut NokhwaCamera,
+    out_frame: *mut NokhwaFrame,
+) -> NokhwaStatus {
+    guard(NokhwaStatus::Panic, || {
+        let (Some(camera), false) = (camera.as_mut(), out_frame.is_null()) else {
+            return invalid_argument("camera or out_frame is null");
+        };
+        let fourcc = pack_fourcc(camera.camera.frame_format());
+        status(camera.camera.frame(), |buffer| {
+            let buffer = camera.last_frame.insert(buffer);
+            *out_frame = NokhwaFrame {
+                data: buffer.buffer().as_ptr(),
+                len: buffer.buffer().len(),
+                width: buffer.resolution().width(),
+                height: buffer.resolution().height(),
+                stride: buffer.stride().unwrap_or(0),
+                fourcc,
+                sequence: buffer.sequence().unwrap_or(0),
+            };
+        })
+    })
+}
+
+/// Stops the camera's stream.
+/// # Safety
+/// `camera` must be a camera returned by [`nokhwa_open()`].
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_stop_stream(camera: *mut NokhwaCamera) -> NokhwaStatus {
+    guard(NokhwaStatus::Panic, || {
+        let Some(camera) = camera.as_mut() else {
+            return invalid_argument("camera is null");
+        };
+        status(camera.camera.stop_stream(), |()| {})
+    })
+}
+
+/// Closes the camera, stopping its stream. Passing null does nothing.
+/// # Safety
+/// `camera` must be null or a camera returned by [`nokhwa_open()`] that has not been closed yet.
+#[no_mangle]
+pub unsafe extern "C" fn nokhwa_close(camera: *mut NokhwaCamera) {
+    guard((), || {
+        if !camera.is_null() {
+            drop(Box::from_raw(camera));
+        }
+    })
+}
@@ -0,0 +1,394 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Python bindings for `nokhwa`. Frames are exposed as `numpy` arrays.
+
+use nokhwa::prelude::{
+    ApiBackend, Buffer, CameraFormat as NokhwaCameraFormat, CameraIndex,
+    CameraInfo as NokhwaCameraInfo, CaptureTrait, ControlValueSetter, FrameFormat, FrameRate,
+    NokhwaError, Resolution,
+};
+use numpy::PyArray1;
+use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyBytes};
+use std::{collections::HashMap, str::FromStr};
+
+create_exception!(nokhwa, NokhwaException, PyException);
+
+fn py_err(why: NokhwaError) -> PyErr {
+    NokhwaException::new_err(why.to_string())
+}
+
+/// Information about a camera, returned by `query()`.
+#[pyclass(frozen)]
+#[derive(Clone)]
+struct CameraInfo {
+    #[pyo3(get)]
+    name: String,
+    #[pyo3(get)]
+    description: String,
+    #[pyo3(get)]
+    misc: String,
+    #[pyo3(get)]
+    index: String,
+}
+
+impl From<NokhwaCameraInfo> for CameraInfo {
+    fn from(info: NokhwaCameraInfo) -> Self {
+        CameraInfo {
+            name: info.human_name().to_string(),
+            description: info.description().to_string(),
+            misc: info.misc().to_string(),
+            index: info.index().as_string(),
+        }
+    }
+}
+
+#[pymethods]
+impl CameraInfo {
+    fn __repr__(&self) -> String {
+        format!("CameraInfo(name={:?}, index={:?})", self.name, self.index)
+    }
+}
+
+/// A camera format: resolution, frame rate, and `FourCC` (e.g. `"MJPG"`, `"YUYV"`).
+///
+/// The frame rate is kept exact, as `frame_rate_numerator / frame_rate_denominator` frames per second (e.g. `30000 / 1001`
+/// for NTSC), so a format read from a camera can be set back on it.
+#[pyclass]
+#[derive(Clone)]
+struct CameraFormat {
+    #[pyo3(get, set)]
+    width: u32,
+    #[pyo3(get, set)]
+    height: u32,
+    #[pyo3(get, set)]
+    frame_rate_numerator: u32,
+    #[pyo3(get, set)]
+    frame_rate_denominator: u32,
+    #[pyo3(get, set)]
+    fourcc: String,
+}
+
+impl From<NokhwaCameraFormat> for CameraFormat {
+    fn from(format: NokhwaCameraFormat) -> Self {
+        let (frame_rate_numerator, frame_rate_denominator) = format.frame_rate().as_fraction();
+        CameraFormat {
+            width: format.width(),
+            height: format.height(),
+            frame_rate_numerator,
+            frame_rate_denominator,
+            fourcc: format
+                .format()
+                .fourcc()
+                .map_or_else(|| format.format().to_string(), ToString::to_string),
+        }
+    }
+}
+
+impl TryFrom<&CameraFormat> for NokhwaCameraFormat {
+    type Error = NokhwaError;
+
+    fn try_from(format: &CameraFormat) -> Result<Self, Self::Error> {
+        if format.frame_rate_denominator == 0 {
+            return Err(NokhwaError::SetPropertyError {
+                property: "FrameRate".to_string(),
+                value: format!("{}/0", format.frame_rate_numerator),
+                error: "The denominator is 0".to_string(),
+            });
+        }
+        Ok(NokhwaCameraFormat::new(
+            Resolution::new(format.width, format.height),
+            FrameFormat::from_str(&format.fourcc)?,
+            FrameRate::new_fraction(format.frame_rate_numerator, format.frame_rate_denominator)
+                .reduced(),
+        ))
+    }
+}
+
+#[pymethods]
+impl CameraFormat {
+    /// `frame_rate` is in frames per second, or the numerator of a fractional rate, e.g.
+    /// `CameraFormat(720, 480, 30000, "YUYV", 1001)` for NTSC.
+    #[new]
+    #[pyo3(signature = (width, height, frame_rate, fourcc, frame_rate_denominator = 1))]
+    fn new(
+        width: u32,
+        height: u32,
+        frame_rate: u32,
+        fourcc: String,
+        frame_rate_denominator: u32,
+    ) -> Self {
+        CameraFormat {
+            width,
+            height,
+            frame_rate_numerator: frame_rate,
+            frame_rate_denominator,
+            fourcc,
+        }
+    }
+
+    /// The frame rate in frames per second, e.g. `29.97` for `30000 / 1001`.
+    #[getter]
+    fn frame_rate(&self) -> f64 {
+        f64::from(self.frame_rate_numerator) / f64::from(self.frame_rate_denominator.max(1))
+    }
+
+    fn __repr__(&self) -> String {
+        let frame_rate = match self.frame_rate_denominator {
+            1 => self.frame_rate_numerator.to_string(),
+            denominator => format!("{}/{denominator}", self.frame_rate_numerator),
+        };
+        format!(
+            "CameraFormat({}x{}@{frame_rate} {})",
+            self.width, self.height, self.fourcc
+        )
+    }
+}
+
+/// A captured frame. The bytes are in the camera's format and are not decoded.
+#[pyclass(frozen)]
+struct Frame {
+    buffer: Buffer,
+    format: FrameFormat,
+}
+
+#[pymethods]
+impl Frame {
+    #[getter]
+    fn width(&self) -> u32 {
+        self.buffer.resolution().width()
+    }
+
+    #[getter]
+    fn height(&self) -> u32 {
+        self.buffer.resolution().height()
+    }
+
+    #[getter]
+    fn fourcc(&self) -> String {
+        self.format
+            .fourcc()
+            .map_or_else(|| self.format.to_string(), ToString::to_string)
+    }
+
+    #[getter]
+    fn sequence(&self) -> Option<u32> {
+        self.buffer.sequence()
+    }
+
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, self.buffer.buffer())
+    }
+
+    /// Copies the frame into a `uint8` `numpy` array.
+    ///
+    /// Packed pixel formats (`YUYV`, `UYVY`, `GREY`, `RGB3`, `AB24`) are shaped `(height, width, channels)`. Everything else
+    /// (e.g. `MJPG`, `NV12`) is returned flat.
+    fn to_numpy(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let data = self.buffer.buffer();
+        let array = PyArray1::from_slice(py, data);
+        let channels = match self.format {
+            FrameFormat::Luma8 => Some(1),
+            FrameFormat::Yuv422 | FrameFormat::Uyv422 => Some(2),
+            FrameFormat::Rgb8 => Some(3),
+            FrameFormat::RgbA8 => Some(4),
+            _ => None,
+        };
+        let (width, height) = (self.width() as usize, self.height() as usize);
+        match channels {
+            Some(channels) if width * height * channels == data.len() => {
+                Ok(array.reshape([height, width, channels])?.to_object(py))
+            }
+            _ => Ok(array.to_object(py)),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.buffer.buffer().len()
+    }
+}
+
+fn control_value(value: &PyAny) -> PyResult<ControlValueSetter> {
+    // `bool` is a subclass of `int` in Python, so it has to be checked first.
+    if let Ok(value) = value.extract::<bool>() {
+        Ok(ControlValueSetter::Boolean(value))
+    } else if let Ok(value) = value.extract::<i64>() {
+        Ok(ControlValueSetter::Integer(value))
+    } else if let Ok(value) = value.extract::<f64>() {
+        Ok(ControlValueSetter::Float(value))
+    } else if let Ok(value) = value.extract::<String>() {
+        Ok(ControlValueSetter::String(value))
+    } else {
+        Err(NokhwaException::new_err(
+            "Control values must be a bool, int, float, or str",
+        ))
+    }
+}
+
+fn control_object(py: Python<'_>, value: ControlValueSetter) -> PyObject {
+    match value {
+        ControlValueSetter::Integer(value) | ControlValueSetter::EnumValue(value) => {
+            value.to_object(py)
+        }
+        ControlValueSetter::Float(value) => value.to_object(py),
+        ControlValueSetter::Boolean(value) => value.to_object(py),
+        ControlValueSetter::String(value) | ControlValueSetter::StringList(value) => {
+            value.to_object(py)
+        }
+        ControlValueSetter::Bytes(value) => PyBytes::new(py, &value).to_object(py),
+        other => other.to_string().to_object(py),
+    }
+}
+
+/// A camera, opened with the native backend of the platform.
+#[pyclass]
+struct Camera {
+    camera: nokhwa::Camera,
+}
+
+#[pymethods]
+impl Camera {
+    /// Opens the camera at `index` (an `int`, or a platform-specific `str`).
+    #[new]
+    #[pyo3(signature = (index = 0))]
+    fn new(index: &PyAny) -> PyResult<Self> {
+        let index = match index.extract::<u32>() {
+            Ok(index) => CameraIndex::Index(index),
+            Err(_) => CameraIndex::String(index.extract::<String>()?),
+        };
+        let mut camera = nokhwa::Camera::new(index).map_err(py_err)?;
+        camera.init().map_err(py_err)?;
+        Ok(Camera { camera })
+    }
+
+    #[getter]
+    fn info(&self) -> CameraInfo {
+        CameraInfo::from(self.camera.info().clone())
+    }
+
+    #[getter]
+    fn format(&self) -> Option<CameraFormat> {
+        self.camera.camera_format().map(CameraFormat::from)
+    }
+
+    #[setter]
+    fn set_format(&mut self, format: &CameraFormat) -> PyResult<()> {
+        let format = NokhwaCameraFormat::try_from(format).map_err(py_err)?;
+        self.camera.set_camera_format(format).map_err(py_err)
+    }
+
+    /// Lists every format the camera supports.
+    fn compatible_formats(&mut self) -> PyResult<Vec<CameraFormat>> {
+        Ok(self
+            .camera
+            .compatible_camera_formats()
+            .map_err(py_err)?
+            .into_iter()
+            .map(CameraFormat::from)
+            .collect())
+    }
+
+    /// Gets every control of the camera as a `dict` of name to current value.
+    fn controls(&self, py: Python<'_>) -> PyResult<HashMap<String, PyObject>> {
+        Ok(self
+            .camera
+            .camera_controls()
+            .map_err(py_err)?
+            .into_iter()
+            .map(|control| {
+                (
+                    control.control().to_string(),
+                    control_object(py, control.value()),
+                )
+            })
+            .collect())
+    }
+
+    /// Sets a control by name (e.g. `"Brightness"`), as listed by `controls()`.
+    fn set_control(&mut self, name: &str, value: &PyAny) -> PyResult<()> {
+        let value = control_value(value)?;
+        let control = self
+            .camera
+            .camera_controls()
+            .map_err(py_err)?
+            .into_iter()
+            .find(|control| control.control().to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| NokhwaException::new_err(format!("Unknown control {name}")))?;
+        self.camera
+            .set_camera_control(control.control(), value)
+            .map_err(py_err)
+    }
+
+    fn open_stream(&mut self) -> PyResult<()> {
+        self.camera.open_stream().map_err(py_err)
+    }
+
+    #[getter]
+    fn is_stream_open(&self) -> bool {
+        self.camera.is_stream_open()
+    }
+
+    /// Captures a frame. The GIL is released while waiting for the camera.
+    fn frame(&mut self, py: Python<'_>) -> PyResult<Frame> {
+        let format = self.camera.frame_format();
+        let camera = &mut self.camera;
+        let buffer = py.allow_threads(|| camera.frame()).map_err(py_err)?;
+        Ok(Frame { buffer, format })
+    }
+
+    fn stop_stream(&mut self) -> PyResult<()> {
+        self.camera.stop_stream().map_err(py_err)
+    }
+
+    fn __enter__(slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+        slf
+    }
+
+    fn __exit__(
+        &mut self,
+        _exc_type: &PyAny,
+        _exc_value: &PyAny,
+        _traceback: &PyAny,
+    ) -> PyResult<()> {
+        if self.camera.is_stream_open() {
+            self.stop_stream()?;
+        }
+        Ok(())
+    }
+}
+
+/// Lists the cameras available with the native backend of the platform.
+#[pyfunction]
+fn query() -> PyResult<Vec<CameraInfo>> {
+    Ok(nokhwa::query(ApiBackend::Auto)
+        .map_err(py_err)?
+        .into_iter()
+        .map(CameraInfo::from)
+        .collect())
+}
+
+#[pymodule]
+#[pyo3(name = "nokhwa")]
+fn nokhwa_python(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add("NokhwaException", py.get_type::<NokhwaException>())?;
+    m.add_class::<CameraInfo>()?;
+    m.add_class::<CameraFormat>()?;
+    m.add_class::<Frame>()?;
+    m.add_class::<Camera>()?;
+    m.add_function(wrap_pyfunction!(query, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use futures::{stream, Stream};
+use nokhwa_core::{buffer::Buffer, error::NokhwaError, traits::AsyncCaptureTrait};
+
+/// A camera that yields its frames asynchronously, as a [`Stream`].
+///
+/// This does **not** spawn a thread per camera. Instead, the backend waits for the device to have a frame ready
+/// (e.g. using `epoll` on `V4L2`), so it can be used with `tokio`, `async-std`, or any other executor.
+/// ```ignore
+/// let mut camera = AsyncCamera::with_backend(device);
+/// camera.open_stream().await?;
+/// let mut frames = camera.frames();
+/// while let Some(frame) = frames.next().await {
+///     let frame = frame?;
+/// }
+/// ```
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-async")))]
+pub struct AsyncCamera {
+    device: Box<dyn AsyncCaptureTrait + Send>,
+}
+
+impl AsyncCamera {
+    /// Creates a new [`AsyncCamera`] from any backend that implements [`AsyncCaptureTrait`].
+    pub fn with_backend(device: impl AsyncCaptureTrait + Send + 'static) -> Self {
+        AsyncCamera {
+            device: Box::new(device),
+        }
+    }
+
+    /// Gets a reference to the inner backend.
+    #[must_use]
+    pub fn inner(&self) -> &(dyn AsyncCaptureTrait + Send) {
+        self.device.as_ref()
+    }
+
+    /// Gets a mutable reference to the inner backend, e.g. to change the format or controls.
+    pub fn inner_mut(&mut self) -> &mut (dyn AsyncCaptureTrait + Send) {
+        self.device.as_mut()
+    }
+
+    /// Will open the camera stream with set parameters.
+    /// # Errors
+    /// If the specific backend fails to open the camera (e.g. already taken, busy, doesn't exist anymore) this will error.
+    pub async fn open_stream(&mut self) -> Result<(), NokhwaError> {
+        self.device.open_stream_async().await
+    }
+
+    /// Will get the next frame from the camera.
+    /// # Errors
+    /// If the backend fails to get the frame (e.g. already taken, busy, doesn't exist anymore), this will error.
+    pub async fn frame(&mut self) -> Result<Buffer, NokhwaError> {
+        self.device.frame_async().await
+    }
+
+    /// Will drop the stream. This will end any [`frames()`](Self::frames) stream.
+    /// # Errors
+    /// Please check the `Quirks` section of each backend.
+    pub async fn stop_stream(&mut self) -> Result<(), NokhwaError> {
+        self.device.stop_stream_async().await
+    }
+
+    /// Gets a [`Stream`] of frames from the camera. You must call [`open_stream()`](Self::open_stream) first.
+    ///
+    /// The stream ends once the camera stream is no longer open. Errors are yielded and do not end the stream,
+    /// so that you may decide if they are recoverable, except that the stream ends after yielding an error that says the
+    /// camera was unplugged (see [`NokhwaError::is_disconnected()`]).
+    pub fn frames(&mut self) -> impl Stream<Item = Result<Buffer, NokhwaError>> + '_ {
+        stream::unfold((self, false), |(camera, disconnected)| async move {
+            if disconnected || !camera.device.is_stream_open() {
+                return None;
+            }
+            let frame = camera.device.frame_async().await;
+            let disconnected = matches!(&frame, Err(why) if why.is_disconnected());
+            Some((frame, (camera, disconnected)))
+        })
+    }
+}
@@ -115,7 +115,7 @@ impl AVFoundationCaptureDevice {
         let camera_format = CameraFormat::new_from(width, height, fourcc, fps);
         AVFoundationCaptureDevice::new(
             &CameraIndex::Index(index as u32),
-            RequestedFormat::new::<RgbFormat>(RequestedFormatType::Exact(camera_format)),
+            RequestedFormat::new(RequestedFormatType::Exact(camera_format)),
         )
     }
 }
@@ -211,7 +211,7 @@ impl CaptureTrait for AVFoundationCaptureDevice {
     }
 
     fn camera_control(&self, control: KnownCameraControl) -> Result<CameraControl, NokhwaError> {
-        for ctrl in self.device.get_controls()? {
+        for ctrl in self.device.controls()? {
             if ctrl.control() == control {
                 return Ok(ctrl);
             }
@@ -224,7 +224,7 @@ impl CaptureTrait for AVFoundationCaptureDevice {
     }
 
     fn camera_controls(&self) -> Result<Vec<CameraControl>, NokhwaError> {
-        self.device.get_controls()
+        self.device.controls()
     }
 
     fn set_camera_control(
@@ -281,7 +281,7 @@ impl CaptureTrait for AVFoundationCaptureDevice {
         self.refresh_camera_format()?;
         let cfmt = self.camera_format();
         let b = self.frame_raw()?;
-        let buffer = Buffer::new(cfmt.resolution(), b.as_ref(), cfmt.format());
+        let buffer = Buffer::from_cow(cfmt.resolution(), b, cfmt.format());
         let _ = self.frame_buffer_receiver.drain();
         Ok(buffer)
     }
@@ -0,0 +1,550 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use nokhwa_core::{
+    buffer::Buffer,
+    error::NokhwaError,
+    format_request::FormatFilter,
+    frame_format::FrameFormat,
+    traits::{Backend, CaptureTrait},
+    types::{
+        ApiBackend, CameraControl, CameraFormat, CameraIndex, CameraInfo, ControlValueSetter,
+        FrameTimestamp, KnownCameraControl, Resolution,
+    },
+};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    thread,
+    time::Duration,
+};
+
+/// An operation of a [`MockCaptureDevice`] that can be scripted to fail with [`MockCaptureDevice::fail_next()`].
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum MockOperation {
+    Init,
+    SetFormat,
+    SetControl,
+    OpenStream,
+    Frame,
+    StopStream,
+}
+
+/// A fake camera whose formats, controls, frames, failures, and timing are scripted from test code.
+///
+/// Use this to unit-test code that captures from a [`CaptureTrait`] (or a [`Camera`](crate::Camera), through
+/// [`Camera::with_custom_backend()`](crate::Camera::with_custom_backend)) without any hardware.
+/// ```ignore
+/// let mut device = MockCaptureDevice::new(vec![CameraFormat::new(Resolution::VGA, FrameFormat::MJpeg, FrameRate::Integer(30))]);
+/// device.push_frame(jpeg_bytes);
+/// device.fail_next(MockOperation::Frame, NokhwaError::ReadFrameError("unplugged".to_string()));
+/// let mut camera = Camera::with_custom_backend(device);
+/// ```
+/// # Quirks
+/// - Frames are served in the order they were pushed. Once they run out, [`frame()`](CaptureTrait::frame) errors,
+/// unless [`set_loop_frames()`](Self::set_loop_frames) is enabled.
+/// - Only formats passed to [`new()`](Self::new) can be set. The first one is the initial format.
+/// - Setting a control is recorded (see [`applied_controls()`](Self::applied_controls)) but does not change the value reported by
+/// [`camera_control()`](CaptureTrait::camera_control).
+pub struct MockCaptureDevice {
+    info: CameraInfo,
+    formats: Vec<CameraFormat>,
+    camera_format: Option<CameraFormat>,
+    controls: Vec<CameraControl>,
+    applied_controls: Vec<(KnownCameraControl, ControlValueSetter)>,
+    frames: VecDeque<Result<Vec<u8>, NokhwaError>>,
+    loop_frames: bool,
+    frame_delay: Option<Duration>,
+    failures: HashMap<MockOperation, VecDeque<NokhwaError>>,
+    initialized: bool,
+    stream_open: bool,
+    sequence: u32,
+    last_frame: Vec<u8>,
+}
+
+impl MockCaptureDevice {
+    /// Creates a new mock camera that supports `formats`. The first format is the initial [`CameraFormat`].
+    #[must_use]
+    pub fn new(formats: Vec<CameraFormat>) -> Self {
+        MockCaptureDevice {
+            info: CameraInfo::new(
+                "Mock Camera",
+                "Scripted camera for tests",
+                "",
+                &CameraIndex::Index(0),
+            ),
+            camera_format: formats.first().copied(),
+            formats,
+            controls: vec![],
+            applied_controls: vec![],
+            frames: VecDeque::new(),
+            loop_frames: false,
+            frame_delay: None,
+            failures: HashMap::new(),
+            initialized: false,
+            stream_open: false,
+            sequence: 0,
+            last_frame: vec![],
+        }
+    }
+
+    /// Sets the [`CameraInfo`] that is reported.
+    #[must_use]
+    pub fn with_info(mut self, info: CameraInfo) -> Self {
+        self.info = info;
+        self
+    }
+
+    /// Sets the controls that are reported.
+    #[must_use]
+    pub fn with_controls(mut self, controls: Vec<CameraControl>) -> Self {
+        self.controls = controls;
+        self
+    }
+
+    /// Queues a frame to be returned by [`frame()`](CaptureTrait::frame).
+    pub fn push_frame(&mut self, frame: impl Into<Vec<u8>>) {
+        self.frames.push_back(Ok(frame.into()));
+    }
+
+    /// Queues an error to be returned by [`frame()`](CaptureTrait::frame) in place of a frame.
+    pub fn push_frame_error(&mut self, error: NokhwaError) {
+        self.frames.push_back(Err(error));
+    }
+
+    /// Sets whether frames are returned to the back of the queue after being served, so the same frames play forever.
+    pub fn set_loop_frames(&mut self, loop_frames: bool) {
+        self.loop_frames = loop_frames;
+    }
+
+    /// Sets how long [`frame()`](CaptureTrait::frame) sleeps before returning, to simulate a camera's frame interval.
+    pub fn set_frame_delay(&mut self, delay: Option<Duration>) {
+        self.frame_delay = delay;
+    }
+
+    /// Makes the next call of `operation` fail with `error`. Several failures for the same operation are returned in order.
+    pub fn fail_next(&mut self, operation: MockOperation, error: NokhwaError) {
+        self.failures.entry(operation).or_default().push_back(error);
+    }
+
+    /// Gets every control that was set, in order.
+    #[must_use]
+    pub fn applied_controls(&self) -> &[(KnownCameraControl, ControlValueSetter)] {
+        &self.applied_controls
+    }
+
+    /// Gets the number of frames still queued.
+    #[must_use]
+    pub fn queued_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn scripted(&mut self, operation: MockOperation) -> Result<(), NokhwaError> {
+        match self
+            .failures
+            .get_mut(&operation)
+            .and_then(VecDeque::pop_front)
+        {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    fn current_format(&self) -> Result<CameraFormat, NokhwaError> {
+        self.camera_format.ok_or(NokhwaError::UnitializedError)
+    }
+
+    fn next_frame(&mut self) -> Result<(), NokhwaError> {
+        self.scripted(MockOperation::Frame)?;
+        if !self.stream_open {
+            return Err(NokhwaError::ReadFrameError(
+                "Stream Not Started".to_string(),
+            ));
+        }
+        if let Some(delay) = self.frame_delay {
+            thread::sleep(delay);
+        }
+        let frame = self
+            .frames
+            .pop_front()
+            .ok_or_else(|| NokhwaError::ReadFrameError("Mock frame queue is empty".to_string()))?;
+        if self.loop_frames {
+            self.frames.push_back(frame.clone());
+        }
+        self.last_frame = frame?;
+        self.sequence = self.sequence.wrapping_add(1);
+        Ok(())
+    }
+}
+
+impl Backend for MockCaptureDevice {
+    const BACKEND: ApiBackend = ApiBackend::Custom("Mock");
+}
+
+impl CaptureTrait for MockCaptureDevice {
+    fn init(&mut self) -> Result<(), NokhwaError> {
+        self.scripted(MockOperation::Init)?;
+        self.initialized = true;
+        Ok(())
+    }
+
+    fn init_with_format(&mut self, _format: FormatFilter) -> Result<CameraFormat, NokhwaError> {
+        Err(NokhwaError::NotImplementedError(
+            "init_with_format on MockCaptureDevice".to_string(),
+        ))
+    }
+
+    fn backend(&self) -> ApiBackend {
+        Self::BACKEND
+    }
+
+    fn camera_info(&self) -> &CameraInfo {
+        &self.info
+    }
+
+    fn refresh_camera_format(&mut self) -> Result<(), NokhwaError> {
+        Ok(())
+    }
+
+    fn camera_format(&self) -> Option<CameraFormat> {
+        self.camera_format
+    }
+
+    fn set_camera_format(&mut self, new_fmt: CameraFormat) -> Result<(), NokhwaError> {
+        self.scripted(MockOperation::SetFormat)?;
+        if !self.formats.contains(&new_fmt) {
+            return Err(NokhwaError::SetPropertyError {
+                property: "CameraFormat".to_string(),
+                value: new_fmt.to_string(),
+                error: "Rejected".to_string(),
+            });
+        }
+        self.camera_format = Some(new_fmt);
+        Ok(())
+    }
+
+    fn compatible_list_by_resolution(
+        &mut self,
+        fourcc: FrameFormat,
+    ) -> Result<HashMap<Resolution, Vec<u32>>, NokhwaError> {
+        let mut res_map: HashMap<Resolution, Vec<u32>> = HashMap::new();
+        for format in self
+            .formats
+            .iter()
+            .filter(|format| format.format() == fourcc)
+        {
+            res_map
+                .entry(format.resolution())
+                .or_default()
+                .push(format.frame_rate().as_u32());
+        }
+        Ok(res_map)
+    }
+
+    fn compatible_camera_formats(&mut self) -> Result<Vec<CameraFormat>, NokhwaError> {
+        Ok(self.formats.clone())
+    }
+
+    fn compatible_fourcc(&mut self) -> Result<Vec<FrameFormat>, NokhwaError> {
+        let mut fourccs = self
+            .formats
+            .iter()
+            .map(CameraFormat::format)
+            .collect::<Vec<FrameFormat>>();
+        fourccs.sort();
+        fourccs.dedup();
+        Ok(fourccs)
+    }
+
+    fn resolution(&self) -> Option<Resolution> {
+        self.camera_format.map(|fmt| fmt.resolution())
+    }
+
+    fn set_resolution(&mut self, new_res: Resolution) -> Result<(), NokhwaError> {
+        let mut new_fmt = self.current_format()?;
+        new_fmt.set_resolution(new_res);
+        self.set_camera_format(new_fmt)
+    }
+
+    fn frame_rate(&self) -> Option<u32> {
+        self.camera_format.map(|fmt| fmt.frame_rate().as_u32())
+    }
+
+    fn set_frame_rate(&mut self, new_fps: u32) -> Result<(), NokhwaError> {
+        let current = self.current_format()?;
+        let new_fmt = self
+            .formats
+            .iter()
+            .copied()
+            .find(|format| {
+                format.resolution() == current.resolution()
+                    && format.format() == current.format()
+                    && format.frame_rate().as_u32() == new_fps
+            })
+            .unwrap_or_else(|| {
+                let mut new_fmt = current;
+                new_fmt.set_frame_rate(new_fps.into());
+                new_fmt
+            });
+        self.set_camera_format(new_fmt)
+    }
+
+    fn frame_format(&self) -> FrameFormat {
+        self.camera_format
+            .map(|fmt| fmt.format())
+            .unwrap_or(FrameFormat::MJpeg)
+    }
+
+    fn set_frame_format(&mut self, fourcc: FrameFormat) -> Result<(), NokhwaError> {
+        let mut new_fmt = self.current_format()?;
+        new_fmt.set_format(fourcc);
+        self.set_camera_format(new_fmt)
+    }
+
+    fn camera_control(&self, control: KnownCameraControl) -> Result<CameraControl, NokhwaError> {
+        self.controls
+            .iter()
+            .find(|supported| supported.control() == control)
+            .cloned()
+            .ok_or(NokhwaError::GetPropertyError {
+                property: control.to_string(),
+                error: "not found/not supported".to_string(),
+            })
+    }
+
+    fn camera_controls(&self) -> Result<Vec<CameraControl>, NokhwaError> {
+        Ok(self.controls.clone())
+    }
+
+    fn set_camera_control(
+        &mut self,
+        id: KnownCameraControl,
+        value: ControlValueSetter,
+    ) -> Result<(), NokhwaError> {
+        self.scripted(MockOperation::SetControl)?;
+        let control = self.camera_control(id)?;
+        if !control.description().verify_setter(&value) {
+            return Err(NokhwaError::SetPropertyError {
+                property: id.to_string(),
+                value: value.to_string(),
+                error: "Invalid value".to_string(),
+            });
+        }
+        self.applied_controls.push((id, value));
+        Ok(())
+    }
+
+    fn open_stream(&mut self) -> Result<(), NokhwaError> {
+        self.scripted(MockOperation::OpenStream)?;
+        if !self.initialized {
+            self.init()?;
+        }
+        self.stream_open = true;
+        Ok(())
+    }
+
+    fn is_stream_open(&self) -> bool {
+        self.stream_open
+    }
+
+    fn frame(&mut self) -> Result<Buffer, NokhwaError> {
+        self.next_frame()?;
+        let cam_fmt = self.current_format()?;
+        Ok(
+            Buffer::new(cam_fmt.resolution(), &self.last_frame, cam_fmt.format())
+                .with_timestamp(FrameTimestamp::now(None))
+                .with_sequence(self.sequence),
+        )
+    }
+
+    fn frame_raw(&mut self) -> Result<Cow<[u8]>, NokhwaError> {
+        self.next_frame()?;
+        Ok(Cow::Borrowed(&self.last_frame))
+    }
+
+    fn stop_stream(&mut self) -> Result<(), NokhwaError> {
+        self.scripted(MockOperation::StopStream)?;
+        self.stream_open = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MockCaptureDevice, MockOperation};
+    use nokhwa_core::{
+        error::NokhwaError,
+        frame_format::FrameFormat,
+        traits::CaptureTrait,
+        types::{CameraFormat, FrameRate, Resolution},
+    };
+
+    const VGA: CameraFormat =
+        CameraFormat::new(Resolution::VGA, FrameFormat::Luma8, FrameRate::Integer(30));
+    const HD: CameraFormat = CameraFormat::new(
+        Resolution::HD_720P,
+        FrameFormat::Luma8,
+        FrameRate::Integer(30),
+    );
+
+    fn open_device() -> MockCaptureDevice {
+        let mut device = MockCaptureDevice::new(vec![VGA]);
+        device.open_stream().unwrap();
+        device
+    }
+
+    #[test]
+    fn frames_are_served_in_order() {
+        let mut device = open_device();
+        device.push_frame(vec![1]);
+        device.push_frame(vec![2]);
+        device.push_frame(vec![3]);
+
+        for (expected, sequence) in [(1, 1), (2, 2), (3, 3)] {
+            let frame = device.frame().unwrap();
+            assert_eq!(frame.buffer(), [expected]);
+            assert_eq!(frame.sequence(), Some(sequence));
+        }
+        assert_eq!(device.queued_frames(), 0);
+    }
+
+    #[test]
+    fn looped_frames_repeat() {
+        let mut device = open_device();
+        device.set_loop_frames(true);
+        device.push_frame(vec![1]);
+        device.push_frame(vec![2]);
+
+        let served = (0..5)
+            .map(|_| device.frame_raw().unwrap()[0])
+            .collect::<Vec<u8>>();
+        assert_eq!(served, [1, 2, 1, 2, 1]);
+        assert_eq!(device.queued_frames(), 2);
+    }
+
+    #[test]
+    fn frame_errors_once_frames_run_out() {
+        let mut device = open_device();
+        device.push_frame(vec![1]);
+
+        assert!(device.frame().is_ok());
+        assert!(matches!(
+            device.frame(),
+            Err(NokhwaError::ReadFrameError(_))
+        ));
+    }
+
+    #[test]
+    fn frame_errors_before_stream_is_opened() {
+        let mut device = MockCaptureDevice::new(vec![VGA]);
+        device.push_frame(vec![1]);
+
+        assert!(matches!(
+            device.frame(),
+            Err(NokhwaError::ReadFrameError(_))
+        ));
+        assert_eq!(device.queued_frames(), 1);
+    }
+
+    #[test]
+    fn pushed_frame_errors_are_returned_in_place() {
+        let mut device = open_device();
+        device.push_frame(vec![1]);
+        device.push_frame_error(NokhwaError::ReadFrameError("dropped".to_string()));
+        device.push_frame(vec![2]);
+
+        assert_eq!(device.frame().unwrap().buffer(), [1]);
+        assert!(device.frame().is_err());
+        assert_eq!(device.frame().unwrap().buffer(), [2]);
+    }
+
+    #[test]
+    fn scripted_failures_fail_once_per_operation() {
+        let mut device = MockCaptureDevice::new(vec![VGA]);
+        device.fail_next(MockOperation::Init, NokhwaError::UnitializedError);
+        device.fail_next(
+            MockOperation::OpenStream,
+            NokhwaError::OpenStreamError("busy".to_string()),
+        );
+        device.fail_next(
+            MockOperation::OpenStream,
+            NokhwaError::OpenStreamError("still busy".to_string()),
+        );
+
+        assert!(matches!(device.init(), Err(NokhwaError::UnitializedError)));
+        assert!(device.init().is_ok());
+        for expected in ["busy", "still busy"] {
+            match device.open_stream() {
+                Err(NokhwaError::OpenStreamError(why)) => assert_eq!(why, expected),
+                other => panic!("expected an OpenStreamError, got {other:?}"),
+            }
+        }
+        assert!(device.open_stream().is_ok());
+        assert!(device.is_stream_open());
+    }
+
+    #[test]
+    fn scripted_failures_cover_every_operation() {
+        let mut device = open_device();
+        device.push_frame(vec![1]);
+        device.fail_next(
+            MockOperation::Frame,
+            NokhwaError::ReadFrameError("unplugged".to_string()),
+        );
+        device.fail_next(
+            MockOperation::SetFormat,
+            NokhwaError::GeneralError("format".to_string()),
+        );
+        device.fail_next(
+            MockOperation::StopStream,
+            NokhwaError::GeneralError("stop".to_string()),
+        );
+
+        // The failed frame does not consume a queued frame.
+        assert!(device.frame().is_err());
+        assert_eq!(device.frame().unwrap().buffer(), [1]);
+
+        assert!(device.set_camera_format(VGA).is_err());
+        assert!(device.set_camera_format(VGA).is_ok());
+
+        assert!(device.stop_stream().is_err());
+        assert!(device.is_stream_open());
+        assert!(device.stop_stream().is_ok());
+        assert!(!device.is_stream_open());
+    }
+
+    #[test]
+    fn unknown_formats_are_rejected() {
+        let mut device = MockCaptureDevice::new(vec![VGA]);
+        assert_eq!(device.camera_format(), Some(VGA));
+
+        assert!(matches!(
+            device.set_camera_format(HD),
+            Err(NokhwaError::SetPropertyError { .. })
+        ));
+        assert!(device.set_resolution(Resolution::HD_720P).is_err());
+        assert!(device.set_frame_format(FrameFormat::MJpeg).is_err());
+        assert_eq!(device.camera_format(), Some(VGA));
+    }
+
+    #[test]
+    fn known_formats_are_accepted() {
+        let mut device = MockCaptureDevice::new(vec![VGA, HD]);
+
+        assert!(device.set_resolution(Resolution::HD_720P).is_ok());
+        assert_eq!(device.camera_format(), Some(HD));
+    }
+}
@@ -21,11 +21,11 @@ macro_rules! resolver_platform {
         $(
             paste::paste! {
                 #[cfg(all(feature = $feat, target_os = $os))]
-                pub(crate) fn [< backend_gen_ $name >](index: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::Backend + nokhwa_core::traits::CaptureTrait>, nokhwa_core::error::NokhwaError> {
-                    $item::new(index).map(|x| std::boxed::Box::new(x.into()))
+                pub(crate) fn [< backend_gen_ $name >](index: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::CaptureTrait + Send>, nokhwa_core::error::NokhwaError> {
+                    $item::new(&index).map(|x| std::boxed::Box::new(x) as Box<dyn nokhwa_core::traits::CaptureTrait + Send>)
                 }
                 #[cfg(not(all(feature = $feat, target_os = $os)))]
-                pub(crate) fn [< backend_gen_ $name >](_: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::Backend + nokhwa_core::traits::CaptureTrait>, nokhwa_core::error::NokhwaError> {
+                pub(crate) fn [< backend_gen_ $name >](_: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::CaptureTrait + Send>, nokhwa_core::error::NokhwaError> {
                     return Err(nokhwa_core::error::NokhwaError::GeneralError("no feature".to_string()))
                 }
             }
@@ -39,12 +39,12 @@ macro_rules! resolver_platform_2 {
     ) => {
         $(
             paste::paste! {
-                #[cfg(all(feature = $feat, target_os = $os1, target_os = $os2))]
-                pub(crate) fn [< backend_gen_ $name >](index: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::Backend + nokhwa_core::traits::CaptureTrait>, nokhwa_core::error::NokhwaError> {
-                    $item::new(index).map(|x| std::boxed::Box::new(x.into()))
+                #[cfg(all(feature = $feat, any(target_os = $os1, target_os = $os2)))]
+                pub(crate) fn [< backend_gen_ $name >](index: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::CaptureTrait + Send>, nokhwa_core::error::NokhwaError> {
+                    $item::new(&index).map(|x| std::boxed::Box::new(x) as Box<dyn nokhwa_core::traits::CaptureTrait + Send>)
                 }
-                #[cfg(not(all(feature = $feat, target_os = $os1, target_os = $os2)))]
-                pub(crate) fn [< backend_gen_ $name >](_: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::Backend + nokhwa_core::traits::CaptureTrait>, nokhwa_core::error::NokhwaError> {
+                #[cfg(not(all(feature = $feat, any(target_os = $os1, target_os = $os2))))]
+                pub(crate) fn [< backend_gen_ $name >](_: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::CaptureTrait + Send>, nokhwa_core::error::NokhwaError> {
                     return Err(nokhwa_core::error::NokhwaError::GeneralError("no feature".to_string()))
                 }
             }
@@ -59,11 +59,11 @@ macro_rules! resolver_cross_platform {
         $(
             paste::paste! {
                 #[cfg(all(feature = $feat))]
-                pub(crate) fn [< backend_gen_ $name >](index: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::Backend + nokhwa_core::traits::CaptureTrait>, nokhwa_core::error::NokhwaError> {
-                    $item::new(index).map(|x| std::boxed::Box::new(x.into()))
+                pub(crate) fn [< backend_gen_ $name >](index: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::CaptureTrait + Send>, nokhwa_core::error::NokhwaError> {
+                    $item::new(&index).map(|x| std::boxed::Box::new(x) as Box<dyn nokhwa_core::traits::CaptureTrait + Send>)
                 }
                 #[cfg(not(all(feature = $feat)))]
-                pub(crate) fn [< backend_gen_ $name >](_: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::Backend + nokhwa_core::traits::CaptureTrait>, nokhwa_core::error::NokhwaError> {
+                pub(crate) fn [< backend_gen_ $name >](_: nokhwa_core::types::CameraIndex) -> Result<Box<dyn nokhwa_core::traits::CaptureTrait + Send>, nokhwa_core::error::NokhwaError> {
                     return Err(nokhwa_core::error::NokhwaError::GeneralError("no feature".to_string()))
                 }
             }
@@ -100,7 +100,9 @@ resolver_cross_platform!(
 
 #[cfg(all(feature = "input-v4l", target_os = "linux"))]
 #[cfg_attr(feature = "docs-features", doc(cfg(feature = "input-v4l")))]
-pub use nokhwa_bindings_linux::V4LCaptureDevice;
+pub use nokhwa_bindings_linux::{
+    CameraId, CaptureScheduler, SchedulerEvent, V4LCaptureDevice, DEFAULT_EVENT_CAPACITY,
+};
 #[cfg(any(
     all(feature = "input-msmf", target_os = "windows"),
     all(feature = "docs-only", feature = "docs-nolink", feature = "input-msmf")
@@ -168,3 +170,9 @@ mod opencv_backend;
 #[cfg(feature = "input-opencv")]
 #[cfg_attr(feature = "docs-features", doc(cfg(feature = "input-opencv")))]
 pub use opencv_backend::OpenCvCaptureDevice;
+#[cfg(feature = "input-mock")]
+mod mock_backend;
+
+#[cfg(feature = "input-mock")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "input-mock")))]
+pub use mock_backend::{MockCaptureDevice, MockOperation};
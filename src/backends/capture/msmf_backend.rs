@@ -51,9 +51,9 @@ impl MediaFoundationCaptureDevice {
         let mut mf_device = MediaFoundationDevice::new(index.clone())?;
 
         let info = CameraInfo::new(
-            &mf_device.name(),
+            mf_device.name(),
             "MediaFoundation Camera Device",
-            &mf_device.symlink(),
+            mf_device.symlink(),
             index.clone(),
         );
 
@@ -87,7 +87,7 @@ impl MediaFoundationCaptureDevice {
         fps: u32,
         fourcc: FrameFormat,
     ) -> Result<Self, NokhwaError> {
-        let camera_format = RequestedFormat::new::<RgbFormat>(RequestedFormatType::Exact(
+        let camera_format = RequestedFormat::new(RequestedFormatType::Exact(
             CameraFormat::new_from(width, height, fourcc, fps),
         ));
         MediaFoundationCaptureDevice::new(index, camera_format)
@@ -244,9 +244,9 @@ impl CaptureTrait for MediaFoundationCaptureDevice {
     fn frame(&mut self) -> Result<Buffer, NokhwaError> {
         self.refresh_camera_format()?;
         let self_ctrl = self.camera_format();
-        Ok(Buffer::new(
+        Ok(Buffer::from_cow(
             self_ctrl.resolution(),
-            &self.inner.raw_bytes()?,
+            self.inner.raw_bytes()?,
             self_ctrl.format(),
         ))
     }
@@ -103,7 +103,7 @@ impl OpenCvCaptureDevice {
         let api_pref = if index.is_string() {
             CAP_ANY
         } else {
-            get_api_pref_int()
+            api_pref_int()
         };
 
         let mut video_capture = match &index {
@@ -482,22 +482,20 @@ impl CaptureTrait for OpenCvCaptureDevice {
     #[allow(clippy::cast_possible_wrap)]
     fn open_stream(&mut self) -> Result<(), NokhwaError> {
         match self.camera_location.clone() {
-            CameraIndex::Index(idx) => {
-                match self.video_capture.open(idx as i32, get_api_pref_int()) {
-                    Ok(open) => {
-                        if open {
-                            return Ok(());
-                        }
-                        Err(NokhwaError::OpenStreamError(
-                            "Stream is not opened after stream open attempt opencv".to_string(),
-                        ))
+            CameraIndex::Index(idx) => match self.video_capture.open(idx as i32, api_pref_int()) {
+                Ok(open) => {
+                    if open {
+                        return Ok(());
                     }
-                    Err(why) => Err(NokhwaError::OpenDeviceError(
-                        idx.to_string(),
-                        format!("Failed to open device: {why}"),
-                    )),
+                    Err(NokhwaError::OpenStreamError(
+                        "Stream is not opened after stream open attempt opencv".to_string(),
+                    ))
                 }
-            }
+                Err(why) => Err(NokhwaError::OpenDeviceError(
+                    idx.to_string(),
+                    format!("Failed to open device: {why}"),
+                )),
+            },
             CameraIndex::String(_) => Err(NokhwaError::OpenDeviceError(
                 "Cannot open".to_string(),
                 "String index not supported (try NetworkCamera instead)".to_string(),
@@ -534,9 +532,9 @@ impl CaptureTrait for OpenCvCaptureDevice {
             );
             data
         };
-        Ok(Buffer::new(
+        Ok(Buffer::from_bytes(
             camera_resolution,
-            &image_data,
+            image_data,
             self.camera_format.format(),
         ))
     }
@@ -554,7 +552,7 @@ impl CaptureTrait for OpenCvCaptureDevice {
     }
 }
 
-fn get_api_pref_int() -> i32 {
+fn api_pref_int() -> i32 {
     match std::env::consts::OS {
         "linux" => CAP_V4L2,
         "windows" => CAP_MSMF,
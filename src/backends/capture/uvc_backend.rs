@@ -17,8 +17,8 @@
 #![allow(clippy::too_many_arguments)]
 
 use crate::{
-    ApiBackend, CameraControl, CameraFormat, CameraInfo, CaptureBackendTrait, FrameFormat,
-    KnownCameraControl, KnownCameraControlFlag, NokhwaError, Resolution,
+    ApiBackend, CameraControl, CameraFormat, CameraIndex, CameraInfo, CaptureBackendTrait,
+    FrameFormat, KnownCameraControl, KnownCameraControlFlag, NokhwaError, Resolution,
 };
 use flume::{Receiver, Sender};
 use image::{ImageBuffer, Rgb};
@@ -83,7 +83,8 @@ impl<'a> UVCCaptureDevice<'a> {
     /// This operation may panic! If the UVC Context fails to retrieve the device from the gotten IDs, this operation will panic.
     /// # Errors
     /// This may error when the `libuvc` backend fails to retrieve the device or its data.
-    pub fn create(index: usize, cam_fmt: Option<CameraFormat>) -> Result<Self, NokhwaError> {
+    pub fn create(index: &CameraIndex, cam_fmt: Option<CameraFormat>) -> Result<Self, NokhwaError> {
+        let index = index.as_index()? as usize;
         let context = match Context::new() {
             Ok(ctx) => ctx,
             Err(why) => {
@@ -0,0 +1,269 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! `nokhwa-cli`: list cameras and their formats and controls, grab snapshots, preview a stream, and set controls.
+//!
+//! Build it with `cargo install nokhwa --features cli,input-native`. When reporting a bug, please include the output of
+//! `nokhwa-cli list` and `nokhwa-cli formats <device>`.
+
+use clap::{Parser, Subcommand};
+use minifb::{Key, Window, WindowOptions};
+use nokhwa::{frame_to_rgb, nokhwa_request_permission, prelude::*, ImageOutputFormat};
+use std::{path::PathBuf, process::ExitCode, str::FromStr, sync::mpsc};
+
+#[derive(Parser)]
+#[command(name = "nokhwa-cli", author, version, about, long_about = None)]
+struct Cli {
+    /// The backend to use (e.g. `Video4Linux`, `MediaFoundation`, `AVFoundation`, `OpenCv`). Defaults to the native backend.
+    #[arg(short, long, global = true)]
+    backend: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Lists the available cameras.
+    List,
+    /// Lists every format a camera supports, best first.
+    Formats {
+        #[arg(default_value = "0")]
+        device: String,
+    },
+    /// Lists every control of a camera and its current value.
+    Controls {
+        #[arg(default_value = "0")]
+        device: String,
+    },
+    /// Sets a control, e.g. `nokhwa-cli set 0 Brightness 128`.
+    Set {
+        device: String,
+        control: String,
+        value: String,
+    },
+    /// Captures a single frame and saves it as an image. The file extension picks the image format.
+    Snapshot {
+        #[arg(default_value = "0")]
+        device: String,
+        #[arg(default_value = "snapshot.png")]
+        path: PathBuf,
+        /// The format to capture in, e.g. `1920x1080@30 MJPG`. Defaults to the highest resolution.
+        #[arg(short, long)]
+        format: Option<String>,
+    },
+    /// Streams the camera into a preview window. Press escape to quit.
+    Preview {
+        #[arg(default_value = "0")]
+        device: String,
+        /// The format to stream in, e.g. `1280x720@30 YUYV`. Defaults to the highest resolution.
+        #[arg(short, long)]
+        format: Option<String>,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let (sender, receiver) = mpsc::channel();
+    nokhwa_request_permission(move |granted| {
+        let _ = sender.send(granted);
+    });
+    if !receiver.recv().unwrap_or(false) {
+        eprintln!("Camera permission was not granted.");
+        return ExitCode::FAILURE;
+    }
+
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(why) => {
+            eprintln!("Error: {why}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), NokhwaError> {
+    let backend = match &cli.backend {
+        Some(name) => parse_backend(name)?,
+        None => ApiBackend::Auto,
+    };
+
+    match cli.command {
+        Command::List => {
+            let api = match backend {
+                ApiBackend::Auto => {
+                    native_api_backend().ok_or(NokhwaError::UnsupportedOperationError(backend))?
+                }
+                api => api,
+            };
+            let cameras = query(api)?;
+            println!("{} camera(s) found with {api}:", cameras.len());
+            for camera in cameras {
+                println!(
+                    " - [{}] {} ({})",
+                    camera.index(),
+                    camera.human_name(),
+                    camera.description()
+                );
+            }
+        }
+        Command::Formats { device } => {
+            let mut camera = open(backend, &device)?;
+            let mut formats = camera.compatible_camera_formats()?;
+            CameraFormat::sort_best_first(&mut formats);
+            println!(
+                "{} supports {} format(s):",
+                camera.info().human_name(),
+                formats.len()
+            );
+            for format in formats {
+                println!(" - {format}");
+            }
+        }
+        Command::Controls { device } => {
+            let camera = open(backend, &device)?;
+            println!("Controls of {}:", camera.info().human_name());
+            for control in camera.camera_controls()? {
+                println!(" - {control}");
+            }
+        }
+        Command::Set {
+            device,
+            control,
+            value,
+        } => {
+            let mut camera = open(backend, &device)?;
+            let control = camera
+                .camera_controls()?
+                .into_iter()
+                .find(|known| known.control().to_string().eq_ignore_ascii_case(&control))
+                .ok_or_else(|| NokhwaError::GetPropertyError {
+                    property: control.clone(),
+                    error: "not found/not supported".to_string(),
+                })?;
+            camera.set_camera_control(control.control(), parse_control_value(&value))?;
+            println!("{}", camera.camera_control(control.control())?);
+        }
+        Command::Snapshot {
+            device,
+            path,
+            format,
+        } => {
+            let image_format = path
+                .extension()
+                .and_then(|extension| {
+                    ImageOutputFormat::from_extension(&extension.to_string_lossy())
+                })
+                .ok_or_else(|| NokhwaError::StructureError {
+                    structure: "ImageOutputFormat".to_string(),
+                    error: "The path must end in .png, .jpg, .jpeg, or .bmp".to_string(),
+                })?;
+            let mut camera = open_with_format(backend, &device, format.as_deref())?;
+            camera.save_frame(&path, image_format)?;
+            println!("Saved a frame to {}", path.display());
+        }
+        Command::Preview { device, format } => {
+            let mut camera = open_with_format(backend, &device, format.as_deref())?;
+            let camera_format = camera
+                .camera_format()
+                .ok_or(NokhwaError::UnitializedError)?;
+            let (width, height) = (
+                camera_format.width() as usize,
+                camera_format.height() as usize,
+            );
+            let mut window = Window::new(
+                &format!("{} - {camera_format}", camera.info().human_name()),
+                width,
+                height,
+                WindowOptions::default(),
+            )
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+            let mut pixels = vec![0_u32; width * height];
+
+            camera.open_stream()?;
+            while window.is_open() && !window.is_key_down(Key::Escape) {
+                let frame = camera.frame()?;
+                let rgb = frame_to_rgb(&frame, camera_format.format())?;
+                for (pixel, rgb) in pixels.iter_mut().zip(rgb.chunks_exact(3)) {
+                    *pixel = u32::from_be_bytes([0, rgb[0], rgb[1], rgb[2]]);
+                }
+                window
+                    .update_with_buffer(&pixels, width, height)
+                    .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+            }
+            camera.stop_stream()?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_backend(name: &str) -> Result<ApiBackend, NokhwaError> {
+    [
+        ApiBackend::Auto,
+        ApiBackend::AVFoundation,
+        ApiBackend::Video4Linux,
+        ApiBackend::UniversalVideoClass,
+        ApiBackend::MediaFoundation,
+        ApiBackend::OpenCv,
+        ApiBackend::GStreamer,
+        ApiBackend::Browser,
+    ]
+    .into_iter()
+    .find(|api| api.to_string().eq_ignore_ascii_case(name))
+    .ok_or_else(|| NokhwaError::StructureError {
+        structure: "ApiBackend".to_string(),
+        error: format!("Unknown backend {name}"),
+    })
+}
+
+fn parse_control_value(value: &str) -> ControlValueSetter {
+    if let Ok(value) = value.parse::<i64>() {
+        ControlValueSetter::Integer(value)
+    } else if let Ok(value) = value.parse::<f64>() {
+        ControlValueSetter::Float(value)
+    } else if let Ok(value) = value.parse::<bool>() {
+        ControlValueSetter::Boolean(value)
+    } else {
+        ControlValueSetter::String(value.to_string())
+    }
+}
+
+fn device_index(device: &str) -> CameraIndex {
+    match device.parse::<u32>() {
+        Ok(index) => CameraIndex::Index(index),
+        Err(_) => CameraIndex::String(device.to_string()),
+    }
+}
+
+fn open(backend: ApiBackend, device: &str) -> Result<Camera, NokhwaError> {
+    let mut camera = Camera::with_api_backend(device_index(device), backend)?;
+    camera.init()?;
+    Ok(camera)
+}
+
+fn open_with_format(
+    backend: ApiBackend,
+    device: &str,
+    format: Option<&str>,
+) -> Result<Camera, NokhwaError> {
+    let request = match format {
+        Some(format) => {
+            RequestedFormat::new(RequestedFormatType::Exact(CameraFormat::from_str(format)?))
+        }
+        None => RequestedFormat::new(RequestedFormatType::AbsoluteHighestResolution),
+    };
+    Camera::with_backend(backend, device_index(device), request)
+}
@@ -14,111 +14,619 @@
  * limitations under the License.
  */
 
+use crate::{
+    backends::capture::{backend_gen_avf, backend_gen_msf, backend_gen_opencv, backend_gen_v4l},
+    native_api_backend, CameraBuilder, LatencyStats, StreamStats,
+};
+#[cfg(feature = "output-image")]
+use crate::{ColorCorrection, Overlay, OverlayContext};
 use nokhwa_core::format_request::FormatFilter;
 use nokhwa_core::frame_format::SourceFrameFormat;
-use nokhwa_core::traits::Backend;
+#[cfg(feature = "output-image")]
+use nokhwa_core::pool::FrameBuffer;
 use nokhwa_core::{
     buffer::Buffer,
     error::NokhwaError,
     pixel_format::FormatDecoder,
     traits::CaptureTrait,
     types::{
-        ApiBackend, CameraControl, CameraFormat, CameraIndex, CameraInfo, ControlValueSetter,
-        FrameFormat, KnownCameraControl, RequestedFormatType, Resolution,
+        ApiBackend, CameraControl, CameraFormat, CameraIndex, CameraInfo, ControlValueDescription,
+        ControlValueSetter, Deinterlace, ExposureValue, FrameFormat, KnownCameraControl, Region,
+        RequestedFormat, Resolution, Rotation,
     },
 };
-use std::{borrow::Cow, collections::HashMap};
-#[cfg(feature = "output-wgpu")]
-use wgpu::{Device as WgpuDevice, Queue as WgpuQueue, Texture as WgpuTexture};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    time::{Instant, SystemTime},
+};
+
+/// How many frames [`Camera::capture_bracketed()`] skips after the first frame captured with a new exposure, for cameras that
+/// take a frame to apply it.
+pub const DEFAULT_BRACKET_SETTLE_FRAMES: usize = 1;
+
+/// The most frames [`Camera::capture_bracketed()`] waits for a new exposure to take effect, in case frames have no timestamps.
+const BRACKET_MAX_STALE_FRAMES: usize = 8;
 
 /// The main `Camera` struct. This is the struct that abstracts over all the backends, providing a simplified interface for use.
+///
+/// The backend is chosen by platform and enabled features (see [`native_api_backend()`]), so applications never need to name
+/// a backend struct such as `V4LCaptureDevice` directly.
 pub struct Camera {
     idx: CameraIndex,
     api: ApiBackend,
-    device: Box<dyn CaptureTrait + Backend>,
+    device: Box<dyn CaptureTrait + Send>,
+    latency: LatencyStats,
+    stream_stats: StreamStats,
+    auto_rotate: bool,
+    #[cfg(feature = "output-image")]
+    deinterlace: Option<Deinterlace>,
+    #[cfg(feature = "output-image")]
+    square_pixels: bool,
+    #[cfg(feature = "output-image")]
+    software_crop: Option<Region>,
+    #[cfg(feature = "output-image")]
+    color_correction: Option<ColorCorrection>,
+    #[cfg(feature = "output-image")]
+    overlay: Option<Overlay>,
 }
 
 impl Camera {
-    pub fn new() -> Result<Self, NokhwaError> {}
+    /// Starts configuring a camera with a [`CameraBuilder`].
+    #[must_use]
+    pub fn builder() -> CameraBuilder {
+        CameraBuilder::new()
+    }
+
+    /// Create a new camera from an `index`, using the native backend of the platform.
+    /// # Errors
+    /// This will error if there is no native backend for this platform (or its feature is not enabled), or the backend fails to open the camera.
+    pub fn new(index: impl Into<CameraIndex>) -> Result<Self, NokhwaError> {
+        Self::with_api_backend(index, ApiBackend::Auto)
+    }
+
+    /// Create a new camera from an `index` using the native backend, and set it to the format that best fits the [`RequestedFormat`].
+    /// # Errors
+    /// This will error if the camera fails to open, or nothing the camera supports fits the request.
+    pub fn with_request(
+        index: impl Into<CameraIndex>,
+        request: RequestedFormat,
+    ) -> Result<Self, NokhwaError> {
+        let mut camera = Self::new(index)?;
+        camera.init()?;
+        camera.set_camera_request(request)?;
+        Ok(camera)
+    }
+
+    /// Create a new camera from an `index` with a specific [`ApiBackend`]. [`ApiBackend::Auto`] picks the native backend.
+    /// # Errors
+    /// This will error if the backend is not supported on this platform, its feature is not enabled, or it fails to open the camera.
+    pub fn with_api_backend(
+        index: impl Into<CameraIndex>,
+        api: ApiBackend,
+    ) -> Result<Self, NokhwaError> {
+        let index = index.into();
+        let api = match api {
+            ApiBackend::Auto => {
+                native_api_backend().ok_or(NokhwaError::UnsupportedOperationError(api))?
+            }
+            api => api,
+        };
+        let device = traced("Open camera", open_backend(&index, api))?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(%index, backend = %api, "Opened camera");
+        Ok(Camera {
+            idx: index,
+            api,
+            device,
+            latency: LatencyStats::default(),
+            stream_stats: StreamStats::default(),
+            auto_rotate: true,
+            #[cfg(feature = "output-image")]
+            deinterlace: None,
+            #[cfg(feature = "output-image")]
+            square_pixels: false,
+            #[cfg(feature = "output-image")]
+            software_crop: None,
+            #[cfg(feature = "output-image")]
+            color_correction: None,
+            #[cfg(feature = "output-image")]
+            overlay: None,
+        })
+    }
+
+    /// Create a new camera with an explicitly chosen [`ApiBackend`], and set it to the format that best fits the [`RequestedFormat`].
+    /// This is for applications that let the user pick the backend (e.g. `V4L2` or `OpenCV`) at runtime.
+    /// # Errors
+    /// This will error if the backend is not supported on this platform, its feature is not enabled, it fails to open the camera,
+    /// or nothing the camera supports fits the request.
+    pub fn with_backend(
+        api: ApiBackend,
+        index: impl Into<CameraIndex>,
+        request: RequestedFormat,
+    ) -> Result<Self, NokhwaError> {
+        let mut camera = Self::with_api_backend(index, api)?;
+        camera.init()?;
+        camera.set_camera_request(request)?;
+        Ok(camera)
+    }
+
+    /// Create a new camera from a custom backend, e.g. one you wrote yourself.
+    pub fn with_custom_backend(device: impl CaptureTrait + Send + 'static) -> Self {
+        Camera {
+            idx: device.camera_info().index().clone(),
+            api: device.backend(),
+            device: Box::new(device),
+            latency: LatencyStats::default(),
+            stream_stats: StreamStats::default(),
+            auto_rotate: true,
+            #[cfg(feature = "output-image")]
+            deinterlace: None,
+            #[cfg(feature = "output-image")]
+            square_pixels: false,
+            #[cfg(feature = "output-image")]
+            software_crop: None,
+            #[cfg(feature = "output-image")]
+            color_correction: None,
+            #[cfg(feature = "output-image")]
+            overlay: None,
+        }
+    }
+
+    /// Gets the current Camera's index.
+    #[must_use]
+    pub fn index(&self) -> &CameraIndex {
+        &self.idx
+    }
+
+    /// Sets the current Camera's index. This re-opens the camera with the same backend, and stops the stream if it is open.
+    /// # Errors
+    /// The backend may fail to open the new camera. In that case, the old camera is kept.
+    pub fn set_index(&mut self, new_idx: &CameraIndex) -> Result<(), NokhwaError> {
+        self.reopen(new_idx.clone(), self.api)
+    }
+
+    /// Sets the current Camera's backend. This re-opens the camera with the new backend, and stops the stream if it is open.
+    /// # Errors
+    /// The new backend may not exist or may fail to open the camera. In that case, the old camera is kept.
+    pub fn set_backend(&mut self, new_backend: ApiBackend) -> Result<(), NokhwaError> {
+        self.reopen(self.idx.clone(), new_backend)
+    }
+
+    /// Gets the camera information such as Name and Index as a [`CameraInfo`].
+    #[must_use]
+    pub fn info(&self) -> &CameraInfo {
+        self.device.camera_info()
+    }
+
+    /// Rolling statistics of how long frames take to reach you, and how long decoding them takes. Use this to see where the
+    /// milliseconds go when tuning a real-time pipeline.
+    #[must_use]
+    pub fn latency_stats(&self) -> &LatencyStats {
+        &self.latency
+    }
+
+    /// Forgets the samples of [`latency_stats()`](Self::latency_stats), e.g. after changing the format.
+    pub fn reset_latency_stats(&mut self) {
+        self.latency.clear();
+    }
+
+    /// What the stream actually delivers: the measured frame rate and bytes per second over a rolling window, and how many
+    /// frames failed to read or decode. Drivers often deliver fewer frames than the configured [`frame_rate()`](Self::frame_rate),
+    /// e.g. when auto exposure lengthens the exposure in the dark.
+    #[must_use]
+    pub fn stream_stats(&self) -> &StreamStats {
+        &self.stream_stats
+    }
+
+    /// Forgets the counts of [`stream_stats()`](Self::stream_stats).
+    pub fn reset_stream_stats(&mut self) {
+        self.stream_stats.clear();
+    }
+
+    /// The rotation [`frame_rgb()`](Self::frame_rgb) and [`frame_rgba()`](Self::frame_rgba) apply to make frames upright.
+    /// This is the sensor [`rotation()`](CameraInfo::rotation) if [`auto_rotate()`](Self::auto_rotate) is on, and
+    /// [`Rotation::None`] otherwise. The decoded frames are [`rotation().apply(resolution())`](Rotation::apply) in size.
+    #[must_use]
+    pub fn rotation(&self) -> Rotation {
+        if self.auto_rotate {
+            self.info().rotation().unwrap_or_default()
+        } else {
+            Rotation::None
+        }
+    }
 
-    pub fn with_api_backend() -> Result<Self, NokhwaError> {}
+    /// Whether decoded frames are rotated to compensate for how the sensor is mounted. On by default.
+    #[must_use]
+    pub fn auto_rotate(&self) -> bool {
+        self.auto_rotate
+    }
+
+    /// Turns rotating decoded frames upright on or off. Turn it off if you rotate frames yourself, e.g. on the GPU.
+    /// Raw frames from [`frame()`](Self::frame) are never rotated.
+    pub fn set_auto_rotate(&mut self, auto_rotate: bool) {
+        self.auto_rotate = auto_rotate;
+    }
+
+    /// Sets how [`frame_rgb()`](Self::frame_rgb) and [`frame_rgba()`](Self::frame_rgba) deinterlace frames the backend
+    /// reports as interlaced (see [`Buffer::field_order()`]), e.g. from analog capture cards. `None`, the default, leaves the
+    /// fields combed. See [`deinterlace()`](crate::deinterlace).
+    #[cfg(feature = "output-image")]
+    pub fn set_deinterlace(&mut self, method: Option<Deinterlace>) {
+        self.deinterlace = method;
+    }
+
+    /// How interlaced frames are deinterlaced when decoded, if at all.
+    #[cfg(feature = "output-image")]
+    #[must_use]
+    pub fn deinterlace(&self) -> Option<Deinterlace> {
+        self.deinterlace
+    }
+
+    /// Turns resampling frames with non-square pixels (see [`Buffer::pixel_aspect()`]) to square pixels in
+    /// [`frame_rgb()`](Self::frame_rgb) and [`frame_rgba()`](Self::frame_rgba) on or off. Off by default. When on, decoded frames
+    /// are [`square_resolution()`](nokhwa_core::types::PixelAspectRatio::square_resolution) wide. See
+    /// [`resample_square_pixels()`](crate::resample_square_pixels).
+    #[cfg(feature = "output-image")]
+    pub fn set_square_pixels(&mut self, square_pixels: bool) {
+        self.square_pixels = square_pixels;
+    }
+
+    /// Whether decoded frames are resampled to square pixels.
+    #[cfg(feature = "output-image")]
+    #[must_use]
+    pub fn square_pixels(&self) -> bool {
+        self.square_pixels
+    }
 
-    pub fn with_custom_backend() -> Result<Self, NokhwaError> {}
+    /// Sets the [`ColorCorrection`] (gamma and color matrix) that [`frame_rgb()`](Self::frame_rgb) and
+    /// [`frame_rgba()`](Self::frame_rgba) apply to frames, before drawing the [`overlay()`](Self::overlay). Pass `None`, the
+    /// default, to leave colors as the camera delivers them.
+    #[cfg(feature = "output-image")]
+    pub fn set_color_correction(&mut self, color_correction: Option<ColorCorrection>) {
+        self.color_correction = color_correction;
+    }
+
+    /// The [`ColorCorrection`] applied to decoded frames, if any.
+    #[cfg(feature = "output-image")]
+    #[must_use]
+    pub fn color_correction(&self) -> Option<&ColorCorrection> {
+        self.color_correction.as_ref()
+    }
+
+    /// Sets the [`Overlay`] (e.g. a burned-in timestamp) that [`frame_rgb()`](Self::frame_rgb) and
+    /// [`frame_rgba()`](Self::frame_rgba) draw onto frames, after rotating them upright. Pass `None` to remove it.
+    #[cfg(feature = "output-image")]
+    pub fn set_overlay(&mut self, overlay: Option<Overlay>) {
+        self.overlay = overlay;
+    }
+
+    /// The [`Overlay`] drawn onto decoded frames, if any.
+    #[cfg(feature = "output-image")]
+    #[must_use]
+    pub fn overlay(&self) -> Option<&Overlay> {
+        self.overlay.as_ref()
+    }
+
+    /// Captures a frame and converts it to packed RGB888, timing the decode (or conversion) in
+    /// [`latency_stats()`](Self::latency_stats). See [`frame_to_rgb()`](crate::frame_to_rgb).
+    /// # Errors
+    /// If capturing or decoding the frame fails, this will error.
+    #[cfg(feature = "output-image")]
+    pub fn frame_rgb(&mut self) -> Result<FrameBuffer, NokhwaError> {
+        self.timed_decode(crate::frame_to_rgb)
+    }
+
+    /// Captures a frame and converts it to packed RGBA8888, timing the decode (or conversion) in
+    /// [`latency_stats()`](Self::latency_stats). See [`frame_to_rgba()`](crate::frame_to_rgba).
+    /// # Errors
+    /// If capturing or decoding the frame fails, this will error.
+    #[cfg(feature = "output-image")]
+    pub fn frame_rgba(&mut self) -> Result<FrameBuffer, NokhwaError> {
+        self.timed_decode(crate::frame_to_rgba)
+    }
+
+    #[cfg(feature = "output-image")]
+    fn timed_decode(
+        &mut self,
+        decode: fn(&Buffer, FrameFormat) -> Result<FrameBuffer, NokhwaError>,
+    ) -> Result<FrameBuffer, NokhwaError> {
+        let frame = self.frame()?;
+        let format = frame.source_frame_format();
+        let started = Instant::now();
+        let decoded = decode(&frame, format).map_err(|why| {
+            self.stream_stats.record_decode_failure();
+            why
+        })?;
+        if format == FrameFormat::MJpeg {
+            self.latency.record_decode(started.elapsed());
+        } else {
+            self.latency.record_conversion(started.elapsed());
+        }
+        let resolution = frame.resolution();
+        let pixels = resolution.width() as usize * resolution.height() as usize;
+        let pixel_size = decoded.len() / pixels.max(1);
+        let decoded = match (self.deinterlace, frame.field_order()) {
+            (Some(method), Some(field_order)) if field_order.is_interlaced() => {
+                crate::deinterlace(&decoded, resolution, pixel_size, field_order, method)?
+            }
+            _ => decoded,
+        };
+        let (decoded, resolution) = match frame.pixel_aspect() {
+            Some(pixel_aspect) if self.square_pixels && !pixel_aspect.is_square() => (
+                crate::resample_square_pixels(&decoded, resolution, pixel_size, pixel_aspect)?,
+                pixel_aspect.square_resolution(resolution),
+            ),
+            _ => (decoded, resolution),
+        };
+        let (decoded, resolution) = match self
+            .software_crop
+            .and_then(|region| region.clamp_to(resolution))
+        {
+            Some(region) => (
+                crate::crop_pixels(&decoded, resolution, pixel_size, region)?,
+                region.resolution(),
+            ),
+            None => (decoded, resolution),
+        };
+        let rotation = self.rotation();
+        let mut decoded = match rotation {
+            Rotation::None => decoded,
+            rotation => crate::rotate_pixels(&decoded, resolution, pixel_size, rotation)?,
+        };
+        if let Some(color_correction) = &self.color_correction {
+            color_correction.apply(&mut decoded, pixel_size);
+        }
+        if let Some(overlay) = &self.overlay {
+            let context = OverlayContext {
+                captured: frame
+                    .timestamp()
+                    .map_or_else(SystemTime::now, |timestamp| timestamp.system()),
+                camera_name: self.info().human_name(),
+            };
+            overlay.apply(
+                &mut decoded,
+                rotation.apply(resolution),
+                pixel_size,
+                &context,
+            );
+        }
+        Ok(decoded)
+    }
+
+    /// Captures one frame per exposure, e.g. `[Stops(-2.0), Stops(0.0), Stops(2.0)]` for an HDR merge, then puts the exposure back.
+    /// Frames are returned in the order of `exposures`. See [`capture_bracketed_with()`](Self::capture_bracketed_with).
+    /// # Errors
+    /// See [`capture_bracketed_with()`](Self::capture_bracketed_with).
+    pub fn capture_bracketed(
+        &mut self,
+        exposures: &[ExposureValue],
+    ) -> Result<Vec<Buffer>, NokhwaError> {
+        self.capture_bracketed_with(exposures, DEFAULT_BRACKET_SETTLE_FRAMES)
+    }
+
+    /// Captures one frame per exposure, skipping the frames that were already in flight when the exposure was changed plus
+    /// `settle_frames` more, then puts the exposure back. The stream is opened (and closed again) if it is not open.
+    ///
+    /// Auto exposure must be off, otherwise the driver ignores or overrides the exposure. On `V4L2` that is the
+    /// `exposure_auto` control, which can be set with [`KnownCameraControl::Other`].
+    /// # Errors
+    /// If the camera has no integer exposure control, or setting it or capturing fails, this will error. The exposure is
+    /// put back even if capturing fails.
+    pub fn capture_bracketed_with(
+        &mut self,
+        exposures: &[ExposureValue],
+        settle_frames: usize,
+    ) -> Result<Vec<Buffer>, NokhwaError> {
+        let (current, min, max, step) = match self
+            .camera_control(KnownCameraControl::Exposure)?
+            .description()
+        {
+            ControlValueDescription::Integer { value, step, .. } => {
+                (*value, i64::MIN, i64::MAX, *step)
+            }
+            ControlValueDescription::IntegerRange {
+                min,
+                max,
+                value,
+                step,
+                ..
+            } => (*value, *min, *max, *step),
+            other => {
+                return Err(NokhwaError::GetPropertyError {
+                    property: KnownCameraControl::Exposure.to_string(),
+                    error: format!("Not an integer control: {other}"),
+                })
+            }
+        };
+        let opened = !self.is_stream_open();
+        if opened {
+            self.open_stream()?;
+        }
+
+        let mut frames = Vec::with_capacity(exposures.len());
+        let mut result = Ok(());
+        for exposure in exposures {
+            let value = exposure.resolve(current, min, max, step);
+            match self.bracket_frame(value, settle_frames) {
+                Ok(frame) => frames.push(frame),
+                Err(why) => {
+                    result = Err(why);
+                    break;
+                }
+            }
+        }
+
+        let restored = self.set_camera_control(
+            KnownCameraControl::Exposure,
+            ControlValueSetter::Integer(current),
+        );
+        if opened {
+            let _stop_stream_err = self.stop_stream();
+        }
+        result.and(restored).map(|()| frames)
+    }
+
+    /// Sets the exposure to `value`, waits for the first frame captured after the change, then skips `settle_frames` more.
+    fn bracket_frame(&mut self, value: i64, settle_frames: usize) -> Result<Buffer, NokhwaError> {
+        self.set_camera_control(
+            KnownCameraControl::Exposure,
+            ControlValueSetter::Integer(value),
+        )?;
+        let changed = SystemTime::now();
+        let mut skipped = 0;
+        let mut frame = loop {
+            let frame = self.frame()?;
+            let fresh = match frame.timestamp() {
+                Some(timestamp) => {
+                    let received = timestamp.system();
+                    let captured = timestamp
+                        .driver_latency()
+                        .and_then(|latency| received.checked_sub(latency))
+                        .unwrap_or(received);
+                    captured >= changed
+                }
+                // Without timestamps there is no telling, so assume the driver had a couple of frames queued.
+                None => skipped >= 2,
+            };
+            if fresh || skipped >= BRACKET_MAX_STALE_FRAMES {
+                break frame;
+            }
+            skipped += 1;
+        };
+        for _ in 0..settle_frames {
+            frame = self.frame()?;
+        }
+        Ok(frame)
+    }
+
+    fn reopen(&mut self, index: CameraIndex, api: ApiBackend) -> Result<(), NokhwaError> {
+        let mut camera = Camera::with_api_backend(index, api)?;
+        camera.auto_rotate = self.auto_rotate;
+        #[cfg(feature = "output-image")]
+        {
+            camera.deinterlace = self.deinterlace;
+            camera.square_pixels = self.square_pixels;
+            camera.software_crop = self.software_crop;
+            camera.color_correction = self.color_correction;
+            camera.overlay.clone_from(&self.overlay);
+        }
+        if self.device.is_stream_open() {
+            self.device.stop_stream()?;
+        }
+        *self = camera;
+        Ok(())
+    }
+}
+
+/// Emits a `tracing` event with the OS error code if `result` is an error. Does nothing without the `tracing` feature.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn traced<T>(operation: &str, result: Result<T, NokhwaError>) -> Result<T, NokhwaError> {
+    #[cfg(feature = "tracing")]
+    if let Err(why) = &result {
+        tracing::warn!(operation, error = %why, os_error_code = ?why.os_error_code(), "Camera operation failed");
+    }
+    result
+}
+
+fn open_backend(
+    index: &CameraIndex,
+    api: ApiBackend,
+) -> Result<Box<dyn CaptureTrait + Send>, NokhwaError> {
+    match api {
+        ApiBackend::Video4Linux => backend_gen_v4l(index.clone()),
+        ApiBackend::MediaFoundation => backend_gen_msf(index.clone()),
+        ApiBackend::AVFoundation => backend_gen_avf(index.clone()),
+        ApiBackend::OpenCv => backend_gen_opencv(index.clone()),
+        api => Err(NokhwaError::UnsupportedOperationError(api)),
+    }
 }
 
 impl CaptureTrait for Camera {
     fn init(&mut self) -> Result<(), NokhwaError> {
-        todo!()
+        self.device.init()
     }
 
     fn init_with_format(&mut self, format: FormatFilter) -> Result<CameraFormat, NokhwaError> {
-        todo!()
+        self.device.init_with_format(format)
     }
 
     fn backend(&self) -> ApiBackend {
-        todo!()
+        self.api
     }
 
     fn camera_info(&self) -> &CameraInfo {
-        todo!()
+        self.device.camera_info()
     }
 
     fn refresh_camera_format(&mut self) -> Result<(), NokhwaError> {
-        todo!()
+        self.device.refresh_camera_format()
     }
 
     fn camera_format(&self) -> Option<CameraFormat> {
-        todo!()
+        self.device.camera_format()
     }
 
     fn set_camera_format(&mut self, new_fmt: CameraFormat) -> Result<(), NokhwaError> {
-        todo!()
+        traced("Set format", self.device.set_camera_format(new_fmt))?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(format = %new_fmt, "Set format");
+        Ok(())
+    }
+
+    fn set_camera_request(
+        &mut self,
+        request: RequestedFormat,
+    ) -> Result<CameraFormat, NokhwaError> {
+        #[cfg(feature = "tracing")]
+        let requested = request.clone();
+        let format = traced("Negotiate format", self.device.set_camera_request(request))?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(%requested, %format, "Negotiated format");
+        Ok(format)
     }
 
     fn compatible_list_by_resolution(
         &mut self,
         fourcc: SourceFrameFormat,
     ) -> Result<HashMap<Resolution, Vec<u32>>, NokhwaError> {
-        todo!()
+        self.device.compatible_list_by_resolution(fourcc)
     }
 
     fn compatible_fourcc(&mut self) -> Result<Vec<SourceFrameFormat>, NokhwaError> {
-        todo!()
+        self.device.compatible_fourcc()
     }
 
     fn resolution(&self) -> Option<Resolution> {
-        todo!()
+        self.device.resolution()
     }
 
     fn set_resolution(&mut self, new_res: Resolution) -> Result<(), NokhwaError> {
-        todo!()
+        self.device.set_resolution(new_res)
     }
 
     fn frame_rate(&self) -> Option<u32> {
-        todo!()
+        self.device.frame_rate()
     }
 
     fn set_frame_rate(&mut self, new_fps: u32) -> Result<(), NokhwaError> {
-        todo!()
+        self.device.set_frame_rate(new_fps)
     }
 
     fn frame_format(&self) -> SourceFrameFormat {
-        todo!()
+        self.device.frame_format()
     }
 
-    fn set_frame_format(
-        &mut self,
-        fourcc: impl Into<SourceFrameFormat>,
-    ) -> Result<(), NokhwaError> {
-        todo!()
+    fn set_frame_format(&mut self, fourcc: SourceFrameFormat) -> Result<(), NokhwaError> {
+        self.device.set_frame_format(fourcc)
     }
 
     fn camera_control(&self, control: KnownCameraControl) -> Result<CameraControl, NokhwaError> {
-        todo!()
+        self.device.camera_control(control)
     }
 
     fn camera_controls(&self) -> Result<Vec<CameraControl>, NokhwaError> {
-        todo!()
+        self.device.camera_controls()
     }
 
     fn set_camera_control(
@@ -126,34 +634,93 @@ impl CaptureTrait for Camera {
         id: KnownCameraControl,
         value: ControlValueSetter,
     ) -> Result<(), NokhwaError> {
-        todo!()
+        traced("Set control", self.device.set_camera_control(id, value))
+    }
+
+    fn crop(&self) -> Option<Region> {
+        #[cfg(feature = "output-image")]
+        if self.software_crop.is_some() {
+            return self.software_crop;
+        }
+        self.device.crop()
+    }
+
+    /// Crops at the source if the device supports it (e.g. the `V4L2` selection API). Otherwise, with the `output-image`
+    /// feature, [`frame_rgb()`](Camera::frame_rgb) and [`frame_rgba()`](Camera::frame_rgba) crop decoded frames instead,
+    /// and `region` is returned as is. Raw frames are only ever cropped by the device.
+    fn set_crop(&mut self, region: Option<Region>) -> Result<Option<Region>, NokhwaError> {
+        match self.device.set_crop(region) {
+            Ok(granted) => {
+                #[cfg(feature = "output-image")]
+                {
+                    self.software_crop = None;
+                }
+                Ok(granted)
+            }
+            #[cfg(feature = "output-image")]
+            Err(NokhwaError::UnsupportedOperationError(_)) => {
+                self.software_crop = region;
+                Ok(region)
+            }
+            Err(why) => traced("Set crop", Err(why)),
+        }
     }
 
     fn open_stream(&mut self) -> Result<(), NokhwaError> {
-        todo!()
+        traced("Open stream", self.device.open_stream())?;
+        self.stream_stats.restart_window();
+        #[cfg(feature = "tracing")]
+        tracing::info!(index = %self.idx, format = ?self.device.camera_format(), "Opened stream");
+        Ok(())
     }
 
     fn is_stream_open(&self) -> bool {
-        todo!()
+        self.device.is_stream_open()
     }
 
     fn frame(&mut self) -> Result<Buffer, NokhwaError> {
-        todo!()
+        let frame = traced("Read frame", self.device.frame()).map_err(|why| {
+            self.stream_stats.record_read_failure();
+            why
+        })?;
+        self.stream_stats.record_frame(frame.buffer().len());
+        if let Some(timestamp) = frame.timestamp() {
+            self.latency
+                .record_delivery(timestamp.age_at(SystemTime::now()));
+        }
+        Ok(frame)
     }
 
     fn frame_raw(&mut self) -> Result<Cow<[u8]>, NokhwaError> {
-        todo!()
+        match self.device.frame_raw() {
+            Ok(frame) => {
+                self.stream_stats.record_frame(frame.len());
+                Ok(frame)
+            }
+            Err(why) => {
+                self.stream_stats.record_read_failure();
+                Err(why)
+            }
+        }
     }
 
     fn stop_stream(&mut self) -> Result<(), NokhwaError> {
-        todo!()
+        traced("Stop stream", self.device.stop_stream())?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(index = %self.idx, "Stopped stream");
+        Ok(())
     }
 }
 
 impl Drop for Camera {
     fn drop(&mut self) {
-        self.stop_stream().unwrap();
+        if self.is_stream_open() {
+            let _stop_stream_err = self.stop_stream();
+        }
     }
 }
 
-unsafe impl Send for Camera {}
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Camera>();
+};
@@ -0,0 +1,180 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::Camera;
+use nokhwa_core::{
+    error::NokhwaError,
+    frame_format::FrameFormat,
+    traits::CaptureTrait,
+    types::{
+        ApiBackend, CameraFormat, CameraIndex, ControlValueSetter, FrameRate, KnownCameraControl,
+        Resolution,
+    },
+};
+
+/// Configures a [`Camera`] and opens it in one go. Create one with [`Camera::builder()`].
+///
+/// Nothing is applied until [`open()`](Self::open). The requested format is checked against what the camera supports and then
+/// set all at once, so you never end up with a camera that has the new resolution but the old frame rate.
+/// ```ignore
+/// let camera = Camera::builder()
+///     .index(0)
+///     .resolution(1280, 720)
+///     .fps(30)
+///     .format(FrameFormat::MJpeg)
+///     .open()?;
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraBuilder {
+    index: CameraIndex,
+    api: ApiBackend,
+    resolution: Option<Resolution>,
+    frame_rate: Option<FrameRate>,
+    format: Option<FrameFormat>,
+    controls: Vec<(KnownCameraControl, ControlValueSetter)>,
+    open_stream: bool,
+}
+
+impl Default for CameraBuilder {
+    fn default() -> Self {
+        CameraBuilder {
+            index: CameraIndex::Index(0),
+            api: ApiBackend::Auto,
+            resolution: None,
+            frame_rate: None,
+            format: None,
+            controls: vec![],
+            open_stream: false,
+        }
+    }
+}
+
+impl CameraBuilder {
+    /// Creates a new [`CameraBuilder`] for the first camera with the native backend.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`CameraIndex`] of the camera to open. Defaults to index 0.
+    #[must_use]
+    pub fn index(mut self, index: impl Into<CameraIndex>) -> Self {
+        self.index = index.into();
+        self
+    }
+
+    /// Sets the backend to use. Defaults to [`ApiBackend::Auto`].
+    #[must_use]
+    pub fn backend(mut self, api: ApiBackend) -> Self {
+        self.api = api;
+        self
+    }
+
+    /// Sets the resolution. If not set, the camera's current resolution is kept.
+    #[must_use]
+    pub fn resolution(mut self, width: u32, height: u32) -> Self {
+        self.resolution = Some(Resolution::new(width, height));
+        self
+    }
+
+    /// Sets the frame rate in whole frames per second. If not set, the camera's current frame rate is kept.
+    ///
+    /// This also matches the fractional rates it rounds to, e.g. 30 opens a camera that only does `30000/1001` (29.97) at that rate.
+    #[must_use]
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.frame_rate = Some(FrameRate::Integer(fps));
+        self
+    }
+
+    /// Sets the exact [`FrameRate`], e.g. `FrameRate::Fraction { numerator: 30000, denominator: 1001 }`. If not set, the
+    /// camera's current frame rate is kept.
+    #[must_use]
+    pub fn frame_rate(mut self, frame_rate: FrameRate) -> Self {
+        self.frame_rate = Some(frame_rate);
+        self
+    }
+
+    /// Sets the [`FrameFormat`]. If not set, the camera's current frame format is kept.
+    #[must_use]
+    pub fn format(mut self, format: FrameFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Adds a control to set once the format is applied. Controls are set in the order they were added.
+    #[must_use]
+    pub fn control(mut self, id: KnownCameraControl, value: ControlValueSetter) -> Self {
+        self.controls.push((id, value));
+        self
+    }
+
+    /// Sets whether to open the stream once everything is applied. Defaults to `false`.
+    #[must_use]
+    pub fn open_stream(mut self, open_stream: bool) -> Self {
+        self.open_stream = open_stream;
+        self
+    }
+
+    /// Opens the camera and applies the configuration.
+    /// # Errors
+    /// This will error if the camera can not be opened, the requested resolution, frame rate, and frame format combination is not
+    /// supported by the camera, or a control or the stream fails to be set. On error, the camera is closed again.
+    pub fn open(self) -> Result<Camera, NokhwaError> {
+        let mut camera = Camera::with_api_backend(self.index, self.api)?;
+        camera.init()?;
+
+        let current = camera.camera_format().unwrap_or_default();
+        let format = self.format.unwrap_or_else(|| current.format());
+        let resolution = self.resolution.unwrap_or_else(|| current.resolution());
+        let frame_rate = self.frame_rate.unwrap_or_else(|| current.frame_rate());
+        let requested = CameraFormat::new(resolution, format, frame_rate);
+
+        let candidates = camera
+            .compatible_camera_formats()?
+            .into_iter()
+            .filter(|candidate| {
+                candidate.resolution() == resolution && candidate.format() == format
+            })
+            .collect::<Vec<_>>();
+        // Prefer the exact rate, and only round for whole rates from `fps()`.
+        let requested = candidates
+            .iter()
+            .find(|candidate| candidate.frame_rate() == frame_rate)
+            .or_else(|| {
+                candidates.iter().find(|candidate| {
+                    matches!(frame_rate, FrameRate::Integer(_))
+                        && candidate.frame_rate().as_u32() == frame_rate.as_u32()
+                })
+            })
+            .copied()
+            .ok_or_else(|| NokhwaError::SetPropertyError {
+                property: "CameraFormat".to_string(),
+                value: requested.to_string(),
+                error: "Not supported by this camera".to_string(),
+            })?;
+        if camera.camera_format() != Some(requested) {
+            camera.set_camera_format(requested)?;
+        }
+
+        for (id, value) in self.controls {
+            camera.set_camera_control(id, value)?;
+        }
+        if self.open_stream {
+            camera.open_stream()?;
+        }
+        Ok(camera)
+    }
+}
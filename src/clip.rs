@@ -0,0 +1,220 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::convert::frame_to_rgb;
+use image::{
+    buffer::ConvertBuffer,
+    codecs::gif::{GifEncoder, Repeat},
+    imageops::{self, FilterType},
+    Delay, Frame, RgbImage, RgbaImage,
+};
+use nokhwa_core::{error::NokhwaError, frame_format::FrameFormat, traits::CaptureTrait};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// The animated image format a [`ClipRecorder`] writes.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum ClipFormat {
+    Gif,
+    WebP,
+}
+
+/// How much a [`ClipRecorder`] captures.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum ClipLength {
+    /// Capture for this long.
+    Duration(Duration),
+    /// Keep this many frames (after [frame skipping](ClipRecorder::with_frame_skip)).
+    Frames(u32),
+}
+
+/// Captures a short clip from a camera into an animated GIF or WebP. This is meant for bug reports, documentation, and quick
+/// tooling - for real video, use a [`Recorder`](crate::Recorder).
+///
+/// Frames are decoded to RGB, optionally downscaled and skipped, and timed by when they arrived, so the clip plays back at
+/// real-time speed.
+/// ```ignore
+/// let gif = ClipRecorder::new(ClipFormat::Gif, ClipLength::Duration(Duration::from_secs(3)))
+///     .with_scale(0.5)
+///     .with_frame_skip(1)
+///     .record(&mut camera, "clip.gif")?;
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClipRecorder {
+    format: ClipFormat,
+    length: ClipLength,
+    scale: f32,
+    frame_skip: u32,
+}
+
+impl ClipRecorder {
+    /// Creates a new [`ClipRecorder`] that keeps every frame at full size.
+    #[must_use]
+    pub fn new(format: ClipFormat, length: ClipLength) -> Self {
+        ClipRecorder {
+            format,
+            length,
+            scale: 1.0,
+            frame_skip: 0,
+        }
+    }
+
+    /// Scales every frame by `scale`, which is clamped between 0.01 and 1.
+    #[must_use]
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale.clamp(0.01, 1.0);
+        self
+    }
+
+    /// Drops `frame_skip` frames after every kept frame, e.g. 1 keeps every second frame.
+    #[must_use]
+    pub fn with_frame_skip(mut self, frame_skip: u32) -> Self {
+        self.frame_skip = frame_skip;
+        self
+    }
+
+    /// Captures a clip from `camera` and writes it to `path`. The stream is opened if needed and left open.
+    /// Returns the number of frames in the clip.
+    /// # Errors
+    /// If capturing or decoding a frame fails, encoding fails, or the file can not be written, this will error.
+    pub fn record(
+        &self,
+        camera: &mut (impl CaptureTrait + ?Sized),
+        path: impl AsRef<Path>,
+    ) -> Result<usize, NokhwaError> {
+        let (clip, frames) = self.record_to_vec(camera)?;
+        let mut file = BufWriter::new(
+            File::create(path).map_err(|why| NokhwaError::from_io("Create clip file", why))?,
+        );
+        file.write_all(&clip)
+            .and_then(|()| file.flush())
+            .map_err(|why| NokhwaError::from_io("Write clip file", why))?;
+        Ok(frames)
+    }
+
+    /// Captures a clip from `camera` and returns the encoded file and the number of frames in it.
+    /// # Errors
+    /// If capturing or decoding a frame fails, or encoding fails, this will error.
+    pub fn record_to_vec(
+        &self,
+        camera: &mut (impl CaptureTrait + ?Sized),
+    ) -> Result<(Vec<u8>, usize), NokhwaError> {
+        if !camera.is_stream_open() {
+            camera.open_stream()?;
+        }
+
+        let mut frames: Vec<(RgbaImage, Duration)> = Vec::new();
+        let start = Instant::now();
+        let mut captured = 0_u32;
+        while !self.is_done(start.elapsed(), frames.len()) {
+            let frame = camera.frame()?;
+            let arrived = start.elapsed();
+            captured += 1;
+            if (captured - 1) % (self.frame_skip + 1) != 0 {
+                continue;
+            }
+
+            let resolution = frame.resolution();
+            let rgb = frame_to_rgb(&frame, frame.source_frame_format())?;
+            let image = RgbImage::from_raw(resolution.width(), resolution.height(), rgb.into_vec())
+                .ok_or_else(|| NokhwaError::ProcessFrameError {
+                    src: frame.source_frame_format(),
+                    destination: "RGB888".to_string(),
+                    error: "decoded buffer has the wrong size".to_string(),
+                })?;
+            let image: RgbaImage = if self.scale < 1.0 {
+                let width = ((resolution.width() as f32 * self.scale) as u32).max(1);
+                let height = ((resolution.height() as f32 * self.scale) as u32).max(1);
+                imageops::resize(&image, width, height, FilterType::Triangle).convert()
+            } else {
+                image.convert()
+            };
+            frames.push((image, arrived));
+        }
+
+        let count = frames.len();
+        let clip = match self.format {
+            ClipFormat::Gif => encode_gif(frames)?,
+            ClipFormat::WebP => encode_webp(frames)?,
+        };
+        Ok((clip, count))
+    }
+
+    fn is_done(&self, elapsed: Duration, frames: usize) -> bool {
+        match self.length {
+            ClipLength::Duration(duration) => elapsed >= duration,
+            ClipLength::Frames(count) => frames >= count as usize,
+        }
+    }
+}
+
+/// Gets how long each frame is shown, from the time the next frame arrived. The last frame reuses the previous delay.
+fn frame_delays(frames: &[(RgbaImage, Duration)]) -> Vec<Duration> {
+    let mut delays = frames
+        .windows(2)
+        .map(|pair| pair[1].1.saturating_sub(pair[0].1))
+        .collect::<Vec<Duration>>();
+    delays.push(delays.last().copied().unwrap_or(Duration::from_millis(100)));
+    delays
+}
+
+fn encode_gif(frames: Vec<(RgbaImage, Duration)>) -> Result<Vec<u8>, NokhwaError> {
+    let delays = frame_delays(&frames);
+    let mut encoded = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut encoded);
+        encoder.set_repeat(Repeat::Infinite).map_err(gif_error)?;
+        let frames = frames.into_iter().zip(delays).map(|((image, _), delay)| {
+            Frame::from_parts(image, 0, 0, Delay::from_saturating_duration(delay))
+        });
+        encoder.encode_frames(frames).map_err(gif_error)?;
+    }
+    Ok(encoded)
+}
+
+fn gif_error(why: image::ImageError) -> NokhwaError {
+    NokhwaError::ProcessFrameError {
+        src: FrameFormat::RgbA8,
+        destination: "GIF".to_string(),
+        error: why.to_string(),
+    }
+}
+
+fn encode_webp(frames: Vec<(RgbaImage, Duration)>) -> Result<Vec<u8>, NokhwaError> {
+    let webp_error = |why: webp_animation::Error| NokhwaError::ProcessFrameError {
+        src: FrameFormat::RgbA8,
+        destination: "WebP".to_string(),
+        error: format!("{why:?}"),
+    };
+    let Some((first, _)) = frames.first() else {
+        return Err(NokhwaError::ReadFrameError(
+            "No frames captured".to_string(),
+        ));
+    };
+    let delays = frame_delays(&frames);
+    let mut encoder = webp_animation::Encoder::new(first.dimensions()).map_err(webp_error)?;
+    let mut timestamp = 0_i32;
+    for ((image, _), delay) in frames.iter().zip(&delays) {
+        encoder.add_frame(image, timestamp).map_err(webp_error)?;
+        timestamp = timestamp.saturating_add(delay.as_millis() as i32);
+    }
+    let webp = encoder.finalize(timestamp).map_err(webp_error)?;
+    Ok(webp.to_vec())
+}
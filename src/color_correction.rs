@@ -0,0 +1,172 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/// Ready made color correction matrices for [`ColorCorrection::preset()`].
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum ColorPreset {
+    /// Leaves colors as they are.
+    Identity,
+    /// Warms up cameras with a blue cast, e.g. under daylight balanced white balance indoors.
+    Warm,
+    /// Cools down cameras with a yellow or orange cast, e.g. under tungsten light.
+    Cool,
+    /// Converts to grayscale with the Rec. 709 luma weights.
+    Grayscale,
+    /// A brown, old photo tint.
+    Sepia,
+}
+
+/// A gamma curve and a 3x3 color correction matrix, applied to decoded RGB(A) frames. Use it to fix cameras with poor factory
+/// color once in the capture layer, instead of in every consumer. Set it on a camera with
+/// [`Camera::set_color_correction()`](crate::Camera::set_color_correction), or [`apply()`](Self::apply) it to RGB(A) data
+/// yourself.
+///
+/// Each pixel is multiplied by the matrix first, then raised to `1 / gamma`, so a gamma above 1 brightens the midtones.
+/// ```ignore
+/// camera.set_color_correction(Some(
+///     ColorCorrection::preset(ColorPreset::Warm).with_gamma(1.2),
+/// ));
+/// let frame = camera.frame_rgb()?;
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorCorrection {
+    matrix: [[f32; 3]; 3],
+    gamma: f32,
+}
+
+impl ColorCorrection {
+    /// The identity matrix.
+    pub const IDENTITY_MATRIX: [[f32; 3]; 3] = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+    /// Creates a color correction that changes nothing: the identity matrix, and a gamma of 1.
+    #[must_use]
+    pub fn new() -> Self {
+        ColorCorrection {
+            matrix: Self::IDENTITY_MATRIX,
+            gamma: 1.0,
+        }
+    }
+
+    /// Creates a color correction from a [`ColorPreset`] matrix, with a gamma of 1.
+    #[must_use]
+    pub fn preset(preset: ColorPreset) -> Self {
+        let matrix = match preset {
+            ColorPreset::Identity => Self::IDENTITY_MATRIX,
+            ColorPreset::Warm => [[1.1, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.85]],
+            ColorPreset::Cool => [[0.85, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.1]],
+            ColorPreset::Grayscale => [[0.2126, 0.7152, 0.0722]; 3],
+            ColorPreset::Sepia => [
+                [0.393, 0.769, 0.189],
+                [0.349, 0.686, 0.168],
+                [0.272, 0.534, 0.131],
+            ],
+        };
+        ColorCorrection::new().with_matrix(matrix)
+    }
+
+    /// Creates a color correction that scales saturation by `amount`: 0 is grayscale, 1 changes nothing, and 2 doubles it.
+    #[must_use]
+    pub fn saturation(amount: f32) -> Self {
+        let luma = [0.2126, 0.7152, 0.0722];
+        let mut matrix = [[0.0; 3]; 3];
+        for (row, out) in matrix.iter_mut().enumerate() {
+            for (column, value) in out.iter_mut().enumerate() {
+                let identity = if row == column { 1.0 } else { 0.0 };
+                *value = luma[column] * (1.0 - amount) + identity * amount;
+            }
+        }
+        ColorCorrection::new().with_matrix(matrix)
+    }
+
+    /// Sets the color correction matrix. Each output channel (row) is the sum of the input R, G, and B (columns) times the
+    /// row's coefficients, e.g. `[[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.9]]` tones down blue by 10%.
+    #[must_use]
+    pub fn with_matrix(mut self, matrix: [[f32; 3]; 3]) -> Self {
+        self.matrix = matrix;
+        self
+    }
+
+    /// Sets the gamma. Values above 1 brighten the midtones and values below 1 darken them. Values that are not positive and
+    /// finite are treated as 1.
+    #[must_use]
+    pub fn with_gamma(mut self, gamma: f32) -> Self {
+        self.gamma = if gamma.is_finite() && gamma > 0.0 {
+            gamma
+        } else {
+            1.0
+        };
+        self
+    }
+
+    /// The color correction matrix.
+    #[must_use]
+    pub fn matrix(&self) -> [[f32; 3]; 3] {
+        self.matrix
+    }
+
+    /// The gamma.
+    #[must_use]
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    /// Returns true if this changes nothing.
+    #[must_use]
+    #[allow(clippy::float_cmp)]
+    pub fn is_identity(&self) -> bool {
+        self.matrix == Self::IDENTITY_MATRIX && self.gamma == 1.0
+    }
+
+    /// Corrects packed `data` of `pixel_size` bytes per pixel (3 for RGB888, 4 for RGBA8888) in place. Alpha is left alone.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn apply(&self, data: &mut [u8], pixel_size: usize) {
+        if pixel_size < 3 || self.is_identity() {
+            return;
+        }
+        let mut curve = [0_u8; 256];
+        for (value, out) in curve.iter_mut().enumerate() {
+            *out = ((value as f32 / 255.0).powf(1.0 / self.gamma) * 255.0).round() as u8;
+        }
+        let identity_matrix = self.matrix == Self::IDENTITY_MATRIX;
+        for pixel in data.chunks_exact_mut(pixel_size) {
+            if identity_matrix {
+                for channel in &mut pixel[..3] {
+                    *channel = curve[usize::from(*channel)];
+                }
+                continue;
+            }
+            let rgb = [
+                f32::from(pixel[0]),
+                f32::from(pixel[1]),
+                f32::from(pixel[2]),
+            ];
+            for (channel, row) in pixel[..3].iter_mut().zip(&self.matrix) {
+                let value = row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2];
+                *channel = curve[value.round().clamp(0.0, 255.0) as usize];
+            }
+        }
+    }
+}
+
+impl Default for ColorCorrection {
+    fn default() -> Self {
+        ColorCorrection::new()
+    }
+}
@@ -0,0 +1,401 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use nokhwa_core::{
+    buffer::Buffer,
+    error::NokhwaError,
+    frame_format::FrameFormat,
+    pool::{BufferPool, FrameBuffer},
+    types::{
+        buf_mjpeg_to_rgb, buf_mjpeg_to_rgb_scaled, buf_nv12_to_rgb, buf_yuyv422_to_rgb,
+        yuyv422_predicted_size, DecodeScale, Deinterlace, FieldOrder, PixelAspectRatio, Region,
+        Resolution, Rotation,
+    },
+};
+
+/// Converts `frame`, which is in `format`, into packed RGB888 (R,G,B,R,G,B,...).
+///
+/// MJPEG, YUYV, NV12, grayscale, RGB, and RGBA frames are supported. The result is a [`FrameBuffer`] from
+/// [`BufferPool::global()`], so converting frame after frame reuses the same few allocations as long as the results are dropped.
+/// # Errors
+/// If the [`FrameFormat`] is not supported, or decoding fails, this will error.
+pub fn frame_to_rgb(frame: &Buffer, format: FrameFormat) -> Result<FrameBuffer, NokhwaError> {
+    decode(frame, format, false)
+}
+
+/// Converts `frame`, which is in `format`, into packed RGBA8888 (R,G,B,A,R,G,B,A,...). See [`frame_to_rgb()`].
+/// # Errors
+/// If the [`FrameFormat`] is not supported, or decoding fails, this will error.
+pub fn frame_to_rgba(frame: &Buffer, format: FrameFormat) -> Result<FrameBuffer, NokhwaError> {
+    decode(frame, format, true)
+}
+
+/// Converts `frame`, which is in `format`, into packed RGB888 shrunk by `scale`. The result is
+/// [`scale.apply(frame.resolution())`](DecodeScale::apply) in size. Use [`DecodeScale::fitting()`] to pick a scale for a preview.
+///
+/// MJPEG frames are scaled while decoding, which skips most of the work. Other formats are decoded in full and then
+/// sampled down, which saves nothing but gives the same result.
+/// # Errors
+/// If the [`FrameFormat`] is not supported, or decoding fails, this will error.
+pub fn frame_to_rgb_scaled(
+    frame: &Buffer,
+    format: FrameFormat,
+    scale: DecodeScale,
+) -> Result<FrameBuffer, NokhwaError> {
+    decode_scaled(frame, format, false, scale)
+}
+
+/// Converts `frame`, which is in `format`, into packed RGBA8888 shrunk by `scale`. See [`frame_to_rgb_scaled()`].
+/// # Errors
+/// If the [`FrameFormat`] is not supported, or decoding fails, this will error.
+pub fn frame_to_rgba_scaled(
+    frame: &Buffer,
+    format: FrameFormat,
+    scale: DecodeScale,
+) -> Result<FrameBuffer, NokhwaError> {
+    decode_scaled(frame, format, true, scale)
+}
+
+fn decode_scaled(
+    frame: &Buffer,
+    format: FrameFormat,
+    rgba: bool,
+    scale: DecodeScale,
+) -> Result<FrameBuffer, NokhwaError> {
+    if scale == DecodeScale::Full {
+        return decode(frame, format, rgba);
+    }
+    let pixel_size = if rgba { 4 } else { 3 };
+    let scaled = scale.apply(frame.resolution());
+    let mut out =
+        BufferPool::global().get(scaled.width() as usize * scaled.height() as usize * pixel_size);
+
+    match format {
+        FrameFormat::MJpeg => buf_mjpeg_to_rgb_scaled(frame.buffer(), &mut out, rgba, scale)?,
+        _ => {
+            let full = decode(frame, format, rgba)?;
+            let step = 8 / scale.numerator() as usize;
+            let full_row = frame.resolution().width() as usize * pixel_size;
+            for (y, row) in out
+                .chunks_exact_mut(scaled.width() as usize * pixel_size)
+                .enumerate()
+            {
+                let source_row = &full[y * step * full_row..];
+                for (x, pixel) in row.chunks_exact_mut(pixel_size).enumerate() {
+                    let source = x * step * pixel_size;
+                    pixel.copy_from_slice(&source_row[source..source + pixel_size]);
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Rotates packed `data`, a `resolution` sized image of `pixel_size` bytes per pixel (e.g. 3 for RGB888), clockwise by
+/// `rotation`. The result is [`rotation.apply(resolution)`](Rotation::apply) in size.
+/// # Errors
+/// If `data` is smaller than `resolution` and `pixel_size` say it should be, this will error.
+pub fn rotate_pixels(
+    data: &[u8],
+    resolution: Resolution,
+    pixel_size: usize,
+    rotation: Rotation,
+) -> Result<FrameBuffer, NokhwaError> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let size = width * height * pixel_size;
+    if data.len() < size {
+        return Err(NokhwaError::GeneralError(format!(
+            "Cannot rotate {} bytes as a {resolution} image of {pixel_size} bytes per pixel",
+            data.len()
+        )));
+    }
+
+    let mut out = BufferPool::global().get(size);
+    let rotated_width = rotation.apply(resolution).width() as usize;
+    for (index, pixel) in data[..size].chunks_exact(pixel_size).enumerate() {
+        let (x, y) = (index % width, index / width);
+        let (to_x, to_y) = match rotation {
+            Rotation::None => (x, y),
+            Rotation::Clockwise90 => (height - 1 - y, x),
+            Rotation::Rotate180 => (width - 1 - x, height - 1 - y),
+            Rotation::Clockwise270 => (y, width - 1 - x),
+        };
+        let to = (to_y * rotated_width + to_x) * pixel_size;
+        out[to..to + pixel_size].copy_from_slice(pixel);
+    }
+    Ok(out)
+}
+
+/// Deinterlaces packed `data`, a `resolution` sized image of `pixel_size` bytes per pixel whose two fields are laid out as
+/// `field_order` says, using `method`. Sequential layouts are woven back into alternating rows first. Frames that are not
+/// interlaced (see [`FieldOrder::is_interlaced()`]) are returned unchanged.
+/// # Errors
+/// If `data` is smaller than `resolution` and `pixel_size` say it should be, this will error.
+#[allow(clippy::cast_possible_truncation)]
+pub fn deinterlace(
+    data: &[u8],
+    resolution: Resolution,
+    pixel_size: usize,
+    field_order: FieldOrder,
+    method: Deinterlace,
+) -> Result<FrameBuffer, NokhwaError> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let stride = width * pixel_size;
+    let size = stride * height;
+    if data.len() < size {
+        return Err(NokhwaError::GeneralError(format!(
+            "Cannot deinterlace {} bytes as a {resolution} image of {pixel_size} bytes per pixel",
+            data.len()
+        )));
+    }
+
+    let mut out = BufferPool::global().get(size);
+    // Parity of the rows belonging to the field that was captured first.
+    let first_parity = match field_order {
+        FieldOrder::TopFirst | FieldOrder::SequentialTopFirst => 0,
+        FieldOrder::BottomFirst | FieldOrder::SequentialBottomFirst => 1,
+        FieldOrder::Progressive | FieldOrder::SingleField => {
+            out.copy_from_slice(&data[..size]);
+            return Ok(out);
+        }
+    };
+
+    let woven;
+    let data = if matches!(
+        field_order,
+        FieldOrder::SequentialTopFirst | FieldOrder::SequentialBottomFirst
+    ) {
+        // The first field fills the upper (rounded up) half of the buffer, the second field the rest.
+        let first_rows = (height + 1 - first_parity) / 2;
+        let mut buf = BufferPool::global().get(size);
+        for (row, line) in data[..size].chunks_exact(stride).enumerate() {
+            let to = if row < first_rows {
+                row * 2 + first_parity
+            } else {
+                (row - first_rows) * 2 + (1 - first_parity)
+            };
+            buf[to * stride..(to + 1) * stride].copy_from_slice(line);
+        }
+        woven = buf;
+        &woven[..]
+    } else {
+        &data[..size]
+    };
+
+    let row = |y: usize| &data[y * stride..(y + 1) * stride];
+    for (y, line) in out.chunks_exact_mut(stride).enumerate() {
+        let above = row(y.saturating_sub(1));
+        let below = row((y + 1).min(height - 1));
+        match method {
+            Deinterlace::Bob if y % 2 == first_parity => line.copy_from_slice(row(y)),
+            Deinterlace::Bob => {
+                // Edge rows only have one neighbour from the kept field.
+                let (above, below) = match (y == 0, y + 1 == height) {
+                    (true, _) => (below, below),
+                    (_, true) => (above, above),
+                    _ => (above, below),
+                };
+                for ((px, a), b) in line.iter_mut().zip(above).zip(below) {
+                    *px = ((u16::from(*a) + u16::from(*b) + 1) / 2) as u8;
+                }
+            }
+            Deinterlace::LinearBlend => {
+                for (((px, a), c), b) in line.iter_mut().zip(above).zip(row(y)).zip(below) {
+                    *px = ((u16::from(*a) + 2 * u16::from(*c) + u16::from(*b) + 2) / 4) as u8;
+                }
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Resamples packed `data`, a `resolution` sized image of `pixel_size` bytes per pixel whose pixels are `pixel_aspect` shaped,
+/// to square pixels by linearly interpolating along each row. The result is
+/// [`pixel_aspect.square_resolution(resolution)`](PixelAspectRatio::square_resolution) in size; square input is copied as is.
+/// # Errors
+/// If `data` is smaller than `resolution` and `pixel_size` say it should be, this will error.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn resample_square_pixels(
+    data: &[u8],
+    resolution: Resolution,
+    pixel_size: usize,
+    pixel_aspect: PixelAspectRatio,
+) -> Result<FrameBuffer, NokhwaError> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let size = width * height * pixel_size;
+    if data.len() < size {
+        return Err(NokhwaError::GeneralError(format!(
+            "Cannot resample {} bytes as a {resolution} image of {pixel_size} bytes per pixel",
+            data.len()
+        )));
+    }
+    let out_width = pixel_aspect.square_resolution(resolution).width() as usize;
+    let mut out = BufferPool::global().get(out_width * height * pixel_size);
+    if out_width == width || width == 0 {
+        out.copy_from_slice(&data[..out.len()]);
+        return Ok(out);
+    }
+
+    // Sample at pixel centres, so both edges line up.
+    let step = width as f32 / out_width as f32;
+    for (in_row, out_row) in data[..size]
+        .chunks_exact(width * pixel_size)
+        .zip(out.chunks_exact_mut(out_width * pixel_size))
+    {
+        for (x, pixel) in out_row.chunks_exact_mut(pixel_size).enumerate() {
+            let source = ((x as f32 + 0.5) * step - 0.5).clamp(0.0, (width - 1) as f32);
+            let left = source as usize;
+            let right = (left + 1).min(width - 1);
+            let weight = source - left as f32;
+            for (channel, value) in pixel.iter_mut().enumerate() {
+                let a = f32::from(in_row[left * pixel_size + channel]);
+                let b = f32::from(in_row[right * pixel_size + channel]);
+                *value = (a + (b - a) * weight + 0.5) as u8;
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Crops packed `data`, a `resolution` sized image of `pixel_size` bytes per pixel, to `region`. The part of `region` outside
+/// the image is left out, so the result is [`region.clamp_to(resolution)`](Region::clamp_to) in size.
+/// # Errors
+/// If `data` is smaller than `resolution` and `pixel_size` say it should be, or `region` lies entirely outside the image,
+/// this will error.
+pub fn crop_pixels(
+    data: &[u8],
+    resolution: Resolution,
+    pixel_size: usize,
+    region: Region,
+) -> Result<FrameBuffer, NokhwaError> {
+    let stride = resolution.width() as usize * pixel_size;
+    if data.len() < stride * resolution.height() as usize {
+        return Err(NokhwaError::GeneralError(format!(
+            "Cannot crop {} bytes as a {resolution} image of {pixel_size} bytes per pixel",
+            data.len()
+        )));
+    }
+    let region = region.clamp_to(resolution).ok_or_else(|| {
+        NokhwaError::GeneralError(format!(
+            "Crop region {region} is outside the {resolution} image"
+        ))
+    })?;
+
+    let row_size = region.width() as usize * pixel_size;
+    let mut out = BufferPool::global().get(row_size * region.height() as usize);
+    let left = region.x() as usize * pixel_size;
+    for (row, line) in out.chunks_exact_mut(row_size).enumerate() {
+        let start = (region.y() as usize + row) * stride + left;
+        line.copy_from_slice(&data[start..start + row_size]);
+    }
+    Ok(out)
+}
+
+fn decode(frame: &Buffer, format: FrameFormat, rgba: bool) -> Result<FrameBuffer, NokhwaError> {
+    let resolution = frame.resolution();
+    let packed;
+    let data = match frame
+        .stride()
+        .and_then(|stride| unpad_rows(frame.buffer(), resolution, format, stride as usize))
+    {
+        Some(buf) => {
+            packed = buf;
+            &packed[..]
+        }
+        None => frame.buffer(),
+    };
+    let pixel_size = if rgba { 4 } else { 3 };
+    let pixels = resolution.width() as usize * resolution.height() as usize;
+    let pool = BufferPool::global();
+
+    let mut out = match format {
+        FrameFormat::Yuv422 => pool.get(yuyv422_predicted_size(data.len(), rgba)),
+        _ => pool.get(pixels * pixel_size),
+    };
+    match format {
+        FrameFormat::MJpeg => buf_mjpeg_to_rgb(data, &mut out, rgba)?,
+        FrameFormat::Yuv422 => buf_yuyv422_to_rgb(data, &mut out, rgba)?,
+        FrameFormat::Nv12 => buf_nv12_to_rgb(resolution, data, &mut out, rgba)?,
+        FrameFormat::Luma8 | FrameFormat::Rgb8 | FrameFormat::RgbA8 => {
+            let in_size = match format {
+                FrameFormat::Luma8 => 1,
+                FrameFormat::Rgb8 => 3,
+                _ => 4,
+            };
+            for (pixel, input) in out
+                .chunks_exact_mut(pixel_size)
+                .zip(data.chunks_exact(in_size))
+            {
+                let rgb = if in_size == 1 {
+                    [input[0]; 3]
+                } else {
+                    [input[0], input[1], input[2]]
+                };
+                pixel[..3].copy_from_slice(&rgb);
+                if rgba {
+                    pixel[3] = input.get(3).copied().unwrap_or(255);
+                }
+            }
+        }
+        other => {
+            return Err(NokhwaError::ProcessFrameError {
+                src: other,
+                destination: if rgba { "RGBA8888" } else { "RGB888" }.to_string(),
+                error: "unsupported".to_string(),
+            })
+        }
+    }
+    Ok(out)
+}
+
+/// Copies the rows of `data`, which are `stride` bytes apart, into a tightly packed buffer. Returns `None` if `format` is
+/// compressed or the rows are already packed, in which case `data` can be used as is.
+fn unpad_rows(
+    data: &[u8],
+    resolution: Resolution,
+    format: FrameFormat,
+    stride: usize,
+) -> Option<FrameBuffer> {
+    let width = resolution.width() as usize;
+    let height = resolution.height() as usize;
+    let (row_size, rows) = match format {
+        FrameFormat::Yuv422 => (width * 2, height),
+        // The Y plane is followed by the interleaved UV plane at half the height, both `stride` apart.
+        FrameFormat::Nv12 => (width, height + height / 2),
+        FrameFormat::Luma8 => (width, height),
+        FrameFormat::Rgb8 => (width * 3, height),
+        FrameFormat::RgbA8 => (width * 4, height),
+        _ => return None,
+    };
+    if stride <= row_size {
+        return None;
+    }
+
+    let mut packed = BufferPool::global().get(row_size * rows);
+    for (line, row) in packed.chunks_exact_mut(row_size).zip(data.chunks(stride)) {
+        // The last row is often not padded out to the full stride.
+        let len = row_size.min(row.len());
+        line[..len].copy_from_slice(&row[..len]);
+    }
+    Some(packed)
+}
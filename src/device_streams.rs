@@ -0,0 +1,375 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{query_grouped, sync_group::capture_loop, Camera};
+use nokhwa_core::{
+    buffer::Buffer,
+    error::NokhwaError,
+    traits::CaptureTrait,
+    types::{ApiBackend, CameraInfo, RequestedFormat, StreamKind},
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, TryRecvError},
+        Arc, Barrier, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, SystemTime},
+};
+
+/// How many frames [`DeviceStreams`] buffers per stream before dropping new ones.
+pub const DEFAULT_STREAM_BACKLOG: usize = 4;
+
+/// A frame from one stream of a [`DeviceStreams`].
+#[derive(Clone, Debug)]
+pub struct TaggedFrame {
+    stream: usize,
+    kind: StreamKind,
+    captured: SystemTime,
+    frame: Buffer,
+}
+
+impl TaggedFrame {
+    /// The index of the stream this came from, in the order the streams were added.
+    #[must_use]
+    pub fn stream(&self) -> usize {
+        self.stream
+    }
+
+    /// What the stream this came from captures.
+    #[must_use]
+    pub fn kind(&self) -> StreamKind {
+        self.kind
+    }
+
+    /// When the frame was captured on the wall clock, for matching frames of different streams.
+    #[must_use]
+    pub fn captured(&self) -> SystemTime {
+        self.captured
+    }
+
+    /// The frame.
+    #[must_use]
+    pub fn frame(&self) -> &Buffer {
+        &self.frame
+    }
+
+    /// Takes the frame out.
+    #[must_use]
+    pub fn into_frame(self) -> Buffer {
+        self.frame
+    }
+}
+
+/// Streams several endpoints of one physical device at the same time, e.g. the color and infrared sensor of a Windows Hello webcam
+/// or the color and depth stream of a depth camera, delivering every frame tagged with the [`StreamKind`] it came from.
+///
+/// Backends expose each endpoint as a camera of its own (a `/dev/videoN` node on `V4L2`, a symbolic link on `Media Foundation`) that
+/// shares a [`group`](CameraInfo::group) with its siblings. [`open_device()`](Self::open_device) opens all of them, or add the endpoints
+/// you want one by one with [`add_stream()`](Self::add_stream). Each stream is read by its own thread. To match the frames of different
+/// streams by capture time, use [`SyncCaptureGroup`](crate::SyncCaptureGroup) instead.
+/// ```ignore
+/// let mut streams = DeviceStreams::open_device(&info, RequestedFormat::new(RequestedFormatType::AbsoluteHighestFrameRate))?;
+/// streams.open_stream()?;
+/// while let Ok(frame) = streams.recv_timeout(Duration::from_secs(1)) {
+///     match frame.kind() {
+///         StreamKind::Infrared => detect_face(frame.frame()),
+///         _ => show(frame.frame()),
+///     }
+/// }
+/// ```
+pub struct DeviceStreams {
+    streams: Vec<(StreamKind, Arc<Mutex<Camera>>)>,
+    backlog: usize,
+    receiver: Option<Receiver<(usize, Buffer, SystemTime)>>,
+    dropped: Arc<AtomicU64>,
+    die_bool: Arc<AtomicBool>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl DeviceStreams {
+    /// Creates an empty set of streams.
+    #[must_use]
+    pub fn new() -> Self {
+        DeviceStreams {
+            streams: Vec::new(),
+            backlog: DEFAULT_STREAM_BACKLOG,
+            receiver: None,
+            dropped: Arc::new(AtomicU64::new(0)),
+            die_bool: Arc::new(AtomicBool::new(false)),
+            handles: Vec::new(),
+        }
+    }
+
+    /// Opens every endpoint of the device `info` belongs to (including `info` itself) with the native backend, sets each to the format
+    /// that best fits `request`, and guesses what each captures with [`StreamKind::guess()`].
+    ///
+    /// Endpoints that cannot satisfy `request` (e.g. an infrared sensor asked for color at 1080p) are skipped.
+    /// # Errors
+    /// This will error if querying fails, or none of the endpoints can be opened.
+    pub fn open_device(info: &CameraInfo, request: RequestedFormat) -> Result<Self, NokhwaError> {
+        let siblings = query_grouped(ApiBackend::Auto)?
+            .into_iter()
+            .find(|group| {
+                group
+                    .iter()
+                    .any(|sibling| sibling.index() == info.index() || sibling.is_sibling_of(info))
+            })
+            .unwrap_or_else(|| vec![info.clone()]);
+
+        let mut streams = DeviceStreams::new();
+        let mut last_error = None;
+        for sibling in siblings {
+            match Camera::with_request(sibling.index().clone(), request.clone()) {
+                Ok(camera) => {
+                    let kind = StreamKind::guess(camera.info(), camera.frame_format());
+                    streams.add_stream(kind, camera)?;
+                }
+                Err(why) => last_error = Some(why),
+            }
+        }
+        if streams.streams.is_empty() {
+            return Err(last_error.unwrap_or_else(|| {
+                NokhwaError::OpenDeviceError(
+                    info.human_name().to_string(),
+                    "No endpoints could be opened".to_string(),
+                )
+            }));
+        }
+        Ok(streams)
+    }
+
+    /// Adds `camera` as a stream capturing `kind`. The camera must have its format set beforehand.
+    ///
+    /// Returns the index of the stream, which [`TaggedFrame::stream()`] reports.
+    /// # Errors
+    /// This will error if the streams are open, or `camera` is known to belong to a different device than the streams added before it.
+    pub fn add_stream(&mut self, kind: StreamKind, camera: Camera) -> Result<usize, NokhwaError> {
+        if self.receiver.is_some() {
+            return Err(NokhwaError::SetPropertyError {
+                property: "Device Streams".to_string(),
+                value: camera.info().human_name().to_string(),
+                error: "Stream is open".to_string(),
+            });
+        }
+        let foreign = self.streams.first().and_then(|(_, first)| {
+            let first = first.lock().ok()?;
+            let (first, other) = (first.info(), camera.info());
+            (first.group().is_some() && other.group().is_some() && !first.is_sibling_of(other))
+                .then(|| first.human_name().to_string())
+        });
+        if let Some(device) = foreign {
+            return Err(NokhwaError::SetPropertyError {
+                property: "Device Streams".to_string(),
+                value: camera.info().human_name().to_string(),
+                error: format!("Not an endpoint of {device}"),
+            });
+        }
+        self.streams.push((kind, Arc::new(Mutex::new(camera))));
+        Ok(self.streams.len() - 1)
+    }
+
+    /// The kind of each stream, in the order they were added.
+    #[must_use]
+    pub fn kinds(&self) -> Vec<StreamKind> {
+        self.streams.iter().map(|(kind, _)| *kind).collect()
+    }
+
+    /// How many streams there are.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Returns true if no streams were added.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    /// Runs `f` with exclusive access to the camera of stream `index`, e.g. to change the infrared emitter's controls.
+    /// While streaming, that stream's capture thread waits until `f` returns.
+    /// # Errors
+    /// This will error if there is no stream at `index` or its lock is poisoned.
+    pub fn with_camera<R>(
+        &self,
+        index: usize,
+        f: impl FnOnce(&mut Camera) -> R,
+    ) -> Result<R, NokhwaError> {
+        let (_, camera) = self
+            .streams
+            .get(index)
+            .ok_or_else(|| NokhwaError::GeneralError(format!("No stream at index {index}")))?;
+        let mut camera = camera
+            .lock()
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+        Ok(f(&mut camera))
+    }
+
+    /// Sets how many frames are buffered per stream (at least 1) before new frames are dropped. Takes effect on the next
+    /// [`open_stream()`](Self::open_stream).
+    pub fn set_backlog(&mut self, backlog: usize) {
+        self.backlog = backlog.max(1);
+    }
+
+    /// How many frames were dropped because they were not received in time.
+    #[must_use]
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if the streams are open.
+    #[must_use]
+    pub fn is_stream_open(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Opens all streams, then starts reading from all of them at once.
+    /// # Errors
+    /// If there are no streams, they are already open, or any of them fails to open, this will error. The streams that did open are closed again.
+    pub fn open_stream(&mut self) -> Result<(), NokhwaError> {
+        if self.streams.is_empty() {
+            return Err(NokhwaError::OpenStreamError("No streams".to_string()));
+        }
+        if self.receiver.is_some() {
+            return Err(NokhwaError::OpenStreamError(
+                "Stream Already Open".to_string(),
+            ));
+        }
+        for (opened, (_, camera)) in self.streams.iter().enumerate() {
+            let result = camera
+                .lock()
+                .map_err(|why| NokhwaError::OpenStreamError(why.to_string()))
+                .and_then(|mut camera| camera.open_stream());
+            if let Err(why) = result {
+                for (_, camera) in &self.streams[..opened] {
+                    if let Ok(mut camera) = camera.lock() {
+                        let _stop_stream_err = camera.stop_stream();
+                    }
+                }
+                return Err(why);
+            }
+        }
+
+        let (sender, receiver) = mpsc::sync_channel(self.streams.len() * self.backlog);
+        let start = Arc::new(Barrier::new(self.streams.len()));
+        self.die_bool.store(false, Ordering::SeqCst);
+        self.handles = self
+            .streams
+            .iter()
+            .enumerate()
+            .map(|(index, (_, camera))| {
+                let camera = camera.clone();
+                let sender = sender.clone();
+                let start = start.clone();
+                let dropped = self.dropped.clone();
+                let die_bool = self.die_bool.clone();
+                std::thread::spawn(move || {
+                    start.wait();
+                    capture_loop(index, &camera, &sender, &dropped, &die_bool);
+                })
+            })
+            .collect();
+        self.receiver = Some(receiver);
+        Ok(())
+    }
+
+    /// Waits at most `timeout` for the next frame of any stream.
+    /// # Errors
+    /// This will return [`TimeoutError`](NokhwaError::TimeoutError) on timeout, or [`ReadFrameError`](NokhwaError::ReadFrameError)
+    /// if the streams are not open.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<TaggedFrame, NokhwaError> {
+        let receiver = self
+            .receiver
+            .as_ref()
+            .ok_or_else(|| NokhwaError::ReadFrameError("Stream Closed".to_string()))?;
+        let received = receiver.recv_timeout(timeout).map_err(|why| match why {
+            RecvTimeoutError::Timeout => NokhwaError::TimeoutError(timeout),
+            RecvTimeoutError::Disconnected => {
+                NokhwaError::ReadFrameError("Stream Closed".to_string())
+            }
+        })?;
+        Ok(self.tag(received))
+    }
+
+    /// Gets the next frame of any stream if one is waiting, without blocking.
+    /// # Errors
+    /// This will error if the streams are not open.
+    pub fn try_recv(&self) -> Result<Option<TaggedFrame>, NokhwaError> {
+        let receiver = self
+            .receiver
+            .as_ref()
+            .ok_or_else(|| NokhwaError::ReadFrameError("Stream Closed".to_string()))?;
+        match receiver.try_recv() {
+            Ok(received) => Ok(Some(self.tag(received))),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                Err(NokhwaError::ReadFrameError("Stream Closed".to_string()))
+            }
+        }
+    }
+
+    /// Stops reading and closes all streams.
+    /// # Errors
+    /// If a capture thread panicked, or a camera fails to close its stream, this will error. All streams are still stopped.
+    pub fn stop_stream(&mut self) -> Result<(), NokhwaError> {
+        self.die_bool.store(true, Ordering::SeqCst);
+        // Dropping the receiver makes the capture threads' sends fail instead of filling the channel.
+        self.receiver = None;
+        let mut result = Ok(());
+        for handle in self.handles.drain(..) {
+            if handle.join().is_err() {
+                result = Err(NokhwaError::StreamShutdownError(
+                    "Capture thread panicked".to_string(),
+                ));
+            }
+        }
+        for (_, camera) in &self.streams {
+            let stopped = camera
+                .lock()
+                .map_err(|why| NokhwaError::StreamShutdownError(why.to_string()))
+                .and_then(|mut camera| camera.stop_stream());
+            if let Err(why) = stopped {
+                result = Err(why);
+            }
+        }
+        result
+    }
+
+    fn tag(&self, (stream, frame, captured): (usize, Buffer, SystemTime)) -> TaggedFrame {
+        TaggedFrame {
+            stream,
+            kind: self.streams[stream].0,
+            captured,
+            frame,
+        }
+    }
+}
+
+impl Default for DeviceStreams {
+    fn default() -> Self {
+        DeviceStreams::new()
+    }
+}
+
+impl Drop for DeviceStreams {
+    fn drop(&mut self) {
+        if self.receiver.is_some() {
+            let _stop_stream_err = self.stop_stream();
+        }
+    }
+}
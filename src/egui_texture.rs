@@ -0,0 +1,110 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{convert::frame_to_rgba, Camera};
+use egui::{ColorImage, Context, TextureHandle, TextureOptions};
+use nokhwa_core::{
+    buffer::Buffer, error::NokhwaError, frame_format::FrameFormat, traits::CaptureTrait,
+};
+
+/// Converts `frame`, which is in `format`, into an [`egui::ColorImage`].
+/// # Errors
+/// If the frame can not be converted to RGBA, this will error.
+pub fn frame_to_color_image(
+    frame: &Buffer,
+    format: FrameFormat,
+) -> Result<ColorImage, NokhwaError> {
+    let resolution = frame.resolution();
+    let size = [resolution.width() as usize, resolution.height() as usize];
+    let rgba = frame_to_rgba(frame, format)?;
+    if rgba.len() != size[0] * size[1] * 4 {
+        return Err(NokhwaError::ProcessFrameError {
+            src: format,
+            destination: "egui ColorImage".to_string(),
+            error: "decoded buffer has the wrong size".to_string(),
+        });
+    }
+    Ok(ColorImage::from_rgba_unmultiplied(size, &rgba))
+}
+
+/// A managed [`egui::TextureHandle`] that shows a live camera preview. Call [`update()`](Self::update) every frame;
+/// the texture is allocated on the first call and then updated in place.
+/// ```ignore
+/// // In your `eframe::App::update`:
+/// let frame = camera.frame()?;
+/// let texture = self.preview.update(ctx, &frame, camera.frame_format())?;
+/// ui.image(texture);
+/// ctx.request_repaint();
+/// ```
+pub struct CameraTexture {
+    name: String,
+    options: TextureOptions,
+    handle: Option<TextureHandle>,
+}
+
+impl CameraTexture {
+    /// Creates a new [`CameraTexture`]. `name` is used by egui for debugging.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        CameraTexture {
+            name: name.into(),
+            options: TextureOptions::LINEAR,
+            handle: None,
+        }
+    }
+
+    /// Sets the [`TextureOptions`] (e.g. filtering) of the texture.
+    #[must_use]
+    pub fn with_options(mut self, options: TextureOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Gets the texture, if a frame has been uploaded.
+    #[must_use]
+    pub fn texture(&self) -> Option<&TextureHandle> {
+        self.handle.as_ref()
+    }
+
+    /// Uploads `frame`, which is in `format`, and returns the texture to draw.
+    /// # Errors
+    /// If the frame can not be converted to RGBA, this will error.
+    pub fn update(
+        &mut self,
+        ctx: &Context,
+        frame: &Buffer,
+        format: FrameFormat,
+    ) -> Result<&TextureHandle, NokhwaError> {
+        let image = frame_to_color_image(frame, format)?;
+        match &mut self.handle {
+            Some(handle) => handle.set(image, self.options),
+            None => {
+                self.handle = Some(ctx.load_texture(self.name.clone(), image, self.options));
+            }
+        }
+        self.handle.as_ref().ok_or(NokhwaError::UnitializedError)
+    }
+}
+
+impl Camera {
+    /// Captures a frame and converts it into an [`egui::ColorImage`]. See [`frame_to_color_image()`].
+    /// # Errors
+    /// If capturing the frame fails, or it can not be converted, this will error.
+    pub fn frame_color_image(&mut self) -> Result<ColorImage, NokhwaError> {
+        let frame = self.frame()?;
+        frame_to_color_image(&frame, frame.source_frame_format())
+    }
+}
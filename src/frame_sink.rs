@@ -0,0 +1,106 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::yuv::{frame_to_i420, I420Frame};
+use nokhwa_core::{buffer::Buffer, error::NokhwaError, traits::CaptureTrait};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Takes planar YUV frames, e.g. to encode them. Frames from a camera are pushed with [`feed_sink()`].
+///
+/// Adapters for `openh264`, `x264`, and `rav1e` are available with the `output-openh264`, `output-x264`, and `output-rav1e`
+/// features. Implementing this for other encoders only takes mapping the planes of an [`I420Frame`] into their input.
+pub trait FrameSink {
+    /// Takes one frame. `timestamp` is the time since the first frame.
+    /// # Errors
+    /// If the sink fails to process the frame, this will error.
+    fn write_frame(&mut self, frame: &I420Frame, timestamp: Duration) -> Result<(), NokhwaError>;
+
+    /// Tells the sink there are no more frames, so it can flush anything it buffered.
+    /// # Errors
+    /// If flushing fails, this will error.
+    fn finish(&mut self) -> Result<(), NokhwaError> {
+        Ok(())
+    }
+}
+
+impl<S: FrameSink + ?Sized> FrameSink for &mut S {
+    fn write_frame(&mut self, frame: &I420Frame, timestamp: Duration) -> Result<(), NokhwaError> {
+        (**self).write_frame(frame, timestamp)
+    }
+
+    fn finish(&mut self) -> Result<(), NokhwaError> {
+        (**self).finish()
+    }
+}
+
+impl<S: FrameSink + ?Sized> FrameSink for Box<S> {
+    fn write_frame(&mut self, frame: &I420Frame, timestamp: Duration) -> Result<(), NokhwaError> {
+        (**self).write_frame(frame, timestamp)
+    }
+
+    fn finish(&mut self) -> Result<(), NokhwaError> {
+        (**self).finish()
+    }
+}
+
+/// A piece of encoded video produced by one of the encoder [`FrameSink`]s.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct EncodedPacket {
+    /// The encoded data, e.g. H.264 in Annex B format, or AV1 OBUs.
+    pub data: Vec<u8>,
+    /// The timestamp of the frame this packet belongs to.
+    pub timestamp: Duration,
+    /// Whether the frame can be decoded on its own.
+    pub keyframe: bool,
+}
+
+/// Captures frames from `camera` for `duration`, converts them to I420, and writes them to `sink`, with timestamps taken from the
+/// frames. The stream is opened if needed and left open, and the sink is not finished, so this can be called again to continue.
+/// # Errors
+/// If capturing a frame, converting it, or writing it to the sink fails, this will error.
+pub fn feed_sink(
+    camera: &mut (impl CaptureTrait + ?Sized),
+    sink: &mut (impl FrameSink + ?Sized),
+    duration: Duration,
+) -> Result<(), NokhwaError> {
+    if !camera.is_stream_open() {
+        camera.open_stream()?;
+    }
+    let start = Instant::now();
+    let mut first = None;
+    while start.elapsed() < duration {
+        let frame = camera.frame()?;
+        let time = frame_time(&frame, start);
+        let timestamp = time.saturating_sub(*first.get_or_insert(time));
+        sink.write_frame(
+            &frame_to_i420(&frame, frame.source_frame_format())?,
+            timestamp,
+        )?;
+    }
+    Ok(())
+}
+
+fn frame_time(frame: &Buffer, started: Instant) -> Duration {
+    match frame.timestamp() {
+        Some(timestamp) => timestamp.capture().unwrap_or_else(|| {
+            timestamp
+                .system()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+        }),
+        None => started.elapsed(),
+    }
+}
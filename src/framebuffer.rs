@@ -0,0 +1,143 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::convert::frame_to_rgb;
+use nokhwa_core::{buffer::Buffer, error::NokhwaError, frame_format::FrameFormat};
+
+/// How [`blit_rgba()`] and [`blit_xrgb()`] fit a frame into a framebuffer of a different size.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum BlitScaling {
+    /// Stretch the frame over the whole framebuffer, ignoring its aspect ratio.
+    Stretch,
+    /// Scale the frame to fit inside the framebuffer, keeping its aspect ratio, with black bars around it.
+    #[default]
+    Fit,
+    /// Scale the frame to cover the framebuffer, keeping its aspect ratio, cropping the edges that do not fit.
+    Fill,
+}
+
+/// Draws `frame`, which is in `format`, into an RGBA8888 framebuffer of `width` x `height`, such as
+/// [`pixels`](https://docs.rs/pixels)' `frame_mut()`. Scaling uses nearest neighbour.
+/// ```ignore
+/// let frame = camera.frame()?;
+/// blit_rgba(&frame, camera.frame_format(), pixels.frame_mut(), WIDTH, HEIGHT, BlitScaling::Fit)?;
+/// pixels.render()?;
+/// ```
+/// # Errors
+/// If the frame can not be decoded, or `framebuffer` is smaller than `width` x `height` pixels, this will error.
+pub fn blit_rgba(
+    frame: &Buffer,
+    format: FrameFormat,
+    framebuffer: &mut [u8],
+    width: u32,
+    height: u32,
+    scaling: BlitScaling,
+) -> Result<(), NokhwaError> {
+    check_len(framebuffer.len() / 4, width, height)?;
+    blit(frame, format, width, height, scaling, |index, [r, g, b]| {
+        framebuffer[index * 4..index * 4 + 4].copy_from_slice(&[r, g, b, 255]);
+    })
+}
+
+/// Draws `frame`, which is in `format`, into a `0RGB` framebuffer of `width` x `height` (one `u32` per pixel, `0x00RRGGBB`),
+/// such as a [`softbuffer`](https://docs.rs/softbuffer) `Buffer`. Scaling uses nearest neighbour.
+/// ```ignore
+/// let mut buffer = surface.buffer_mut()?;
+/// blit_xrgb(&camera.frame()?, camera.frame_format(), &mut buffer, width, height, BlitScaling::Fit)?;
+/// buffer.present()?;
+/// ```
+/// # Errors
+/// If the frame can not be decoded, or `framebuffer` is smaller than `width` x `height` pixels, this will error.
+pub fn blit_xrgb(
+    frame: &Buffer,
+    format: FrameFormat,
+    framebuffer: &mut [u32],
+    width: u32,
+    height: u32,
+    scaling: BlitScaling,
+) -> Result<(), NokhwaError> {
+    check_len(framebuffer.len(), width, height)?;
+    blit(frame, format, width, height, scaling, |index, [r, g, b]| {
+        framebuffer[index] = u32::from_be_bytes([0, r, g, b]);
+    })
+}
+
+fn check_len(pixels: usize, width: u32, height: u32) -> Result<(), NokhwaError> {
+    if pixels < width as usize * height as usize {
+        return Err(NokhwaError::StructureError {
+            structure: "framebuffer".to_string(),
+            error: format!("{pixels} pixels is too small for {width}x{height}"),
+        });
+    }
+    Ok(())
+}
+
+fn blit(
+    frame: &Buffer,
+    format: FrameFormat,
+    width: u32,
+    height: u32,
+    scaling: BlitScaling,
+    mut write: impl FnMut(usize, [u8; 3]),
+) -> Result<(), NokhwaError> {
+    let rgb = frame_to_rgb(frame, format)?;
+    let resolution = frame.resolution();
+    let (src_width, src_height) = (
+        i64::from(resolution.width()),
+        i64::from(resolution.height()),
+    );
+    let (dst_width, dst_height) = (i64::from(width), i64::from(height));
+    if src_width == 0 || src_height == 0 || rgb.len() < (src_width * src_height * 3) as usize {
+        return Err(NokhwaError::ProcessFrameError {
+            src: format,
+            destination: "framebuffer".to_string(),
+            error: "The decoded frame does not match its resolution".to_string(),
+        });
+    }
+
+    // The size of the scaled frame. It is centred, so it either has borders (fit) or is cropped (fill).
+    let width_limited = dst_width * src_height <= dst_height * src_width;
+    let (scaled_width, scaled_height) = match scaling {
+        BlitScaling::Stretch => (dst_width, dst_height),
+        BlitScaling::Fit if width_limited => (dst_width, src_height * dst_width / src_width),
+        BlitScaling::Fit => (src_width * dst_height / src_height, dst_height),
+        BlitScaling::Fill if width_limited => (src_width * dst_height / src_height, dst_height),
+        BlitScaling::Fill => (dst_width, src_height * dst_width / src_width),
+    };
+    let (scaled_width, scaled_height) = (scaled_width.max(1), scaled_height.max(1));
+    let offset_x = (dst_width - scaled_width) / 2;
+    let offset_y = (dst_height - scaled_height) / 2;
+
+    for y in 0..dst_height {
+        let scaled_y = y - offset_y;
+        let src_y = (0..scaled_height)
+            .contains(&scaled_y)
+            .then(|| scaled_y * src_height / scaled_height);
+        for x in 0..dst_width {
+            let scaled_x = x - offset_x;
+            let pixel = match src_y {
+                Some(src_y) if (0..scaled_width).contains(&scaled_x) => {
+                    let src_x = scaled_x * src_width / scaled_width;
+                    let index = ((src_y * src_width + src_x) * 3) as usize;
+                    [rgb[index], rgb[index + 1], rgb[index + 2]]
+                }
+                _ => [0, 0, 0],
+            };
+            write((y * dst_width + x) as usize, pixel);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,237 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::convert::frame_to_rgba;
+use glow::{HasContext, PixelUnpackData};
+use nokhwa_core::{
+    buffer::Buffer, error::NokhwaError, frame_format::FrameFormat, types::Resolution,
+};
+
+/// How a [`GlFrameTexture`] stores a frame, which tells you how to sample it in a shader.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum GlTextureLayout {
+    /// `GL_RGBA8`. Sample it directly.
+    Rgba,
+    /// `GL_RGB8`. Sample it directly.
+    Rgb,
+    /// `GL_R8`. The grayscale value is in the red channel.
+    Luma,
+    /// `GL_RGBA8` at half the frame width. Each texel holds two pixels as `(Y0, U, Y1, V)`.
+    /// See [`YUYV_TO_RGB_GLSL`] for converting it in a fragment shader.
+    Yuyv422,
+}
+
+impl GlTextureLayout {
+    fn for_format(format: FrameFormat) -> Self {
+        match format {
+            FrameFormat::Rgb8 => GlTextureLayout::Rgb,
+            FrameFormat::Luma8 => GlTextureLayout::Luma,
+            FrameFormat::Yuv422 => GlTextureLayout::Yuyv422,
+            _ => GlTextureLayout::Rgba,
+        }
+    }
+
+    /// Gets `(internal format, format, bytes per texel)`.
+    fn gl_formats(self) -> (u32, u32, u32) {
+        match self {
+            GlTextureLayout::Rgba | GlTextureLayout::Yuyv422 => (glow::RGBA8, glow::RGBA, 4),
+            GlTextureLayout::Rgb => (glow::RGB8, glow::RGB, 3),
+            GlTextureLayout::Luma => (glow::R8, glow::RED, 1),
+        }
+    }
+
+    fn texture_width(self, resolution: Resolution) -> u32 {
+        match self {
+            GlTextureLayout::Yuyv422 => resolution.width() / 2,
+            _ => resolution.width(),
+        }
+    }
+}
+
+/// A GLSL function that converts a texel of a [`GlTextureLayout::Yuyv422`] texture into RGB (BT.601).
+/// `x` is the horizontal position of the output pixel, in frame pixels.
+pub const YUYV_TO_RGB_GLSL: &str = r"
+vec3 nokhwa_yuyv_to_rgb(vec4 yuyv, float x) {
+    float y = mod(floor(x), 2.0) < 1.0 ? yuyv.r : yuyv.b;
+    float u = yuyv.g - 0.5;
+    float v = yuyv.a - 0.5;
+    return clamp(vec3(y + 1.402 * v, y - 0.344 * u - 0.714 * v, y + 1.772 * u), 0.0, 1.0);
+}
+";
+
+/// A `GL_TEXTURE_2D` that frames are uploaded into with `glTexSubImage2D`, without reallocating it.
+///
+/// RGB, RGBA, grayscale, and YUYV frames are uploaded as-is (see [`GlTextureLayout`]); everything else (e.g. MJPEG) is
+/// converted to RGBA on the CPU first.
+///
+/// All methods must be called with the GL context that created the texture current.
+pub struct GlFrameTexture {
+    texture: glow::Texture,
+    resolution: Resolution,
+    format: FrameFormat,
+    layout: GlTextureLayout,
+}
+
+impl GlFrameTexture {
+    /// Allocates a texture for frames of `resolution` in `format`.
+    /// # Errors
+    /// If creating the texture fails, this will error.
+    /// # Safety
+    /// `gl` must be current on this thread. See [`glow::HasContext`].
+    pub unsafe fn new(
+        gl: &glow::Context,
+        resolution: Resolution,
+        format: FrameFormat,
+    ) -> Result<Self, NokhwaError> {
+        let layout = GlTextureLayout::for_format(format);
+        let (internal_format, gl_format, _) = layout.gl_formats();
+        let texture = gl
+            .create_texture()
+            .map_err(|why| NokhwaError::ProcessFrameError {
+                src: format,
+                destination: "OpenGL Texture".to_string(),
+                error: why,
+            })?;
+
+        gl.bind_texture(glow::TEXTURE_2D, Some(texture));
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::LINEAR as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_WRAP_S,
+            glow::CLAMP_TO_EDGE as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_WRAP_T,
+            glow::CLAMP_TO_EDGE as i32,
+        );
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            internal_format as i32,
+            layout.texture_width(resolution) as i32,
+            resolution.height() as i32,
+            0,
+            gl_format,
+            glow::UNSIGNED_BYTE,
+            None,
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+
+        Ok(GlFrameTexture {
+            texture,
+            resolution,
+            format,
+            layout,
+        })
+    }
+
+    /// The GL texture name.
+    #[must_use]
+    pub fn texture(&self) -> glow::Texture {
+        self.texture
+    }
+
+    /// How the frame is stored in the texture.
+    #[must_use]
+    pub fn layout(&self) -> GlTextureLayout {
+        self.layout
+    }
+
+    /// The resolution of the frames this texture holds.
+    #[must_use]
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    /// Uploads `frame` into the texture.
+    /// # Errors
+    /// If the frame's resolution does not match, it is too small, or it can not be converted to RGBA, this will error.
+    /// # Safety
+    /// `gl` must be current on this thread and be the context that created this texture.
+    pub unsafe fn upload(&self, gl: &glow::Context, frame: &Buffer) -> Result<(), NokhwaError> {
+        if frame.resolution() != self.resolution {
+            return Err(NokhwaError::ProcessFrameError {
+                src: self.format,
+                destination: "OpenGL Texture".to_string(),
+                error: format!(
+                    "Frame is {}, texture is {}",
+                    frame.resolution(),
+                    self.resolution
+                ),
+            });
+        }
+
+        let (_, gl_format, bytes_per_texel) = self.layout.gl_formats();
+        let width = self.layout.texture_width(self.resolution);
+        let height = self.resolution.height();
+        let decoded;
+        let (data, row_length): (&[u8], u32) = match self.layout {
+            GlTextureLayout::Rgba if self.format != FrameFormat::RgbA8 => {
+                decoded = frame_to_rgba(frame, self.format)?;
+                (&decoded, width)
+            }
+            _ => (
+                frame.buffer(),
+                frame
+                    .stride()
+                    .map_or(width, |stride| stride / bytes_per_texel),
+            ),
+        };
+        if data.len() < ((row_length * (height - 1) + width) * bytes_per_texel) as usize {
+            return Err(NokhwaError::ProcessFrameError {
+                src: self.format,
+                destination: "OpenGL Texture".to_string(),
+                error: "Frame is too small".to_string(),
+            });
+        }
+
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+        gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, row_length as i32);
+        gl.tex_sub_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            0,
+            0,
+            width as i32,
+            height as i32,
+            gl_format,
+            glow::UNSIGNED_BYTE,
+            PixelUnpackData::Slice(data),
+        );
+        gl.pixel_store_i32(glow::UNPACK_ROW_LENGTH, 0);
+        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        Ok(())
+    }
+
+    /// Deletes the texture.
+    /// # Safety
+    /// `gl` must be current on this thread and be the context that created this texture.
+    pub unsafe fn delete(self, gl: &glow::Context) {
+        gl.delete_texture(self.texture);
+    }
+}
@@ -0,0 +1,278 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use gstreamer::{Caps, ClockTime, FlowError, Fraction};
+use gstreamer_app::AppSrc;
+use gstreamer_video::{VideoFormat, VideoFrameFlags, VideoInfo, VideoMeta};
+use nokhwa_core::{
+    buffer::Buffer, error::NokhwaError, frame_format::FrameFormat, traits::CaptureTrait,
+    types::CameraFormat,
+};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Pushes frames into a `GStreamer` `appsrc`, so a pipeline you built (e.g. `appsrc ! videoconvert ! x264enc ! ...`) can use a
+/// camera as its source.
+///
+/// The `appsrc` is set up as a live source in [`Format::Time`](gstreamer::Format::Time). Its caps are set from the
+/// [`CameraFormat`] of the frames, and updated if the format changes. Buffers are timestamped from the frame timestamps,
+/// relative to the first frame, so the pipeline sees the camera's real timing rather than the time the buffer was pushed.
+///
+/// Raw formats become `video/x-raw`, [`FrameFormat::MJpeg`] becomes `image/jpeg`, and [`FrameFormat::H264`] becomes
+/// `video/x-h264` in byte-stream format. Other formats can not be pushed.
+/// ```ignore
+/// let pipeline = gstreamer::parse_launch("appsrc name=src ! videoconvert ! autovideosink")?;
+/// let appsrc = pipeline.downcast_ref::<gstreamer::Bin>().unwrap().by_name("src").unwrap();
+/// let mut bridge = AppSrcBridge::new(appsrc.downcast().unwrap());
+/// pipeline.set_state(gstreamer::State::Playing)?;
+/// bridge.stream(&mut camera)?;
+/// ```
+pub struct AppSrcBridge {
+    appsrc: AppSrc,
+    format: Option<CameraFormat>,
+    video_info: Option<VideoInfo>,
+    first_timestamp: Option<Duration>,
+    started: Instant,
+}
+
+impl AppSrcBridge {
+    /// Wraps `appsrc`, setting it up as a live, time-based source.
+    #[must_use]
+    pub fn new(appsrc: AppSrc) -> Self {
+        appsrc.set_format(gstreamer::Format::Time);
+        appsrc.set_is_live(true);
+        appsrc.set_do_timestamp(false);
+        AppSrcBridge {
+            appsrc,
+            format: None,
+            video_info: None,
+            first_timestamp: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// The wrapped `appsrc`.
+    #[must_use]
+    pub fn appsrc(&self) -> &AppSrc {
+        &self.appsrc
+    }
+
+    /// The [`CameraFormat`] the caps were last set from, if a frame has been pushed.
+    #[must_use]
+    pub fn camera_format(&self) -> Option<CameraFormat> {
+        self.format
+    }
+
+    /// Pushes one frame captured in `format`.
+    /// # Errors
+    /// If the [`FrameFormat`] can not be described as caps, the frame does not match `format`, or the `appsrc` refuses the
+    /// buffer (e.g. the pipeline is flushing or has stopped), this will error.
+    pub fn push_frame(&mut self, frame: &Buffer, format: CameraFormat) -> Result<(), NokhwaError> {
+        self.push(frame, format)?.map_err(|why| flow_error(&why))
+    }
+
+    // The outer error is from preparing the buffer, the inner one from pushing it.
+    fn push(
+        &mut self,
+        frame: &Buffer,
+        format: CameraFormat,
+    ) -> Result<Result<(), FlowError>, NokhwaError> {
+        if frame.resolution() != format.resolution() {
+            return Err(NokhwaError::ProcessFrameError {
+                src: format.format(),
+                destination: "appsrc".to_string(),
+                error: format!(
+                    "Frame is {}, expected {}",
+                    frame.resolution(),
+                    format.resolution()
+                ),
+            });
+        }
+        if self.format != Some(format) {
+            self.set_caps(format)?;
+        }
+
+        let timestamp = frame_time(frame, self.started);
+        let first = *self.first_timestamp.get_or_insert(timestamp);
+        let pts = timestamp.saturating_sub(first);
+        let (numerator, denominator) = format.frame_rate().as_fraction();
+
+        let mut buffer = gstreamer::Buffer::from_slice(frame.buffer().to_vec());
+        {
+            let buffer = buffer.get_mut().ok_or_else(|| {
+                NokhwaError::GeneralError("Failed to get a writable buffer".to_string())
+            })?;
+            buffer.set_pts(ClockTime::from_nseconds(pts.as_nanos() as u64));
+            if numerator != 0 {
+                buffer.set_duration(ClockTime::from_nseconds(
+                    u64::from(denominator) * 1_000_000_000 / u64::from(numerator),
+                ));
+            }
+            if let (Some(info), Some(stride)) = (&self.video_info, frame.stride()) {
+                // The caps assume rows are packed to the default stride. If the driver pads rows differently, describe
+                // the real layout so downstream elements read the planes correctly.
+                if info.stride()[0] != stride as i32 {
+                    let (offsets, strides) = plane_layout(info, stride);
+                    VideoMeta::add_full(
+                        buffer,
+                        VideoFrameFlags::empty(),
+                        info.format(),
+                        info.width(),
+                        info.height(),
+                        &offsets,
+                        &strides,
+                    )
+                    .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+                }
+            }
+        }
+
+        Ok(self.appsrc.push_buffer(buffer).map(|_| ()))
+    }
+
+    /// Captures frames from `camera` and pushes them until the pipeline stops accepting buffers (it is flushing or reached
+    /// end-of-stream). The stream is opened if needed and left open.
+    /// # Errors
+    /// If capturing a frame fails, or the `appsrc` refuses a buffer for any other reason, this will error.
+    pub fn stream(&mut self, camera: &mut (impl CaptureTrait + ?Sized)) -> Result<(), NokhwaError> {
+        if !camera.is_stream_open() {
+            camera.open_stream()?;
+        }
+        let format = camera
+            .camera_format()
+            .ok_or_else(|| NokhwaError::GetPropertyError {
+                property: "CameraFormat".to_string(),
+                error: "The camera has no format".to_string(),
+            })?;
+        loop {
+            let frame = camera.frame()?;
+            match self.push(&frame, format)? {
+                Ok(()) => {}
+                Err(FlowError::Flushing | FlowError::Eos) => return Ok(()),
+                Err(why) => return Err(flow_error(&why)),
+            }
+        }
+    }
+
+    /// Signals end-of-stream to the pipeline, e.g. so a muxer can finish writing its file.
+    /// # Errors
+    /// If the `appsrc` refuses the event, this will error.
+    pub fn end_of_stream(&self) -> Result<(), NokhwaError> {
+        self.appsrc
+            .end_of_stream()
+            .map(|_| ())
+            .map_err(|why| flow_error(&why))
+    }
+
+    fn set_caps(&mut self, format: CameraFormat) -> Result<(), NokhwaError> {
+        let (numerator, denominator) = format.frame_rate().as_fraction();
+        let frame_rate = Fraction::new(numerator as i32, denominator.max(1) as i32);
+        let caps_error = |error: String| NokhwaError::ProcessFrameError {
+            src: format.format(),
+            destination: "appsrc caps".to_string(),
+            error,
+        };
+
+        let (caps, video_info) = match format.format() {
+            FrameFormat::MJpeg => (
+                Caps::builder("image/jpeg")
+                    .field("width", format.width() as i32)
+                    .field("height", format.height() as i32)
+                    .field("framerate", frame_rate)
+                    .build(),
+                None,
+            ),
+            FrameFormat::H264 => (
+                Caps::builder("video/x-h264")
+                    .field("width", format.width() as i32)
+                    .field("height", format.height() as i32)
+                    .field("framerate", frame_rate)
+                    .field("stream-format", "byte-stream")
+                    .field("alignment", "au")
+                    .build(),
+                None,
+            ),
+            other => {
+                let video_format =
+                    raw_video_format(other).ok_or_else(|| caps_error("unsupported".to_string()))?;
+                let info = VideoInfo::builder(video_format, format.width(), format.height())
+                    .fps(frame_rate)
+                    .build()
+                    .map_err(|why| caps_error(why.to_string()))?;
+                let caps = info.to_caps().map_err(|why| caps_error(why.to_string()))?;
+                (caps, Some(info))
+            }
+        };
+
+        self.appsrc.set_caps(Some(&caps));
+        self.format = Some(format);
+        self.video_info = video_info;
+        Ok(())
+    }
+}
+
+fn raw_video_format(format: FrameFormat) -> Option<VideoFormat> {
+    match format {
+        FrameFormat::Yuv422 => Some(VideoFormat::Yuy2),
+        FrameFormat::Uyv422 => Some(VideoFormat::Uyvy),
+        FrameFormat::Nv12 => Some(VideoFormat::Nv12),
+        FrameFormat::Nv21 => Some(VideoFormat::Nv21),
+        FrameFormat::Yv12 => Some(VideoFormat::Yv12),
+        FrameFormat::Luma8 => Some(VideoFormat::Gray8),
+        FrameFormat::Rgb8 => Some(VideoFormat::Rgb),
+        FrameFormat::RgbA8 => Some(VideoFormat::Rgba),
+        _ => None,
+    }
+}
+
+// Plane offsets and strides for a frame whose first plane has `stride` bytes per row, assuming the remaining planes are
+// scaled the same way as in the default layout.
+fn plane_layout(info: &VideoInfo, stride: u32) -> (Vec<usize>, Vec<i32>) {
+    let default_stride = info.stride()[0].max(1) as usize;
+    let stride = stride as usize;
+    let height = info.height() as usize;
+    let mut offsets = Vec::with_capacity(info.n_planes() as usize);
+    let mut strides = Vec::with_capacity(info.n_planes() as usize);
+    let mut offset = 0;
+    for plane in 0..info.n_planes() as usize {
+        let plane_stride = info.stride()[plane] as usize * stride / default_stride;
+        let plane_height = if plane == 0 {
+            height
+        } else {
+            // Every planar format nokhwa supports subsamples chroma vertically by 2.
+            (height + 1) / 2
+        };
+        offsets.push(offset);
+        strides.push(plane_stride as i32);
+        offset += plane_stride * plane_height;
+    }
+    (offsets, strides)
+}
+
+fn frame_time(frame: &Buffer, started: Instant) -> Duration {
+    match frame.timestamp() {
+        Some(timestamp) => timestamp.capture().unwrap_or_else(|| {
+            timestamp
+                .system()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+        }),
+        None => started.elapsed(),
+    }
+}
+
+fn flow_error(why: &FlowError) -> NokhwaError {
+    NokhwaError::GeneralError(format!("appsrc refused the buffer: {why}"))
+}
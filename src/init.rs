@@ -14,6 +14,9 @@
  * limitations under the License.
  */
 
+use nokhwa_core::error::NokhwaError;
+use std::fmt::{Display, Formatter};
+
 #[cfg(not(all(
     feature = "input-avfoundation",
     any(target_os = "macos", target_os = "ios")
@@ -36,21 +39,53 @@ fn init_avfoundation(callback: impl Fn(bool) + Send + Sync + 'static) {
     feature = "input-avfoundation",
     any(target_os = "macos", target_os = "ios")
 )))]
-fn status_avfoundation() -> bool {
-    true
+fn status_avfoundation() -> PermissionStatus {
+    PermissionStatus::Authorized
 }
 
 #[cfg(all(
     feature = "input-avfoundation",
     any(target_os = "macos", target_os = "ios")
 ))]
-fn status_avfoundation() -> bool {
+fn status_avfoundation() -> PermissionStatus {
     use nokhwa_bindings_macos::{current_authorization_status, AVAuthorizationStatus};
 
-    matches!(
-        current_authorization_status(),
-        AVAuthorizationStatus::Authorized
-    )
+    match current_authorization_status() {
+        AVAuthorizationStatus::NotDetermined => PermissionStatus::NotDetermined,
+        AVAuthorizationStatus::Restricted => PermissionStatus::Restricted,
+        AVAuthorizationStatus::Denied => PermissionStatus::Denied,
+        AVAuthorizationStatus::Authorized => PermissionStatus::Authorized,
+    }
+}
+
+/// The status of the permission the user has given (or not given) this application to access cameras.
+/// - `NotDetermined`: The user has not been asked yet. Call [`nokhwa_request_permission`] to prompt them.
+/// - `Restricted`: The user can not grant access (e.g. parental controls).
+/// - `Denied`: The user has explicitly denied access.
+/// - `Authorized`: The application may access cameras.
+///
+/// On platforms that do not gate camera access behind a permission prompt, this is always `Authorized`.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum PermissionStatus {
+    NotDetermined,
+    Restricted,
+    Denied,
+    Authorized,
+}
+
+impl PermissionStatus {
+    /// Returns true if the application may access cameras.
+    #[must_use]
+    pub fn is_authorized(self) -> bool {
+        matches!(self, PermissionStatus::Authorized)
+    }
+}
+
+impl Display for PermissionStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
 }
 
 #[cfg(feature = "input-jscam")]
@@ -105,5 +140,29 @@ pub fn nokhwa_initialize_callback(on_complete: impl Fn(bool) + Send + Sync + 'st
 /// True if the initialization is successful (ready-to-use)
 #[must_use]
 pub fn nokhwa_check() -> bool {
+    nokhwa_check_permission().is_authorized()
+}
+
+/// Check the current camera [`PermissionStatus`] without prompting the user.
+///
+/// Use this to decide if you need to call [`nokhwa_request_permission`] before opening a camera, instead of
+/// getting an opaque error when opening it.
+#[must_use]
+pub fn nokhwa_check_permission() -> PermissionStatus {
     status_avfoundation()
 }
+
+/// Ask the user for permission to access cameras.
+///
+/// The `on_complete` callback is called once the user has answered the prompt, with whether access was granted.
+/// If permission has already been decided, the callback is called immediately with the existing answer.
+///
+/// On platforms without a permission prompt, `on_complete` is immediately called with `true`.
+/// # JS-WASM
+/// In the browser, please use the `async` [`request_permission`] instead.
+pub fn nokhwa_request_permission(on_complete: impl Fn(bool) + Send + Sync + 'static) {
+    match nokhwa_check_permission() {
+        PermissionStatus::NotDetermined => init_avfoundation(on_complete),
+        status => on_complete(status.is_authorized()),
+    }
+}
@@ -32,6 +32,8 @@ use std::{
     fmt::{Debug, Display, Formatter},
     ops::Deref,
 };
+#[cfg(feature = "output-wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::{JsCast, JsValue};
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{
@@ -901,7 +903,7 @@ impl JSCameraConstraintsBuilder {
     #[allow(clippy::too_many_lines)]
     #[must_use]
     pub fn build(self) -> JSCameraConstraints {
-        let null_resolution = Resolution::default();
+        let null_resolution = Resolution::new(0, 0);
         let null_string = String::new();
 
         let mut video_object = Object::new();
@@ -1696,9 +1698,9 @@ impl JSCamera {
     /// # Errors
     /// This may error if permission is not granted, or the constraints are invalid.
     /// # JS-WASM
-    /// This is the constructor for `NokhwaCamera`. It returns a promise and may throw an error.
+    /// This is exported as `NokhwaCamera.open(constraints)`, since `wasm-bindgen` constructors can not be `async`. It returns a promise and may throw an error.
     #[cfg(feature = "output-wasm")]
-    #[cfg_attr(feature = "output-wasm", wasm_bindgen(constructor))]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(js_name = open))]
     pub async fn js_new(constraints: JSCameraConstraints) -> Result<JSCamera, JsValue> {
         match JSCamera::new(constraints).await {
             Ok(camera) => Ok(camera),
@@ -1842,6 +1844,41 @@ impl JSCamera {
         }
     }
 
+    /// The minimum length of the `Uint8Array` needed by [`writeFrameToBuffer`](crate::js_camera::JSCamera::js_write_frame_to_buffer).
+    /// # JS-WASM
+    /// This is exported as `minBufferSize`.
+    #[cfg(feature = "output-wasm")]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(js_name = minBufferSize))]
+    #[must_use]
+    pub fn js_min_buffer_size(&self, use_rgba: bool) -> usize {
+        self.min_buffer_size(use_rgba)
+    }
+
+    /// Writes the current frame (RGB24, or RGBA if `convert_rgba` is true) into a user provided `Uint8Array`, so it can be
+    /// reused every frame without allocating. Returns the amount of bytes written.
+    /// # Errors
+    /// If reading the frame fails, or the buffer is not exactly [`minBufferSize`](crate::js_camera::JSCamera::js_min_buffer_size) long, this will error.
+    /// # JS-WASM
+    /// This is exported as `writeFrameToBuffer`. This may throw an error.
+    #[cfg(feature = "output-wasm")]
+    #[cfg_attr(feature = "output-wasm", wasm_bindgen(js_name = writeFrameToBuffer))]
+    pub fn js_write_frame_to_buffer(
+        &mut self,
+        buffer: &mut [u8],
+        convert_rgba: bool,
+    ) -> Result<usize, JsValue> {
+        if buffer.len() != self.min_buffer_size(convert_rgba) {
+            return Err(JsValue::from(format!(
+                "Buffer must be {} bytes long",
+                self.min_buffer_size(convert_rgba)
+            )));
+        }
+        match self.write_frame_to_buffer(buffer, convert_rgba) {
+            Ok(written) => Ok(written),
+            Err(why) => Err(JsValue::from(why.to_string())),
+        }
+    }
+
     /// Copies camera frame to a `html_id`(by-id, canvas).
     ///
     /// If `generate_new` is true, the generated element will have an Id of `html_id`+`-canvas`. For example, if you pass "nokhwaisbest" for `html_id`, the new `<canvas>`'s ID will be "nokhwaisbest-canvas".
@@ -0,0 +1,176 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{collections::VecDeque, time::Duration};
+
+/// How many samples [`RollingStats`] keeps by default, about 4 seconds at 30 FPS.
+pub const DEFAULT_LATENCY_WINDOW: usize = 120;
+
+/// Statistics over the last few durations of something, e.g. how long decoding a frame took.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RollingStats {
+    samples: VecDeque<Duration>,
+    window: usize,
+}
+
+impl RollingStats {
+    /// Creates empty statistics that keep the last `window` samples (at least 1).
+    #[must_use]
+    pub fn new(window: usize) -> Self {
+        RollingStats {
+            samples: VecDeque::with_capacity(window.max(1)),
+            window: window.max(1),
+        }
+    }
+
+    /// Adds a sample, forgetting the oldest one if the window is full.
+    pub fn record(&mut self, sample: Duration) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Forgets all samples.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// How many samples there are.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// The most recent sample.
+    #[must_use]
+    pub fn last(&self) -> Option<Duration> {
+        self.samples.back().copied()
+    }
+
+    /// The smallest sample.
+    #[must_use]
+    pub fn min(&self) -> Option<Duration> {
+        self.samples.iter().min().copied()
+    }
+
+    /// The largest sample.
+    #[must_use]
+    pub fn max(&self) -> Option<Duration> {
+        self.samples.iter().max().copied()
+    }
+
+    /// The average of the samples.
+    #[must_use]
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.samples.len())
+            .ok()
+            .filter(|count| *count > 0)?;
+        Some(self.samples.iter().sum::<Duration>() / count)
+    }
+
+    /// The sample that `percent` percent of the samples are at or below, e.g. `99.0` for the 99th percentile.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn percentile(&self, percent: f64) -> Option<Duration> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted = self.samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        let rank = (percent.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64).round();
+        sorted.get(rank as usize).copied()
+    }
+}
+
+impl Default for RollingStats {
+    fn default() -> Self {
+        RollingStats::new(DEFAULT_LATENCY_WINDOW)
+    }
+}
+
+/// Where the time between the camera capturing a frame and the application getting it goes. See
+/// [`Camera::latency_stats()`](crate::Camera::latency_stats).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LatencyStats {
+    capture_to_delivery: RollingStats,
+    decode: RollingStats,
+    conversion: RollingStats,
+}
+
+impl LatencyStats {
+    /// Creates empty statistics that keep the last `window` samples of each measurement.
+    #[must_use]
+    pub fn new(window: usize) -> Self {
+        LatencyStats {
+            capture_to_delivery: RollingStats::new(window),
+            decode: RollingStats::new(window),
+            conversion: RollingStats::new(window),
+        }
+    }
+
+    /// From the camera capturing a frame (the driver timestamp, where the backend provides one) to
+    /// [`frame()`](nokhwa_core::traits::CaptureTrait::frame) returning it.
+    ///
+    /// Backends that cannot tell how long a frame sat in the driver only measure from when nokhwa received it.
+    #[must_use]
+    pub fn capture_to_delivery(&self) -> &RollingStats {
+        &self.capture_to_delivery
+    }
+
+    /// Decompressing MJPEG frames, in [`Camera::frame_rgb()`](crate::Camera::frame_rgb) and
+    /// [`Camera::frame_rgba()`](crate::Camera::frame_rgba).
+    #[must_use]
+    pub fn decode(&self) -> &RollingStats {
+        &self.decode
+    }
+
+    /// Converting uncompressed frames (e.g. YUYV, NV12) to RGB, in [`Camera::frame_rgb()`](crate::Camera::frame_rgb) and
+    /// [`Camera::frame_rgba()`](crate::Camera::frame_rgba).
+    #[must_use]
+    pub fn conversion(&self) -> &RollingStats {
+        &self.conversion
+    }
+
+    /// Forgets all samples.
+    pub fn clear(&mut self) {
+        self.capture_to_delivery.clear();
+        self.decode.clear();
+        self.conversion.clear();
+    }
+
+    pub(crate) fn record_delivery(&mut self, latency: Duration) {
+        self.capture_to_delivery.record(latency);
+    }
+
+    pub(crate) fn record_decode(&mut self, time: Duration) {
+        self.decode.record(time);
+    }
+
+    pub(crate) fn record_conversion(&mut self, time: Duration) {
+        self.conversion.record(time);
+    }
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        LatencyStats::new(DEFAULT_LATENCY_WINDOW)
+    }
+}
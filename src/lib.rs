@@ -33,32 +33,178 @@
 /// Raw access to each of Nokhwa's backends.
 pub mod backends;
 mod camera;
+mod camera_builder;
+#[cfg(feature = "output-image")]
+mod color_correction;
+#[cfg(feature = "output-image")]
+mod convert;
+#[cfg(feature = "output-threaded")]
+mod device_streams;
+#[cfg(feature = "output-egui")]
+mod egui_texture;
+#[cfg(feature = "output-sink")]
+mod frame_sink;
+#[cfg(feature = "output-framebuffer")]
+mod framebuffer;
+#[cfg(feature = "output-glow")]
+mod gl_texture;
+#[cfg(feature = "output-gst")]
+mod gst_appsrc;
 mod init;
 /// A camera that uses native browser APIs meant for WASM applications.
 #[cfg(feature = "input-jscam")]
 #[cfg_attr(feature = "docs-features", doc(cfg(feature = "input-jscam")))]
 pub mod js_camera;
+mod latency;
+#[cfg(feature = "output-mjpeg-server")]
+mod mjpeg_server;
+#[cfg(feature = "output-openh264")]
+mod openh264_sink;
+#[cfg(feature = "output-image")]
+mod overlay;
 mod platform_resolver;
 
 pub use nokhwa_core::pixel_format::FormatDecoder;
 #[cfg(feature = "output-async")]
 #[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-async")))]
 pub mod async_camera;
+#[cfg(feature = "output-clip")]
+mod clip;
+#[cfg(feature = "output-image")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-image")))]
+pub mod decoders;
 mod query;
+#[cfg(feature = "output-rav1e")]
+mod rav1e_sink;
+mod raw_reader;
+#[cfg(feature = "output-recorder")]
+mod recorder;
+#[cfg(all(feature = "output-shm", unix))]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-shm")))]
+pub mod shm_export;
+#[cfg(feature = "output-image")]
+mod snapshot;
+mod stream_stats;
+#[cfg(feature = "output-threaded")]
+mod sync_group;
 /// A camera that runs in a different thread and can call your code based on callbacks.
 #[cfg(feature = "output-threaded")]
 #[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-threaded")))]
 pub mod threaded;
-pub mod decoders;
+mod timelapse;
+/// A camera whose blocking calls run on `tokio`'s blocking thread pool.
+#[cfg(feature = "output-tokio")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-tokio")))]
+pub mod tokio_camera;
+mod typestate;
+#[cfg(feature = "output-webrtc")]
+mod webrtc_track;
+#[cfg(feature = "output-wgpu")]
+mod wgpu_texture;
+#[cfg(feature = "output-x264")]
+mod x264_sink;
+#[cfg(any(feature = "output-webrtc", feature = "output-sink"))]
+mod yuv;
 
-pub use camera::Camera;
+pub use camera::{Camera, DEFAULT_BRACKET_SETTLE_FRAMES};
+pub use camera_builder::CameraBuilder;
+#[cfg(feature = "output-clip")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-clip")))]
+pub use clip::{ClipFormat, ClipLength, ClipRecorder};
+#[cfg(feature = "output-image")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-image")))]
+pub use color_correction::{ColorCorrection, ColorPreset};
+#[cfg(feature = "output-image")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-image")))]
+pub use convert::{
+    crop_pixels, deinterlace, frame_to_rgb, frame_to_rgb_scaled, frame_to_rgba,
+    frame_to_rgba_scaled, resample_square_pixels, rotate_pixels,
+};
+#[cfg(feature = "output-threaded")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-threaded")))]
+pub use device_streams::{DeviceStreams, TaggedFrame, DEFAULT_STREAM_BACKLOG};
+#[cfg(feature = "output-egui")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-egui")))]
+pub use egui_texture::{frame_to_color_image, CameraTexture};
+#[cfg(feature = "output-sink")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-sink")))]
+pub use frame_sink::{feed_sink, EncodedPacket, FrameSink};
+#[cfg(feature = "output-framebuffer")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-framebuffer")))]
+pub use framebuffer::{blit_rgba, blit_xrgb, BlitScaling};
+#[cfg(feature = "output-glow")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-glow")))]
+pub use gl_texture::{GlFrameTexture, GlTextureLayout, YUYV_TO_RGB_GLSL};
+#[cfg(feature = "output-gst")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-gst")))]
+pub use gst_appsrc::AppSrcBridge;
 pub use init::*;
-pub use nokhwa_core::buffer::Buffer;
+pub use latency::{LatencyStats, RollingStats, DEFAULT_LATENCY_WINDOW};
+#[cfg(feature = "output-mjpeg-server")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-mjpeg-server")))]
+pub use mjpeg_server::{MjpegServer, MjpegStopHandle};
+pub use nokhwa_core::buffer::{Buffer, Frame};
 pub use nokhwa_core::error::NokhwaError;
+pub use nokhwa_core::pool::{BufferPool, FrameBuffer};
+#[cfg(feature = "output-openh264")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-openh264")))]
+pub use openh264_sink::OpenH264Sink;
+#[cfg(feature = "output-image")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-image")))]
+pub use overlay::{Overlay, OverlayContext, OverlayItem, OverlayPosition};
 pub use query::*;
+#[cfg(feature = "output-rav1e")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-rav1e")))]
+pub use rav1e_sink::Rav1eSink;
+pub use raw_reader::{RawFrameReader, RAW_FRAME_HEADER_LEN, RAW_FRAME_MAGIC};
+#[cfg(feature = "output-recorder")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-recorder")))]
+pub use recorder::{Recorder, VideoCodec, VideoContainer};
+#[cfg(feature = "output-image")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-image")))]
+pub use snapshot::ImageOutputFormat;
+pub use stream_stats::{StreamStats, DEFAULT_STREAM_STATS_WINDOW};
+#[cfg(feature = "output-threaded")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-threaded")))]
+pub use sync_group::{FrameSet, SyncCaptureGroup, DEFAULT_SYNC_BACKLOG};
 #[cfg(feature = "output-threaded")]
 #[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-threaded")))]
-pub use threaded::CallbackCamera;
+pub use threaded::{
+    CallbackCamera, ChangeDetection, DropPolicy, FrameQueue, FrameReceiver, ThreadedCamera,
+};
+pub use timelapse::{Timelapse, TimelapseHandle, TimelapsePower, DEFAULT_TIMELAPSE_WARMUP_FRAMES};
+pub use typestate::{CameraState, Configured, Streaming, TypedCamera};
+#[cfg(feature = "output-webrtc")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-webrtc")))]
+pub use webrtc_track::{CameraVideoTrack, VideoEncoder};
+#[cfg(feature = "output-wgpu")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-wgpu")))]
+pub use wgpu_texture::{frame_to_texture, update_texture};
+#[cfg(feature = "output-x264")]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-x264")))]
+pub use x264_sink::X264Sink;
+#[cfg(any(feature = "output-webrtc", feature = "output-sink"))]
+#[cfg_attr(
+    feature = "docs-features",
+    doc(cfg(any(feature = "output-webrtc", feature = "output-sink")))
+)]
+pub use yuv::{frame_to_i420, I420Frame};
+
+/// The types and functions most applications need, in one `use nokhwa::prelude::*;`.
+pub mod prelude {
+    pub use crate::{
+        native_api_backend, nokhwa_check, nokhwa_initialize_callback, probe, query, query_grouped,
+        Buffer, Camera, CameraBuilder, Frame, NokhwaError,
+    };
+    pub use nokhwa_core::{
+        frame_format::FrameFormat,
+        traits::CaptureTrait,
+        types::{
+            ApiBackend, CameraFormat, CameraIndex, CameraInfo, ControlValueSetter, FrameRate,
+            KnownCameraControl, RequestedFormat, RequestedFormatType, Resolution,
+        },
+    };
+}
 
 pub mod utils {
     pub use nokhwa_core::types::*;
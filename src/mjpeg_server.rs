@@ -0,0 +1,322 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::convert::frame_to_rgb;
+use image::{codecs::jpeg::JpegEncoder, ColorType, ImageEncoder};
+use nokhwa_core::{
+    buffer::Buffer, error::NokhwaError, frame_format::FrameFormat, traits::CaptureTrait,
+};
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+const BOUNDARY: &str = "nokhwa-frame";
+
+/// How long a client may take to send its request, or to take a frame, before it is disconnected.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serves a camera as an MJPEG stream over HTTP (`multipart/x-mixed-replace`), which browsers show like a video when it is
+/// opened directly or used as the `src` of an `<img>`.
+///
+/// If the camera outputs [`FrameFormat::MJpeg`], its frames are sent as they are, without decoding or re-encoding them. Frames in
+/// any other format are encoded to JPEG. Every client gets the latest frame; slow clients skip frames rather than falling behind.
+///
+/// Frames are only encoded while a client is connected, and clients that stall for more than 10 seconds are dropped.
+/// ```ignore
+/// // Open http://<host>:8080/stream in a browser.
+/// let server = MjpegServer::bind("0.0.0.0:8080")?;
+/// let stop = server.stop_handle();
+/// // Call `stop.stop()` from another thread to end `serve()`.
+/// server.serve(&mut camera)?;
+/// ```
+pub struct MjpegServer {
+    listener: TcpListener,
+    path: String,
+    quality: u8,
+    stop: Arc<AtomicBool>,
+}
+
+/// Stops [`MjpegServer::serve()`] from another thread. Get one with [`MjpegServer::stop_handle()`].
+#[derive(Clone, Debug)]
+pub struct MjpegStopHandle {
+    stop: Arc<AtomicBool>,
+}
+
+impl MjpegStopHandle {
+    /// Makes `serve()` return after the frame it is capturing, disconnecting every client.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+
+    /// Checks if [`stop()`](Self::stop) was called.
+    #[must_use]
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+}
+
+impl MjpegServer {
+    /// Listens on `address`. The stream is served at `/stream`.
+    /// # Errors
+    /// If the address can not be bound, this will error.
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, NokhwaError> {
+        let listener = TcpListener::bind(address)
+            .map_err(|why| NokhwaError::from_io("Bind MJPEG server", why))?;
+        Ok(MjpegServer {
+            listener,
+            path: "/stream".to_string(),
+            quality: 80,
+            stop: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// Serves the stream at `path` (e.g. `/` or `/camera.mjpg`) instead of `/stream`. Other paths get a `404`.
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// The JPEG quality (1-100) used for cameras that do not output MJPEG. Defaults to 80.
+    #[must_use]
+    pub fn with_quality(mut self, quality: u8) -> Self {
+        self.quality = quality.clamp(1, 100);
+        self
+    }
+
+    /// The address the server is listening on, e.g. to find the port after binding to port `0`.
+    /// # Errors
+    /// If the address can not be read from the socket, this will error.
+    pub fn local_addr(&self) -> Result<SocketAddr, NokhwaError> {
+        self.listener
+            .local_addr()
+            .map_err(|why| NokhwaError::from_io("Get MJPEG server address", why))
+    }
+
+    /// A handle to stop [`serve()`](Self::serve) with, from another thread.
+    #[must_use]
+    pub fn stop_handle(&self) -> MjpegStopHandle {
+        MjpegStopHandle {
+            stop: self.stop.clone(),
+        }
+    }
+
+    /// Captures frames from `camera` and serves them until capturing fails or [`MjpegStopHandle::stop()`] is called. The
+    /// stream is opened if needed and left open.
+    ///
+    /// Clients are accepted and served on background threads, which stop when this returns.
+    /// # Errors
+    /// If capturing or encoding a frame fails, this will error.
+    pub fn serve(self, camera: &mut (impl CaptureTrait + ?Sized)) -> Result<(), NokhwaError> {
+        if !camera.is_stream_open() {
+            camera.open_stream()?;
+        }
+        let address = self.local_addr()?;
+        let shared = Arc::new(Shared {
+            latest: Mutex::new(Latest {
+                number: 0,
+                jpeg: Arc::new(Vec::new()),
+            }),
+            new_frame: Condvar::new(),
+            clients: AtomicUsize::new(0),
+            stopped: AtomicBool::new(false),
+        });
+
+        let acceptor = {
+            let shared = shared.clone();
+            let path = self.path.clone();
+            let listener = self.listener;
+            thread::spawn(move || accept_clients(&listener, &path, &shared))
+        };
+
+        let result = publish_frames(camera, self.quality, &self.stop, &shared);
+
+        // Hold the lock so no client can miss the wake up between checking the flag and waiting.
+        if let Ok(_latest) = shared.latest.lock() {
+            shared.stopped.store(true, Ordering::SeqCst);
+        }
+        shared.new_frame.notify_all();
+        // Wake the acceptor up from `accept()` so it sees the flag.
+        let _ = TcpStream::connect(wake_address(address));
+        let _ = acceptor.join();
+        result
+    }
+}
+
+struct Latest {
+    number: u64,
+    jpeg: Arc<Vec<u8>>,
+}
+
+struct Shared {
+    latest: Mutex<Latest>,
+    new_frame: Condvar,
+    // The clients being streamed to. Frames are not encoded while there are none.
+    clients: AtomicUsize,
+    stopped: AtomicBool,
+}
+
+/// Counts a client for as long as it is streamed to.
+struct ClientGuard<'a>(&'a AtomicUsize);
+
+impl<'a> ClientGuard<'a> {
+    fn new(clients: &'a AtomicUsize) -> Self {
+        clients.fetch_add(1, Ordering::SeqCst);
+        ClientGuard(clients)
+    }
+}
+
+impl Drop for ClientGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Connecting to an unspecified address (`0.0.0.0`, `::`) does not work everywhere, so connect over loopback instead.
+fn wake_address(mut address: SocketAddr) -> SocketAddr {
+    if address.ip().is_unspecified() {
+        address.set_ip(match address {
+            SocketAddr::V4(_) => Ipv4Addr::LOCALHOST.into(),
+            SocketAddr::V6(_) => Ipv6Addr::LOCALHOST.into(),
+        });
+    }
+    address
+}
+
+fn publish_frames(
+    camera: &mut (impl CaptureTrait + ?Sized),
+    quality: u8,
+    stop: &AtomicBool,
+    shared: &Shared,
+) -> Result<(), NokhwaError> {
+    while !stop.load(Ordering::SeqCst) {
+        // Keep capturing while nobody watches, so the first client gets a fresh frame rather than a stale buffered one.
+        let frame = camera.frame()?;
+        if shared.clients.load(Ordering::SeqCst) == 0 {
+            continue;
+        }
+        let jpeg = to_jpeg(&frame, frame.source_frame_format(), quality)?;
+        if let Ok(mut latest) = shared.latest.lock() {
+            latest.number += 1;
+            latest.jpeg = Arc::new(jpeg);
+        }
+        shared.new_frame.notify_all();
+    }
+    Ok(())
+}
+
+fn to_jpeg(frame: &Buffer, format: FrameFormat, quality: u8) -> Result<Vec<u8>, NokhwaError> {
+    if format == FrameFormat::MJpeg {
+        return Ok(frame.buffer().to_vec());
+    }
+    let resolution = frame.resolution();
+    let rgb = frame_to_rgb(frame, format)?;
+    let mut jpeg = Vec::new();
+    JpegEncoder::new_with_quality(&mut jpeg, quality)
+        .write_image(
+            &rgb,
+            resolution.width(),
+            resolution.height(),
+            ColorType::Rgb8,
+        )
+        .map_err(|why| NokhwaError::ProcessFrameError {
+            src: format,
+            destination: "JPEG".to_string(),
+            error: why.to_string(),
+        })?;
+    Ok(jpeg)
+}
+
+fn accept_clients(listener: &TcpListener, path: &str, shared: &Arc<Shared>) {
+    for stream in listener.incoming() {
+        if shared.stopped.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let shared = shared.clone();
+        let path = path.to_string();
+        thread::spawn(move || {
+            let _ = serve_client(stream, &path, &shared);
+        });
+    }
+}
+
+fn serve_client(mut stream: TcpStream, path: &str, shared: &Shared) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Skip the headers, we do not need any of them.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next(), parts.next());
+    let requested = target.map(|target| target.split('?').next().unwrap_or(target));
+    if method != Some("GET") || requested != Some(path) {
+        return stream.write_all(
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        );
+    }
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: multipart/x-mixed-replace; boundary={BOUNDARY}\r\nCache-Control: no-cache, no-store\r\nPragma: no-cache\r\nConnection: close\r\n\r\n"
+    )?;
+    let _client = ClientGuard::new(&shared.clients);
+    // Start with the next frame. The latest one may be from before anyone was watching.
+    let mut sent = match shared.latest.lock() {
+        Ok(latest) => latest.number,
+        Err(_) => return Ok(()),
+    };
+    loop {
+        let jpeg = {
+            let Ok(mut latest) = shared.latest.lock() else {
+                return Ok(());
+            };
+            while latest.number == sent && !shared.stopped.load(Ordering::SeqCst) {
+                latest = match shared.new_frame.wait(latest) {
+                    Ok(latest) => latest,
+                    Err(_) => return Ok(()),
+                };
+            }
+            if shared.stopped.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+            sent = latest.number;
+            latest.jpeg.clone()
+        };
+        write!(
+            stream,
+            "--{BOUNDARY}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+            jpeg.len()
+        )?;
+        stream.write_all(&jpeg)?;
+        stream.write_all(b"\r\n")?;
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    frame_sink::{EncodedPacket, FrameSink},
+    yuv::I420Frame,
+};
+use nokhwa_core::{error::NokhwaError, types::Resolution};
+use openh264::{
+    encoder::{Encoder, EncoderConfig, FrameType},
+    formats::YUVSource,
+};
+use std::time::Duration;
+
+/// A [`FrameSink`] that encodes frames to H.264 (Annex B) with `openh264`, and passes every [`EncodedPacket`] to a callback.
+///
+/// The encoder is created for the resolution of the first frame. Every frame must have that resolution.
+/// ```ignore
+/// let mut file = File::create("out.h264")?;
+/// let mut sink = OpenH264Sink::new(|packet| file.write_all(&packet.data).unwrap());
+/// feed_sink(&mut camera, &mut sink, Duration::from_secs(10))?;
+/// ```
+pub struct OpenH264Sink<F: FnMut(EncodedPacket)> {
+    encoder: Option<(Encoder, Resolution)>,
+    bitrate: Option<u32>,
+    on_packet: F,
+}
+
+impl<F: FnMut(EncodedPacket)> OpenH264Sink<F> {
+    /// Creates a sink that calls `on_packet` with every encoded frame.
+    pub fn new(on_packet: F) -> Self {
+        OpenH264Sink {
+            encoder: None,
+            bitrate: None,
+            on_packet,
+        }
+    }
+
+    /// Sets the target bitrate in bits per second, instead of `openh264`'s default.
+    #[must_use]
+    pub fn with_bitrate(mut self, bitrate: u32) -> Self {
+        self.bitrate = Some(bitrate);
+        self
+    }
+}
+
+impl<F: FnMut(EncodedPacket)> FrameSink for OpenH264Sink<F> {
+    fn write_frame(&mut self, frame: &I420Frame, timestamp: Duration) -> Result<(), NokhwaError> {
+        let resolution = frame.resolution();
+        let (encoder, expected) = match &mut self.encoder {
+            Some(encoder) => encoder,
+            encoder @ None => {
+                let mut config = EncoderConfig::new(resolution.width(), resolution.height());
+                if let Some(bitrate) = self.bitrate {
+                    config = config.set_bitrate_bps(bitrate);
+                }
+                let created = Encoder::with_config(config)
+                    .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+                encoder.insert((created, resolution))
+            }
+        };
+        if resolution != *expected {
+            return Err(NokhwaError::GeneralError(format!(
+                "Frame is {resolution}, but the encoder was created for {expected}"
+            )));
+        }
+
+        let bitstream = encoder
+            .encode(&I420Source(frame))
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+        let keyframe = matches!(bitstream.frame_type(), FrameType::IDR | FrameType::I);
+        let data = bitstream.to_vec();
+        if !data.is_empty() {
+            (self.on_packet)(EncodedPacket {
+                data,
+                timestamp,
+                keyframe,
+            });
+        }
+        Ok(())
+    }
+}
+
+struct I420Source<'a>(&'a I420Frame);
+
+impl YUVSource for I420Source<'_> {
+    fn width(&self) -> i32 {
+        self.0.resolution().width() as i32
+    }
+
+    fn height(&self) -> i32 {
+        self.0.resolution().height() as i32
+    }
+
+    fn y(&self) -> &[u8] {
+        self.0.y()
+    }
+
+    fn u(&self) -> &[u8] {
+        self.0.u()
+    }
+
+    fn v(&self) -> &[u8] {
+        self.0.v()
+    }
+
+    fn y_stride(&self) -> i32 {
+        self.0.resolution().width() as i32
+    }
+
+    fn u_stride(&self) -> i32 {
+        self.0.chroma_resolution().width() as i32
+    }
+
+    fn v_stride(&self) -> i32 {
+        self.0.chroma_resolution().width() as i32
+    }
+}
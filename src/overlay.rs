@@ -0,0 +1,321 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::snapshot::utc_date_time;
+use nokhwa_core::types::Resolution;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where an [`Overlay`] item is drawn. Corners keep a small margin from the edge of the frame.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum OverlayPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    /// The top left corner of the item, in pixels from the top left of the frame.
+    At(u32, u32),
+}
+
+/// Something an [`Overlay`] draws onto frames.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OverlayItem {
+    /// Fixed text. Only ASCII letters, digits, and common punctuation are drawn; lowercase is drawn as uppercase, and anything else as `?`.
+    Text(String),
+    /// The time the frame was captured, in UTC, as `YYYY-MM-DD HH:MM:SS.mmm`.
+    Timestamp,
+    /// The human readable name of the camera.
+    CameraName,
+    /// A small RGBA8888 image, e.g. a logo, blended using its alpha channel.
+    Image {
+        rgba: Vec<u8>,
+        resolution: Resolution,
+    },
+}
+
+/// What an [`Overlay`] needs to know about the frame it draws onto.
+#[derive(Copy, Clone, Debug)]
+pub struct OverlayContext<'a> {
+    /// When the frame was captured.
+    pub captured: SystemTime,
+    /// The name of the camera that captured the frame.
+    pub camera_name: &'a str,
+}
+
+/// Burns text (timestamps, the camera name) and small images (watermarks) into decoded frames, e.g. for dashcams,
+/// lab recordings, and surveillance. Set it on a camera with [`Camera::set_overlay()`](crate::Camera::set_overlay), or
+/// [`apply()`](Self::apply) it to RGB(A) data yourself.
+///
+/// Text uses a built in 5x7 pixel font, scaled up by [`with_scale()`](Self::with_scale).
+/// ```ignore
+/// camera.set_overlay(Some(
+///     Overlay::new()
+///         .with_item(OverlayItem::Timestamp, OverlayPosition::BottomLeft)
+///         .with_item(OverlayItem::CameraName, OverlayPosition::TopLeft),
+/// ));
+/// let frame = camera.frame_rgb()?;
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Overlay {
+    items: Vec<(OverlayItem, OverlayPosition)>,
+    scale: u32,
+    color: [u8; 4],
+    background: Option<[u8; 4]>,
+}
+
+impl Overlay {
+    /// Creates an empty overlay that draws white text at twice the font size on a translucent black box.
+    #[must_use]
+    pub fn new() -> Self {
+        Overlay {
+            items: Vec::new(),
+            scale: 2,
+            color: [255, 255, 255, 255],
+            background: Some([0, 0, 0, 160]),
+        }
+    }
+
+    /// Adds an item to draw at `position`. Items are drawn in the order they were added, so later ones are on top.
+    #[must_use]
+    pub fn with_item(mut self, item: OverlayItem, position: OverlayPosition) -> Self {
+        self.items.push((item, position));
+        self
+    }
+
+    /// Sets how many frame pixels each font pixel covers (at least 1).
+    #[must_use]
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Sets the RGBA color of text.
+    #[must_use]
+    pub fn with_color(mut self, color: [u8; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the RGBA color of the box behind text, or `None` for no box.
+    #[must_use]
+    pub fn with_background(mut self, background: Option<[u8; 4]>) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// The items, in drawing order.
+    #[must_use]
+    pub fn items(&self) -> &[(OverlayItem, OverlayPosition)] {
+        &self.items
+    }
+
+    /// Draws the overlay onto `data`, a packed `resolution` sized image with `pixel_size` bytes per pixel (3 for RGB888, 4 for
+    /// RGBA8888). Anything that falls outside the frame is clipped.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn apply(
+        &self,
+        data: &mut [u8],
+        resolution: Resolution,
+        pixel_size: usize,
+        context: &OverlayContext<'_>,
+    ) {
+        let mut canvas = Canvas {
+            data,
+            width: resolution.width(),
+            height: resolution.height(),
+            pixel_size: pixel_size.max(3),
+        };
+        for (item, position) in &self.items {
+            match item {
+                OverlayItem::Text(text) => self.draw_text(&mut canvas, text, *position),
+                OverlayItem::Timestamp => {
+                    self.draw_text(&mut canvas, &format_timestamp(context.captured), *position);
+                }
+                OverlayItem::CameraName => {
+                    self.draw_text(&mut canvas, context.camera_name, *position);
+                }
+                OverlayItem::Image { rgba, resolution } => {
+                    let (x, y) = canvas.place(
+                        resolution.width(),
+                        resolution.height(),
+                        *position,
+                        self.scale,
+                    );
+                    if resolution.width() == 0 {
+                        continue;
+                    }
+                    for (index, pixel) in rgba.chunks_exact(4).enumerate() {
+                        let index = index as u32;
+                        let (column, row) =
+                            (index % resolution.width(), index / resolution.width());
+                        if row >= resolution.height() {
+                            break;
+                        }
+                        canvas.blend(x + i64::from(column), y + i64::from(row), pixel);
+                    }
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn draw_text(&self, canvas: &mut Canvas<'_>, text: &str, position: OverlayPosition) {
+        let scale = self.scale;
+        let glyphs = text.chars().count() as u32;
+        let width = (glyphs * GLYPH_ADVANCE).saturating_sub(1) * scale;
+        let height = GLYPH_HEIGHT * scale;
+        let (x, y) = canvas.place(width, height, position, scale);
+
+        if let Some(background) = self.background {
+            let padding = i64::from(scale);
+            for row in y - padding..y + i64::from(height) + padding {
+                for column in x - padding..x + i64::from(width) + padding {
+                    canvas.blend(column, row, &background);
+                }
+            }
+        }
+        for (index, character) in text.chars().enumerate() {
+            let left = x + i64::from(index as u32 * GLYPH_ADVANCE * scale);
+            for (row, bits) in glyph(character).iter().enumerate() {
+                for column in 0..5 {
+                    if bits & (0x10 >> column) == 0 {
+                        continue;
+                    }
+                    let top = y + i64::from(row as u32 * scale);
+                    let left = left + i64::from(column * scale);
+                    for dy in 0..i64::from(scale) {
+                        for dx in 0..i64::from(scale) {
+                            canvas.blend(left + dx, top + dy, &self.color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Overlay::new()
+    }
+}
+
+struct Canvas<'a> {
+    data: &'a mut [u8],
+    width: u32,
+    height: u32,
+    pixel_size: usize,
+}
+
+impl Canvas<'_> {
+    /// The top left corner of a `width` by `height` item at `position`.
+    fn place(&self, width: u32, height: u32, position: OverlayPosition, scale: u32) -> (i64, i64) {
+        let margin = i64::from(4 * scale);
+        let right = i64::from(self.width) - i64::from(width) - margin;
+        let bottom = i64::from(self.height) - i64::from(height) - margin;
+        match position {
+            OverlayPosition::TopLeft => (margin, margin),
+            OverlayPosition::TopRight => (right, margin),
+            OverlayPosition::BottomLeft => (margin, bottom),
+            OverlayPosition::BottomRight => (right, bottom),
+            OverlayPosition::At(x, y) => (i64::from(x), i64::from(y)),
+        }
+    }
+
+    /// Blends the RGBA `color` over the pixel at (`x`, `y`), if it is inside the frame.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn blend(&mut self, x: i64, y: i64, color: &[u8]) {
+        if x < 0 || y < 0 || x >= i64::from(self.width) || y >= i64::from(self.height) {
+            return;
+        }
+        let at = (y as usize * self.width as usize + x as usize) * self.pixel_size;
+        let Some(pixel) = self.data.get_mut(at..at + 3) else {
+            return;
+        };
+        let alpha = u16::from(color[3]);
+        for (channel, source) in pixel.iter_mut().zip(color) {
+            *channel =
+                ((u16::from(*source) * alpha + u16::from(*channel) * (255 - alpha)) / 255) as u8;
+        }
+    }
+}
+
+/// Formats `time` in UTC as `YYYY-MM-DD HH:MM:SS.mmm`.
+fn format_timestamp(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second) = utc_date_time(time);
+    let millis = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.subsec_millis());
+    format!("{year:04}-{month:02}-{day:02} {hour:02}:{minute:02}:{second:02}.{millis:03}")
+}
+
+const GLYPH_HEIGHT: u32 = 7;
+/// Glyph width plus one column of spacing.
+const GLYPH_ADVANCE: u32 = 6;
+
+/// The 5x7 bitmap of `character`, one byte per row with the leftmost pixel in bit 4.
+fn glyph(character: char) -> [u8; 7] {
+    match character.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '%' => [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
@@ -16,7 +16,7 @@
 
 use nokhwa_core::{
     error::NokhwaError,
-    types::{ApiBackend, CameraInfo},
+    types::{ApiBackend, AudioDeviceInfo, CameraIndex, CameraInfo},
 };
 
 /// Gets the native [`ApiBackend`]
@@ -105,8 +105,99 @@ pub fn query(api: ApiBackend) -> Result<Vec<CameraInfo>, NokhwaError> {
     }
 }
 
+/// Query the system for a list of available devices (see [`query`]), grouped by the physical device they belong to.
+///
+/// Each inner `Vec` is a set of sibling cameras (e.g. the RGB and IR camera of a Windows Hello webcam) that can be opened together.
+/// Cameras whose backend cannot tell which device they belong to are returned in a group of their own.
+/// # Errors
+/// See [`query`].
+pub fn query_grouped(api: ApiBackend) -> Result<Vec<Vec<CameraInfo>>, NokhwaError> {
+    let mut groups: Vec<Vec<CameraInfo>> = vec![];
+    for info in query(api)? {
+        match groups
+            .iter_mut()
+            .find(|group| group[0].is_sibling_of(&info))
+        {
+            Some(group) => group.push(info),
+            None => groups.push(vec![info]),
+        }
+    }
+    Ok(groups)
+}
+
+/// Gets the [`CameraInfo`] of one camera without opening it, e.g. to show a list of cameras at startup without waiting for
+/// each of them to open. This does not claim the camera, so it works even if another application is capturing from it.
+///
+/// With `V4L2` this only reads sysfs. Other backends [`query`] every camera and pick the one at `index`.
+/// # Errors
+/// If there is no camera at `index`, or see [`query`].
+pub fn probe(api: ApiBackend, index: &CameraIndex) -> Result<CameraInfo, NokhwaError> {
+    let api = match api {
+        ApiBackend::Auto => {
+            native_api_backend().ok_or(NokhwaError::UnsupportedOperationError(api))?
+        }
+        api => api,
+    };
+    if api == ApiBackend::Video4Linux {
+        return probe_v4l(index);
+    }
+    query(api)?
+        .into_iter()
+        .find(|info| info.index() == index)
+        .ok_or_else(|| NokhwaError::OpenDeviceError(index.to_string(), "Not Found".to_string()))
+}
+
+/// Finds the microphone built into the same physical device as the camera `info` describes, so applications can offer
+/// "use this camera's microphone". This does not open the camera.
+///
+/// Returns `Ok(None)` if the camera has no microphone, or it could not be matched to one.
+/// # Quirks
+/// - `V4L2`: Matched by USB topology in sysfs. The id is the ALSA device name, e.g. `hw:CARD=C920`.
+/// - Other backends are not supported yet.
+/// # Errors
+/// If the backend is not supported, this will error.
+pub fn associated_microphone(
+    api: ApiBackend,
+    info: &CameraInfo,
+) -> Result<Option<AudioDeviceInfo>, NokhwaError> {
+    let api = match api {
+        ApiBackend::Auto => {
+            native_api_backend().ok_or(NokhwaError::UnsupportedOperationError(api))?
+        }
+        api => api,
+    };
+    match api {
+        ApiBackend::Video4Linux => associated_microphone_v4l(info.index()),
+        api => Err(NokhwaError::UnsupportedOperationError(api)),
+    }
+}
+
 // TODO: More
 
+#[cfg(all(feature = "input-v4l", target_os = "linux"))]
+fn probe_v4l(index: &CameraIndex) -> Result<CameraInfo, NokhwaError> {
+    nokhwa_bindings_linux::probe(index)
+}
+
+#[cfg(any(not(feature = "input-v4l"), not(target_os = "linux")))]
+fn probe_v4l(_index: &CameraIndex) -> Result<CameraInfo, NokhwaError> {
+    Err(NokhwaError::UnsupportedOperationError(
+        ApiBackend::Video4Linux,
+    ))
+}
+
+#[cfg(all(feature = "input-v4l", target_os = "linux"))]
+fn associated_microphone_v4l(index: &CameraIndex) -> Result<Option<AudioDeviceInfo>, NokhwaError> {
+    nokhwa_bindings_linux::associated_audio_device(index)
+}
+
+#[cfg(any(not(feature = "input-v4l"), not(target_os = "linux")))]
+fn associated_microphone_v4l(_index: &CameraIndex) -> Result<Option<AudioDeviceInfo>, NokhwaError> {
+    Err(NokhwaError::UnsupportedOperationError(
+        ApiBackend::Video4Linux,
+    ))
+}
+
 #[cfg(all(feature = "input-v4l", target_os = "linux"))]
 fn query_v4l() -> Result<Vec<CameraInfo>, NokhwaError> {
     nokhwa_bindings_linux::query()
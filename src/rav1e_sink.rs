@@ -0,0 +1,145 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    frame_sink::{EncodedPacket, FrameSink},
+    yuv::I420Frame,
+};
+use nokhwa_core::{error::NokhwaError, types::Resolution};
+use rav1e::{
+    config::SpeedSettings,
+    prelude::{ChromaSampling, Config, Context, EncoderConfig, EncoderStatus, FrameType, Rational},
+};
+use std::{collections::HashMap, time::Duration};
+
+/// A [`FrameSink`] that encodes frames to AV1 with `rav1e`, and passes every [`EncodedPacket`] (a temporal unit of OBUs) to a
+/// callback.
+///
+/// The encoder is set up for low latency, and is created for the resolution of the first frame. Every frame must have that
+/// resolution. Call [`finish()`](FrameSink::finish) to get the last frames out of the encoder.
+/// ```ignore
+/// let mut sink = Rav1eSink::new(30, |packet| packets.push(packet));
+/// feed_sink(&mut camera, &mut sink, Duration::from_secs(10))?;
+/// sink.finish()?;
+/// ```
+pub struct Rav1eSink<F: FnMut(EncodedPacket)> {
+    context: Option<(Context<u8>, Resolution)>,
+    frame_rate: u32,
+    speed: u8,
+    // rav1e only numbers frames, so remember the timestamp of each until its packet comes out.
+    timestamps: HashMap<u64, Duration>,
+    frames_sent: u64,
+    on_packet: F,
+}
+
+impl<F: FnMut(EncodedPacket)> Rav1eSink<F> {
+    /// Creates a sink for video at about `frame_rate` frames per second, that calls `on_packet` with every encoded frame.
+    pub fn new(frame_rate: u32, on_packet: F) -> Self {
+        Rav1eSink {
+            context: None,
+            frame_rate: frame_rate.max(1),
+            speed: 10,
+            timestamps: HashMap::new(),
+            frames_sent: 0,
+            on_packet,
+        }
+    }
+
+    /// Sets the speed preset, from 0 (slowest, best quality) to 10 (fastest, the default).
+    #[must_use]
+    pub fn with_speed(mut self, speed: u8) -> Self {
+        self.speed = speed.min(10);
+        self
+    }
+
+    fn receive_packets(&mut self) -> Result<(), NokhwaError> {
+        let Some((context, _)) = &mut self.context else {
+            return Ok(());
+        };
+        loop {
+            match context.receive_packet() {
+                Ok(packet) => {
+                    let timestamp = self
+                        .timestamps
+                        .remove(&packet.input_frameno)
+                        .unwrap_or_default();
+                    (self.on_packet)(EncodedPacket {
+                        data: packet.data,
+                        timestamp,
+                        keyframe: packet.frame_type == FrameType::KEY,
+                    });
+                }
+                Err(EncoderStatus::Encoded) => {}
+                Err(EncoderStatus::NeedMoreData | EncoderStatus::LimitReached) => return Ok(()),
+                Err(why) => return Err(NokhwaError::GeneralError(why.to_string())),
+            }
+        }
+    }
+}
+
+impl<F: FnMut(EncodedPacket)> FrameSink for Rav1eSink<F> {
+    fn write_frame(&mut self, frame: &I420Frame, timestamp: Duration) -> Result<(), NokhwaError> {
+        let resolution = frame.resolution();
+        let (context, expected) = match &mut self.context {
+            Some(context) => context,
+            context @ None => {
+                let config = Config::new().with_encoder_config(EncoderConfig {
+                    width: resolution.width() as usize,
+                    height: resolution.height() as usize,
+                    chroma_sampling: ChromaSampling::Cs420,
+                    time_base: Rational::new(1, u64::from(self.frame_rate)),
+                    low_latency: true,
+                    speed_settings: SpeedSettings::from_preset(self.speed),
+                    ..Default::default()
+                });
+                let created = config
+                    .new_context()
+                    .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+                context.insert((created, resolution))
+            }
+        };
+        if resolution != *expected {
+            return Err(NokhwaError::GeneralError(format!(
+                "Frame is {resolution}, but the encoder was created for {expected}"
+            )));
+        }
+
+        let mut input = context.new_frame();
+        let luma_stride = resolution.width() as usize;
+        let chroma_stride = frame.chroma_resolution().width() as usize;
+        input.planes[0].copy_from_raw_u8(frame.y(), luma_stride, 1);
+        input.planes[1].copy_from_raw_u8(frame.u(), chroma_stride, 1);
+        input.planes[2].copy_from_raw_u8(frame.v(), chroma_stride, 1);
+        context
+            .send_frame(input)
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+        self.timestamps.insert(self.frames_sent, timestamp);
+        self.frames_sent += 1;
+
+        self.receive_packets()
+    }
+
+    fn finish(&mut self) -> Result<(), NokhwaError> {
+        if let Some((context, _)) = &mut self.context {
+            context.flush();
+        }
+        self.receive_packets()?;
+        self.context = None;
+        self.timestamps.clear();
+        self.frames_sent = 0;
+        Ok(())
+    }
+}
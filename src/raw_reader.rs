@@ -0,0 +1,101 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use nokhwa_core::traits::CaptureTrait;
+use std::io::{self, Read};
+
+/// The magic bytes at the start of every frame header written by [`RawFrameReader`].
+pub const RAW_FRAME_MAGIC: [u8; 4] = *b"NKFR";
+
+/// The size of a frame header written by [`RawFrameReader`], in bytes.
+pub const RAW_FRAME_HEADER_LEN: usize = 16;
+
+/// An [`io::Read`] adapter that emits the raw frames of a camera back to back, so they can be piped into anything that takes a byte stream
+/// (e.g. `ffmpeg -f rawvideo -i -`). The stream is opened on the first read if needed.
+///
+/// With [`with_header()`](Self::with_header), every frame is preceded by a [`RAW_FRAME_HEADER_LEN`] byte header:
+/// [`RAW_FRAME_MAGIC`], then the width, height, and payload length as little endian `u32`s. This is needed for
+/// compressed formats such as MJPEG, where frames are not all the same size.
+///
+/// Capture errors are returned as [`io::ErrorKind::Other`] with the [`NokhwaError`](nokhwa_core::error::NokhwaError) as the inner error.
+pub struct RawFrameReader<'a, C: CaptureTrait + ?Sized> {
+    camera: &'a mut C,
+    header: bool,
+    pending: Vec<u8>,
+    position: usize,
+}
+
+impl<'a, C: CaptureTrait + ?Sized> RawFrameReader<'a, C> {
+    /// Creates a new [`RawFrameReader`] that emits frames without headers.
+    pub fn new(camera: &'a mut C) -> Self {
+        RawFrameReader {
+            camera,
+            header: false,
+            pending: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Sets whether each frame is preceded by a header.
+    #[must_use]
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    fn next_frame(&mut self) -> io::Result<()> {
+        if !self.camera.is_stream_open() {
+            self.camera
+                .open_stream()
+                .map_err(|why| io::Error::new(io::ErrorKind::Other, why))?;
+        }
+        let frame = self
+            .camera
+            .frame()
+            .map_err(|why| io::Error::new(io::ErrorKind::Other, why))?;
+
+        self.pending.clear();
+        self.position = 0;
+        if self.header {
+            let length = u32::try_from(frame.buffer().len())
+                .map_err(|why| io::Error::new(io::ErrorKind::InvalidData, why))?;
+            self.pending.extend_from_slice(&RAW_FRAME_MAGIC);
+            self.pending
+                .extend_from_slice(&frame.resolution().width().to_le_bytes());
+            self.pending
+                .extend_from_slice(&frame.resolution().height().to_le_bytes());
+            self.pending.extend_from_slice(&length.to_le_bytes());
+        }
+        self.pending.extend_from_slice(frame.buffer());
+        Ok(())
+    }
+}
+
+impl<'a, C: CaptureTrait + ?Sized> Read for RawFrameReader<'a, C> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.position >= self.pending.len() {
+            self.next_frame()?;
+        }
+        let remaining = &self.pending[self.position..];
+        let count = remaining.len().min(buf.len());
+        buf[..count].copy_from_slice(&remaining[..count]);
+        self.position += count;
+        Ok(count)
+    }
+}
@@ -0,0 +1,308 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use nokhwa_core::{
+    buffer::Buffer, error::NokhwaError, frame_format::FrameFormat, traits::CaptureTrait,
+    types::CameraFormat,
+};
+use std::{
+    ffi::OsString,
+    io::Write,
+    path::Path,
+    process::{Child, ChildStdin, Command, Stdio},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// The video codec a [`Recorder`] encodes with.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum VideoCodec {
+    /// H.264 with `libx264`.
+    H264,
+    /// VP9 with `libvpx-vp9`.
+    Vp9,
+}
+
+impl VideoCodec {
+    fn encoder(self) -> &'static str {
+        match self {
+            VideoCodec::H264 => "libx264",
+            VideoCodec::Vp9 => "libvpx-vp9",
+        }
+    }
+}
+
+/// The container a [`Recorder`] writes. Both can hold either [`VideoCodec`].
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum VideoContainer {
+    Mp4,
+    Mkv,
+}
+
+impl VideoContainer {
+    /// Picks the container from a file extension (`mp4`, `mkv`).
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "mp4" => Some(VideoContainer::Mp4),
+            "mkv" => Some(VideoContainer::Mkv),
+            _ => None,
+        }
+    }
+
+    fn muxer(self) -> &'static str {
+        match self {
+            VideoContainer::Mp4 => "mp4",
+            VideoContainer::Mkv => "matroska",
+        }
+    }
+}
+
+/// Records frames into an MP4 or MKV file by piping them into an `ffmpeg` process, which must be installed (and on the `PATH`,
+/// unless you use [`with_ffmpeg()`](Self::with_ffmpeg)).
+///
+/// Cameras do not deliver frames at a perfectly constant rate, so the video is written at the nominal frame rate of the
+/// [`CameraFormat`], and frames are repeated or dropped according to their timestamps to keep the video in sync with real time.
+///
+/// The file is finalized by [`finish()`](Self::finish), or when the [`Recorder`] is dropped (ignoring errors).
+/// ```ignore
+/// let mut recorder = Recorder::new("out.mp4", camera.camera_format().unwrap(), VideoCodec::H264)?;
+/// recorder.record(&mut camera, Duration::from_secs(10))?;
+/// recorder.finish()?;
+/// ```
+pub struct Recorder {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    format: CameraFormat,
+    frame_rate: f64,
+    first_timestamp: Option<Duration>,
+    started: Instant,
+    frames_written: u64,
+}
+
+impl Recorder {
+    /// Starts recording to `path`. The container is picked from the file extension.
+    /// # Errors
+    /// If the extension is not `mp4` or `mkv`, the [`FrameFormat`] can not be recorded, or `ffmpeg` could not be started, this will error.
+    pub fn new(
+        path: impl AsRef<Path>,
+        format: CameraFormat,
+        codec: VideoCodec,
+    ) -> Result<Self, NokhwaError> {
+        Self::with_ffmpeg("ffmpeg", path, format, codec)
+    }
+
+    /// Starts recording to `path` using the `ffmpeg` executable at `ffmpeg`.
+    /// # Errors
+    /// If the extension is not `mp4` or `mkv`, the [`FrameFormat`] can not be recorded, or `ffmpeg` could not be started, this will error.
+    pub fn with_ffmpeg(
+        ffmpeg: impl Into<OsString>,
+        path: impl AsRef<Path>,
+        format: CameraFormat,
+        codec: VideoCodec,
+    ) -> Result<Self, NokhwaError> {
+        let path = path.as_ref();
+        let container = path
+            .extension()
+            .and_then(|extension| VideoContainer::from_extension(&extension.to_string_lossy()))
+            .ok_or_else(|| NokhwaError::StructureError {
+                structure: "VideoContainer".to_string(),
+                error: "The path must end in .mp4 or .mkv".to_string(),
+            })?;
+        let (numerator, denominator) = format.frame_rate().as_fraction();
+        if numerator == 0 || denominator == 0 {
+            return Err(NokhwaError::StructureError {
+                structure: "CameraFormat".to_string(),
+                error: "The frame rate must be above 0".to_string(),
+            });
+        }
+
+        let mut command = Command::new(ffmpeg.into());
+        command.args(["-hide_banner", "-loglevel", "error", "-y"]);
+        match format.format() {
+            FrameFormat::MJpeg => {
+                command.args(["-f", "mjpeg"]);
+            }
+            other => {
+                let pixel_format =
+                    raw_pixel_format(other).ok_or_else(|| NokhwaError::ProcessFrameError {
+                        src: other,
+                        destination: codec.encoder().to_string(),
+                        error: "unsupported".to_string(),
+                    })?;
+                command.args(["-f", "rawvideo", "-pixel_format", pixel_format]);
+                command.args([
+                    "-video_size",
+                    &format!("{}x{}", format.width(), format.height()),
+                ]);
+            }
+        }
+        command.args([
+            "-framerate",
+            &format!("{numerator}/{denominator}"),
+            "-i",
+            "-",
+        ]);
+        command.args(["-c:v", codec.encoder(), "-pix_fmt", "yuv420p"]);
+        command.args(["-f", container.muxer()]);
+        command.arg(path);
+
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|why| NokhwaError::from_io("Start ffmpeg", why))?;
+        let stdin = child.stdin.take();
+
+        Ok(Recorder {
+            child,
+            stdin,
+            format,
+            frame_rate: f64::from(numerator) / f64::from(denominator),
+            first_timestamp: None,
+            started: Instant::now(),
+            frames_written: 0,
+        })
+    }
+
+    /// The [`CameraFormat`] this [`Recorder`] expects frames in.
+    #[must_use]
+    pub fn camera_format(&self) -> CameraFormat {
+        self.format
+    }
+
+    /// The number of frames written to the video so far, including repeated frames.
+    #[must_use]
+    pub fn frames_written(&self) -> u64 {
+        self.frames_written
+    }
+
+    /// The length of the video written so far.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frames_written as f64 / self.frame_rate)
+    }
+
+    /// Adds a frame to the video. The frame is written zero or more times, depending on its timestamp (see the [`Recorder`] docs).
+    /// # Errors
+    /// If the frame does not match the [`CameraFormat`], or writing to `ffmpeg` fails (e.g. it exited), this will error.
+    pub fn write_frame(&mut self, frame: &Buffer) -> Result<(), NokhwaError> {
+        if frame.resolution() != self.format.resolution() {
+            return Err(NokhwaError::ProcessFrameError {
+                src: self.format.format(),
+                destination: "Recorder".to_string(),
+                error: format!(
+                    "Frame is {}, expected {}",
+                    frame.resolution(),
+                    self.format.resolution()
+                ),
+            });
+        }
+
+        let timestamp = self.frame_time(frame);
+        let first = *self.first_timestamp.get_or_insert(timestamp);
+        let elapsed = timestamp.saturating_sub(first).as_secs_f64();
+        let target = (elapsed * self.frame_rate).round() as u64 + 1;
+        let copies = target.saturating_sub(self.frames_written);
+
+        let stdin = self
+            .stdin
+            .as_mut()
+            .ok_or_else(|| NokhwaError::GeneralError("Recorder is finished".to_string()))?;
+        for _ in 0..copies {
+            stdin
+                .write_all(frame.buffer())
+                .map_err(|why| NokhwaError::from_io("Write frame to ffmpeg", why))?;
+        }
+        self.frames_written += copies;
+        Ok(())
+    }
+
+    /// Records frames from `camera` for `duration`. The stream is opened if needed and left open.
+    /// # Errors
+    /// If capturing a frame or writing it fails, this will error.
+    pub fn record(
+        &mut self,
+        camera: &mut (impl CaptureTrait + ?Sized),
+        duration: Duration,
+    ) -> Result<(), NokhwaError> {
+        if !camera.is_stream_open() {
+            camera.open_stream()?;
+        }
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            let frame = camera.frame()?;
+            self.write_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    /// Finishes the video and waits for `ffmpeg` to write it out.
+    /// # Errors
+    /// If `ffmpeg` fails to finalize the file, this will error.
+    pub fn finish(mut self) -> Result<(), NokhwaError> {
+        self.finalize()
+    }
+
+    fn finalize(&mut self) -> Result<(), NokhwaError> {
+        // Closing stdin signals the end of the input to ffmpeg.
+        if self.stdin.take().is_none() {
+            return Ok(());
+        }
+        let status = self
+            .child
+            .wait()
+            .map_err(|why| NokhwaError::from_io("Wait for ffmpeg", why))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(NokhwaError::GeneralError(format!(
+                "ffmpeg failed to finish the video: {status}"
+            )))
+        }
+    }
+
+    fn frame_time(&self, frame: &Buffer) -> Duration {
+        match frame.timestamp() {
+            Some(timestamp) => timestamp.capture().unwrap_or_else(|| {
+                timestamp
+                    .system()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+            }),
+            None => self.started.elapsed(),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let _ = self.finalize();
+    }
+}
+
+fn raw_pixel_format(format: FrameFormat) -> Option<&'static str> {
+    match format {
+        FrameFormat::Yuv422 => Some("yuyv422"),
+        FrameFormat::Uyv422 => Some("uyvy422"),
+        FrameFormat::Nv12 => Some("nv12"),
+        FrameFormat::Nv21 => Some("nv21"),
+        FrameFormat::Luma8 => Some("gray"),
+        FrameFormat::Rgb8 => Some("rgb24"),
+        FrameFormat::RgbA8 => Some("rgba"),
+        _ => None,
+    }
+}
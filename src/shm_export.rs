@@ -0,0 +1,190 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Exports frames into a shared memory ring buffer, so that one capture process can feed many consumer processes.
+//!
+//! The ring lives in `/dev/shm/<name>`. Consumers `mmap` that file read-only. All integers are little endian.
+//!
+//! The file starts with a [`SHM_HEADER_LEN`] byte header:
+//!
+//! | Offset | Size | Field                                              |
+//! |--------|------|----------------------------------------------------|
+//! | 0      | 8    | [`SHM_MAGIC`]                                      |
+//! | 8      | 4    | Protocol version ([`SHM_VERSION`])                 |
+//! | 12     | 4    | Slot count                                         |
+//! | 16     | 8    | Slot size in bytes, including the slot header      |
+//! | 24     | 8    | Sequence of the latest complete frame (0 for none) |
+//!
+//! Followed by the slots. Frame `n` (starting at 1) is written to slot `(n - 1) % slot count`. Each slot starts with a [`SHM_SLOT_HEADER_LEN`] byte header:
+//!
+//! | Offset | Size | Field                                                      |
+//! |--------|------|------------------------------------------------------------|
+//! | 0      | 8    | Twice the frame's sequence. Odd while being written.       |
+//! | 8      | 4    | Width                                                      |
+//! | 12     | 4    | Height                                                     |
+//! | 16     | 4    | Payload length                                             |
+//! | 20     | 16   | Frame format name, ASCII, padded with `0`s                 |
+//!
+//! To read a slot, read its sequence, copy it, then read the sequence again. If the sequence is odd or changed, the copy is torn and must be discarded.
+
+use nokhwa_core::{buffer::Buffer, error::NokhwaError};
+use std::{
+    fs::{File, OpenOptions},
+    os::unix::fs::FileExt,
+    path::{Path, PathBuf},
+};
+
+/// The magic bytes at the start of the shared memory file.
+pub const SHM_MAGIC: [u8; 8] = *b"NOKHWASH";
+/// The protocol version.
+pub const SHM_VERSION: u32 = 1;
+/// The size of the file header, in bytes.
+pub const SHM_HEADER_LEN: u64 = 32;
+/// The size of a slot header, in bytes.
+pub const SHM_SLOT_HEADER_LEN: u64 = 36;
+
+const FORMAT_NAME_LEN: usize = 16;
+
+/// Writes frames into a named shared memory ring buffer. See the [module documentation](self) for the layout.
+///
+/// The shared memory file is removed when this is dropped.
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-shm")))]
+pub struct SharedMemoryExporter {
+    file: File,
+    path: PathBuf,
+    slot_count: u32,
+    slot_size: u64,
+    last_sequence: u64,
+}
+
+impl SharedMemoryExporter {
+    /// Creates (or replaces) the shared memory ring `/dev/shm/<name>` with `slot_count` slots, each large enough for a frame of `max_frame_len` bytes.
+    /// # Errors
+    /// If `name` is not a plain file name, `slot_count` is 0, or the file cannot be created, this will error.
+    pub fn create(name: &str, slot_count: u32, max_frame_len: u64) -> Result<Self, NokhwaError> {
+        if name.is_empty() || name.contains('/') || slot_count == 0 {
+            return Err(NokhwaError::StructureError {
+                structure: "SharedMemoryExporter".to_string(),
+                error: format!("invalid name {name:?} or slot count {slot_count}"),
+            });
+        }
+        let path = Path::new("/dev/shm").join(name);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(|why| NokhwaError::StructureError {
+                structure: path.display().to_string(),
+                error: why.to_string(),
+            })?;
+
+        let slot_size = SHM_SLOT_HEADER_LEN + max_frame_len;
+        let mut header = Vec::with_capacity(SHM_HEADER_LEN as usize);
+        header.extend_from_slice(&SHM_MAGIC);
+        header.extend_from_slice(&SHM_VERSION.to_le_bytes());
+        header.extend_from_slice(&slot_count.to_le_bytes());
+        header.extend_from_slice(&slot_size.to_le_bytes());
+        header.extend_from_slice(&0_u64.to_le_bytes());
+
+        let exporter = SharedMemoryExporter {
+            file,
+            path,
+            slot_count,
+            slot_size,
+            last_sequence: 0,
+        };
+        exporter
+            .file
+            .set_len(SHM_HEADER_LEN + slot_size * u64::from(slot_count))
+            .and_then(|()| exporter.file.write_all_at(&header, 0))
+            .map_err(|why| NokhwaError::StructureError {
+                structure: exporter.path.display().to_string(),
+                error: why.to_string(),
+            })?;
+        Ok(exporter)
+    }
+
+    /// The path of the shared memory file, for consumers to open.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The sequence number of the last frame written, or 0 if none was.
+    #[must_use]
+    pub fn last_sequence(&self) -> u64 {
+        self.last_sequence
+    }
+
+    /// Writes a frame into the next slot, returning its sequence number.
+    /// # Errors
+    /// If the frame is larger than a slot, or the write fails, this will error.
+    pub fn write_frame(&mut self, frame: &Buffer) -> Result<u64, NokhwaError> {
+        let payload = frame.buffer();
+        let payload_len = u32::try_from(payload.len())
+            .ok()
+            .filter(|len| u64::from(*len) + SHM_SLOT_HEADER_LEN <= self.slot_size)
+            .ok_or_else(|| NokhwaError::ProcessFrameError {
+                src: frame.source_frame_format(),
+                destination: "Shared Memory".to_string(),
+                error: format!("frame of {} bytes does not fit a slot", payload.len()),
+            })?;
+
+        let sequence = self.last_sequence + 1;
+        let slot = (sequence - 1) % u64::from(self.slot_count);
+        let offset = SHM_HEADER_LEN + slot * self.slot_size;
+
+        let mut format_name = [0_u8; FORMAT_NAME_LEN];
+        let name = format!("{:?}", frame.source_frame_format());
+        let name_len = name.len().min(FORMAT_NAME_LEN);
+        format_name[..name_len].copy_from_slice(&name.as_bytes()[..name_len]);
+
+        let mut slot_header = Vec::with_capacity(SHM_SLOT_HEADER_LEN as usize);
+        // Written as odd first, so readers know the slot is in flux.
+        slot_header.extend_from_slice(&(sequence * 2 - 1).to_le_bytes());
+        slot_header.extend_from_slice(&frame.resolution().width().to_le_bytes());
+        slot_header.extend_from_slice(&frame.resolution().height().to_le_bytes());
+        slot_header.extend_from_slice(&payload_len.to_le_bytes());
+        slot_header.extend_from_slice(&format_name);
+
+        self.file
+            .write_all_at(&slot_header, offset)
+            .and_then(|()| {
+                self.file
+                    .write_all_at(payload, offset + SHM_SLOT_HEADER_LEN)
+            })
+            .and_then(|()| {
+                self.file
+                    .write_all_at(&(sequence * 2).to_le_bytes(), offset)
+            })
+            .and_then(|()| self.file.write_all_at(&sequence.to_le_bytes(), 24))
+            .map_err(|why| NokhwaError::ProcessFrameError {
+                src: frame.source_frame_format(),
+                destination: "Shared Memory".to_string(),
+                error: why.to_string(),
+            })?;
+        self.last_sequence = sequence;
+        Ok(sequence)
+    }
+}
+
+impl Drop for SharedMemoryExporter {
+    fn drop(&mut self) {
+        let _remove_err = std::fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,242 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{convert::frame_to_rgb, Camera};
+use image::{
+    codecs::{bmp::BmpEncoder, jpeg::JpegEncoder, png::PngEncoder},
+    ColorType, ImageEncoder,
+};
+use nokhwa_core::{buffer::Buffer, error::NokhwaError, traits::CaptureTrait, types::Resolution};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The image format [`Camera::save_frame()`] writes.
+#[derive(Copy, Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum ImageOutputFormat {
+    Png,
+    /// JPEG with a quality from 1 to 100. The camera name, capture time, and resolution are embedded as EXIF.
+    Jpeg(u8),
+    Bmp,
+}
+
+impl ImageOutputFormat {
+    /// Picks the format from a file extension (`png`, `jpg`/`jpeg`, `bmp`). JPEGs use a quality of 90.
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "png" => Some(ImageOutputFormat::Png),
+            "jpg" | "jpeg" => Some(ImageOutputFormat::Jpeg(90)),
+            "bmp" => Some(ImageOutputFormat::Bmp),
+            _ => None,
+        }
+    }
+
+    /// The usual file extension of the format, without the dot.
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageOutputFormat::Png => "png",
+            ImageOutputFormat::Jpeg(_) => "jpg",
+            ImageOutputFormat::Bmp => "bmp",
+        }
+    }
+}
+
+impl Camera {
+    /// Captures one frame and writes it to `path` as a PNG, JPEG, or BMP. If the stream is not open, it is opened for the capture
+    /// and closed again afterwards.
+    ///
+    /// JPEGs get a basic EXIF block with the camera name, the time the frame was captured (in UTC, tagged with a `+00:00`
+    /// `OffsetTime`), and the resolution.
+    /// # Errors
+    /// If capturing the frame fails, its [`FrameFormat`](nokhwa_core::frame_format::FrameFormat) can not be decoded to RGB, encoding fails, or the file can not be written, this will error.
+    pub fn save_frame(
+        &mut self,
+        path: impl AsRef<Path>,
+        format: ImageOutputFormat,
+    ) -> Result<(), NokhwaError> {
+        let was_open = self.is_stream_open();
+        if !was_open {
+            self.open_stream()?;
+        }
+        let frame = self.frame();
+        if !was_open {
+            self.stop_stream()?;
+        }
+        self.save_buffer(&frame?, path, format)
+    }
+
+    /// Writes a frame captured earlier from this camera to `path` as a PNG, JPEG, or BMP. See [`save_frame()`](Self::save_frame).
+    /// # Errors
+    /// If the frame's [`FrameFormat`](nokhwa_core::frame_format::FrameFormat) can not be decoded to RGB, encoding fails, or the file can not be written, this will error.
+    pub fn save_buffer(
+        &self,
+        frame: &Buffer,
+        path: impl AsRef<Path>,
+        format: ImageOutputFormat,
+    ) -> Result<(), NokhwaError> {
+        let resolution = frame.resolution();
+        let source_format = frame.source_frame_format();
+        let rgb = frame_to_rgb(frame, source_format)?;
+        let mut encoded = Vec::new();
+        let result = match format {
+            ImageOutputFormat::Png => PngEncoder::new(&mut encoded).write_image(
+                &rgb,
+                resolution.width(),
+                resolution.height(),
+                ColorType::Rgb8,
+            ),
+            ImageOutputFormat::Jpeg(quality) => {
+                JpegEncoder::new_with_quality(&mut encoded, quality.clamp(1, 100)).write_image(
+                    &rgb,
+                    resolution.width(),
+                    resolution.height(),
+                    ColorType::Rgb8,
+                )
+            }
+            ImageOutputFormat::Bmp => BmpEncoder::new(&mut encoded).write_image(
+                &rgb,
+                resolution.width(),
+                resolution.height(),
+                ColorType::Rgb8,
+            ),
+        };
+        result.map_err(|why| NokhwaError::ProcessFrameError {
+            src: source_format,
+            destination: format!("{format:?}"),
+            error: why.to_string(),
+        })?;
+
+        if let ImageOutputFormat::Jpeg(_) = format {
+            let captured = frame
+                .timestamp()
+                .map_or_else(SystemTime::now, |timestamp| timestamp.system());
+            let exif = exif_segment(self.info().human_name(), captured, resolution);
+            // The APP1 segment goes after the SOI marker, and after the JFIF APP0 segment if there is one.
+            let position = match encoded.get(2..6) {
+                Some([0xFF, 0xE0, high, low]) => 4 + usize::from(u16::from_be_bytes([*high, *low])),
+                _ => 2,
+            };
+            encoded.splice(position..position, exif);
+        }
+
+        let path = path.as_ref();
+        let mut file = BufWriter::new(
+            File::create(path).map_err(|why| NokhwaError::from_io("Create image file", why))?,
+        );
+        file.write_all(&encoded)
+            .and_then(|()| file.flush())
+            .map_err(|why| NokhwaError::from_io("Write image file", why))
+    }
+}
+
+const TIFF_ASCII: u16 = 2;
+const TIFF_LONG: u16 = 4;
+/// EXIF times are written in UTC, so every timestamp is tagged with a zero offset.
+const EXIF_UTC_OFFSET: &[u8] = b"+00:00\0";
+
+/// Builds a JPEG APP1 segment holding a little endian TIFF with `ImageWidth`, `ImageLength`, `Model`, and `DateTime`, and an
+/// EXIF IFD with `DateTimeOriginal`, `OffsetTime`, and `OffsetTimeOriginal`, so readers know the times are UTC.
+fn exif_segment(camera_name: &str, captured: SystemTime, resolution: Resolution) -> Vec<u8> {
+    let mut model = camera_name.as_bytes().to_vec();
+    model.push(0);
+    let mut date_time = exif_date_time(captured).into_bytes();
+    date_time.push(0);
+
+    // Header (8) + IFD0 with 5 entries (2 + 5 * 12 + 4), then the EXIF IFD with 3 entries (2 + 3 * 12 + 4).
+    let exif_ifd_offset = 8 + 2 + 5 * 12 + 4;
+    let data_offset = exif_ifd_offset + 2 + 3 * 12 + 4;
+    let date_time_offset = data_offset + model.len();
+    let utc_offset_offset = date_time_offset + date_time.len();
+    let mut tiff = Vec::with_capacity(utc_offset_offset + EXIF_UTC_OFFSET.len());
+    tiff.extend_from_slice(b"II*\0");
+    tiff.extend_from_slice(&8_u32.to_le_bytes());
+
+    let mut ifd = |entries: &[(u16, u16, usize, usize)]| {
+        tiff.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for &(tag, kind, count, value) in entries {
+            tiff.extend_from_slice(&tag.to_le_bytes());
+            tiff.extend_from_slice(&kind.to_le_bytes());
+            tiff.extend_from_slice(&(count as u32).to_le_bytes());
+            tiff.extend_from_slice(&(value as u32).to_le_bytes());
+        }
+        tiff.extend_from_slice(&0_u32.to_le_bytes());
+    };
+    ifd(&[
+        (0x0100, TIFF_LONG, 1, resolution.width() as usize),
+        (0x0101, TIFF_LONG, 1, resolution.height() as usize),
+        (0x0110, TIFF_ASCII, model.len(), data_offset),
+        (0x0132, TIFF_ASCII, date_time.len(), date_time_offset),
+        (0x8769, TIFF_LONG, 1, exif_ifd_offset),
+    ]);
+    ifd(&[
+        (0x9003, TIFF_ASCII, date_time.len(), date_time_offset),
+        (0x9010, TIFF_ASCII, EXIF_UTC_OFFSET.len(), utc_offset_offset),
+        (0x9011, TIFF_ASCII, EXIF_UTC_OFFSET.len(), utc_offset_offset),
+    ]);
+    tiff.extend_from_slice(&model);
+    tiff.extend_from_slice(&date_time);
+    tiff.extend_from_slice(EXIF_UTC_OFFSET);
+
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend_from_slice(&((2 + 6 + tiff.len()) as u16).to_be_bytes());
+    segment.extend_from_slice(b"Exif\0\0");
+    segment.extend_from_slice(&tiff);
+    segment
+}
+
+/// Formats `time` (in UTC) the way EXIF wants it: `YYYY:MM:DD HH:MM:SS`. The offset is recorded separately, as `OffsetTime`.
+fn exif_date_time(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second) = utc_date_time(time);
+    format!("{year:04}:{month:02}:{day:02} {hour:02}:{minute:02}:{second:02}")
+}
+
+/// Splits `time` into its UTC year, month, day, hour, minute, and second.
+pub(crate) fn utc_date_time(time: SystemTime) -> (i64, i64, i64, u64, u64, u64) {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (days, seconds_of_day) = (seconds / 86400, seconds % 86400);
+
+    // Howard Hinnant's `civil_from_days`.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60,
+    )
+}
@@ -0,0 +1,148 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// How far back [`StreamStats`] looks when measuring frame rate and throughput by default.
+pub const DEFAULT_STREAM_STATS_WINDOW: Duration = Duration::from_secs(2);
+
+/// What a stream actually delivers: frame rate and throughput over a rolling window, and how many frames failed to read or
+/// decode. The configured frame rate is what was asked for; this is what arrived. See
+/// [`Camera::stream_stats()`](crate::Camera::stream_stats).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamStats {
+    // When each frame in the window arrived, and its size in bytes.
+    arrivals: VecDeque<(Instant, usize)>,
+    window: Duration,
+    total_frames: u64,
+    total_bytes: u64,
+    read_failures: u64,
+    decode_failures: u64,
+}
+
+impl StreamStats {
+    /// Creates empty statistics that measure rates over the last `window` of frames.
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        StreamStats {
+            arrivals: VecDeque::new(),
+            window,
+            total_frames: 0,
+            total_bytes: 0,
+            read_failures: 0,
+            decode_failures: 0,
+        }
+    }
+
+    /// How far back rates are measured.
+    #[must_use]
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// The frame rate the stream delivered over the window, or `None` until two frames have arrived.
+    #[must_use]
+    pub fn fps(&self) -> Option<f64> {
+        let span = self.span()?;
+        #[allow(clippy::cast_precision_loss)]
+        Some((self.arrivals.len() - 1) as f64 / span.as_secs_f64())
+    }
+
+    /// The bytes per second the stream delivered over the window, or `None` until two frames have arrived. This is the size
+    /// of the frames as delivered, so it is the compressed size for MJPEG.
+    #[must_use]
+    pub fn bytes_per_second(&self) -> Option<f64> {
+        let span = self.span()?;
+        // The first frame arrived at the start of the span, so only the ones after it were delivered within it.
+        let bytes = self
+            .arrivals
+            .iter()
+            .skip(1)
+            .map(|(_, bytes)| *bytes)
+            .sum::<usize>();
+        #[allow(clippy::cast_precision_loss)]
+        Some(bytes as f64 / span.as_secs_f64())
+    }
+
+    /// How many frames have been delivered in total.
+    #[must_use]
+    pub fn total_frames(&self) -> u64 {
+        self.total_frames
+    }
+
+    /// How many bytes of frames have been delivered in total.
+    #[must_use]
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    /// How many times reading a frame from the backend failed (including timeouts).
+    #[must_use]
+    pub fn read_failures(&self) -> u64 {
+        self.read_failures
+    }
+
+    /// How many frames failed to decode, e.g. corrupt MJPEG from a camera on an overloaded USB bus.
+    #[must_use]
+    pub fn decode_failures(&self) -> u64 {
+        self.decode_failures
+    }
+
+    /// Forgets everything, including the totals.
+    pub fn clear(&mut self) {
+        *self = StreamStats::new(self.window);
+    }
+
+    /// Forgets the frames in the window, so rates start over (e.g. after the stream was stopped). Totals are kept.
+    pub(crate) fn restart_window(&mut self) {
+        self.arrivals.clear();
+    }
+
+    pub(crate) fn record_frame(&mut self, bytes: usize) {
+        let now = Instant::now();
+        while self.arrivals.front().map_or(false, |(arrived, _)| {
+            now.duration_since(*arrived) > self.window
+        }) {
+            self.arrivals.pop_front();
+        }
+        self.arrivals.push_back((now, bytes));
+        self.total_frames += 1;
+        self.total_bytes += bytes as u64;
+    }
+
+    pub(crate) fn record_read_failure(&mut self) {
+        self.read_failures += 1;
+    }
+
+    pub(crate) fn record_decode_failure(&mut self) {
+        self.decode_failures += 1;
+    }
+
+    fn span(&self) -> Option<Duration> {
+        let (first, _) = self.arrivals.front()?;
+        let (last, _) = self.arrivals.back()?;
+        Some(last.duration_since(*first)).filter(|span| !span.is_zero())
+    }
+}
+
+impl Default for StreamStats {
+    fn default() -> Self {
+        StreamStats::new(DEFAULT_STREAM_STATS_WINDOW)
+    }
+}
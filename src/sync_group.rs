@@ -0,0 +1,393 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::Camera;
+use nokhwa_core::{buffer::Buffer, error::NokhwaError, traits::CaptureTrait};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError},
+        Arc, Barrier, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// How many unmatched frames [`SyncCaptureGroup`] holds per camera before dropping the oldest.
+pub const DEFAULT_SYNC_BACKLOG: usize = 4;
+
+/// One frame from each camera of a [`SyncCaptureGroup`], captured within its tolerance of each other.
+#[derive(Clone, Debug)]
+pub struct FrameSet {
+    frames: Vec<Buffer>,
+    spread: Duration,
+}
+
+impl FrameSet {
+    /// The frames, in the order the cameras were given to [`SyncCaptureGroup::new()`].
+    #[must_use]
+    pub fn frames(&self) -> &[Buffer] {
+        &self.frames
+    }
+
+    /// The frame of the camera at `index`.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Buffer> {
+        self.frames.get(index)
+    }
+
+    /// Takes the frames out of the set.
+    #[must_use]
+    pub fn into_frames(self) -> Vec<Buffer> {
+        self.frames
+    }
+
+    /// The time between the earliest and the latest captured frame of the set.
+    #[must_use]
+    pub fn spread(&self) -> Duration {
+        self.spread
+    }
+}
+
+/// Captures from several cameras at once and hands out [`FrameSet`]s of frames captured within `tolerance` of each other,
+/// e.g. for stereo vision or volumetric capture.
+///
+/// Each camera is read by its own thread. All streams are opened before any thread starts reading, so the cameras start as close
+/// together as the backends allow. Frames are matched by when they were captured (the receive time minus the driver latency, where
+/// the backend reports one), so cameras do not need a shared clock. Frames that have no partner within the tolerance are dropped.
+///
+/// The cameras must have their formats set beforehand. Cameras running at different frame rates work, the faster ones just drop more frames.
+/// ```ignore
+/// let mut group = SyncCaptureGroup::new(vec![left, right], Duration::from_millis(5))?;
+/// group.open_stream()?;
+/// let set = group.next_set(Duration::from_secs(1))?;
+/// let (left, right) = (set.get(0), set.get(1));
+/// ```
+pub struct SyncCaptureGroup {
+    cameras: Vec<Arc<Mutex<Camera>>>,
+    tolerance: Duration,
+    backlog: usize,
+    pending: Vec<VecDeque<(Buffer, SystemTime)>>,
+    receiver: Option<Receiver<(usize, Buffer, SystemTime)>>,
+    dropped: Arc<AtomicU64>,
+    die_bool: Arc<AtomicBool>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl SyncCaptureGroup {
+    /// Creates a group of `cameras` whose frames are matched within `tolerance`.
+    /// # Errors
+    /// This will error if there are no cameras.
+    pub fn new(cameras: Vec<Camera>, tolerance: Duration) -> Result<Self, NokhwaError> {
+        if cameras.is_empty() {
+            return Err(NokhwaError::GeneralError(
+                "A capture group needs at least one camera".to_string(),
+            ));
+        }
+        Ok(SyncCaptureGroup {
+            pending: cameras.iter().map(|_| VecDeque::new()).collect(),
+            cameras: cameras
+                .into_iter()
+                .map(|camera| Arc::new(Mutex::new(camera)))
+                .collect(),
+            tolerance,
+            backlog: DEFAULT_SYNC_BACKLOG,
+            receiver: None,
+            dropped: Arc::new(AtomicU64::new(0)),
+            die_bool: Arc::new(AtomicBool::new(false)),
+            handles: Vec::new(),
+        })
+    }
+
+    /// How many cameras are in the group.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cameras.len()
+    }
+
+    /// Always false, a group has at least one camera.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cameras.is_empty()
+    }
+
+    /// Runs `f` with exclusive access to the camera at `index`, e.g. to change its controls.
+    /// While streaming, that camera's capture thread waits until `f` returns.
+    /// # Errors
+    /// This will error if there is no camera at `index` or its lock is poisoned.
+    pub fn with_camera<R>(
+        &self,
+        index: usize,
+        f: impl FnOnce(&mut Camera) -> R,
+    ) -> Result<R, NokhwaError> {
+        let camera = self.cameras.get(index).ok_or_else(|| {
+            NokhwaError::GeneralError(format!("No camera at index {index} in the group"))
+        })?;
+        let mut camera = camera
+            .lock()
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+        Ok(f(&mut camera))
+    }
+
+    /// The largest time between the captured frames of a [`FrameSet`].
+    #[must_use]
+    pub fn tolerance(&self) -> Duration {
+        self.tolerance
+    }
+
+    /// Sets the largest time between the captured frames of a [`FrameSet`]. Half the frame interval is a good start.
+    pub fn set_tolerance(&mut self, tolerance: Duration) {
+        self.tolerance = tolerance;
+    }
+
+    /// How many unmatched frames are held per camera. Defaults to [`DEFAULT_SYNC_BACKLOG`].
+    #[must_use]
+    pub fn backlog(&self) -> usize {
+        self.backlog
+    }
+
+    /// Sets how many unmatched frames are held per camera (at least 1) while waiting for the other cameras to catch up.
+    /// A larger backlog copes with cameras that deliver late, at the cost of memory.
+    pub fn set_backlog(&mut self, backlog: usize) {
+        self.backlog = backlog.max(1);
+        for pending in &mut self.pending {
+            while pending.len() > self.backlog {
+                pending.pop_front();
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// How many frames were dropped for not having a partner within the tolerance, or for arriving while the backlog was full.
+    #[must_use]
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if the cameras are streaming.
+    #[must_use]
+    pub fn is_stream_open(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Opens the streams of all cameras, then starts reading from all of them at once.
+    /// # Errors
+    /// If the group is already streaming, or any camera fails to open its stream, this will error. The streams that did open are closed again.
+    pub fn open_stream(&mut self) -> Result<(), NokhwaError> {
+        if self.receiver.is_some() {
+            return Err(NokhwaError::OpenStreamError(
+                "Stream Already Open".to_string(),
+            ));
+        }
+        for (opened, camera) in self.cameras.iter().enumerate() {
+            let result = camera
+                .lock()
+                .map_err(|why| NokhwaError::OpenStreamError(why.to_string()))
+                .and_then(|mut camera| camera.open_stream());
+            if let Err(why) = result {
+                for camera in &self.cameras[..opened] {
+                    if let Ok(mut camera) = camera.lock() {
+                        let _stop_stream_err = camera.stop_stream();
+                    }
+                }
+                return Err(why);
+            }
+        }
+
+        let (sender, receiver) = mpsc::sync_channel(self.cameras.len() * self.backlog);
+        let start = Arc::new(Barrier::new(self.cameras.len()));
+        self.die_bool.store(false, Ordering::SeqCst);
+        for pending in &mut self.pending {
+            pending.clear();
+        }
+        self.handles = self
+            .cameras
+            .iter()
+            .enumerate()
+            .map(|(index, camera)| {
+                let camera = camera.clone();
+                let sender = sender.clone();
+                let start = start.clone();
+                let dropped = self.dropped.clone();
+                let die_bool = self.die_bool.clone();
+                std::thread::spawn(move || {
+                    start.wait();
+                    capture_loop(index, &camera, &sender, &dropped, &die_bool);
+                })
+            })
+            .collect();
+        self.receiver = Some(receiver);
+        Ok(())
+    }
+
+    /// Waits at most `timeout` for the next [`FrameSet`].
+    /// # Errors
+    /// This will return [`TimeoutError`](NokhwaError::TimeoutError) on timeout, or [`ReadFrameError`](NokhwaError::ReadFrameError)
+    /// if the group is not streaming.
+    pub fn next_set(&mut self, timeout: Duration) -> Result<FrameSet, NokhwaError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(set) = self.match_pending() {
+                return Ok(set);
+            }
+            let receiver = self
+                .receiver
+                .as_ref()
+                .ok_or_else(|| NokhwaError::ReadFrameError("Stream Closed".to_string()))?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let (index, frame, captured) =
+                receiver.recv_timeout(remaining).map_err(|why| match why {
+                    RecvTimeoutError::Timeout => NokhwaError::TimeoutError(timeout),
+                    RecvTimeoutError::Disconnected => {
+                        NokhwaError::ReadFrameError("Stream Closed".to_string())
+                    }
+                })?;
+            let pending = &mut self.pending[index];
+            if pending.len() >= self.backlog {
+                pending.pop_front();
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            pending.push_back((frame, captured));
+        }
+    }
+
+    /// Gets the next [`FrameSet`] if one can be matched from the frames that already arrived, without blocking.
+    /// # Errors
+    /// This will error if the group is not streaming.
+    pub fn try_next_set(&mut self) -> Result<Option<FrameSet>, NokhwaError> {
+        let receiver = self
+            .receiver
+            .as_ref()
+            .ok_or_else(|| NokhwaError::ReadFrameError("Stream Closed".to_string()))?;
+        for (index, frame, captured) in receiver.try_iter() {
+            let pending = &mut self.pending[index];
+            if pending.len() >= self.backlog {
+                pending.pop_front();
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            pending.push_back((frame, captured));
+        }
+        Ok(self.match_pending())
+    }
+
+    /// Stops reading and closes the streams of all cameras.
+    /// # Errors
+    /// If a capture thread panicked, or a camera fails to close its stream, this will error. All cameras are still stopped.
+    pub fn stop_stream(&mut self) -> Result<(), NokhwaError> {
+        self.die_bool.store(true, Ordering::SeqCst);
+        // Dropping the receiver makes the capture threads' sends fail instead of filling the channel.
+        self.receiver = None;
+        let mut result = Ok(());
+        for handle in self.handles.drain(..) {
+            if handle.join().is_err() {
+                result = Err(NokhwaError::StreamShutdownError(
+                    "Capture thread panicked".to_string(),
+                ));
+            }
+        }
+        for camera in &self.cameras {
+            let stopped = camera
+                .lock()
+                .map_err(|why| NokhwaError::StreamShutdownError(why.to_string()))
+                .and_then(|mut camera| camera.stop_stream());
+            if let Err(why) = stopped {
+                result = Err(why);
+            }
+        }
+        for pending in &mut self.pending {
+            pending.clear();
+        }
+        result
+    }
+
+    /// Takes a set out of the pending frames if the oldest frames of all cameras are within the tolerance. Frames that
+    /// are too old to ever be matched are dropped along the way.
+    fn match_pending(&mut self) -> Option<FrameSet> {
+        loop {
+            let heads = self
+                .pending
+                .iter()
+                .map(|pending| pending.front().map(|(_, captured)| *captured))
+                .collect::<Option<Vec<_>>>()?;
+            let (oldest, earliest) = heads
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, captured)| **captured)?;
+            let latest = heads.iter().max()?;
+            let spread = latest.duration_since(*earliest).unwrap_or(Duration::ZERO);
+            if spread <= self.tolerance {
+                let frames = self
+                    .pending
+                    .iter_mut()
+                    .filter_map(|pending| pending.pop_front().map(|(frame, _)| frame))
+                    .collect();
+                return Some(FrameSet { frames, spread });
+            }
+            // Every other camera's next frame is later than this one, and at least one is too late, so it can never be matched.
+            self.pending[oldest].pop_front();
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for SyncCaptureGroup {
+    fn drop(&mut self) {
+        if self.receiver.is_some() {
+            let _stop_stream_err = self.stop_stream();
+        }
+    }
+}
+
+pub(crate) fn capture_loop(
+    index: usize,
+    camera: &Mutex<Camera>,
+    sender: &SyncSender<(usize, Buffer, SystemTime)>,
+    dropped: &AtomicU64,
+    die_bool: &AtomicBool,
+) {
+    while !die_bool.load(Ordering::SeqCst) {
+        let frame = match camera.lock() {
+            Ok(mut camera) => camera.frame(),
+            Err(_) => break,
+        };
+        let Ok(frame) = frame else {
+            continue;
+        };
+        let captured = captured_at(&frame);
+        match sender.try_send((index, frame, captured)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(TrySendError::Disconnected(_)) => break,
+        }
+    }
+}
+
+/// When `frame` was captured on the wall clock: when it was received, minus how long it sat in the driver if the backend knows.
+fn captured_at(frame: &Buffer) -> SystemTime {
+    match frame.timestamp() {
+        Some(timestamp) => {
+            let received = timestamp.system();
+            timestamp
+                .driver_latency()
+                .and_then(|latency| received.checked_sub(latency))
+                .unwrap_or(received)
+        }
+        None => SystemTime::now(),
+    }
+}
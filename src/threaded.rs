@@ -15,6 +15,8 @@
  */
 
 use crate::Camera;
+#[cfg(feature = "output-image")]
+use nokhwa_core::pool::FrameBuffer;
 use nokhwa_core::{
     buffer::Buffer,
     error::NokhwaError,
@@ -25,11 +27,14 @@ use nokhwa_core::{
 };
 use std::thread::JoinHandle;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
+    fmt::{Display, Formatter},
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TryRecvError, TrySendError},
+        Arc, Condvar, Mutex,
     },
+    time::{Duration, Instant},
 };
 
 type AtomicLock<T> = Arc<Mutex<T>>;
@@ -73,7 +78,7 @@ impl CallbackCamera {
         format: RequestedFormat,
         callback: impl FnMut(Buffer) + Send + 'static,
     ) -> Result<Self, NokhwaError> {
-        let arc_camera = Arc::new(Mutex::new(Camera::new(index, format)?));
+        let arc_camera = Arc::new(Mutex::new(Camera::with_request(index, format)?));
         let current_camera = arc_camera
             .lock()
             .map_err(|why| NokhwaError::GetPropertyError {
@@ -459,6 +464,7 @@ impl CallbackCamera {
                     error: why.to_string(),
                 })?
                 .open_stream()?;
+            self.die_bool.store(false, Ordering::SeqCst);
             let die_bool_clone = self.die_bool.clone();
             let camera_clone = self.camera.clone();
             let last_frame = self.last_frame_captured.clone();
@@ -527,10 +533,35 @@ impl CallbackCamera {
             .is_stream_open())
     }
 
-    /// Will drop the stream.
+    /// Checks if the capture thread is running, i.e. if the callback is being called.
+    pub fn is_capturing(&self) -> Result<bool, NokhwaError> {
+        Ok(self
+            .handle
+            .lock()
+            .map_err(|why| NokhwaError::GetPropertyError {
+                property: "thread handle".to_string(),
+                error: why.to_string(),
+            })?
+            .as_ref()
+            .map_or(false, |handle| !handle.is_finished()))
+    }
+
+    /// Will stop the capture thread, wait for it to finish, then drop the stream.
+    /// The callback will not be called after this returns. You can call [`open_stream()`](Self::open_stream) to start capturing again.
     /// # Errors
     /// Please check the `Quirks` section of each backend.
     pub fn stop_stream(&mut self) -> Result<(), NokhwaError> {
+        self.die_bool.store(true, Ordering::SeqCst);
+        let handle = self
+            .handle
+            .lock()
+            .map_err(|why| NokhwaError::StreamShutdownError(why.to_string()))?
+            .take();
+        if let Some(handle) = handle {
+            handle.join().map_err(|_| {
+                NokhwaError::StreamShutdownError("Capture thread panicked".to_string())
+            })?;
+        }
         self.camera
             .lock()
             .map_err(|why| NokhwaError::StreamShutdownError(why.to_string()))?
@@ -541,7 +572,1125 @@ impl CallbackCamera {
 impl Drop for CallbackCamera {
     fn drop(&mut self) {
         let _stop_stream_err = self.stop_stream();
+    }
+}
+
+/// Two frame slots. The capture thread writes into the back slot and then flips `front`, so readers
+/// only ever contend with the capture thread if they are still cloning a frame two frames later.
+struct FrameDoubleBuffer {
+    slots: [Mutex<Option<(Buffer, FrameFormat)>>; 2],
+    front: AtomicUsize,
+}
+
+impl FrameDoubleBuffer {
+    fn new() -> Self {
+        FrameDoubleBuffer {
+            slots: [Mutex::new(None), Mutex::new(None)],
+            front: AtomicUsize::new(0),
+        }
+    }
+
+    fn publish(&self, frame: Buffer, format: FrameFormat) {
+        let back = 1 - self.front.load(Ordering::Acquire);
+        if let Ok(mut slot) = self.slots[back].lock() {
+            *slot = Some((frame, format));
+        }
+        self.front.store(back, Ordering::Release);
+    }
+
+    fn latest(&self) -> Result<Option<Buffer>, NokhwaError> {
+        Ok(self.latest_with_format()?.map(|(frame, _)| frame))
+    }
+
+    fn latest_with_format(&self) -> Result<Option<(Buffer, FrameFormat)>, NokhwaError> {
+        let front = self.front.load(Ordering::Acquire);
+        Ok(self.slots[front]
+            .lock()
+            .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))?
+            .clone())
+    }
+
+    fn clear(&self) {
+        for slot in &self.slots {
+            if let Ok(mut slot) = slot.lock() {
+                *slot = None;
+            }
+        }
+    }
+}
+
+/// What a [`FrameQueue`] does with a new frame when it is full.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DropPolicy {
+    /// Discard the oldest queued frame to make room. Favours latency.
+    DropOldest,
+    /// Discard the new frame. Favours keeping a contiguous run of frames.
+    DropNewest,
+    /// Wait for the consumer to make room. No frames are lost in the queue, but the driver will drop frames if the consumer is too slow.
+    Block,
+}
+
+impl Display for DropPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// The bytes of frame data held by the buffering features of one camera, see [`ThreadedCamera::set_memory_limit()`].
+struct MemoryBudget {
+    limit: AtomicUsize,
+    used: AtomicUsize,
+}
+
+impl MemoryBudget {
+    fn new() -> Self {
+        MemoryBudget {
+            limit: AtomicUsize::new(usize::MAX),
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    fn fits(&self, bytes: usize) -> bool {
+        self.used.load(Ordering::Acquire).saturating_add(bytes)
+            <= self.limit.load(Ordering::Acquire)
+    }
+
+    fn acquire(&self, bytes: usize) {
+        self.used.fetch_add(bytes, Ordering::AcqRel);
+    }
+
+    fn release(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, Ordering::AcqRel);
+    }
+}
+
+/// A bounded queue of frames, shared between a capture thread and a consumer.
+///
+/// See [`DropPolicy`] for what happens when the consumer falls behind, or when the camera's
+/// [memory limit](ThreadedCamera::set_memory_limit) is reached.
+pub struct FrameQueue {
+    frames: Mutex<VecDeque<Buffer>>,
+    capacity: usize,
+    policy: DropPolicy,
+    budget: Arc<MemoryBudget>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    closed: AtomicBool,
+    dropped: AtomicU64,
+}
+
+impl FrameQueue {
+    /// Creates a new [`FrameQueue`] holding at most `capacity` frames. A `capacity` of 0 is treated as 1.
+    #[must_use]
+    pub fn new(capacity: usize, policy: DropPolicy) -> Self {
+        Self::with_budget(capacity, policy, Arc::new(MemoryBudget::new()))
+    }
+
+    fn with_budget(capacity: usize, policy: DropPolicy, budget: Arc<MemoryBudget>) -> Self {
+        let capacity = capacity.max(1);
+        FrameQueue {
+            frames: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            policy,
+            budget,
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            closed: AtomicBool::new(false),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// The maximum number of frames this queue holds.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The [`DropPolicy`] of this queue.
+    #[must_use]
+    pub fn policy(&self) -> DropPolicy {
+        self.policy
+    }
+
+    /// The number of frames currently queued.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.frames.lock().map_or(0, |frames| frames.len())
+    }
+
+    /// Checks if no frames are queued.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of frames discarded by the [`DropPolicy`] so far.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Checks if the queue was closed, i.e. no more frames will be pushed.
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Pushes a frame, applying the [`DropPolicy`] if the queue is full (or over its camera's memory limit).
+    /// Returns `false` if the queue has been closed, in which case the frame is discarded.
+    pub fn push(&self, frame: Buffer) -> bool {
+        let mut frames = match self.frames.lock() {
+            Ok(frames) => frames,
+            Err(_) => return false,
+        };
+        let bytes = frame.buffer().len();
+        // An empty queue always takes a frame, so a limit smaller than one frame can not stall the queue for good.
+        while frames.len() >= self.capacity || (!frames.is_empty() && !self.budget.fits(bytes)) {
+            if self.is_closed() {
+                return false;
+            }
+            match self.policy {
+                DropPolicy::DropOldest => {
+                    self.take_front(&mut frames);
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                DropPolicy::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return true;
+                }
+                DropPolicy::Block => {
+                    frames = match self.not_full.wait(frames) {
+                        Ok(frames) => frames,
+                        Err(_) => return false,
+                    };
+                }
+            }
+        }
+        if self.is_closed() {
+            return false;
+        }
+        self.budget.acquire(bytes);
+        frames.push_back(frame);
+        self.not_empty.notify_one();
+        true
+    }
+
+    fn take_front(&self, frames: &mut VecDeque<Buffer>) -> Option<Buffer> {
+        let frame = frames.pop_front()?;
+        self.budget.release(frame.buffer().len());
+        Some(frame)
+    }
+
+    /// Takes the oldest frame, waiting for one to arrive.
+    /// Returns `None` once the queue is closed and empty.
+    pub fn pop(&self) -> Option<Buffer> {
+        let mut frames = self.frames.lock().ok()?;
+        loop {
+            if let Some(frame) = self.take_front(&mut frames) {
+                self.not_full.notify_one();
+                return Some(frame);
+            }
+            if self.is_closed() {
+                return None;
+            }
+            frames = self.not_empty.wait(frames).ok()?;
+        }
+    }
+
+    /// Takes the oldest frame, waiting at most `timeout` for one to arrive.
+    pub fn pop_timeout(&self, timeout: Duration) -> Option<Buffer> {
+        let frames = self.frames.lock().ok()?;
+        let (mut frames, _) = self
+            .not_empty
+            .wait_timeout_while(frames, timeout, |frames| {
+                frames.is_empty() && !self.is_closed()
+            })
+            .ok()?;
+        let frame = self.take_front(&mut frames);
+        if frame.is_some() {
+            self.not_full.notify_one();
+        }
+        frame
+    }
+
+    /// Takes the oldest frame if there is one, without waiting.
+    pub fn try_pop(&self) -> Option<Buffer> {
+        let frame = self.take_front(&mut *self.frames.lock().ok()?);
+        if frame.is_some() {
+            self.not_full.notify_one();
+        }
+        frame
+    }
+
+    /// Closes the queue, waking up anyone waiting on it. Queued frames can still be popped.
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        // Take the lock so a waiter can't miss the wakeup between checking `closed` and waiting.
+        let _frames = self.frames.lock();
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    /// Empties and re-opens a closed queue.
+    fn reopen(&self) {
+        if let Ok(mut frames) = self.frames.lock() {
+            while self.take_front(&mut frames).is_some() {}
+        }
+        self.closed.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Drop for FrameQueue {
+    fn drop(&mut self) {
+        if let Ok(frames) = self.frames.get_mut() {
+            let bytes = frames.iter().map(|frame| frame.buffer().len()).sum();
+            self.budget.release(bytes);
+        }
+    }
+}
+
+/// A subscription to the frames of a [`ThreadedCamera`], created with [`subscribe()`](ThreadedCamera::subscribe).
+///
+/// Every subscriber gets every frame as a shared [`Arc`], so there is no copy per subscriber. If a subscriber falls behind
+/// by more than its capacity, new frames are dropped for that subscriber only.
+/// The receiver iterates until the camera stops streaming or is dropped.
+pub struct FrameReceiver {
+    receiver: Receiver<Arc<Buffer>>,
+}
+
+impl FrameReceiver {
+    /// Waits for the next frame. Returns `None` once the camera is no longer streaming.
+    pub fn recv(&self) -> Option<Arc<Buffer>> {
+        self.receiver.recv().ok()
+    }
+
+    /// Waits at most `timeout` for the next frame.
+    /// # Errors
+    /// This will return [`TimeoutError`](NokhwaError::TimeoutError) on timeout, or [`ReadFrameError`](NokhwaError::ReadFrameError) if the camera is no longer streaming.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Arc<Buffer>, NokhwaError> {
+        self.receiver
+            .recv_timeout(timeout)
+            .map_err(|why| match why {
+                RecvTimeoutError::Timeout => NokhwaError::TimeoutError(timeout),
+                RecvTimeoutError::Disconnected => {
+                    NokhwaError::ReadFrameError("Stream Closed".to_string())
+                }
+            })
+    }
+
+    /// Gets the next frame if one is waiting, without blocking.
+    /// # Errors
+    /// This will error if the camera is no longer streaming.
+    pub fn try_recv(&self) -> Result<Option<Arc<Buffer>>, NokhwaError> {
+        match self.receiver.try_recv() {
+            Ok(frame) => Ok(Some(frame)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                Err(NokhwaError::ReadFrameError("Stream Closed".to_string()))
+            }
+        }
+    }
+}
+
+impl Iterator for FrameReceiver {
+    type Item = Arc<Buffer>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+/// Sends a frame to every subscriber, forgetting the ones that hung up.
+fn broadcast_frame(subscribers: &Mutex<Vec<SyncSender<Arc<Buffer>>>>, frame: &Buffer) {
+    let mut subscribers = match subscribers.lock() {
+        Ok(subscribers) => subscribers,
+        Err(_) => return,
+    };
+    if subscribers.is_empty() {
+        return;
+    }
+    let frame = Arc::new(frame.clone());
+    subscribers.retain(|sender| match sender.try_send(frame.clone()) {
+        Ok(()) | Err(TrySendError::Full(_)) => true,
+        Err(TrySendError::Disconnected(_)) => false,
+    });
+}
+
+type ChannelList = Mutex<Vec<(SyncSender<Buffer>, DropPolicy)>>;
+
+/// How long the capture thread waits before retrying a full channel with [`DropPolicy::Block`].
+const CHANNEL_RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+/// How long the capture thread of a [`ThreadedCamera`] waits after a failed frame, doubling up to the maximum while it keeps failing.
+const CAPTURE_RETRY_MIN: Duration = Duration::from_millis(10);
+const CAPTURE_RETRY_MAX: Duration = Duration::from_millis(500);
+
+/// Sends a frame into every channel according to its [`DropPolicy`], forgetting the channels whose receiver hung up.
+/// Blocking channels give up once `die_bool` is set, so that stopping the stream never hangs on a stuck consumer.
+fn send_to_channels(channels: &ChannelList, frame: &Buffer, die_bool: &AtomicBool) {
+    let mut channels = match channels.lock() {
+        Ok(channels) => channels,
+        Err(_) => return,
+    };
+    channels.retain(|(sender, policy)| {
+        let mut frame = frame.clone();
+        loop {
+            match sender.try_send(frame) {
+                Ok(()) => return true,
+                Err(TrySendError::Disconnected(_)) => return false,
+                Err(TrySendError::Full(returned)) => {
+                    if *policy != DropPolicy::Block || die_bool.load(Ordering::SeqCst) {
+                        return true;
+                    }
+                    frame = returned;
+                    std::thread::sleep(CHANNEL_RETRY_INTERVAL);
+                }
+            }
+        }
+    });
+}
+
+/// The frames captured in the last `window`, oldest first.
+struct PreRoll {
+    window: Option<Duration>,
+    frames: VecDeque<(Instant, Buffer)>,
+    budget: Arc<MemoryBudget>,
+}
+
+impl PreRoll {
+    fn push(&mut self, frame: &Buffer) {
+        let window = match self.window {
+            Some(window) => window,
+            None => return,
+        };
+        let now = Instant::now();
+        let bytes = frame.buffer().len();
+        // Past the memory limit, the window shrinks: the oldest frames go first.
+        while let Some((captured, _)) = self.frames.front() {
+            if now.duration_since(*captured) <= window && self.budget.fits(bytes) {
+                break;
+            }
+            self.pop_front();
+        }
+        self.budget.acquire(bytes);
+        self.frames.push_back((now, frame.clone()));
+    }
+
+    fn pop_front(&mut self) {
+        if let Some((_, frame)) = self.frames.pop_front() {
+            self.budget.release(frame.buffer().len());
+        }
+    }
+
+    fn clear(&mut self) {
+        while !self.frames.is_empty() {
+            self.pop_front();
+        }
+        self.frames = VecDeque::new();
+    }
+}
+
+/// Picks the frames closest to a fixed output rate out of a faster source.
+struct FramePacer {
+    period: Duration,
+    next_tick: Option<Instant>,
+    last_arrival: Option<Instant>,
+    source_interval: Duration,
+}
+
+impl FramePacer {
+    fn new(fps: u32) -> Self {
+        FramePacer {
+            period: Duration::from_secs(1) / fps.max(1),
+            next_tick: None,
+            last_arrival: None,
+            source_interval: Duration::ZERO,
+        }
+    }
+
+    fn should_deliver(&mut self, now: Instant) -> bool {
+        if let Some(last) = self.last_arrival.replace(now) {
+            self.source_interval = now.duration_since(last);
+        }
+        let next_tick = match self.next_tick {
+            Some(tick) => tick,
+            None => {
+                self.next_tick = Some(now + self.period);
+                return true;
+            }
+        };
+        // A frame is the closest one to the tick if the next frame would land further past it than this one is before it.
+        if now + self.source_interval / 2 < next_tick {
+            return false;
+        }
+        let mut tick = next_tick + self.period;
+        // Fell behind (e.g. the camera stalled), so don't try to catch up with a burst of frames.
+        if tick <= now {
+            tick = now + self.period;
+        }
+        self.next_tick = Some(tick);
+        true
+    }
+}
+
+/// How many points of each frame [`ChangeDetection`] compares, in a grid this wide and tall.
+const CHANGE_SAMPLE_GRID: (u32, u32) = (32, 24);
+
+/// Settings for skipping frames that are (almost) identical to the last delivered one, e.g. a surveillance camera watching an
+/// empty room. See [`ThreadedCamera::set_change_detection()`].
+///
+/// Frames are compared by the average difference in brightness (0 to 255) over a grid of sample points, read straight from the
+/// raw frame without decoding it. MJPEG frames need decoding, so they are decoded at 1/8 scale when the `output-image` feature
+/// is enabled, and always count as changed otherwise.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChangeDetection {
+    threshold: f32,
+    heartbeat: Option<Duration>,
+}
+
+impl ChangeDetection {
+    /// Skips frames whose average brightness difference from the last delivered frame is below `threshold`. Sensor noise
+    /// is usually around 1 to 2, so 3 is a good start.
+    #[must_use]
+    pub fn new(threshold: f32) -> Self {
+        ChangeDetection {
+            threshold,
+            heartbeat: None,
+        }
+    }
+
+    /// Delivers a frame at least every `interval` even if nothing changed, so consumers can tell a static scene from a dead camera.
+    #[must_use]
+    pub fn with_heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat = Some(interval);
+        self
+    }
+
+    /// The brightness difference below which frames are skipped.
+    #[must_use]
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// The longest time between delivered frames, if any.
+    #[must_use]
+    pub fn heartbeat(&self) -> Option<Duration> {
+        self.heartbeat
+    }
+}
+
+impl Default for ChangeDetection {
+    fn default() -> Self {
+        ChangeDetection::new(3.0)
+    }
+}
+
+/// Remembers the samples of the last delivered frame for [`ChangeDetection`].
+struct ChangeDetector {
+    settings: ChangeDetection,
+    last: Option<(Instant, Vec<u8>)>,
+}
+
+impl ChangeDetector {
+    fn new(settings: ChangeDetection) -> Self {
+        ChangeDetector {
+            settings,
+            last: None,
+        }
+    }
+
+    fn should_deliver(&mut self, frame: &Buffer, format: FrameFormat, now: Instant) -> bool {
+        let Some(samples) = luma_samples(frame, format) else {
+            return true;
+        };
+        if let Some((delivered, last)) = &self.last {
+            let due = self.settings.heartbeat.map_or(false, |heartbeat| {
+                now.duration_since(*delivered) >= heartbeat
+            });
+            if !due
+                && last.len() == samples.len()
+                && mean_difference(last, &samples) < self.settings.threshold
+            {
+                return false;
+            }
+        }
+        self.last = Some((now, samples));
+        true
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn mean_difference(a: &[u8], b: &[u8]) -> f32 {
+    let total: u64 = a
+        .iter()
+        .zip(b)
+        .map(|(a, b)| u64::from(a.abs_diff(*b)))
+        .sum();
+    total as f32 / a.len().max(1) as f32
+}
+
+/// Reads the brightness at [`CHANGE_SAMPLE_GRID`] points of a raw frame. Returns `None` if the format is not supported or the
+/// frame is too short.
+fn luma_samples(frame: &Buffer, format: FrameFormat) -> Option<Vec<u8>> {
+    let resolution = frame.resolution();
+    let data = frame.buffer();
+    let (width, height) = (resolution.width() as usize, resolution.height() as usize);
+    // Byte offset of pixel (x, y)'s luma, and how to turn the bytes there into brightness.
+    let (bytes_per_pixel, luma_offset) = match format {
+        FrameFormat::Yuv422 => (2, 0),
+        FrameFormat::Uyv422 => (2, 1),
+        FrameFormat::Nv12 | FrameFormat::Nv21 | FrameFormat::Yv12 | FrameFormat::Luma8 => (1, 0),
+        FrameFormat::Rgb8 => (3, 0),
+        FrameFormat::RgbA8 => (4, 0),
+        #[cfg(feature = "output-image")]
+        FrameFormat::MJpeg => return mjpeg_luma_samples(frame),
+        _ => return None,
+    };
+    let stride = frame
+        .stride()
+        .map_or(width * bytes_per_pixel, |stride| stride as usize);
+    if width == 0 || height == 0 || data.len() < stride * (height - 1) + width * bytes_per_pixel {
+        return None;
+    }
+
+    let (columns, rows) = CHANGE_SAMPLE_GRID;
+    let mut samples = Vec::with_capacity((columns * rows) as usize);
+    for row in 0..rows as usize {
+        let y = (row * 2 + 1) * height / (rows as usize * 2);
+        for column in 0..columns as usize {
+            let x = (column * 2 + 1) * width / (columns as usize * 2);
+            let at = y * stride + x * bytes_per_pixel;
+            samples.push(match bytes_per_pixel {
+                3 | 4 => rgb_luma(&data[at..at + 3]),
+                _ => data[at + luma_offset],
+            });
+        }
+    }
+    Some(samples)
+}
+
+#[cfg(feature = "output-image")]
+fn mjpeg_luma_samples(frame: &Buffer) -> Option<Vec<u8>> {
+    use nokhwa_core::types::DecodeScale;
+    let rgb = crate::frame_to_rgb_scaled(frame, FrameFormat::MJpeg, DecodeScale::Eighth).ok()?;
+    Some(rgb.chunks_exact(3).step_by(4).map(rgb_luma).collect())
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn rgb_luma(pixel: &[u8]) -> u8 {
+    ((u16::from(pixel[0]) + 2 * u16::from(pixel[1]) + u16::from(pixel[2])) / 4) as u8
+}
+
+/// A camera that continuously captures on its own thread, so that the most recent frame is always available
+/// without waiting on the device.
+///
+/// This is meant for render loops (GUIs, game engines) that want "whatever the camera last saw" every tick. Frames that
+/// are not read before the next one arrives are simply replaced. If you need every frame, enable the frame queue with
+/// [`set_frame_queue()`](Self::set_frame_queue) and read from it with [`next_frame()`](Self::next_frame), or use [`CallbackCamera`] instead.
+/// ```ignore
+/// let mut camera = ThreadedCamera::new(index, requested)?;
+/// camera.open_stream()?;
+/// loop {
+///     if let Some(frame) = camera.last_frame()? {
+///         // draw the frame
+///     }
+/// }
+/// ```
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-threaded")))]
+pub struct ThreadedCamera {
+    camera: AtomicLock<Camera>,
+    frames: Arc<FrameDoubleBuffer>,
+    queue: Option<Arc<FrameQueue>>,
+    subscribers: Arc<Mutex<Vec<SyncSender<Arc<Buffer>>>>>,
+    channels: Arc<ChannelList>,
+    preroll: Arc<Mutex<PreRoll>>,
+    budget: Arc<MemoryBudget>,
+    pacer: Arc<Mutex<Option<FramePacer>>>,
+    change_detector: Arc<Mutex<Option<ChangeDetector>>>,
+    static_frames: Arc<AtomicU64>,
+    last_error: Arc<Mutex<Option<NokhwaError>>>,
+    #[cfg(feature = "output-image")]
+    decoded: Mutex<DecodedCache>,
+    die_bool: Arc<AtomicBool>,
+    current_camera: CameraInfo,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// The last frame decoded by [`ThreadedCamera::last_frame_rgb()`] or [`ThreadedCamera::last_frame_rgba()`], so asking again
+/// before a new frame arrives does not decode it again.
+#[cfg(feature = "output-image")]
+#[derive(Default)]
+struct DecodedCache {
+    rgb: Option<(Buffer, Arc<FrameBuffer>)>,
+    rgba: Option<(Buffer, Arc<FrameBuffer>)>,
+}
+
+impl ThreadedCamera {
+    /// Create a new `ThreadedCamera` from a [`CameraIndex`] and [`RequestedFormat`]
+    ///
+    /// # Errors
+    /// This will error if you either have a bad platform configuration (e.g. `input-v4l` but not on linux) or the backend cannot create the camera (e.g. permission denied).
+    pub fn new(index: CameraIndex, format: RequestedFormat) -> Result<Self, NokhwaError> {
+        Ok(Self::with_custom(Camera::with_request(index, format)?))
+    }
+
+    /// Allows creation of a [`ThreadedCamera`] with a custom [`Camera`].
+    ///
+    /// You **must** have set a format beforehand.
+    pub fn with_custom(camera: Camera) -> Self {
+        let current_camera = camera.info().clone();
+        let budget = Arc::new(MemoryBudget::new());
+        ThreadedCamera {
+            camera: Arc::new(Mutex::new(camera)),
+            frames: Arc::new(FrameDoubleBuffer::new()),
+            queue: None,
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            channels: Arc::new(Mutex::new(Vec::new())),
+            preroll: Arc::new(Mutex::new(PreRoll {
+                window: None,
+                frames: VecDeque::new(),
+                budget: budget.clone(),
+            })),
+            budget,
+            pacer: Arc::new(Mutex::new(None)),
+            change_detector: Arc::new(Mutex::new(None)),
+            static_frames: Arc::new(AtomicU64::new(0)),
+            last_error: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "output-image")]
+            decoded: Mutex::new(DecodedCache::default()),
+            die_bool: Arc::new(AtomicBool::new(false)),
+            current_camera,
+            handle: None,
+        }
+    }
+
+    /// Gets the camera information such as Name and Index as a [`CameraInfo`].
+    #[must_use]
+    pub fn info(&self) -> &CameraInfo {
+        &self.current_camera
+    }
+
+    /// Runs `f` with exclusive access to the underlying [`Camera`], e.g. to change its format or controls.
+    /// The capture thread will wait until `f` returns.
+    /// # Errors
+    /// This will error if the camera lock is poisoned.
+    pub fn with_camera<R>(&self, f: impl FnOnce(&mut Camera) -> R) -> Result<R, NokhwaError> {
+        let mut camera = self
+            .camera
+            .lock()
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+        Ok(f(&mut camera))
+    }
+
+    /// Opens the camera stream and starts the capture thread.
+    /// # Errors
+    /// If the specific backend fails to open the camera (e.g. already taken, busy, doesn't exist anymore) this will error.
+    pub fn open_stream(&mut self) -> Result<(), NokhwaError> {
+        if self.handle.is_some() {
+            return Err(NokhwaError::OpenStreamError(
+                "Stream Already Open".to_string(),
+            ));
+        }
+        self.camera
+            .lock()
+            .map_err(|why| NokhwaError::OpenStreamError(why.to_string()))?
+            .open_stream()?;
+        self.die_bool.store(false, Ordering::SeqCst);
+        if let Some(queue) = &self.queue {
+            queue.reopen();
+        }
+        let camera = self.camera.clone();
+        let frames = self.frames.clone();
+        let queue = self.queue.clone();
+        let subscribers = self.subscribers.clone();
+        let channels = self.channels.clone();
+        let preroll = self.preroll.clone();
+        let pacer = self.pacer.clone();
+        let change_detector = self.change_detector.clone();
+        let static_frames = self.static_frames.clone();
+        let last_error = self.last_error.clone();
+        if let Ok(mut last_error) = last_error.lock() {
+            *last_error = None;
+        }
+        let die_bool = self.die_bool.clone();
+        self.handle = Some(std::thread::spawn(move || {
+            let mut retry = CAPTURE_RETRY_MIN;
+            while !die_bool.load(Ordering::SeqCst) {
+                let frame = match camera.lock() {
+                    Ok(mut camera) => camera.frame(),
+                    Err(_) => break,
+                };
+                let frame = match frame {
+                    Ok(frame) => {
+                        retry = CAPTURE_RETRY_MIN;
+                        frame
+                    }
+                    Err(why) => {
+                        let disconnected = why.is_disconnected();
+                        if let Ok(mut last_error) = last_error.lock() {
+                            *last_error = Some(why);
+                        }
+                        // An unplugged camera will not come back on its own; end the stream instead of spinning.
+                        if disconnected {
+                            if let Some(queue) = &queue {
+                                queue.close();
+                            }
+                            break;
+                        }
+                        std::thread::sleep(retry);
+                        retry = (retry * 2).min(CAPTURE_RETRY_MAX);
+                        continue;
+                    }
+                };
+                // The format the frame was captured in, which may differ from the camera's if it was changed since.
+                let format = frame.source_frame_format();
+                let deliver = match pacer.lock() {
+                    Ok(mut pacer) => pacer
+                        .as_mut()
+                        .map_or(true, |pacer| pacer.should_deliver(Instant::now())),
+                    Err(_) => true,
+                };
+                if !deliver {
+                    continue;
+                }
+                let changed = match change_detector.lock() {
+                    Ok(mut detector) => detector.as_mut().map_or(true, |detector| {
+                        detector.should_deliver(&frame, format, Instant::now())
+                    }),
+                    Err(_) => true,
+                };
+                if !changed {
+                    static_frames.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                if let Some(queue) = &queue {
+                    queue.push(frame.clone());
+                }
+                broadcast_frame(&subscribers, &frame);
+                send_to_channels(&channels, &frame, &die_bool);
+                if let Ok(mut preroll) = preroll.lock() {
+                    preroll.push(&frame);
+                }
+                frames.publish(frame, format);
+            }
+        }));
+        Ok(())
+    }
+
+    /// Enables the frame queue: every captured frame is also pushed into a [`FrameQueue`] of `capacity` frames with the given [`DropPolicy`].
+    /// Pass `None` to disable it. This must be set before [`open_stream()`](Self::open_stream).
+    /// # Errors
+    /// If the capture thread is running, this will error.
+    pub fn set_frame_queue(
+        &mut self,
+        queue: Option<(usize, DropPolicy)>,
+    ) -> Result<(), NokhwaError> {
+        if self.handle.is_some() {
+            return Err(NokhwaError::SetPropertyError {
+                property: "Frame Queue".to_string(),
+                value: format!("{queue:?}"),
+                error: "Stream is open".to_string(),
+            });
+        }
+        self.queue = queue.map(|(capacity, policy)| {
+            Arc::new(FrameQueue::with_budget(
+                capacity,
+                policy,
+                self.budget.clone(),
+            ))
+        });
+        Ok(())
+    }
+
+    /// Gets the [`FrameQueue`], if enabled. This can be cloned and handed to a consumer thread.
+    #[must_use]
+    pub fn frame_queue(&self) -> Option<&Arc<FrameQueue>> {
+        self.queue.as_ref()
+    }
+
+    /// Subscribes to every frame captured from now on. Each subscriber buffers up to `capacity` frames before it starts missing frames.
+    ///
+    /// This can be called any number of times, e.g. once for a preview and once for a recorder, while the stream is open or not.
+    /// # Errors
+    /// This will error if the subscriber lock is poisoned.
+    pub fn subscribe(&self, capacity: usize) -> Result<FrameReceiver, NokhwaError> {
+        let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+        self.subscribers
+            .lock()
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))?
+            .push(sender);
+        Ok(FrameReceiver { receiver })
+    }
+
+    /// Sends every captured frame into `sender`, starting the capture thread if it is not running yet.
+    /// When the channel is full, [`DropPolicy::DropNewest`] skips the frame and [`DropPolicy::Block`] waits for the receiver
+    /// (which also holds back every other consumer of this camera).
+    ///
+    /// The channel is forgotten once its receiver is dropped. The sender is dropped when the stream is stopped, ending the receiver's iteration.
+    /// # Errors
+    /// A channel cannot evict frames on the receiving side, so [`DropPolicy::DropOldest`] is rejected. Use [`subscribe()`](Self::subscribe) or the
+    /// [`FrameQueue`] instead. This will also error if the stream fails to open.
+    pub fn stream_to_channel(
+        &mut self,
+        sender: SyncSender<Buffer>,
+        policy: DropPolicy,
+    ) -> Result<(), NokhwaError> {
+        if policy == DropPolicy::DropOldest {
+            return Err(NokhwaError::SetPropertyError {
+                property: "Channel Drop Policy".to_string(),
+                value: policy.to_string(),
+                error: "Channels can not drop their oldest frame".to_string(),
+            });
+        }
+        self.channels
+            .lock()
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))?
+            .push((sender, policy));
+        if self.handle.is_none() {
+            self.open_stream()?;
+        }
+        Ok(())
+    }
+
+    /// Caps the rate at which frames are delivered (to [`last_frame()`](Self::last_frame), the frame queue, subscribers, channels, and the pre-roll)
+    /// to `fps`, picking the captured frame closest to each tick and dropping the rest before anyone processes them.
+    /// Use this when you need e.g. 5 FPS from a camera that only offers 30 FPS modes. Pass `None` to deliver every frame (the default).
+    /// # Errors
+    /// This will error if the pacer lock is poisoned.
+    pub fn set_output_framerate_cap(&self, fps: Option<u32>) -> Result<(), NokhwaError> {
+        *self
+            .pacer
+            .lock()
+            .map_err(|why| NokhwaError::SetPropertyError {
+                property: "Output Framerate Cap".to_string(),
+                value: format!("{fps:?}"),
+                error: why.to_string(),
+            })? = fps.map(FramePacer::new);
+        Ok(())
+    }
+
+    /// Skips frames that look the same as the last delivered one before anyone decodes or receives them, see [`ChangeDetection`].
+    /// Skipped frames are not delivered to [`last_frame()`](Self::last_frame), the frame queue, subscribers, channels, or the
+    /// pre-roll. Pass `None` to deliver every frame (the default).
+    ///
+    /// This runs after [`set_output_framerate_cap()`](Self::set_output_framerate_cap), so only frames that pass the cap are compared.
+    /// # Errors
+    /// This will error if the change detector lock is poisoned.
+    pub fn set_change_detection(
+        &self,
+        detection: Option<ChangeDetection>,
+    ) -> Result<(), NokhwaError> {
+        *self
+            .change_detector
+            .lock()
+            .map_err(|why| NokhwaError::SetPropertyError {
+                property: "Change Detection".to_string(),
+                value: format!("{detection:?}"),
+                error: why.to_string(),
+            })? = detection.map(ChangeDetector::new);
+        Ok(())
+    }
+
+    /// How many frames [`set_change_detection()`](Self::set_change_detection) skipped because nothing changed.
+    #[must_use]
+    pub fn static_frames(&self) -> u64 {
+        self.static_frames.load(Ordering::Relaxed)
+    }
+
+    /// Keeps every frame captured in the last `window` in memory, so that footage from before an event can be saved
+    /// with [`preroll_snapshot()`](Self::preroll_snapshot) (e.g. a dashcam). Pass `None` to disable it and free the frames (the default).
+    ///
+    /// Memory use is the frame size times the frame rate times `window`. Prefer a compressed format such as MJPEG for long windows.
+    /// # Errors
+    /// This will error if the pre-roll lock is poisoned.
+    pub fn set_preroll(&self, window: Option<Duration>) -> Result<(), NokhwaError> {
+        let mut preroll = self
+            .preroll
+            .lock()
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+        preroll.window = window;
+        if window.is_none() {
+            preroll.clear();
+        }
+        Ok(())
+    }
+
+    /// Copies out the frames in the pre-roll window, oldest first. Frames are reference counted, so this is cheap.
+    /// # Errors
+    /// This will error if the pre-roll lock is poisoned.
+    pub fn preroll_snapshot(&self) -> Result<Vec<Buffer>, NokhwaError> {
+        Ok(self
+            .preroll
+            .lock()
+            .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))?
+            .frames
+            .iter()
+            .map(|(_, frame)| frame.clone())
+            .collect())
+    }
+
+    /// Caps the frame data held by the [frame queue](Self::set_frame_queue) and the [pre-roll](Self::set_preroll) together at
+    /// `bytes`, so a slow consumer or a long pre-roll can not make a long-running process balloon. Pass `None` to remove the
+    /// cap (the default).
+    ///
+    /// When a new frame would go over the cap, the frame queue applies its [`DropPolicy`] and the pre-roll drops its oldest
+    /// frames. A frame held by both counts twice. Subscribers and channels are not counted, as they are bounded by their capacity.
+    pub fn set_memory_limit(&self, bytes: Option<usize>) {
+        self.budget
+            .limit
+            .store(bytes.unwrap_or(usize::MAX), Ordering::Release);
+    }
+
+    /// The memory limit set with [`set_memory_limit()`](Self::set_memory_limit), if any.
+    #[must_use]
+    pub fn memory_limit(&self) -> Option<usize> {
+        Some(self.budget.limit.load(Ordering::Acquire)).filter(|limit| *limit != usize::MAX)
+    }
+
+    /// The bytes of frame data currently held by the frame queue and the pre-roll.
+    #[must_use]
+    pub fn memory_used(&self) -> usize {
+        self.budget.used.load(Ordering::Acquire)
+    }
+
+    /// Takes the oldest frame from the frame queue, waiting for one to arrive.
+    ///
+    /// Returns `Ok(None)` once the stream has been stopped and the queue is drained.
+    /// # Errors
+    /// If the frame queue is not enabled (see [`set_frame_queue()`](Self::set_frame_queue)), this will error.
+    pub fn next_frame(&self) -> Result<Option<Buffer>, NokhwaError> {
+        match &self.queue {
+            Some(queue) => Ok(queue.pop()),
+            None => Err(NokhwaError::ReadFrameError(
+                "Frame queue is not enabled".to_string(),
+            )),
+        }
+    }
+
+    /// Gets the most recent frame captured. This never waits on the camera.
+    ///
+    /// Returns `None` if no frame has been captured since the stream was opened.
+    /// # Errors
+    /// This will error if the frame lock is poisoned.
+    pub fn last_frame(&self) -> Result<Option<Buffer>, NokhwaError> {
+        self.frames.latest()
+    }
+
+    /// Gets the most recent frame captured, decoded to packed RGB888 (see [`frame_to_rgb()`](crate::frame_to_rgb)).
+    ///
+    /// The capture thread keeps frames raw, so frames are only decoded when asked for: a consumer that runs at 10 FPS only pays
+    /// for decoding 10 frames a second, however fast the camera is. Asking again before a new frame arrives returns the same
+    /// decoded frame without decoding it again.
+    ///
+    /// Returns `None` if no frame has been captured since the stream was opened.
+    /// # Errors
+    /// This will error if the frame lock is poisoned or the frame fails to decode.
+    #[cfg(feature = "output-image")]
+    pub fn last_frame_rgb(&self) -> Result<Option<(Buffer, Arc<FrameBuffer>)>, NokhwaError> {
+        self.last_frame_decoded(false)
+    }
+
+    /// Gets the most recent frame captured, decoded to packed RGBA8888 (see [`frame_to_rgba()`](crate::frame_to_rgba)).
+    /// Like [`last_frame_rgb()`](Self::last_frame_rgb), frames are only decoded when asked for.
+    ///
+    /// Returns `None` if no frame has been captured since the stream was opened.
+    /// # Errors
+    /// This will error if the frame lock is poisoned or the frame fails to decode.
+    #[cfg(feature = "output-image")]
+    pub fn last_frame_rgba(&self) -> Result<Option<(Buffer, Arc<FrameBuffer>)>, NokhwaError> {
+        self.last_frame_decoded(true)
+    }
+
+    #[cfg(feature = "output-image")]
+    fn last_frame_decoded(
+        &self,
+        rgba: bool,
+    ) -> Result<Option<(Buffer, Arc<FrameBuffer>)>, NokhwaError> {
+        let Some((frame, format)) = self.frames.latest_with_format()? else {
+            return Ok(None);
+        };
+        let mut cache = self
+            .decoded
+            .lock()
+            .map_err(|why| NokhwaError::ReadFrameError(why.to_string()))?;
+        let cached = if rgba {
+            &mut cache.rgba
+        } else {
+            &mut cache.rgb
+        };
+        // Clones of a frame share its data, and the cache keeps that data alive, so the same address means the same frame.
+        if let Some((decoded_frame, decoded)) = cached {
+            if decoded_frame.buffer().as_ptr() == frame.buffer().as_ptr() {
+                return Ok(Some((decoded_frame.clone(), decoded.clone())));
+            }
+        }
+        let decoded = Arc::new(if rgba {
+            crate::frame_to_rgba(&frame, format)?
+        } else {
+            crate::frame_to_rgb(&frame, format)?
+        });
+        *cached = Some((frame.clone(), decoded.clone()));
+        Ok(Some((frame, decoded)))
+    }
+
+    /// Checks if the capture thread is running. It stops on its own if the camera is unplugged, see [`last_error()`](Self::last_error).
+    #[must_use]
+    pub fn is_capturing(&self) -> bool {
+        self.handle
+            .as_ref()
+            .map_or(false, |handle| !handle.is_finished())
+    }
+
+    /// The last error the capture thread got from the camera since the stream was opened, if any.
+    ///
+    /// The capture thread retries failed frames, waiting a little longer each time (up to half a second). If the camera was
+    /// unplugged (see [`NokhwaError::is_disconnected()`]) it stops instead; call [`stop_stream()`](Self::stop_stream) before
+    /// opening the stream again.
+    /// # Errors
+    /// This will error if the error lock is poisoned.
+    pub fn last_error(&self) -> Result<Option<NokhwaError>, NokhwaError> {
+        self.last_error
+            .lock()
+            .map(|last_error| last_error.clone())
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))
+    }
+
+    /// Will stop the capture thread, wait for it to finish, then drop the stream.
+    /// # Errors
+    /// Please check the `Quirks` section of each backend.
+    pub fn stop_stream(&mut self) -> Result<(), NokhwaError> {
         self.die_bool.store(true, Ordering::SeqCst);
+        // Wake the capture thread if it is blocked on a full queue.
+        if let Some(queue) = &self.queue {
+            queue.close();
+        }
+        if let Some(handle) = self.handle.take() {
+            handle.join().map_err(|_| {
+                NokhwaError::StreamShutdownError("Capture thread panicked".to_string())
+            })?;
+        }
+        // Hang up on subscribers so their iterators end.
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.clear();
+        }
+        if let Ok(mut channels) = self.channels.lock() {
+            channels.clear();
+        }
+        self.frames.clear();
+        #[cfg(feature = "output-image")]
+        if let Ok(mut decoded) = self.decoded.lock() {
+            *decoded = DecodedCache::default();
+        }
+        self.camera
+            .lock()
+            .map_err(|why| NokhwaError::StreamShutdownError(why.to_string()))?
+            .stop_stream()
+    }
+}
+
+impl Drop for ThreadedCamera {
+    fn drop(&mut self) {
+        let _stop_stream_err = self.stop_stream();
     }
 }
 
@@ -552,6 +1701,9 @@ fn camera_frame_thread_loop(
     die_bool: Arc<AtomicBool>,
 ) {
     loop {
+        if die_bool.load(Ordering::SeqCst) {
+            break;
+        }
         if let Ok(mut camera) = camera.lock() {
             if let Ok(frame) = camera.frame() {
                 if let Ok(mut last_frame) = last_frame_captured.lock() {
@@ -562,8 +1714,189 @@ fn camera_frame_thread_loop(
                 }
             }
         }
-        if die_bool.load(Ordering::SeqCst) {
-            break;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DropPolicy, FramePacer, FrameQueue, MemoryBudget};
+    use nokhwa_core::{
+        buffer::Buffer,
+        types::{FrameFormat, Resolution},
+    };
+    use std::{
+        sync::{atomic::Ordering, Arc},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    fn frame(value: u8) -> Buffer {
+        Buffer::new(Resolution::new(1, 1), &[value], FrameFormat::Luma8)
+    }
+
+    fn sized_frame(value: u8, bytes: usize) -> Buffer {
+        Buffer::new(
+            Resolution::new(1, 1),
+            &vec![value; bytes],
+            FrameFormat::Luma8,
+        )
+    }
+
+    fn budget(limit: usize) -> Arc<MemoryBudget> {
+        let budget = Arc::new(MemoryBudget::new());
+        budget.limit.store(limit, Ordering::Release);
+        budget
+    }
+
+    fn drain(queue: &FrameQueue) -> Vec<u8> {
+        std::iter::from_fn(|| queue.try_pop())
+            .map(|frame| frame.buffer()[0])
+            .collect()
+    }
+
+    #[test]
+    fn drop_oldest_keeps_the_newest_frames() {
+        let queue = FrameQueue::new(2, DropPolicy::DropOldest);
+        for value in 1..=4 {
+            assert!(queue.push(frame(value)));
         }
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(drain(&queue), [3, 4]);
+    }
+
+    #[test]
+    fn drop_newest_keeps_the_oldest_frames() {
+        let queue = FrameQueue::new(2, DropPolicy::DropNewest);
+        for value in 1..=4 {
+            assert!(queue.push(frame(value)));
+        }
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(drain(&queue), [1, 2]);
+    }
+
+    #[test]
+    fn block_waits_for_room() {
+        let queue = FrameQueue::new(1, DropPolicy::Block);
+        assert!(queue.push(frame(1)));
+        thread::scope(|scope| {
+            let pusher = scope.spawn(|| queue.push(frame(2)));
+            thread::sleep(Duration::from_millis(50));
+            assert!(!pusher.is_finished());
+            assert_eq!(queue.pop().map(|frame| frame.buffer()[0]), Some(1));
+            assert!(pusher.join().unwrap());
+        });
+        assert_eq!(queue.dropped(), 0);
+        assert_eq!(drain(&queue), [2]);
+    }
+
+    #[test]
+    fn close_wakes_a_blocked_push() {
+        let queue = FrameQueue::new(1, DropPolicy::Block);
+        assert!(queue.push(frame(1)));
+        thread::scope(|scope| {
+            let pusher = scope.spawn(|| queue.push(frame(2)));
+            thread::sleep(Duration::from_millis(50));
+            queue.close();
+            assert!(!pusher.join().unwrap());
+        });
+        // Frames queued before closing can still be taken.
+        assert_eq!(drain(&queue), [1]);
+        assert!(!queue.push(frame(3)));
+    }
+
+    #[test]
+    fn close_wakes_a_waiting_pop() {
+        let queue = FrameQueue::new(1, DropPolicy::Block);
+        thread::scope(|scope| {
+            let popper = scope.spawn(|| queue.pop().is_none());
+            thread::sleep(Duration::from_millis(50));
+            queue.close();
+            assert!(popper.join().unwrap());
+        });
+    }
+
+    #[test]
+    fn zero_capacity_holds_one_frame() {
+        let queue = FrameQueue::new(0, DropPolicy::DropOldest);
+        assert_eq!(queue.capacity(), 1);
+        assert!(queue.push(frame(1)));
+        assert!(queue.push(frame(2)));
+        assert_eq!(drain(&queue), [2]);
+    }
+
+    #[test]
+    fn memory_limit_evicts_the_oldest_frames() {
+        let budget = budget(10);
+        let queue = FrameQueue::with_budget(8, DropPolicy::DropOldest, budget.clone());
+        for value in 1..=3 {
+            assert!(queue.push(sized_frame(value, 4)));
+        }
+        assert_eq!(queue.dropped(), 1);
+        assert_eq!(budget.used.load(Ordering::Acquire), 8);
+        assert_eq!(drain(&queue), [2, 3]);
+        assert_eq!(budget.used.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    fn memory_limit_drops_new_frames() {
+        let budget = budget(10);
+        let queue = FrameQueue::with_budget(8, DropPolicy::DropNewest, budget.clone());
+        for value in 1..=3 {
+            assert!(queue.push(sized_frame(value, 4)));
+        }
+        assert_eq!(queue.dropped(), 1);
+        assert_eq!(drain(&queue), [1, 2]);
+    }
+
+    #[test]
+    fn memory_limit_smaller_than_a_frame_still_queues_one() {
+        let budget = budget(2);
+        let queue = FrameQueue::with_budget(8, DropPolicy::DropOldest, budget.clone());
+        assert!(queue.push(sized_frame(1, 4)));
+        assert!(queue.push(sized_frame(2, 4)));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(budget.used.load(Ordering::Acquire), 4);
+        assert_eq!(drain(&queue), [2]);
+    }
+
+    #[test]
+    fn dropping_a_queue_releases_its_memory() {
+        let budget = budget(usize::MAX);
+        let queue = FrameQueue::with_budget(8, DropPolicy::DropOldest, budget.clone());
+        assert!(queue.push(sized_frame(1, 4)));
+        assert!(queue.push(sized_frame(2, 4)));
+        assert_eq!(budget.used.load(Ordering::Acquire), 8);
+        drop(queue);
+        assert_eq!(budget.used.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    fn pacer_picks_every_third_frame_of_a_30fps_source_for_10fps() {
+        let mut pacer = FramePacer::new(10);
+        let start = Instant::now();
+        let interval = Duration::from_secs(1) / 30;
+        let delivered: Vec<u32> = (0..30)
+            .filter(|frame| pacer.should_deliver(start + interval * *frame))
+            .collect();
+        assert_eq!(delivered, [0, 3, 6, 9, 12, 15, 18, 21, 24, 27]);
+    }
+
+    #[test]
+    fn pacer_delivers_every_frame_of_a_slower_source() {
+        let mut pacer = FramePacer::new(30);
+        let start = Instant::now();
+        let interval = Duration::from_secs(1) / 10;
+        assert!((0..10).all(|frame| pacer.should_deliver(start + interval * frame)));
+    }
+
+    #[test]
+    fn pacer_does_not_catch_up_after_a_stall() {
+        let mut pacer = FramePacer::new(10);
+        let start = Instant::now();
+        assert!(pacer.should_deliver(start));
+        assert!(pacer.should_deliver(start + Duration::from_secs(2)));
+        assert!(!pacer.should_deliver(start + Duration::from_millis(2033)));
+        assert!(!pacer.should_deliver(start + Duration::from_millis(2066)));
+        assert!(pacer.should_deliver(start + Duration::from_millis(2100)));
     }
 }
@@ -0,0 +1,315 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::Camera;
+#[cfg(feature = "output-image")]
+use crate::ImageOutputFormat;
+use nokhwa_core::{buffer::Buffer, error::NokhwaError, traits::CaptureTrait};
+#[cfg(feature = "output-image")]
+use std::path::Path;
+use std::{
+    fmt::{Display, Formatter},
+    sync::mpsc::{self, RecvTimeoutError, Sender},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// How many frames [`Timelapse`] throws away before each shot by default, while auto exposure settles and stale frames
+/// queued in the driver are flushed.
+pub const DEFAULT_TIMELAPSE_WARMUP_FRAMES: usize = 3;
+
+/// What a [`Timelapse`] does with the camera between shots.
+#[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub enum TimelapsePower {
+    /// Keep streaming. Uses the most power, but shots are taken the fastest. Best for intervals of a few seconds.
+    KeepStreaming,
+    /// Stop the stream between shots, which lets most cameras idle (and turns off their activity LED) while keeping the device open.
+    #[default]
+    StopStream,
+    /// Close the device between shots and open it again for the next, so other applications can use the camera in between and
+    /// the device can suspend. Best for intervals of minutes. The format is set again after reopening.
+    Reopen,
+}
+
+impl Display for TimelapsePower {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Captures one frame every `interval`, e.g. every 10 seconds for a day long timelapse, keeping the camera idle between shots
+/// according to its [`TimelapsePower`] policy.
+///
+/// Shots are scheduled from the start time, so slow shots do not make the timelapse drift. If a shot takes longer than the interval,
+/// the missed shots are skipped rather than taken back to back.
+/// ```ignore
+/// let timelapse = Timelapse::new(Duration::from_secs(60)).with_shot_limit(Some(24 * 60));
+/// timelapse.run_to_directory(camera, "timelapse", ImageOutputFormat::Jpeg(90))?;
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timelapse {
+    interval: Duration,
+    power: TimelapsePower,
+    warmup_frames: usize,
+    shot_limit: Option<usize>,
+}
+
+impl Timelapse {
+    /// Creates a timelapse that takes a shot every `interval`, stopping the stream in between, until stopped.
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Timelapse {
+            interval,
+            power: TimelapsePower::default(),
+            warmup_frames: DEFAULT_TIMELAPSE_WARMUP_FRAMES,
+            shot_limit: None,
+        }
+    }
+
+    /// Sets what to do with the camera between shots.
+    #[must_use]
+    pub fn with_power(mut self, power: TimelapsePower) -> Self {
+        self.power = power;
+        self
+    }
+
+    /// Sets how many frames are thrown away before each shot. Defaults to [`DEFAULT_TIMELAPSE_WARMUP_FRAMES`].
+    #[must_use]
+    pub fn with_warmup_frames(mut self, warmup_frames: usize) -> Self {
+        self.warmup_frames = warmup_frames;
+        self
+    }
+
+    /// Stops after `shots` shots. `None` (the default) runs until stopped.
+    #[must_use]
+    pub fn with_shot_limit(mut self, shots: Option<usize>) -> Self {
+        self.shot_limit = shots;
+        self
+    }
+
+    /// The time between shots.
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// What is done with the camera between shots.
+    #[must_use]
+    pub fn power(&self) -> TimelapsePower {
+        self.power
+    }
+
+    /// How many frames are thrown away before each shot.
+    #[must_use]
+    pub fn warmup_frames(&self) -> usize {
+        self.warmup_frames
+    }
+
+    /// How many shots are taken before stopping, if limited.
+    #[must_use]
+    pub fn shot_limit(&self) -> Option<usize> {
+        self.shot_limit
+    }
+
+    /// Runs the timelapse on this thread, calling `on_shot` with the number (from 0) and frame of each shot.
+    /// Return `false` from `on_shot` to stop. Returns how many shots were taken.
+    ///
+    /// The camera must have its format set. With [`TimelapsePower::Reopen`], it is dropped after each shot and opened again with the same
+    /// index, backend, and format for the next.
+    /// # Errors
+    /// If the camera fails to open, capture, or close, this will error.
+    pub fn run(
+        &self,
+        camera: Camera,
+        mut on_shot: impl FnMut(usize, Buffer) -> bool,
+    ) -> Result<usize, NokhwaError> {
+        self.run_until(camera, &mut |_, shot, frame| on_shot(shot, frame), |wait| {
+            std::thread::sleep(wait);
+            false
+        })
+    }
+
+    /// Runs the timelapse on this thread, writing shot `n` to `directory/frame_{n:06}.{ext}` with
+    /// [`Camera::save_buffer()`]. Returns how many shots were taken. See [`run()`](Self::run).
+    /// # Errors
+    /// If the camera fails, or an image can not be encoded or written, this will error.
+    #[cfg(feature = "output-image")]
+    #[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-image")))]
+    pub fn run_to_directory(
+        &self,
+        camera: Camera,
+        directory: impl AsRef<Path>,
+        format: ImageOutputFormat,
+    ) -> Result<usize, NokhwaError> {
+        let directory = directory.as_ref();
+        std::fs::create_dir_all(directory)
+            .map_err(|why| NokhwaError::from_io("Create timelapse directory", why))?;
+        let mut saved = Ok(());
+        let mut save = |camera: &Camera, shot: usize, frame: Buffer| {
+            let path = directory.join(format!("frame_{shot:06}.{}", format.extension()));
+            saved = camera.save_buffer(&frame, path, format);
+            saved.is_ok()
+        };
+        let shots = self.run_until(camera, &mut save, |wait| {
+            std::thread::sleep(wait);
+            false
+        })?;
+        saved.map(|()| shots)
+    }
+
+    /// Runs the timelapse on its own thread. Stop it with [`TimelapseHandle::stop()`]; a shot in progress is finished first.
+    pub fn spawn(
+        self,
+        camera: Camera,
+        mut on_shot: impl FnMut(usize, Buffer) -> bool + Send + 'static,
+    ) -> TimelapseHandle {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let handle = std::thread::spawn(move || {
+            self.run_until(camera, &mut |_, shot, frame| on_shot(shot, frame), |wait| {
+                // The handle hanging up (or sending anything) stops the timelapse.
+                !matches!(stopped.recv_timeout(wait), Err(RecvTimeoutError::Timeout))
+            })
+        });
+        TimelapseHandle {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+
+    /// The timelapse loop. `wait` sleeps for the given time, returning `true` if the timelapse should stop instead.
+    fn run_until(
+        &self,
+        camera: Camera,
+        on_shot: &mut dyn FnMut(&Camera, usize, Buffer) -> bool,
+        mut wait: impl FnMut(Duration) -> bool,
+    ) -> Result<usize, NokhwaError> {
+        let index = camera.index().clone();
+        let api = camera.backend();
+        let format = camera.camera_format();
+        let mut slot = Some(camera);
+        let start = Instant::now();
+        let mut shots = 0;
+        let mut next = 0_u32;
+        while self.shot_limit.map_or(true, |limit| shots < limit) {
+            let mut camera = match slot.take() {
+                Some(camera) => camera,
+                None => {
+                    let mut camera = Camera::with_api_backend(index.clone(), api)?;
+                    if let Some(format) = format {
+                        camera.set_camera_format(format)?;
+                    }
+                    camera
+                }
+            };
+            if !camera.is_stream_open() {
+                camera.open_stream()?;
+            }
+            for _ in 0..self.warmup_frames {
+                camera.frame()?;
+            }
+            let frame = camera.frame()?;
+            let keep_going = on_shot(&camera, shots, frame);
+            shots += 1;
+            match self.power {
+                TimelapsePower::KeepStreaming => slot = Some(camera),
+                TimelapsePower::StopStream => {
+                    camera.stop_stream()?;
+                    slot = Some(camera);
+                }
+                // Dropping the camera closes the device until the next shot.
+                TimelapsePower::Reopen => camera.stop_stream()?,
+            }
+            if !keep_going {
+                break;
+            }
+
+            // Skip shots that were missed while this one was being taken.
+            let elapsed = start.elapsed();
+            next = next.max(1);
+            while !self.interval.is_zero() && self.interval * next <= elapsed {
+                next += 1;
+            }
+            let until = start + self.interval * next;
+            let stopped = match &mut slot {
+                Some(camera) if camera.is_stream_open() => {
+                    wait_streaming(camera, until, &mut wait)?
+                }
+                _ => wait(until.saturating_duration_since(Instant::now())),
+            };
+            if stopped {
+                break;
+            }
+        }
+        if let Some(camera) = &mut slot {
+            if camera.is_stream_open() {
+                camera.stop_stream()?;
+            }
+        }
+        Ok(shots)
+    }
+}
+
+/// Reads and discards frames until `until`, so the driver does not fill up with stale frames. Checks `wait` between frames to
+/// see if the timelapse was stopped, returning `true` if so.
+fn wait_streaming(
+    camera: &mut Camera,
+    until: Instant,
+    wait: &mut impl FnMut(Duration) -> bool,
+) -> Result<bool, NokhwaError> {
+    while Instant::now() < until {
+        camera.frame()?;
+        if wait(Duration::ZERO) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// A [`Timelapse`] running on its own thread, from [`Timelapse::spawn()`]. Dropping it stops the timelapse.
+pub struct TimelapseHandle {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<Result<usize, NokhwaError>>>,
+}
+
+impl TimelapseHandle {
+    /// Returns true if the timelapse is done, because it reached its shot limit, the callback stopped it, or it failed.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().map_or(true, JoinHandle::is_finished)
+    }
+
+    /// Stops the timelapse (finishing a shot in progress) and returns how many shots were taken.
+    /// # Errors
+    /// If the timelapse thread panicked, or the timelapse failed, this will error.
+    pub fn stop(mut self) -> Result<usize, NokhwaError> {
+        self.stop.take();
+        let handle = self.handle.take().ok_or_else(|| {
+            NokhwaError::StreamShutdownError("Timelapse already stopped".to_string())
+        })?;
+        handle.join().map_err(|_| {
+            NokhwaError::StreamShutdownError("Timelapse thread panicked".to_string())
+        })?
+    }
+}
+
+impl Drop for TimelapseHandle {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(handle) = self.handle.take() {
+            let _join_err = handle.join();
+        }
+    }
+}
@@ -0,0 +1,130 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::Camera;
+use nokhwa_core::{
+    buffer::Buffer,
+    error::NokhwaError,
+    traits::CaptureTrait,
+    types::{CameraIndex, CameraInfo, RequestedFormat},
+};
+use std::sync::{Arc, Mutex};
+
+/// A [`Camera`] for use in `tokio` applications. Every call that may block on the device is run with
+/// [`spawn_blocking`](tokio::task::spawn_blocking), so it never stalls the async runtime.
+///
+/// This can be cloned cheaply; all clones share the same camera.
+/// ```ignore
+/// let camera = TokioCamera::open(CameraIndex::Index(0), requested).await?;
+/// camera.open_stream().await?;
+/// let frame = camera.frame().await?;
+/// camera.shutdown().await?;
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "docs-features", doc(cfg(feature = "output-tokio")))]
+pub struct TokioCamera {
+    camera: Arc<Mutex<Camera>>,
+    info: CameraInfo,
+}
+
+impl TokioCamera {
+    /// Opens a camera from a [`CameraIndex`] and [`RequestedFormat`].
+    /// # Errors
+    /// This will error if you either have a bad platform configuration (e.g. `input-v4l` but not on linux) or the backend cannot create the camera (e.g. permission denied).
+    pub async fn open(index: CameraIndex, format: RequestedFormat) -> Result<Self, NokhwaError> {
+        let camera = tokio::task::spawn_blocking(move || Camera::with_request(index, format))
+            .await
+            .map_err(|why| NokhwaError::GeneralError(why.to_string()))??;
+        Ok(Self::with_custom(camera))
+    }
+
+    /// Creates a [`TokioCamera`] from an existing [`Camera`].
+    #[must_use]
+    pub fn with_custom(camera: Camera) -> Self {
+        let info = camera.info().clone();
+        TokioCamera {
+            camera: Arc::new(Mutex::new(camera)),
+            info,
+        }
+    }
+
+    /// Gets the camera information such as Name and Index as a [`CameraInfo`].
+    #[must_use]
+    pub fn info(&self) -> &CameraInfo {
+        &self.info
+    }
+
+    /// Runs `f` with exclusive access to the underlying [`Camera`] on the blocking thread pool, e.g. to change its format or controls.
+    /// # Errors
+    /// This will error if `f` errors, the camera lock is poisoned, or the blocking task panicked.
+    pub async fn with_camera<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut Camera) -> Result<R, NokhwaError> + Send + 'static,
+    ) -> Result<R, NokhwaError> {
+        let camera = self.camera.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut camera = camera
+                .lock()
+                .map_err(|why| NokhwaError::GeneralError(why.to_string()))?;
+            f(&mut camera)
+        })
+        .await
+        .map_err(|why| NokhwaError::GeneralError(why.to_string()))?
+    }
+
+    /// Will open the camera stream with set parameters.
+    /// # Errors
+    /// If the specific backend fails to open the camera (e.g. already taken, busy, doesn't exist anymore) this will error.
+    pub async fn open_stream(&self) -> Result<(), NokhwaError> {
+        self.with_camera(|camera| camera.open_stream()).await
+    }
+
+    /// Will get the next frame from the camera.
+    /// # Errors
+    /// If the backend fails to get the frame (e.g. already taken, busy, doesn't exist anymore), this will error.
+    pub async fn frame(&self) -> Result<Buffer, NokhwaError> {
+        self.with_camera(|camera| camera.frame()).await
+    }
+
+    /// Checks if stream if open. If it is, it will return true.
+    /// # Errors
+    /// This will error if the camera lock is poisoned, or the blocking task panicked.
+    pub async fn is_stream_open(&self) -> Result<bool, NokhwaError> {
+        self.with_camera(|camera| Ok(camera.is_stream_open())).await
+    }
+
+    /// Will drop the stream. The camera can be reopened with [`open_stream()`](Self::open_stream).
+    /// # Errors
+    /// Please check the `Quirks` section of each backend.
+    pub async fn stop_stream(&self) -> Result<(), NokhwaError> {
+        self.with_camera(|camera| camera.stop_stream()).await
+    }
+
+    /// Stops the stream, waiting for the device to be released. Prefer this over dropping the camera inside the runtime,
+    /// as dropping a [`Camera`] with an open stream blocks while the backend shuts down.
+    /// # Errors
+    /// Please check the `Quirks` section of each backend.
+    pub async fn shutdown(self) -> Result<(), NokhwaError> {
+        self.with_camera(|camera| {
+            if camera.is_stream_open() {
+                camera.stop_stream()
+            } else {
+                Ok(())
+            }
+        })
+        .await
+    }
+}
@@ -0,0 +1,228 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::Camera;
+use nokhwa_core::{
+    buffer::Buffer,
+    error::NokhwaError,
+    frame_format::FrameFormat,
+    traits::CaptureTrait,
+    types::{
+        CameraControl, CameraFormat, CameraIndex, CameraInfo, ControlValueSetter,
+        KnownCameraControl, RequestedFormat, Resolution,
+    },
+};
+use std::{borrow::Cow, collections::HashMap, marker::PhantomData};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The state of a [`TypedCamera`]. Either [`Configured`] or [`Streaming`].
+pub trait CameraState: sealed::Sealed {}
+
+/// A [`TypedCamera`] whose stream is closed. The format can be changed, but no frames can be read.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Configured;
+
+/// A [`TypedCamera`] whose stream is open. Frames can be read, but the format can not be changed.
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Streaming;
+
+impl sealed::Sealed for Configured {}
+impl sealed::Sealed for Streaming {}
+impl CameraState for Configured {}
+impl CameraState for Streaming {}
+
+/// A [`Camera`] that tracks whether its stream is open in its type.
+///
+/// Format-changing methods only exist on `TypedCamera<Configured>` and frame methods only exist on `TypedCamera<Streaming>`,
+/// so "stream not open" and "format changed under an open stream" are caught at compile time instead of at runtime.
+/// [`open_stream()`](TypedCamera::open_stream) and [`stop_stream()`](TypedCamera::stop_stream) move between the two.
+/// ```ignore
+/// let camera = TypedCamera::new(0)?;
+/// camera.set_resolution(Resolution::HD_720P)?;
+/// let mut camera = camera.open_stream()?;
+/// let frame = camera.frame()?;
+/// let camera = camera.stop_stream()?;
+/// ```
+pub struct TypedCamera<S: CameraState = Configured> {
+    camera: Camera,
+    _state: PhantomData<S>,
+}
+
+impl<S: CameraState> TypedCamera<S> {
+    fn with_state<N: CameraState>(camera: Camera) -> TypedCamera<N> {
+        TypedCamera {
+            camera,
+            _state: PhantomData,
+        }
+    }
+
+    /// Gets the camera information such as Name and Index as a [`CameraInfo`].
+    #[must_use]
+    pub fn info(&self) -> &CameraInfo {
+        self.camera.info()
+    }
+
+    /// Gets the current [`CameraFormat`].
+    #[must_use]
+    pub fn camera_format(&self) -> Option<CameraFormat> {
+        self.camera.camera_format()
+    }
+
+    /// Gets the value of a [`KnownCameraControl`].
+    /// # Errors
+    /// If the control is not supported or the value could not be read, this will error.
+    pub fn camera_control(
+        &self,
+        control: KnownCameraControl,
+    ) -> Result<CameraControl, NokhwaError> {
+        self.camera.camera_control(control)
+    }
+
+    /// Gets all supported camera controls.
+    /// # Errors
+    /// If the controls could not be read, this will error.
+    pub fn camera_controls(&self) -> Result<Vec<CameraControl>, NokhwaError> {
+        self.camera.camera_controls()
+    }
+
+    /// Sets a control. Controls such as exposure do not change the format, so they can be set in either state.
+    /// # Errors
+    /// If the control is not supported or the value is rejected, this will error.
+    pub fn set_camera_control(
+        &mut self,
+        id: KnownCameraControl,
+        value: ControlValueSetter,
+    ) -> Result<(), NokhwaError> {
+        self.camera.set_camera_control(id, value)
+    }
+
+    /// Gets the underlying [`Camera`]. Note that this gives up the compile-time guarantees.
+    #[must_use]
+    pub fn into_inner(self) -> Camera {
+        self.camera
+    }
+}
+
+impl TypedCamera<Configured> {
+    /// Opens a camera from an index, using the native backend of the platform.
+    /// # Errors
+    /// This will error if there is no native backend, or the backend fails to open or initialize the camera.
+    pub fn new(index: impl Into<CameraIndex>) -> Result<Self, NokhwaError> {
+        let mut camera = Camera::new(index)?;
+        camera.init()?;
+        Self::from_camera(camera)
+    }
+
+    /// Wraps an existing [`Camera`]. If its stream is open, it is stopped.
+    /// # Errors
+    /// If the stream could not be stopped, this will error.
+    pub fn from_camera(mut camera: Camera) -> Result<Self, NokhwaError> {
+        if camera.is_stream_open() {
+            camera.stop_stream()?;
+        }
+        Ok(Self::with_state(camera))
+    }
+
+    /// Sets the [`CameraFormat`].
+    /// # Errors
+    /// If the camera rejects the new camera format, this will error.
+    pub fn set_camera_format(&mut self, new_fmt: CameraFormat) -> Result<(), NokhwaError> {
+        self.camera.set_camera_format(new_fmt)
+    }
+
+    /// Sets the format that best fits the [`RequestedFormat`] and returns it.
+    /// # Errors
+    /// If nothing the camera supports fits the request or the camera rejects it, this will error.
+    pub fn set_camera_request(
+        &mut self,
+        request: RequestedFormat,
+    ) -> Result<CameraFormat, NokhwaError> {
+        self.camera.set_camera_request(request)
+    }
+
+    /// Sets the [`Resolution`].
+    /// # Errors
+    /// If the camera rejects the new resolution, this will error.
+    pub fn set_resolution(&mut self, new_res: Resolution) -> Result<(), NokhwaError> {
+        self.camera.set_resolution(new_res)
+    }
+
+    /// Sets the frame rate.
+    /// # Errors
+    /// If the camera rejects the new frame rate, this will error.
+    pub fn set_frame_rate(&mut self, new_fps: u32) -> Result<(), NokhwaError> {
+        self.camera.set_frame_rate(new_fps)
+    }
+
+    /// Sets the [`FrameFormat`].
+    /// # Errors
+    /// If the camera rejects the new frame format, this will error.
+    pub fn set_frame_format(&mut self, fourcc: FrameFormat) -> Result<(), NokhwaError> {
+        self.camera.set_frame_format(fourcc.into())
+    }
+
+    /// Lists the [`CameraFormat`]s the camera supports.
+    /// # Errors
+    /// If the formats could not be listed, this will error.
+    pub fn compatible_camera_formats(&mut self) -> Result<Vec<CameraFormat>, NokhwaError> {
+        self.camera.compatible_camera_formats()
+    }
+
+    /// A hashmap of [`Resolution`]s mapped to frame rates for a [`FrameFormat`].
+    /// # Errors
+    /// If the formats could not be listed, this will error.
+    pub fn compatible_list_by_resolution(
+        &mut self,
+        fourcc: FrameFormat,
+    ) -> Result<HashMap<Resolution, Vec<u32>>, NokhwaError> {
+        self.camera.compatible_list_by_resolution(fourcc.into())
+    }
+
+    /// Opens the stream.
+    /// # Errors
+    /// If the stream could not be opened, this will error. The camera is dropped in that case.
+    pub fn open_stream(mut self) -> Result<TypedCamera<Streaming>, NokhwaError> {
+        self.camera.open_stream()?;
+        Ok(Self::with_state(self.camera))
+    }
+}
+
+impl TypedCamera<Streaming> {
+    /// Gets a frame from the camera.
+    /// # Errors
+    /// If the frame could not be read, this will error.
+    pub fn frame(&mut self) -> Result<Buffer, NokhwaError> {
+        self.camera.frame()
+    }
+
+    /// Gets the raw bytes of a frame from the camera.
+    /// # Errors
+    /// If the frame could not be read, this will error.
+    pub fn frame_raw(&mut self) -> Result<Cow<[u8]>, NokhwaError> {
+        self.camera.frame_raw()
+    }
+
+    /// Stops the stream, so the format can be changed again.
+    /// # Errors
+    /// If the stream could not be stopped, this will error. The camera is dropped in that case.
+    pub fn stop_stream(mut self) -> Result<TypedCamera<Configured>, NokhwaError> {
+        self.camera.stop_stream()?;
+        Ok(Self::with_state(self.camera))
+    }
+}
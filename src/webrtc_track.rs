@@ -0,0 +1,172 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    tokio_camera::TokioCamera,
+    yuv::{frame_to_i420, I420Frame},
+};
+use bytes::Bytes;
+use nokhwa_core::{buffer::Buffer, error::NokhwaError, frame_format::FrameFormat};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant, SystemTime},
+};
+use webrtc::{
+    media::Sample, rtp_transceiver::rtp_codec::RTCRtpCodecCapability,
+    track::track_local::track_local_static_sample::TrackLocalStaticSample,
+};
+
+/// Encodes [`I420Frame`]s for a [`CameraVideoTrack`], e.g. by wrapping `openh264` or `libvpx`.
+pub trait VideoEncoder: Send {
+    /// The MIME type of the encoded video, e.g. [`MIME_TYPE_H264`](webrtc::api::media_engine::MIME_TYPE_H264).
+    fn mime_type(&self) -> &str;
+
+    /// Encodes one frame. Returns `None` if the encoder did not produce any output for this frame.
+    /// # Errors
+    /// If encoding fails, this will error.
+    fn encode(&mut self, frame: &I420Frame) -> Result<Option<Vec<u8>>, NokhwaError>;
+}
+
+/// Publishes a camera as a `webrtc` video track: frames are converted to I420, encoded with a [`VideoEncoder`], and written to
+/// a [`TrackLocalStaticSample`] that you add to your peer connection with
+/// [`add_track()`](webrtc::peer_connection::RTCPeerConnection::add_track).
+///
+/// Each sample lasts until the next frame, going by the frame timestamps, so the remote side plays the video at the rate it was
+/// captured. With [`with_max_frame_rate()`](Self::with_max_frame_rate), frames that arrive faster than that are dropped before
+/// they are encoded.
+/// ```ignore
+/// let mut track = CameraVideoTrack::new(MyH264Encoder::new()?, "video", "nokhwa");
+/// peer_connection.add_track(track.track()).await?;
+/// track.stream(&camera).await?;
+/// ```
+pub struct CameraVideoTrack<E: VideoEncoder> {
+    track: Arc<TrackLocalStaticSample>,
+    encoder: E,
+    min_interval: Option<Duration>,
+    last_timestamp: Option<Duration>,
+    started: Instant,
+}
+
+impl<E: VideoEncoder> CameraVideoTrack<E> {
+    /// Creates the track, with the codec of `encoder`. `id` and `stream_id` identify the track and its media stream to the
+    /// remote side.
+    pub fn new(encoder: E, id: impl Into<String>, stream_id: impl Into<String>) -> Self {
+        let track = TrackLocalStaticSample::new(
+            RTCRtpCodecCapability {
+                mime_type: encoder.mime_type().to_string(),
+                clock_rate: 90000,
+                ..Default::default()
+            },
+            id.into(),
+            stream_id.into(),
+        );
+        CameraVideoTrack {
+            track: Arc::new(track),
+            encoder,
+            min_interval: None,
+            last_timestamp: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Drops frames that arrive faster than `frame_rate` frames per second. `0` disables the limit.
+    #[must_use]
+    pub fn with_max_frame_rate(mut self, frame_rate: u32) -> Self {
+        self.min_interval = (frame_rate != 0).then(|| Duration::from_secs(1) / frame_rate);
+        self
+    }
+
+    /// The track to add to a peer connection.
+    #[must_use]
+    pub fn track(&self) -> Arc<TrackLocalStaticSample> {
+        self.track.clone()
+    }
+
+    /// The encoder.
+    #[must_use]
+    pub fn encoder(&self) -> &E {
+        &self.encoder
+    }
+
+    /// The encoder, e.g. to request a keyframe.
+    pub fn encoder_mut(&mut self) -> &mut E {
+        &mut self.encoder
+    }
+
+    /// Converts, encodes, and writes one frame, which is in `format`. Returns `false` if the frame was dropped to keep under the
+    /// maximum frame rate, or the encoder produced no output.
+    /// # Errors
+    /// If the frame can not be converted to I420, encoding fails, or the sample can not be written, this will error.
+    pub async fn write_frame(
+        &mut self,
+        frame: &Buffer,
+        format: FrameFormat,
+    ) -> Result<bool, NokhwaError> {
+        let timestamp = self.frame_time(frame);
+        let duration = match self.last_timestamp {
+            Some(last) => {
+                let elapsed = timestamp.saturating_sub(last);
+                if self.min_interval.map_or(false, |min| elapsed < min) {
+                    return Ok(false);
+                }
+                elapsed
+            }
+            None => self.min_interval.unwrap_or(Duration::from_millis(33)),
+        };
+        self.last_timestamp = Some(timestamp);
+
+        let i420 = frame_to_i420(frame, format)?;
+        let Some(encoded) = self.encoder.encode(&i420)? else {
+            return Ok(false);
+        };
+        self.track
+            .write_sample(&Sample {
+                data: Bytes::from(encoded),
+                duration,
+                ..Default::default()
+            })
+            .await
+            .map_err(|why| NokhwaError::GeneralError(format!("Failed to write sample: {why}")))?;
+        Ok(true)
+    }
+
+    /// Captures frames from `camera` and writes them to the track until capturing or writing fails. The stream is opened if
+    /// needed.
+    /// # Errors
+    /// If capturing, converting, encoding, or writing a frame fails, this will error.
+    pub async fn stream(&mut self, camera: &TokioCamera) -> Result<(), NokhwaError> {
+        if !camera.is_stream_open().await? {
+            camera.open_stream().await?;
+        }
+        loop {
+            let frame = camera.frame().await?;
+            self.write_frame(&frame, frame.source_frame_format())
+                .await?;
+        }
+    }
+
+    fn frame_time(&self, frame: &Buffer) -> Duration {
+        match frame.timestamp() {
+            Some(timestamp) => timestamp.capture().unwrap_or_else(|| {
+                timestamp
+                    .system()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+            }),
+            None => self.started.elapsed(),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{convert::frame_to_rgba, Camera};
+use nokhwa_core::{
+    buffer::Buffer, error::NokhwaError, frame_format::FrameFormat, traits::CaptureTrait,
+};
+use wgpu::{
+    Device, Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, Texture, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+
+/// Creates a new `Rgba8UnormSrgb` texture from `frame`, which is in `format`. The texture can be sampled and copied to, so it
+/// can be refreshed every frame with [`update_texture()`].
+///
+/// RGBA frames are uploaded as-is (honouring their stride), everything else is converted to RGBA first.
+/// # Errors
+/// If the frame can not be converted to RGBA, or the resolution is 0 on any axis, this will error.
+pub fn frame_to_texture(
+    device: &Device,
+    queue: &Queue,
+    frame: &Buffer,
+    format: FrameFormat,
+    label: Option<&str>,
+) -> Result<Texture, NokhwaError> {
+    let resolution = frame.resolution();
+    if resolution.width() == 0 || resolution.height() == 0 {
+        return Err(NokhwaError::ProcessFrameError {
+            src: format,
+            destination: "wgpu Texture".to_string(),
+            error: "Resolution is 0".to_string(),
+        });
+    }
+
+    let texture = device.create_texture(&TextureDescriptor {
+        label,
+        size: texture_size(frame),
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8UnormSrgb,
+        usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        view_formats: &[TextureFormat::Rgba8UnormSrgb],
+    });
+    update_texture(queue, &texture, frame, format)?;
+    Ok(texture)
+}
+
+/// Uploads `frame`, which is in `format`, into an existing texture made by [`frame_to_texture()`], without reallocating it.
+/// # Errors
+/// If the frame can not be converted to RGBA, or its resolution does not match the texture, this will error.
+pub fn update_texture(
+    queue: &Queue,
+    texture: &Texture,
+    frame: &Buffer,
+    format: FrameFormat,
+) -> Result<(), NokhwaError> {
+    let size = texture_size(frame);
+    if texture.size() != size {
+        return Err(NokhwaError::ProcessFrameError {
+            src: format,
+            destination: "wgpu Texture".to_string(),
+            error: format!(
+                "Frame is {}x{}, texture is {}x{}",
+                size.width,
+                size.height,
+                texture.width(),
+                texture.height()
+            ),
+        });
+    }
+
+    let decoded;
+    let (data, bytes_per_row): (&[u8], u32) = match format {
+        FrameFormat::RgbA8 => (frame.buffer(), frame.stride().unwrap_or(4 * size.width)),
+        format => {
+            decoded = frame_to_rgba(frame, format)?;
+            (&decoded, 4 * size.width)
+        }
+    };
+    if data.len() < (bytes_per_row * (size.height - 1) + 4 * size.width) as usize {
+        return Err(NokhwaError::ProcessFrameError {
+            src: format,
+            destination: "wgpu Texture".to_string(),
+            error: "Frame is too small".to_string(),
+        });
+    }
+
+    queue.write_texture(
+        ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        data,
+        ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(bytes_per_row),
+            rows_per_image: Some(size.height),
+        },
+        size,
+    );
+    Ok(())
+}
+
+fn texture_size(frame: &Buffer) -> Extent3d {
+    Extent3d {
+        width: frame.resolution().width(),
+        height: frame.resolution().height(),
+        depth_or_array_layers: 1,
+    }
+}
+
+impl Camera {
+    /// Captures a frame and uploads it into a new texture. See [`frame_to_texture()`].
+    /// # Errors
+    /// If capturing the frame fails, or it can not be uploaded, this will error.
+    pub fn frame_texture(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        label: Option<&str>,
+    ) -> Result<Texture, NokhwaError> {
+        let frame = self.frame()?;
+        frame_to_texture(device, queue, &frame, frame.source_frame_format(), label)
+    }
+
+    /// Captures a frame and uploads it into `texture`. See [`update_texture()`].
+    /// # Errors
+    /// If capturing the frame fails, or it can not be uploaded, this will error.
+    pub fn update_frame_texture(
+        &mut self,
+        queue: &Queue,
+        texture: &Texture,
+    ) -> Result<(), NokhwaError> {
+        let frame = self.frame()?;
+        update_texture(queue, texture, &frame, frame.source_frame_format())
+    }
+}
@@ -0,0 +1,143 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    frame_sink::{EncodedPacket, FrameSink},
+    yuv::I420Frame,
+};
+use nokhwa_core::{error::NokhwaError, types::Resolution};
+use std::time::Duration;
+use x264::{Colorspace, Encoder, Image, Plane, Preset, Setup, Tune};
+
+/// A [`FrameSink`] that encodes frames to H.264 (Annex B) with `x264`, and passes every [`EncodedPacket`] to a callback.
+///
+/// The encoder uses the `veryfast` preset with zero latency tuning, and is created for the resolution of the first frame. Every
+/// frame must have that resolution. Call [`finish()`](FrameSink::finish) to get the last frames out of the encoder.
+/// ```ignore
+/// let mut file = File::create("out.h264")?;
+/// let mut sink = X264Sink::new(30, |packet| file.write_all(&packet.data).unwrap());
+/// feed_sink(&mut camera, &mut sink, Duration::from_secs(10))?;
+/// sink.finish()?;
+/// ```
+pub struct X264Sink<F: FnMut(EncodedPacket)> {
+    encoder: Option<(Encoder, Resolution)>,
+    frame_rate: u32,
+    on_packet: F,
+}
+
+impl<F: FnMut(EncodedPacket)> X264Sink<F> {
+    /// Creates a sink for video at about `frame_rate` frames per second, that calls `on_packet` with every encoded frame.
+    pub fn new(frame_rate: u32, on_packet: F) -> Self {
+        X264Sink {
+            encoder: None,
+            frame_rate: frame_rate.max(1),
+            on_packet,
+        }
+    }
+}
+
+// x264 timestamps are in milliseconds.
+const TIMEBASE: u32 = 1000;
+
+impl<F: FnMut(EncodedPacket)> FrameSink for X264Sink<F> {
+    fn write_frame(&mut self, frame: &I420Frame, timestamp: Duration) -> Result<(), NokhwaError> {
+        let resolution = frame.resolution();
+        let (encoder, expected) = match &mut self.encoder {
+            Some(encoder) => encoder,
+            encoder @ None => {
+                let created = Setup::preset(Preset::Veryfast, Tune::None, false, true)
+                    .fps(self.frame_rate, 1)
+                    .timebase(1, TIMEBASE)
+                    .build(
+                        Colorspace::I420,
+                        resolution.width() as i32,
+                        resolution.height() as i32,
+                    )
+                    .map_err(|_| {
+                        NokhwaError::GeneralError("Failed to create the x264 encoder".to_string())
+                    })?;
+                encoder.insert((created, resolution))
+            }
+        };
+        if resolution != *expected {
+            return Err(NokhwaError::GeneralError(format!(
+                "Frame is {resolution}, but the encoder was created for {expected}"
+            )));
+        }
+
+        let luma_stride = resolution.width() as i32;
+        let chroma_stride = frame.chroma_resolution().width() as i32;
+        let image = Image::new(
+            Colorspace::I420,
+            resolution.width() as i32,
+            resolution.height() as i32,
+            &[
+                Plane {
+                    stride: luma_stride,
+                    data: frame.y(),
+                },
+                Plane {
+                    stride: chroma_stride,
+                    data: frame.u(),
+                },
+                Plane {
+                    stride: chroma_stride,
+                    data: frame.v(),
+                },
+            ],
+        );
+        let (data, picture) = encoder
+            .encode(timestamp.as_millis() as i64, image)
+            .map_err(|_| NokhwaError::GeneralError("x264 failed to encode a frame".to_string()))?;
+        emit(
+            &mut self.on_packet,
+            data.entirety(),
+            picture.pts(),
+            picture.keyframe(),
+        );
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), NokhwaError> {
+        let Some((encoder, _)) = self.encoder.take() else {
+            return Ok(());
+        };
+        let mut flush = encoder.flush();
+        while let Some(result) = flush.next() {
+            let (data, picture) = result.map_err(|_| {
+                NokhwaError::GeneralError("x264 failed to flush a frame".to_string())
+            })?;
+            emit(
+                &mut self.on_packet,
+                data.entirety(),
+                picture.pts(),
+                picture.keyframe(),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn emit(on_packet: &mut impl FnMut(EncodedPacket), data: &[u8], pts: i64, keyframe: bool) {
+    if data.is_empty() {
+        return;
+    }
+    on_packet(EncodedPacket {
+        data: data.to_vec(),
+        timestamp: Duration::from_millis(pts.max(0) as u64),
+        keyframe,
+    });
+}
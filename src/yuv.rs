@@ -0,0 +1,299 @@
+/*
+ * Copyright 2022 l1npengtul <l1npengtul@protonmail.com> / The Nokhwa Contributors
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use nokhwa_core::{
+    buffer::Buffer, error::NokhwaError, frame_format::FrameFormat, types::Resolution,
+};
+
+/// A frame in planar YUV 4:2:0 (I420): a full resolution Y plane, followed by quarter resolution U and V planes.
+///
+/// The planes are tightly packed. The chroma planes are `(width + 1) / 2` by `(height + 1) / 2`, so odd sizes are kept.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct I420Frame {
+    resolution: Resolution,
+    data: Vec<u8>,
+}
+
+impl I420Frame {
+    /// The resolution of the Y plane.
+    #[must_use]
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+
+    /// The width and height of the U and V planes.
+    #[must_use]
+    pub fn chroma_resolution(&self) -> Resolution {
+        Resolution::new(
+            (self.resolution.width() + 1) / 2,
+            (self.resolution.height() + 1) / 2,
+        )
+    }
+
+    /// The Y plane.
+    #[must_use]
+    pub fn y(&self) -> &[u8] {
+        &self.data[..self.luma_len()]
+    }
+
+    /// The U (Cb) plane.
+    #[must_use]
+    pub fn u(&self) -> &[u8] {
+        let start = self.luma_len();
+        &self.data[start..start + self.chroma_len()]
+    }
+
+    /// The V (Cr) plane.
+    #[must_use]
+    pub fn v(&self) -> &[u8] {
+        &self.data[self.luma_len() + self.chroma_len()..]
+    }
+
+    /// All three planes, one after another.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Takes the planes out of this frame.
+    #[must_use]
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    fn luma_len(&self) -> usize {
+        self.resolution.width() as usize * self.resolution.height() as usize
+    }
+
+    fn chroma_len(&self) -> usize {
+        let chroma = self.chroma_resolution();
+        chroma.width() as usize * chroma.height() as usize
+    }
+}
+
+/// Converts `frame`, which is in `format`, into [`I420Frame`].
+///
+/// YUYV, UYVY, NV12, NV21, YV12, and grayscale frames are converted directly, without going through RGB. With the
+/// `output-image` feature, MJPEG, RGB, and RGBA frames are also supported, by converting them from RGB (BT.601, limited range).
+/// # Errors
+/// If the [`FrameFormat`] is not supported, the frame is too short for its resolution, or decoding fails, this will error.
+pub fn frame_to_i420(frame: &Buffer, format: FrameFormat) -> Result<I420Frame, NokhwaError> {
+    let resolution = frame.resolution();
+    let (width, height) = (resolution.width() as usize, resolution.height() as usize);
+    let data = frame.buffer();
+    let error = |error: String| NokhwaError::ProcessFrameError {
+        src: format,
+        destination: "I420".to_string(),
+        error,
+    };
+    let check_len = |needed: usize| {
+        if data.len() < needed {
+            Err(error(format!(
+                "Buffer is {} bytes, expected at least {needed}",
+                data.len()
+            )))
+        } else {
+            Ok(())
+        }
+    };
+
+    let planes = match format {
+        FrameFormat::Yuv422 | FrameFormat::Uyv422 => {
+            let stride = frame.stride().map_or(width * 2, |stride| stride as usize);
+            check_len(stride * height.saturating_sub(1) + (width + 1) / 2 * 4)?;
+            // YUYV is Y0 U Y1 V, UYVY is U Y0 V Y1.
+            let (luma, u, v) = if format == FrameFormat::Yuv422 {
+                (0, 1, 3)
+            } else {
+                (1, 0, 2)
+            };
+            from_422(
+                width,
+                height,
+                |x, y| data[y * stride + x * 2 + luma],
+                |cx, y| {
+                    let pair = y * stride + cx * 4;
+                    (data[pair + u], data[pair + v])
+                },
+            )
+        }
+        FrameFormat::Nv12 | FrameFormat::Nv21 => {
+            let stride = frame.stride().map_or(width, |stride| stride as usize);
+            let chroma_height = (height + 1) / 2;
+            let chroma_start = stride * height;
+            check_len(
+                chroma_start + stride * chroma_height.saturating_sub(1) + (width + 1) / 2 * 2,
+            )?;
+            let (u, v) = if format == FrameFormat::Nv12 {
+                (0, 1)
+            } else {
+                (1, 0)
+            };
+            from_420(
+                width,
+                height,
+                |x, y| data[y * stride + x],
+                |cx, cy| {
+                    let pair = chroma_start + cy * stride + cx * 2;
+                    (data[pair + u], data[pair + v])
+                },
+            )
+        }
+        FrameFormat::Yv12 => {
+            let stride = frame.stride().map_or(width, |stride| stride as usize);
+            let chroma_stride = (stride + 1) / 2;
+            let chroma_height = (height + 1) / 2;
+            let v_start = stride * height;
+            let u_start = v_start + chroma_stride * chroma_height;
+            check_len(u_start + chroma_stride * chroma_height)?;
+            from_420(
+                width,
+                height,
+                |x, y| data[y * stride + x],
+                |cx, cy| {
+                    let offset = cy * chroma_stride + cx;
+                    (data[u_start + offset], data[v_start + offset])
+                },
+            )
+        }
+        FrameFormat::Luma8 => {
+            let stride = frame.stride().map_or(width, |stride| stride as usize);
+            check_len(stride * height.saturating_sub(1) + width)?;
+            from_420(
+                width,
+                height,
+                |x, y| data[y * stride + x],
+                |_, _| (128, 128),
+            )
+        }
+        #[cfg(feature = "output-image")]
+        FrameFormat::MJpeg | FrameFormat::Rgb8 | FrameFormat::RgbA8 => {
+            let rgb = crate::convert::frame_to_rgb(frame, format)?;
+            check_rgb_len(&rgb, width, height).map_err(error)?;
+            rgb_to_i420(&rgb, width, height)
+        }
+        _ => return Err(error("unsupported".to_string())),
+    };
+
+    Ok(I420Frame {
+        resolution,
+        data: planes,
+    })
+}
+
+// Builds I420 from 4:2:2 sources, where `chroma(cx, y)` is the chroma of the pixel pair `cx` on row `y`. The two rows of each
+// block are averaged.
+fn from_422(
+    width: usize,
+    height: usize,
+    luma: impl Fn(usize, usize) -> u8,
+    chroma: impl Fn(usize, usize) -> (u8, u8),
+) -> Vec<u8> {
+    let last_row = height.saturating_sub(1);
+    from_420(width, height, luma, |cx, cy| {
+        let (u0, v0) = chroma(cx, cy * 2);
+        let (u1, v1) = chroma(cx, (cy * 2 + 1).min(last_row));
+        (average(u0, u1), average(v0, v1))
+    })
+}
+
+// Builds I420 from sources that are already 4:2:0, where `chroma(cx, cy)` is the chroma of the 2x2 block at `cx, cy`.
+fn from_420(
+    width: usize,
+    height: usize,
+    luma: impl Fn(usize, usize) -> u8,
+    chroma: impl Fn(usize, usize) -> (u8, u8),
+) -> Vec<u8> {
+    let (chroma_width, chroma_height) = ((width + 1) / 2, (height + 1) / 2);
+    let chroma_len = chroma_width * chroma_height;
+    let mut planes = vec![0; width * height + chroma_len * 2];
+    let (y_plane, uv_planes) = planes.split_at_mut(width * height);
+    let (u_plane, v_plane) = uv_planes.split_at_mut(chroma_len);
+
+    for y in 0..height {
+        for x in 0..width {
+            y_plane[y * width + x] = luma(x, y);
+        }
+    }
+    for cy in 0..chroma_height {
+        for cx in 0..chroma_width {
+            let (u, v) = chroma(cx, cy);
+            u_plane[cy * chroma_width + cx] = u;
+            v_plane[cy * chroma_width + cx] = v;
+        }
+    }
+    planes
+}
+
+#[cfg(feature = "output-image")]
+fn check_rgb_len(rgb: &[u8], width: usize, height: usize) -> Result<(), String> {
+    if rgb.len() < width * height * 3 {
+        Err(format!(
+            "Decoded {} bytes, expected {}",
+            rgb.len(),
+            width * height * 3
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "output-image")]
+fn rgb_to_i420(rgb: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let pixel = |x: usize, y: usize| {
+        let index = (y * width + x) * 3;
+        (
+            i32::from(rgb[index]),
+            i32::from(rgb[index + 1]),
+            i32::from(rgb[index + 2]),
+        )
+    };
+    from_420(
+        width,
+        height,
+        |x, y| {
+            let (r, g, b) = pixel(x, y);
+            clamp(((66 * r + 129 * g + 25 * b + 128) >> 8) + 16)
+        },
+        |cx, cy| {
+            // Average the (up to) four pixels of the block before converting.
+            let (mut r, mut g, mut b, mut count) = (0, 0, 0, 0);
+            for y in cy * 2..(cy * 2 + 2).min(height) {
+                for x in cx * 2..(cx * 2 + 2).min(width) {
+                    let (pr, pg, pb) = pixel(x, y);
+                    r += pr;
+                    g += pg;
+                    b += pb;
+                    count += 1;
+                }
+            }
+            let (r, g, b) = (r / count, g / count, b / count);
+            (
+                clamp(((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128),
+                clamp(((112 * r - 94 * g - 18 * b + 128) >> 8) + 128),
+            )
+        },
+    )
+}
+
+#[cfg(feature = "output-image")]
+fn clamp(value: i32) -> u8 {
+    value.clamp(0, 255) as u8
+}
+
+fn average(a: u8, b: u8) -> u8 {
+    ((u16::from(a) + u16::from(b) + 1) / 2) as u8
+}